@@ -0,0 +1,254 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use thiserror::Error;
+
+/// KIS `msg_cd` values of token problems.
+const AUTH_CODES: &[&str] = &["EGW00121", "EGW00122", "EGW00123"];
+/// KIS `msg_cd` values of request throttling (per-second TR limit, token issue limit).
+const RATE_LIMIT_CODES: &[&str] = &["EGW00201", "EGW00133"];
+/// KIS `msg_cd` values of orders exceeding the orderable cash or holdings.
+const INSUFFICIENT_BALANCE_CODES: &[&str] = &["APBK0952"];
+
+/// Status fields of a failed broker response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BrokerStatus {
+    pub http_status: Option<u16>,
+    pub rt_cd: String,
+    pub msg_cd: String,
+    pub msg1: String,
+}
+
+impl BrokerStatus {
+    /// Read `rt_cd` / `msg_cd` / `msg1` from a KIS body. The OAuth endpoints use `error_code` / `error_description`.
+    pub fn from_body(http_status: u16, body: &Value) -> Self {
+        let text = |keys: &[&str]| keys.iter().find_map(|k| body[*k].as_str()).unwrap_or("").to_string();
+        BrokerStatus {
+            http_status: Some(http_status),
+            rt_cd: text(&["rt_cd"]),
+            msg_cd: text(&["msg_cd", "error_code"]),
+            msg1: text(&["msg1", "error_description"]),
+        }
+    }
+}
+
+impl fmt::Display for BrokerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.msg_cd, self.msg1)?;
+        if !self.rt_cd.is_empty() {
+            write!(f, " (rt_cd={})", self.rt_cd)?;
+        }
+        if let Some(status) = self.http_status {
+            write!(f, " (HTTP {})", status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by `Adapter` methods.
+#[derive(Debug, Error)]
+pub enum AdapterError {
+    /// Access token missing, expired or refused.
+    #[error("Authentication failed: {0}")]
+    Auth(BrokerStatus),
+    /// Too many requests.
+    #[error("Rate limited: {0}")]
+    RateLimited(BrokerStatus),
+    /// Order exceeds the orderable cash or holdings.
+    #[error("Insufficient balance: {0}")]
+    InsufficientBalance(BrokerStatus),
+    /// Any other non-zero `rt_cd`.
+    #[error("Rejected by broker: {0}")]
+    Rejected(BrokerStatus),
+    /// Non-2xx response without a broker status in the body.
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+    /// No response in time. The broker may have acted on the request.
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    /// The request could not be delivered (no connection was made).
+    #[error("Network error: {0}")]
+    Network(String),
+    /// The connection broke once the request was under way. The broker may have acted on it.
+    #[error("Connection lost: {0}")]
+    ConnectionLost(String),
+    /// Client order id unknown to the adapter.
+    #[error("Order ID not found in local map: {0}")]
+    UnknownOrder(String),
+    #[error("{0} not supported")]
+    Unsupported(String),
+    /// Response body could not be read.
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+pub type AdapterResult<T> = std::result::Result<T, AdapterError>;
+
+impl AdapterError {
+    /// Classify a KIS response with a non-zero `rt_cd` or a non-2xx status.
+    pub fn from_response(http_status: u16, body: &Value) -> Self {
+        let status = BrokerStatus::from_body(http_status, body);
+        let code = status.msg_cd.as_str();
+        if AUTH_CODES.contains(&code) || http_status == 401 || http_status == 403 {
+            AdapterError::Auth(status)
+        } else if RATE_LIMIT_CODES.contains(&code) || http_status == 429 {
+            AdapterError::RateLimited(status)
+        } else if INSUFFICIENT_BALANCE_CODES.contains(&code) || status.msg1.contains("주문가능금액") {
+            AdapterError::InsufficientBalance(status)
+        } else if status.rt_cd.is_empty() && status.msg_cd.is_empty() {
+            AdapterError::Http { status: http_status, body: body.to_string() }
+        } else {
+            AdapterError::Rejected(status)
+        }
+    }
+
+    /// Short name of the error kind, used in logs and on Python exceptions.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AdapterError::Auth(_) => "auth",
+            AdapterError::RateLimited(_) => "rate_limited",
+            AdapterError::InsufficientBalance(_) => "insufficient_balance",
+            AdapterError::Rejected(_) => "rejected",
+            AdapterError::Http { .. } => "http",
+            AdapterError::Timeout(_) => "timeout",
+            AdapterError::Network(_) => "network",
+            AdapterError::ConnectionLost(_) => "connection_lost",
+            AdapterError::UnknownOrder(_) => "unknown_order",
+            AdapterError::Unsupported(_) => "unsupported",
+            AdapterError::InvalidResponse(_) => "invalid_response",
+            AdapterError::Other(_) => "other",
+        }
+    }
+
+    pub fn broker_status(&self) -> Option<&BrokerStatus> {
+        match self {
+            AdapterError::Auth(s) | AdapterError::RateLimited(s) | AdapterError::InsufficientBalance(s) | AdapterError::Rejected(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            AdapterError::Http { status, .. } => Some(*status),
+            _ => self.broker_status().and_then(|s| s.http_status),
+        }
+    }
+
+    /// The same request may succeed if sent again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AdapterError::RateLimited(_) | AdapterError::Timeout(_) | AdapterError::Network(_) | AdapterError::ConnectionLost(_) => true,
+            AdapterError::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// The broker may have accepted the request despite the error, so the order state is unknown
+    /// until the next reconciliation.
+    pub fn is_outcome_unknown(&self) -> bool {
+        match self {
+            AdapterError::Timeout(_) | AdapterError::ConnectionLost(_) | AdapterError::InvalidResponse(_) | AdapterError::Other(_) => true,
+            AdapterError::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for AdapterError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            AdapterError::Timeout(e.to_string())
+        } else if e.is_connect() {
+            // Nothing reached the broker
+            AdapterError::Network(e.to_string())
+        } else if e.is_decode() || e.is_body() {
+            AdapterError::InvalidResponse(e.to_string())
+        } else {
+            // Failed while sending or awaiting the response: the request may have gone through
+            AdapterError::ConnectionLost(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for AdapterError {
+    fn from(e: serde_json::Error) -> Self {
+        AdapterError::InvalidResponse(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AdapterError {
+    fn from(e: std::io::Error) -> Self {
+        AdapterError::Other(e.into())
+    }
+}
+
+create_exception!(didius.core, AdapterException, PyRuntimeError, "Error raised by a broker adapter.");
+create_exception!(didius.core, AuthError, AdapterException, "Access token missing, expired or refused.");
+create_exception!(didius.core, RateLimitError, AdapterException, "Too many requests.");
+create_exception!(didius.core, OrderRejectedError, AdapterException, "Request refused by the broker.");
+create_exception!(didius.core, InsufficientBalanceError, OrderRejectedError, "Order exceeds the orderable cash or holdings.");
+create_exception!(didius.core, BrokerHttpError, AdapterException, "Non-2xx response without a broker status.");
+create_exception!(didius.core, AdapterTimeoutError, AdapterException, "No response in time. The order state is unknown.");
+create_exception!(didius.core, NetworkError, AdapterException, "The request could not be delivered.");
+create_exception!(didius.core, ConnectionLostError, AdapterException, "Connection lost once the request was under way. The order state is unknown.");
+create_exception!(didius.core, UnknownOrderError, AdapterException, "Client order id unknown to the adapter.");
+create_exception!(didius.core, UnsupportedError, AdapterException, "Operation not supported by the adapter.");
+
+impl From<AdapterError> for PyErr {
+    fn from(e: AdapterError) -> PyErr {
+        let message = e.to_string();
+        let err = match &e {
+            AdapterError::Auth(_) => AuthError::new_err(message),
+            AdapterError::RateLimited(_) => RateLimitError::new_err(message),
+            AdapterError::InsufficientBalance(_) => InsufficientBalanceError::new_err(message),
+            AdapterError::Rejected(_) => OrderRejectedError::new_err(message),
+            AdapterError::Http { .. } => BrokerHttpError::new_err(message),
+            AdapterError::Timeout(_) => AdapterTimeoutError::new_err(message),
+            AdapterError::Network(_) => NetworkError::new_err(message),
+            AdapterError::ConnectionLost(_) => ConnectionLostError::new_err(message),
+            AdapterError::UnknownOrder(_) => UnknownOrderError::new_err(message),
+            AdapterError::Unsupported(_) => UnsupportedError::new_err(message),
+            AdapterError::InvalidResponse(_) | AdapterError::Other(_) => AdapterException::new_err(message),
+        };
+        Python::with_gil(|py| {
+            let value = err.value(py);
+            let status = e.broker_status().cloned().unwrap_or_default();
+            let _ = value.setattr("kind", e.kind());
+            let _ = value.setattr("retryable", e.is_retryable());
+            let _ = value.setattr("http_status", e.http_status());
+            let _ = value.setattr("rt_cd", status.rt_cd);
+            let _ = value.setattr("msg_cd", status.msg_cd);
+            let _ = value.setattr("msg1", status.msg1);
+        });
+        err
+    }
+}
+
+/// Convert an engine error to a Python exception, keeping the adapter error class when there is one.
+pub fn to_py_err(e: anyhow::Error) -> PyErr {
+    match e.downcast::<AdapterError>() {
+        Ok(adapter_error) => adapter_error.into(),
+        Err(e) => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("AdapterException", py.get_type::<AdapterException>())?;
+    m.add("AuthError", py.get_type::<AuthError>())?;
+    m.add("RateLimitError", py.get_type::<RateLimitError>())?;
+    m.add("OrderRejectedError", py.get_type::<OrderRejectedError>())?;
+    m.add("InsufficientBalanceError", py.get_type::<InsufficientBalanceError>())?;
+    m.add("BrokerHttpError", py.get_type::<BrokerHttpError>())?;
+    m.add("AdapterTimeoutError", py.get_type::<AdapterTimeoutError>())?;
+    m.add("NetworkError", py.get_type::<NetworkError>())?;
+    m.add("ConnectionLostError", py.get_type::<ConnectionLostError>())?;
+    m.add("UnknownOrderError", py.get_type::<UnknownOrderError>())?;
+    m.add("UnsupportedError", py.get_type::<UnsupportedError>())?;
+    Ok(())
+}
@@ -1,11 +1,13 @@
-use crate::adapter::Adapter;
+use crate::adapter::{runtime, AdapterError, AdapterResult, AsyncAdapter, BrokerExecution, BrokerOrder};
 use crate::oms::account::AccountState;
 use crate::oms::order::{Order, OrderSide, OrderType, OrderState};
 use crate::oms::order_book::OrderBook;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local};
 use log::{error, info, warn};
-use reqwest::blocking::Client;
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
@@ -13,12 +15,13 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
 use std::sync::mpsc;
-use std::collections::HashMap;
-use tungstenite::{connect, Message};
-use url::Url;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
 use crate::adapter::IncomingMessage;
+use crate::message::ConnectionStatus;
 use rust_decimal::Decimal;
 use std::str::FromStr;
 use crate::oms::order_book::{OrderBookSnapshot};
@@ -44,6 +47,8 @@ pub struct HantooConfig {
     pub my_prod_future: Option<String>,
     pub my_htsid: Option<String>,
     pub ops: Option<String>, // WebSocket URL
+    #[serde(default)]
+    pub auth_dir: Option<String>, // Token cache directory (default: auth)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -61,9 +66,9 @@ pub struct HantooAdapter {
     auth_dir: PathBuf,
     // WebSocket state
     approval_key: Mutex<Option<String>>,
-    ws_thread: Mutex<Option<thread::JoinHandle<()>>>,
+    ws: Mutex<Option<KisWebSocket>>,
     // Map ClientOrderID -> (OrgNo, OrderNo)
-    // Changed to Arc<Mutex> to share with WS thread
+    // Changed to Arc<Mutex> to share with WS task
     order_map: Arc<Mutex<HashMap<String, HantooOrderInfo>>>,
     // Channel to Engine
     sender: Mutex<Option<mpsc::Sender<IncomingMessage>>>,
@@ -71,19 +76,291 @@ pub struct HantooAdapter {
     subscribed_symbols: Mutex<Vec<String>>,
     // Debug flag for WS logging
     debug_ws: Arc<AtomicBool>,
-    
-    // Encryption
-    ws_aes_iv: Arc<Mutex<Option<Vec<u8>>>>,
-    ws_aes_key: Arc<Mutex<Option<Vec<u8>>>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct HantooOrderInfo {
     org_no: String,
     order_no: String,
     exchange: String
 }
 
+/// One order of the KIS daily order/execution inquiry, with its revisions and cancels merged in.
+#[derive(Debug, Clone)]
+pub(crate) struct DailyOrder {
+    pub org_no: String,
+    pub order_no: String,
+    pub orig_order_no: String,
+    /// Every broker order number of the revision chain, oldest first
+    pub order_nos: Vec<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub filled: i64,
+    pub filled_amount: Decimal,
+    pub remaining: i64,
+    pub exchange: String,
+}
+
+impl DailyOrder {
+    /// Parse a row of `inquire-daily-ccld` (stock) or `inquire-ngt-ccnl` (night future/option).
+    pub(crate) fn from_row(item: &Value, night: bool) -> Self {
+        fn text(item: &Value, keys: &[&str]) -> String {
+            keys.iter().find_map(|k| item[*k].as_str()).unwrap_or("").trim().to_string()
+        }
+        fn int(item: &Value, keys: &[&str]) -> i64 {
+            text(item, keys).parse::<Decimal>().ok().and_then(|d| i64::try_from(d).ok()).unwrap_or(0)
+        }
+        fn dec(item: &Value, keys: &[&str]) -> Decimal {
+            Decimal::from_str(&text(item, keys)).unwrap_or_default()
+        }
+
+        let order_no = text(item, &["odno"]);
+        let filled = int(item, &["tot_ccld_qty"]);
+        let mut filled_amount = dec(item, &["tot_ccld_amt"]);
+        if filled_amount.is_zero() {
+            filled_amount = dec(item, &["avg_prvs", "avg_idx"]) * Decimal::from(filled);
+        }
+        let canceled = text(item, &["cncl_yn"]) == "Y";
+        let market = match text(item, &["ord_dvsn_cd", "ord_dvsn"]).as_str() {
+            "01" => !night,
+            "02" => night,
+            _ => false,
+        };
+        let price = dec(item, &["ord_unpr", "ord_idx", "unit_price"]);
+        DailyOrder {
+            org_no: text(item, &["ord_gno_brno"]),
+            orig_order_no: text(item, &["orgn_odno"]),
+            order_nos: vec![order_no.clone()],
+            order_no,
+            symbol: text(item, &["pdno", "shtn_pdno"]),
+            side: if text(item, &["sll_buy_dvsn_cd"]) == "01" { OrderSide::SELL } else { OrderSide::BUY },
+            order_type: if market { OrderType::MARKET } else { OrderType::LIMIT },
+            price: if market || price.is_zero() { None } else { Some(price) },
+            filled,
+            filled_amount,
+            remaining: if canceled { 0 } else { int(item, &["rmn_qty", "qty"]) },
+            exchange: { let e = text(item, &["excg_id_dvsn_cd"]); if e.is_empty() { "KRX".to_string() } else { e } },
+        }
+    }
+
+    /// Fold revision and cancel rows (linked by `orgn_odno`) into the order they amend.
+    /// Fills are summed over the chain; the reference and price are those of the working row.
+    pub(crate) fn merge_revisions(rows: Vec<DailyOrder>) -> Vec<DailyOrder> {
+        let known: HashSet<String> = rows.iter().map(|r| r.order_no.clone()).collect();
+        let mut children: HashMap<String, Vec<DailyOrder>> = HashMap::new();
+        let mut roots = Vec::new();
+        for row in rows {
+            if !row.orig_order_no.is_empty() && row.orig_order_no != row.order_no && known.contains(&row.orig_order_no) {
+                children.entry(row.orig_order_no.clone()).or_default().push(row);
+            } else {
+                roots.push(row);
+            }
+        }
+
+        roots.into_iter().map(|root| {
+            let mut merged = root.clone();
+            let mut pending = vec![root.order_no.clone()];
+            while let Some(no) = pending.pop() {
+                for child in children.remove(&no).unwrap_or_default() {
+                    merged.order_nos.push(child.order_no.clone());
+                    merged.filled += child.filled;
+                    merged.filled_amount += child.filled_amount;
+                    merged.remaining += child.remaining;
+                    if child.remaining > 0 || merged.remaining == 0 {
+                        merged.org_no = child.org_no.clone();
+                        merged.order_no = child.order_no.clone();
+                        if child.price.is_some() || child.order_type == OrderType::MARKET {
+                            merged.price = child.price;
+                            merged.order_type = child.order_type.clone();
+                        }
+                    }
+                    pending.push(child.order_no);
+                }
+            }
+            merged
+        }).collect()
+    }
+
+    pub(crate) fn average_price(&self) -> Decimal {
+        if self.filled > 0 { self.filled_amount / Decimal::from(self.filled) } else { Decimal::ZERO }
+    }
+
+    pub(crate) fn to_broker_order(&self, order_id: Option<String>, order_ref: Value) -> BrokerOrder {
+        BrokerOrder {
+            order_id,
+            exchange_order_id: self.order_no.clone(),
+            order_ref,
+            symbol: self.symbol.clone(),
+            side: self.side.clone(),
+            order_type: self.order_type.clone(),
+            quantity: self.filled + self.remaining,
+            price: self.price,
+            filled_quantity: self.filled,
+        }
+    }
+
+    pub(crate) fn to_execution(&self, order_id: Option<String>) -> BrokerExecution {
+        BrokerExecution {
+            order_id,
+            exchange_order_id: self.order_no.clone(),
+            symbol: self.symbol.clone(),
+            side: self.side.clone(),
+            filled_quantity: self.filled,
+            average_price: self.average_price(),
+        }
+    }
+}
+
+/// Body of a KIS REST response. HTTP failures and a non-zero `rt_cd` become a typed `AdapterError`.
+pub(crate) async fn kis_response(resp: Response) -> AdapterResult<Value> {
+    let status = resp.status();
+    let text = resp.text().await?;
+    let data: Value = match serde_json::from_str(&text) {
+        Ok(data) => data,
+        Err(_) if !status.is_success() => return Err(AdapterError::Http { status: status.as_u16(), body: text }),
+        Err(e) => return Err(e.into()),
+    };
+    let refused = data["rt_cd"].as_str().map(|c| c != "0").unwrap_or(false);
+    if !status.is_success() || refused {
+        return Err(AdapterError::from_response(status.as_u16(), &data));
+    }
+    Ok(data)
+}
+
+/// Order state of an accept notice (`CNTG_YN` 1) from its `RCTF_CLS` and `RFUS_YN`: a confirmed cancel
+/// is `CANCELED`, a confirmed modify acknowledges the replacement (`NEW`). `None` for a refused modify
+/// or cancel, which leaves the order as it was.
+pub(crate) fn notice_state(rctf_cls: &str, rfus_yn: &str) -> Option<(OrderState, Option<String>)> {
+    match (rctf_cls, rfus_yn == "Y") {
+        ("1" | "2", true) => None,
+        (_, true) => Some((OrderState::REJECTED, None)),
+        ("2", false) => Some((OrderState::CANCELED, Some("Canceled".to_string()))),
+        ("1", false) => Some((OrderState::NEW, Some("Replaced".to_string()))),
+        _ => Some((OrderState::NEW, None)),
+    }
+}
+
+/// AES key and IV announced by the broker when subscribing to execution notices.
+pub(crate) struct WsAesKeys {
+    iv: Vec<u8>,
+    key: Vec<u8>,
+}
+
+/// One KIS WebSocket connection, run as a task on the shared adapter runtime.
+/// Subscriptions may be added at any time; each (tr_id, tr_key) is sent once. Dropping it closes the connection.
+pub(crate) struct KisWebSocket {
+    approval_key: String,
+    commands: UnboundedSender<String>,
+    subscribed: Mutex<HashSet<(String, String)>>,
+    task: JoinHandle<()>,
+}
+
+impl KisWebSocket {
+    /// Connect to `{ws_url}/tryitout/H0STCNT0`. `on_data` receives every realtime data frame (`0|...` / `1|...`);
+    /// `monitor` gets `ConnectionStatus` `Connected` once the socket is up and `Disconnected` when it goes down.
+    pub(crate) fn spawn<F>(ws_url: &str, approval_key: String, debug_ws: Arc<AtomicBool>, monitor: Option<mpsc::Sender<IncomingMessage>>, on_data: F) -> Self
+    where
+        F: Fn(&str, Option<&WsAesKeys>) + Send + 'static,
+    {
+        let url = format!("{}/tryitout/H0STCNT0", ws_url); // Typical suffix
+        let (commands, rx) = unbounded_channel();
+        let task = runtime::runtime().spawn(Self::run(url, rx, debug_ws, monitor, on_data));
+        KisWebSocket { approval_key, commands, subscribed: Mutex::new(HashSet::new()), task }
+    }
+
+    pub(crate) fn subscribe(&self, tr_id: &str, tr_key: &str) {
+        if !self.subscribed.lock().unwrap().insert((tr_id.to_string(), tr_key.to_string())) {
+            return;
+        }
+        let sub_body = serde_json::json!({
+            "header": {"approval_key": self.approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
+            "body": {"input": {"tr_id": tr_id, "tr_key": tr_key}}
+        });
+        let _ = self.commands.send(sub_body.to_string());
+    }
+
+    async fn run<F>(url: String, mut commands: UnboundedReceiver<String>, debug_ws: Arc<AtomicBool>, monitor: Option<mpsc::Sender<IncomingMessage>>, on_data: F)
+    where
+        F: Fn(&str, Option<&WsAesKeys>) + Send + 'static,
+    {
+        let status = |status: ConnectionStatus| {
+            if let Some(m) = &monitor {
+                let _ = m.send(IncomingMessage::ConnectionStatus(status));
+            }
+        };
+        info!("Connecting to WebSocket: {}", url);
+        let socket = match connect_async(url.as_str()).await {
+            Ok((socket, response)) => {
+                info!("WebSocket Connected. Response: {:?}", response);
+                socket
+            }
+            Err(e) => {
+                error!("Connection failed: {}", e);
+                status(ConnectionStatus::Disconnected);
+                return;
+            }
+        };
+        status(ConnectionStatus::Connected);
+        let (mut write, mut read) = socket.split();
+        let mut keys: Option<WsAesKeys> = None;
+
+        loop {
+            tokio::select! {
+                Some(frame) = commands.recv() => {
+                    if let Err(e) = write.send(Message::Text(frame)).await {
+                        error!("WS Error: {}", e);
+                        break;
+                    }
+                }
+                msg = read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        if debug_ws.load(Ordering::Relaxed) {
+                            println!("[{}] WS_RECV: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), text);
+                        }
+                        if text.contains("PINGPONG") {
+                            let _ = write.send(Message::Text(text)).await;
+                            continue;
+                        }
+
+                        // Check for Subscription Response with IV/Key
+                        if text.contains("SUBSCRIBE SUCCESS") && text.contains("iv") {
+                            if let Ok(val) = serde_json::from_str::<Value>(&text) {
+                                if let Some(output) = val.get("body").and_then(|b| b.get("output")) {
+                                    let iv_str = output["iv"].as_str().unwrap_or("");
+                                    let key_str = output["key"].as_str().unwrap_or("");
+                                    if !iv_str.is_empty() && !key_str.is_empty() {
+                                        info!("Received Encryption Keys: IV={}, Key={}", iv_str, key_str);
+                                        keys = Some(WsAesKeys { iv: iv_str.as_bytes().to_vec(), key: key_str.as_bytes().to_vec() });
+                                    }
+                                }
+                            }
+                        }
+
+                        if text.starts_with('0') || text.starts_with('1') { // Data
+                            on_data(&text, keys.as_ref());
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        error!("WS Error: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+        status(ConnectionStatus::Disconnected);
+    }
+}
+
+impl Drop for KisWebSocket {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 impl HantooAdapter {
     pub fn new(config_path: &str) -> Result<Self> {
         let config_str = fs::read_to_string(config_path)
@@ -91,25 +368,26 @@ impl HantooAdapter {
         let config: HantooConfig = serde_yaml::from_str(&config_str)
             .map_err(|e| anyhow!("Failed to parse hantoo config: {}", e))?;
 
-        let adapter = HantooAdapter {
+        Ok(Self::from_config(config))
+    }
+
+    pub fn from_config(config: HantooConfig) -> Self {
+        let auth_dir = PathBuf::from(config.auth_dir.as_deref().unwrap_or("auth"));
+
+        HantooAdapter {
             config,
             token: Mutex::new(None),
             token_exp: Mutex::new(None),
             client: Client::new(),
-            auth_dir: PathBuf::from("auth"),
+            auth_dir,
             approval_key: Mutex::new(None),
 
-            ws_thread: Mutex::new(None), 
+            ws: Mutex::new(None),
             order_map: Arc::new(Mutex::new(HashMap::new())),
             sender: Mutex::new(None),
             subscribed_symbols: Mutex::new(Vec::new()),
             debug_ws: Arc::new(AtomicBool::new(false)),
-            
-            ws_aes_iv: Arc::new(Mutex::new(None)),
-            ws_aes_key: Arc::new(Mutex::new(None)),
-        };
-
-        Ok(adapter)
+        }
     }
 
     pub(crate) fn config(&self) -> &HantooConfig {
@@ -120,16 +398,84 @@ impl HantooAdapter {
         &self.client
     }
 
+    /// GET an inquiry TR and collect `output1` rows over all continuation pages.
+    /// `ctx` names the continuation keys (e.g. `CTX_AREA_FK100` / `CTX_AREA_NK100`).
+    pub(crate) async fn inquire_pages(&self, path: &str, tr_id: &str, params: &[(&str, String)], ctx: (&str, &str)) -> AdapterResult<Vec<Value>> {
+        let url = format!("{}{}", self.config.prod, path);
+        let mut rows = Vec::new();
+        let (mut fk, mut nk) = (String::new(), String::new());
+        let mut tr_cont = "";
+
+        for _ in 0..100 {
+            let mut query: Vec<(&str, String)> = params.to_vec();
+            query.push((ctx.0, fk.clone()));
+            query.push((ctx.1, nk.clone()));
+
+            let (data, more) = self.send_authorized_paged(|token| self.client.get(&url)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .header("appkey", &self.config.my_app)
+                .header("appsecret", &self.config.my_sec)
+                .header("tr_id", tr_id)
+                .header("tr_cont", tr_cont)
+                .header("custtype", "P")
+                .query(&query)).await?;
+            if let Some(items) = data["output1"].as_array().or_else(|| data["output"].as_array()) {
+                rows.extend(items.iter().cloned());
+            }
+            if !more {
+                return Ok(rows);
+            }
+            fk = data[ctx.0.to_lowercase()].as_str().unwrap_or("").to_string();
+            nk = data[ctx.1.to_lowercase()].as_str().unwrap_or("").to_string();
+            tr_cont = "N";
+        }
+        Err(AdapterError::InvalidResponse(format!("Inquiry {} did not finish paging", tr_id)))
+    }
+
+    /// Today's stock orders from the daily order/execution inquiry.
+    async fn daily_orders(&self) -> AdapterResult<Vec<DailyOrder>> {
+        let is_virtual = self.config.prod.contains("openapivts");
+        let tr_id = if is_virtual { "VTTC8001R" } else { "TTTC8001R" };
+        let today = Local::now().format("%Y%m%d").to_string();
+        let params = [
+            ("CANO", self.config.my_acct.clone().unwrap_or_default()),
+            ("ACNT_PRDT_CD", self.config.my_prod.clone().unwrap_or("01".to_string())),
+            ("INQR_STRT_DT", today.clone()),
+            ("INQR_END_DT", today),
+            ("SLL_BUY_DVSN_CD", "00".to_string()),
+            ("INQR_DVSN", "00".to_string()),
+            ("PDNO", "".to_string()),
+            ("CCLD_DVSN", "00".to_string()),
+            ("ORD_GNO_BRNO", "".to_string()),
+            ("ODNO", "".to_string()),
+            ("INQR_DVSN_3", "00".to_string()),
+            ("INQR_DVSN_1", "".to_string()),
+        ];
+        let rows = self.inquire_pages("/uapi/domestic-stock/v1/trading/inquire-daily-ccld", tr_id, &params, ("CTX_AREA_FK100", "CTX_AREA_NK100")).await?;
+        Ok(DailyOrder::merge_revisions(rows.iter().map(|r| DailyOrder::from_row(r, false)).collect()))
+    }
+
+    fn client_id_of(&self, order: &DailyOrder) -> Option<String> {
+        let map = self.order_map.lock().unwrap();
+        map.iter().find(|(_, info)| order.order_nos.contains(&info.order_no)).map(|(id, _)| id.clone())
+    }
+
     pub(crate) fn set_monitor_internal(&self, sender: mpsc::Sender<IncomingMessage>) {
         let mut guard = self.sender.lock().unwrap();
         *guard = Some(sender);
     }
     
-    pub fn subscribe_market(&self, symbols: &[String]) -> Result<()> {
+    /// Add symbols to the market data subscription. Sent at once when the WebSocket is running.
+    pub fn subscribe_market(&self, symbols: &[String]) -> AdapterResult<()> {
         let mut guard = self.subscribed_symbols.lock().unwrap();
+        let ws = self.ws.lock().unwrap();
         for s in symbols {
             if !guard.contains(s) {
                 guard.push(s.to_string());
+                if let Some(ws) = ws.as_ref() {
+                    Self::subscribe_symbol(ws, s);
+                }
             }
         }
         Ok(())
@@ -139,7 +485,7 @@ impl HantooAdapter {
     //     // Placeholder driven by start_ws_thread using subscribed_symbols
     // }
     
-    pub(crate) fn get_token(&self) -> Result<String> {
+    pub(crate) async fn get_token(&self) -> AdapterResult<String> {
         {
             let token_guard = self.token.lock().unwrap();
             let exp_guard = self.token_exp.lock().unwrap();
@@ -156,7 +502,47 @@ impl HantooAdapter {
              return Ok(cached_token);
         }
 
-        self.refresh_token()
+        self.refresh_token().await
+    }
+
+    /// Send the request `build` makes with the access token and read the KIS response. A token the
+    /// server no longer accepts (revoked, or replaced by a newer issue) is dropped from memory and
+    /// the cache file, and the request is sent once more with a freshly issued one.
+    pub(crate) async fn send_authorized<F>(&self, build: F) -> AdapterResult<Value>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        self.send_authorized_paged(build).await.map(|(data, _)| data)
+    }
+
+    /// `send_authorized`, also telling whether the response says more pages follow (`tr_cont` F or M).
+    pub(crate) async fn send_authorized_paged<F>(&self, build: F) -> AdapterResult<(Value, bool)>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let token = self.get_token().await?;
+        match Self::send_paged(build(&token)).await {
+            Err(AdapterError::Auth(status)) => {
+                warn!("Access token refused ({} {}), issuing a new one", status.msg_cd, status.msg1);
+                self.invalidate_token();
+                let token = self.refresh_token().await?;
+                Self::send_paged(build(&token)).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send_paged(request: RequestBuilder) -> AdapterResult<(Value, bool)> {
+        let resp = request.send().await?;
+        let more = resp.headers().get("tr_cont").and_then(|v| v.to_str().ok()).map(|v| v == "F" || v == "M").unwrap_or(false);
+        Ok((kis_response(resp).await?, more))
+    }
+
+    /// Forget the access token, in memory and in the cache file.
+    fn invalidate_token(&self) {
+        self.token.lock().unwrap().take();
+        self.token_exp.lock().unwrap().take();
+        let _ = fs::remove_file(self.auth_dir.join("hantoo_token.yaml"));
     }
 
     fn read_token_from_file(&self) -> Result<String> {
@@ -182,7 +568,7 @@ impl HantooAdapter {
         Err(anyhow!("Token expired"))
     }
 
-    fn refresh_token(&self) -> Result<String> {
+    async fn refresh_token(&self) -> AdapterResult<String> {
         let url = format!("{}/oauth2/tokenP", self.config.prod);
         let body = serde_json::json!({
             "grant_type": "client_credentials",
@@ -192,23 +578,20 @@ impl HantooAdapter {
 
         let resp = self.client.post(&url)
             .json(&body)
-            .send()
-            .map_err(|e| anyhow!("Token request failed: {}", e))?;
+            .send().await?;
 
-        if !resp.status().is_success() {
-             let text = resp.text().unwrap_or_default();
-             return Err(anyhow!("Token request error: {}", text));
-        }
-
-        let data: Value = resp.json()
-             .map_err(|e| anyhow!("Failed to parse token response: {}", e))?;
+        // Any refusal of the token endpoint other than throttling is an authentication failure
+        let data = kis_response(resp).await.map_err(|e| match e {
+            AdapterError::Rejected(s) | AdapterError::InsufficientBalance(s) => AdapterError::Auth(s),
+            e => e,
+        })?;
 
         let access_token = data["access_token"].as_str()
-            .ok_or_else(|| anyhow!("No access_token in response"))?
+            .ok_or_else(|| AdapterError::InvalidResponse("No access_token in response".to_string()))?
             .to_string();
             
         let expired_str = data["access_token_token_expired"].as_str()
-            .ok_or_else(|| anyhow!("No expiration in response"))?
+            .ok_or_else(|| AdapterError::InvalidResponse("No expiration in response".to_string()))?
             .to_string();
 
         self.save_token_to_file(&access_token, &expired_str)?;
@@ -218,7 +601,7 @@ impl HantooAdapter {
         *token_guard = Some(access_token.clone());
         
         let expiry = chrono::NaiveDateTime::parse_from_str(&expired_str, "%Y-%m-%d %H:%M:%S")
-            .map_err(|e| anyhow!("Failed to parse new token date: {}", e))?
+            .map_err(|e| AdapterError::InvalidResponse(format!("Failed to parse new token date: {}", e)))?
             .and_local_timezone(Local)
             .unwrap();
             
@@ -241,7 +624,7 @@ impl HantooAdapter {
         Ok(())
     }
 
-    pub(crate) fn get_ws_approval_key(&self) -> Result<String> {
+    pub(crate) async fn get_ws_approval_key(&self) -> Result<String> {
         // Check memory
         {
             let key = self.approval_key.lock().unwrap();
@@ -260,14 +643,15 @@ impl HantooAdapter {
         let resp = self.client.post(&url)
             .json(&body)
             .send()
+            .await
             .map_err(|e| anyhow!("WS Approval request failed: {}", e))?;
 
         if !resp.status().is_success() {
-            let text = resp.text().unwrap_or_default();
+            let text = resp.text().await.unwrap_or_default();
             return Err(anyhow!("WS Approval error: {}", text));
         }
 
-        let data: Value = resp.json()?;
+        let data: Value = resp.json().await?;
         let key = data["approval_key"].as_str()
             .ok_or_else(|| anyhow!("No approval_key in response"))?
             .to_string();
@@ -282,129 +666,40 @@ impl HantooAdapter {
         self.debug_ws.store(enabled, Ordering::Relaxed);
     }
 
-    fn start_ws_thread(&self) -> Result<()> {
+    async fn start_ws(&self) -> Result<()> {
         let ws_url_str = self.config.ops.clone().ok_or(anyhow!("No WebSocket URL (ops) in config"))?;
-        let approval_key = self.get_ws_approval_key()?;
+        let approval_key = self.get_ws_approval_key().await?;
         let my_htsid = self.config.my_htsid.clone().unwrap_or_default();
-        
-        // Sender clone
+
         let sender = self.sender.lock().unwrap().clone();
-        
-        // Get symbols to subscribe
-        let symbols: Vec<String> = self.subscribed_symbols.lock().unwrap().clone();
-        let debug_ws = self.debug_ws.clone();
         let order_map_clone = self.order_map.clone();
-        
-        let aes_iv = self.ws_aes_iv.clone();
-        let aes_key = self.ws_aes_key.clone();
-        
-        let handle = thread::spawn(move || {
-            let full_url = format!("{}/tryitout/H0STCNT0", ws_url_str); // Typical suffix
-            let url = Url::parse(&full_url).expect("Invalid WS URL");
-
-            info!("Connecting to WebSocket: {}", url);
-            match connect(url) {
-                Ok((mut socket, response)) => {
-                    info!("WebSocket Connected. Response: {:?}", response);
-
-                    // Subscribe to Execution (Private)
-                    if !my_htsid.is_empty() {
-                         let tr_id = if ws_url_str.contains("openapivts") { "H0STCNI9" } else { "H0STCNI0" };
-                         let sub_body = serde_json::json!({
-                            "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                            "body": {"input": {"tr_id": tr_id, "tr_key": my_htsid}}
-                         });
-                         let _ = socket.send(Message::Text(sub_body.to_string()));
-                    }
-                    
-                    // Subscribe to Market Data for all symbols
-                    for target_symbol in symbols {
-                        // Trade
-                        {
-                            let sub_body = serde_json::json!({
-                                "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                                "body": {"input": {"tr_id": "H0SCCNT0", "tr_key": target_symbol}} // H0SCCNT0 is "Realtime Stock Conclusion" (KOSPI)
-                            }); 
-                            let _ = socket.send(Message::Text(sub_body.to_string()));
-                        }
-                        
-                        // Asking Price (Total - 10 levels) H0UNASP0
-                        {
-                            let sub_body = serde_json::json!({
-                                "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                                "body": {"input": {"tr_id": "H0UNASP0", "tr_key": target_symbol}} 
-                            });
-                            let _ = socket.send(Message::Text(sub_body.to_string()));
-                        }
-                        
-                        info!("Subscribed to {} Trade/Ask(Total)", target_symbol);
-                    }
-
-                    // Loop
-                    loop {
-                        match socket.read() {
-                            Ok(msg) => {
-                                match msg {
-                                    Message::Text(text) => {
-                                        if debug_ws.load(Ordering::Relaxed) {
-                                            println!("[{}] WS_RECV: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), text);
-                                        }
-                                        if text.contains("PINGPONG") {
-                                            let _ = socket.send(Message::Text(text)); 
-                                            continue;
-                                        }
-                                        
-                                        // Check for Subscription Response with IV/Key
-                                        if text.contains("SUBSCRIBE SUCCESS") && text.contains("iv") {
-                                            if let Ok(val) = serde_json::from_str::<Value>(&text) {
-                                                if let Some(output) = val.get("body").and_then(|b| b.get("output")) {
-                                                    let iv_str = output["iv"].as_str().unwrap_or("");
-                                                    let key_str = output["key"].as_str().unwrap_or("");
-                                                    if !iv_str.is_empty() && !key_str.is_empty() {
-                                                        info!("Received Encryption Keys: IV={}, Key={}", iv_str, key_str);
-                                                        *aes_iv.lock().unwrap() = Some(iv_str.as_bytes().to_vec());
-                                                        *aes_key.lock().unwrap() = Some(key_str.as_bytes().to_vec());
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        
-                                        if let Some(first) = text.chars().next() {
-                                            if first == '0' || first == '1' { // Data
-                                                if let Some(s) = &sender {
-                                                    // Pass keys to parse
-                                                    let iv = aes_iv.lock().unwrap().clone();
-                                                    let key = aes_key.lock().unwrap().clone();
-                                                    
-                                                    if let Some(msg) = Self::parse_ws_message(&text, &order_map_clone, iv, key) {
-                                                        let _ = s.send(msg);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    },
-                                    Message::Close(_) => break,
-                                    _ => {}
-                                }
-                            },
-                            Err(e) => {
-                                error!("WS Error: {}", e);
-                                break;
-                            }
-                        }
-                    }
-                },
-                Err(e) => error!("Connection failed: {}", e),
+        let ws = KisWebSocket::spawn(&ws_url_str, approval_key, self.debug_ws.clone(), sender.clone(), move |text, keys| {
+            if let Some(s) = &sender {
+                if let Some(msg) = Self::parse_ws_message(text, &order_map_clone, keys) {
+                    let _ = s.send(msg);
+                }
             }
         });
-        
-        let mut thread_guard = self.ws_thread.lock().unwrap();
-        *thread_guard = Some(handle);
-        
+
+        // Subscribe to Execution (Private)
+        if !my_htsid.is_empty() {
+            let tr_id = if ws_url_str.contains("openapivts") { "H0STCNI9" } else { "H0STCNI0" };
+            ws.subscribe(tr_id, &my_htsid);
+        }
+        for symbol in self.subscribed_symbols.lock().unwrap().iter() {
+            Self::subscribe_symbol(&ws, symbol);
+        }
+        *self.ws.lock().unwrap() = Some(ws);
         Ok(())
     }
+
+    fn subscribe_symbol(ws: &KisWebSocket, symbol: &str) {
+        ws.subscribe("H0SCCNT0", symbol); // Realtime Stock Conclusion (KOSPI)
+        ws.subscribe("H0UNASP0", symbol); // Asking Price (Total - 10 levels)
+        info!("Subscribed to {} Trade/Ask(Total)", symbol);
+    }
     
-    fn parse_ws_message(text: &str, order_map: &Mutex<HashMap<String, HantooOrderInfo>>, iv_opt: Option<Vec<u8>>, key_opt: Option<Vec<u8>>) -> Option<IncomingMessage> {
+    fn parse_ws_message(text: &str, order_map: &Mutex<HashMap<String, HantooOrderInfo>>, keys: Option<&WsAesKeys>) -> Option<IncomingMessage> {
         let parts: Vec<&str> = text.split('|').collect();
         if parts.len() < 4 { return None; }
         
@@ -416,11 +711,11 @@ impl HantooAdapter {
         let final_data = if tr_id == "H0STCNI0" || tr_id == "H0STCNI9" {
              // Check if it looks encrypted (Base64 is alphanumeric + +/=)
              // And if we have keys
-             if let (Some(iv), Some(key)) = (iv_opt, key_opt) {
+             if let Some(WsAesKeys { iv, key }) = keys {
                  // Try to decode base64
                  if let Ok(mut ciphertext) = BASE64.decode(&data_part) {
                      // Decrypt
-                     let decryptor = Aes256CbcDec::new_from_slices(&key, &iv).ok()?;
+                     let decryptor = Aes256CbcDec::new_from_slices(key, iv).ok()?;
                      // We need a buffer copy because decrypt works in place usually or writes to out
                      // Using decrypt_padded_mut
                      if let Ok(plaintext_slice) = decryptor.decrypt_padded_mut::<Pkcs7>(&mut ciphertext) {
@@ -451,12 +746,13 @@ impl HantooAdapter {
         
         match tr_id {
             "H0STCNT0" | "H0SCCNT0" => { // Trade
+                // 0: MKSC_SHRN_ISCD, 1: STCK_CNTG_HOUR, 2: STCK_PRPR, ... 12: CNTG_VOL
                 if fields.len() > 2 {
-                    let price = Decimal::from_str(fields[1]).unwrap_or_default();
+                    let price = Decimal::from_str(fields[2]).unwrap_or_default();
                     let qty = if fields.len() > 12 { fields[12].parse().unwrap_or(0) } else { 0 };
                    
                     return Some(IncomingMessage::MarketTrade {
-                        symbol: symbol.to_string(),
+                        symbol: fields[0].to_string(),
                         price,
                         quantity: qty,
                         timestamp: Local::now().timestamp_millis() as f64 / 1000.0,
@@ -488,17 +784,18 @@ impl HantooAdapter {
             },
             "H0STCNI0" | "H0STCNI9" => { // Execution Notice
                  // fields parsing based on ccnl_notice.py
-                 // 0: CUST_ID, 1: ACNT_NO, 2: ODER_NO, 3: ODER_QTY, ... 
-                 // 9: CNTG_QTY, 10: CNTG_UNPR, ...
+                 // 0: CUST_ID, 1: ACNT_NO, 2: ODER_NO, 3: OODER_NO, ... 
+                 // 5: RCTF_CLS (0: Order, 1: Modify, 2: Cancel), 9: CNTG_QTY, 10: CNTG_UNPR, ...
                  // 12: RFUS_YN, 13: CNTG_YN (1: Accept, 2: Execute)
                  
                  if fields.len() > 14 {
                      let order_no = fields[2];
+                     let orig_order_no = fields[3];
                      let cntg_yn = fields[13]; // 1 or 2
                      
-                     // Find Client Order ID
+                     // Find Client Order ID; a modify or cancel carries the order it revises in OODER_NO
                      let map = order_map.lock().unwrap();
-                     if let Some((client_id, info)) = map.iter().find(|(_, info)| info.order_no == order_no) {
+                     if let Some((client_id, info)) = map.iter().find(|(_, info)| info.order_no == order_no || (!orig_order_no.is_empty() && info.order_no == orig_order_no)) {
                           info!("Hantoo Parse: Found Order Map for OrderNo: {} -> ClientID: {}", order_no, client_id);
                           info!("Hantoo Parse: fields[9](qty)={}, fields[10](price)={}, cntg_yn={}, rfus_yn={}", fields[9], fields[10], cntg_yn, fields[12]);
 
@@ -514,14 +811,10 @@ impl HantooAdapter {
                                });
                           } else if cntg_yn == "1" { // Accepted / Modify / Cancel
                                let rfus_yn = fields[12];
-                               
-                               let state = if rfus_yn == "Y" { 
-                                   OrderState::REJECTED 
-                               } else { 
-                                   // Simply NEW for now, could be CANCELED if msg implies. 
-                                   // But H0STCNI0 is complex. 
-                                   // For MVP, if it is not refused, we assume NEW or PENDING->NEW.
-                                   OrderState::NEW 
+                               let Some((state, msg)) = notice_state(fields[5], rfus_yn) else {
+                                   // The order itself is untouched; reconciliation settles the pending request
+                                   warn!("Hantoo Parse: Revision of {} refused (rctf_cls={})", client_id, fields[5]);
+                                   return None;
                                };
                                
                                info!("Hantoo Parse: Update for {}, state={:?}", client_id, state);
@@ -530,7 +823,7 @@ impl HantooAdapter {
                                    state,
                                    filled_qty: 0,
                                    filled_price: None,
-                                   msg: None,
+                                   msg,
                                    updated_at: Local::now().timestamp_millis() as f64 / 1000.0,
                                });
                           }
@@ -581,32 +874,61 @@ impl HantooAdapter {
     }
 }
 
-impl Adapter for HantooAdapter {
+#[async_trait]
+impl AsyncAdapter for HantooAdapter {
     fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>) {
         self.set_monitor_internal(sender);
     }
-    fn connect(&self) -> Result<()> {
-        let _ = self.get_token()?;
+
+    fn get_order_ref(&self, order_id: &str) -> Option<Value> {
+        let map = self.order_map.lock().unwrap();
+        map.get(order_id).and_then(|info| serde_json::to_value(info).ok())
+    }
+
+    fn restore_order_ref(&self, order_id: &str, order_ref: Value) -> AdapterResult<()> {
+        let info: HantooOrderInfo = serde_json::from_value(order_ref)?;
+        self.order_map.lock().unwrap().insert(order_id.to_string(), info);
+        Ok(())
+    }
+
+    async fn get_open_orders(&self) -> AdapterResult<Vec<BrokerOrder>> {
+        Ok(self.daily_orders().await?.into_iter()
+            .filter(|o| o.remaining > 0)
+            .map(|o| {
+                let info = HantooOrderInfo { org_no: o.org_no.clone(), order_no: o.order_no.clone(), exchange: o.exchange.clone() };
+                o.to_broker_order(self.client_id_of(&o), serde_json::to_value(info).unwrap_or_default())
+            })
+            .collect())
+    }
+
+    async fn get_executions(&self) -> AdapterResult<Vec<BrokerExecution>> {
+        Ok(self.daily_orders().await?.into_iter()
+            .filter(|o| o.filled > 0)
+            .map(|o| o.to_execution(self.client_id_of(&o)))
+            .collect())
+    }
+    async fn connect(&self) -> AdapterResult<()> {
+        let _ = self.get_token().await?;
         info!("HantooAdapter connected (token verified)");
         
-        if let Err(e) = self.start_ws_thread() {
+        if let Err(e) = self.start_ws().await {
             warn!("Failed to start WebSocket: {}", e);
         }
 
         Ok(())
     }
     
-    fn subscribe(&self, symbols: &[String]) -> Result<()> {
+    async fn subscribe(&self, symbols: &[String]) -> AdapterResult<()> {
         self.subscribe_market(symbols)
     }
 
-    fn disconnect(&self) -> Result<()> {
+    async fn disconnect(&self) -> AdapterResult<()> {
+        self.ws.lock().unwrap().take();
         info!("HantooAdapter disconnected");
         Ok(())
     }
 
-    fn place_order(&self, order: &Order) -> Result<bool> {
-        let token = self.get_token()?;
+    async fn place_order(&self, order: &Order) -> AdapterResult<bool> {
         let url = format!("{}/uapi/domestic-stock/v1/trading/order-cash", self.config.prod);
         
         let is_virtual = self.config.prod.contains("openapivts");
@@ -638,52 +960,35 @@ impl Adapter for HantooAdapter {
             "CNDT_PRIC": ""
         });
 
-        let resp = self.client.post(&url)
+        let data = self.send_authorized(|token| self.client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &self.config.my_app)
             .header("appsecret", &self.config.my_sec)
             .header("tr_id", tr_id)
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-            
-        if resp.status().is_success() {
-             let text = resp.text().unwrap_or_default();
-             let data: Value = serde_json::from_str(&text).map_err(|e| anyhow!("Failed to parse response: {}", e))?;
-
-             if data["rt_cd"].as_str().unwrap_or("") != "0" {
-                 let msg = data["msg1"].as_str().unwrap_or("Unknown error");
-                 error!("Order placement API error: {}", msg);
-                 return Ok(false);
-             }
-             
-             if let Some(output) = data.get("output") {
-                 let org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("").to_string();
-                 let order_no = output["ODNO"].as_str().unwrap_or("").to_string();
-                 let exchange = order.exchange.clone();
-
-                 if !org_no.is_empty() && !order_no.is_empty() {
-                     info!("Order Placed: OrgNo={}, OrderNo={}, Exhange={}", org_no, order_no, exchange);
-                     
-                     if let Some(client_id) = &order.order_id {
-                          let info = HantooOrderInfo { org_no, order_no, exchange };
-                          let mut map = self.order_map.lock().unwrap();
-                          map.insert(client_id.clone(), info);
-                     }
-                 }
-             }
+            .json(&body)).await.inspect_err(|e| error!("Order placement failed: {}", e))?;
 
-             Ok(true)
-        } else {
-             let text = resp.text().unwrap_or_default();
-             error!("Order placement failed: {}", text);
-             Ok(false)
+        if let Some(output) = data.get("output") {
+            let org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("").to_string();
+            let order_no = output["ODNO"].as_str().unwrap_or("").to_string();
+            let exchange = order.exchange.clone();
+
+            if !org_no.is_empty() && !order_no.is_empty() {
+                info!("Order Placed: OrgNo={}, OrderNo={}, Exhange={}", org_no, order_no, exchange);
+
+                if let Some(client_id) = &order.order_id {
+                     let info = HantooOrderInfo { org_no, order_no, exchange };
+                     let mut map = self.order_map.lock().unwrap();
+                     map.insert(client_id.clone(), info);
+                }
+            }
         }
+
+        Ok(true)
     }
 
-    fn cancel_order(&self, order_id: &str) -> Result<bool> {
-        let token = self.get_token()?;
+    async fn cancel_order(&self, order_id: &str) -> AdapterResult<bool> {
         let url = format!("{}/uapi/domestic-stock/v1/trading/order-rvsecncl", self.config.prod);
         
         let (org_no, order_no, exchange) = {
@@ -691,7 +996,7 @@ impl Adapter for HantooAdapter {
             match map.get(order_id) {
                 Some(info) => (info.org_no.clone(), info.order_no.clone(), info.exchange.clone()),
                 None => {
-                    return Err(anyhow!("Order ID not found in local map: {}", order_id));
+                    return Err(AdapterError::UnknownOrder(order_id.to_string()));
                 }
             }
         };
@@ -715,36 +1020,19 @@ impl Adapter for HantooAdapter {
             "EXCG_ID_DVSN_CD": exchange
         });
 
-        let resp = self.client.post(&url)
+        self.send_authorized(|token| self.client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &self.config.my_app)
             .header("appsecret", &self.config.my_sec)
             .header("tr_id", tr_id)
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-
-        if resp.status().is_success() {
-             let text = resp.text().unwrap_or_default();
-             let data: Value = serde_json::from_str(&text)?;
-             if data["rt_cd"].as_str().unwrap_or("") == "0" {
-                 info!("Order Cancelled: {}", order_id);
-                 Ok(true)
-             } else {
-                 let msg = data["msg1"].as_str().unwrap_or("Unknown error");
-                 error!("Cancel failed API error: {}", msg);
-                 Err(anyhow!("Cancel failed: {}", msg))
-             }
-        } else {
-             let text = resp.text().unwrap_or_default();
-             error!("Cancel Request failed: {}", text);
-             Ok(false)
-        }
+            .json(&body)).await.inspect_err(|e| error!("Cancel failed: {}", e))?;
+        info!("Order Cancelled: {}", order_id);
+        Ok(true)
     }
 
-    fn get_order_book_snapshot(&self, symbol: &str) -> Result<OrderBook> {
-        let token = self.get_token()?;
+    async fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook> {
         let url = format!("{}/uapi/domestic-stock/v1/quotations/inquire-asking-price-exp-ccn", self.config.prod);
         
         let tr_id = "FHKST01010200";
@@ -754,23 +1042,13 @@ impl Adapter for HantooAdapter {
             ("FID_INPUT_ISCD", symbol)
         ];
 
-        let resp = self.client.get(&url)
+        let data = self.send_authorized(|token| self.client.get(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &self.config.my_app)
             .header("appsecret", &self.config.my_sec)
             .header("tr_id", tr_id)
-            .query(&params)
-            .send()?;
-
-        if !resp.status().is_success() {
-             return Err(anyhow!("Snapshot failed: {}", resp.status()));
-        }
-
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
-        }
+            .query(&params)).await?;
         
         let mut ob = OrderBook::new(symbol.to_string());
         if let Some(out1) = data["output1"].as_object() {
@@ -802,8 +1080,7 @@ impl Adapter for HantooAdapter {
         Ok(ob)
     }
 
-    fn get_account_snapshot(&self, _account_id: &str) -> Result<AccountState> {
-        let token = self.get_token()?;
+    async fn get_account_snapshot(&self, _account_id: &str) -> AdapterResult<AccountState> {
         let url = format!("{}/uapi/domestic-stock/v1/trading/inquire-balance", self.config.prod);
         
         let cano_config = self.config.my_acct.as_deref().unwrap_or("");
@@ -832,25 +1109,13 @@ impl Adapter for HantooAdapter {
             ("CTX_AREA_NK100", "")
         ];
 
-        let resp = self.client.get(&url)
+        let data = self.send_authorized(|token| self.client.get(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &self.config.my_app)
             .header("appsecret", &self.config.my_sec)
             .header("tr_id", tr_id)
-            .query(&params)
-            .send()?;
-
-        if !resp.status().is_success() {
-             let text = resp.text().unwrap_or_default();
-             return Err(anyhow!("Account snapshot failed: {}", text));
-        }
-
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             let msg = data["msg1"].as_str().unwrap_or("Unknown error");
-             return Err(anyhow!("API Error: {}", msg));
-        }
+            .query(&params)).await?;
 
         let mut acct = AccountState::new();
         
@@ -885,8 +1150,7 @@ impl Adapter for HantooAdapter {
         Ok(acct)
     }
 
-    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> Result<bool> {
-        let token = self.get_token()?;
+    async fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool> {
         let url = format!("{}/uapi/domestic-stock/v1/trading/order-rvsecncl", self.config.prod);
 
         let (org_no, order_no, exchange) = {
@@ -894,7 +1158,7 @@ impl Adapter for HantooAdapter {
             match map.get(order_id) {
                 Some(info) => (info.org_no.clone(), info.order_no.clone(), info.exchange.clone()),
                 None => {
-                    return Err(anyhow!("Order ID not found in local map: {}", order_id));
+                    return Err(AdapterError::UnknownOrder(order_id.to_string()));
                 }
             }
         };
@@ -925,44 +1189,28 @@ impl Adapter for HantooAdapter {
             "CNDT_PRIC": ""
         });
 
-        let resp = self.client.post(&url)
+        let data = self.send_authorized(|token| self.client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &self.config.my_app)
             .header("appsecret", &self.config.my_sec)
             .header("tr_id", tr_id)
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-
-        if resp.status().is_success() {
-             let text = resp.text().unwrap_or_default();
-             let data: Value = serde_json::from_str(&text)?;
-             if data["rt_cd"].as_str().unwrap_or("") == "0" {
-                 info!("Order Modified: {}", order_id);
-                 
-                 if let Some(output) = data.get("output") {
-                     let new_order_no = output["ODNO"].as_str().unwrap_or("");
-                     let new_org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("");
-                     if !new_order_no.is_empty() && !new_org_no.is_empty() {
-                         let mut map = self.order_map.lock().unwrap();
-                         if let Some(info) = map.get_mut(order_id) {
-                             info.order_no = new_order_no.to_string();
-                             info.org_no = new_org_no.to_string();
-                         }
-                     }
-                 }
-                 
-                 Ok(true)
-             } else {
-                 let msg = data["msg1"].as_str().unwrap_or("Unknown error");
-                 error!("Modify failed API error: {}", msg);
-                 Ok(false)
-             }
-        } else {
-             let text = resp.text().unwrap_or_default();
-             error!("Modify Request failed: {}", text);
-             Ok(false)
+            .json(&body)).await.inspect_err(|e| error!("Modify failed: {}", e))?;
+        info!("Order Modified: {}", order_id);
+
+        if let Some(output) = data.get("output") {
+            let new_order_no = output["ODNO"].as_str().unwrap_or("");
+            let new_org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("");
+            if !new_order_no.is_empty() && !new_org_no.is_empty() {
+                let mut map = self.order_map.lock().unwrap();
+                if let Some(info) = map.get_mut(order_id) {
+                    info.order_no = new_order_no.to_string();
+                    info.org_no = new_org_no.to_string();
+                }
+            }
         }
+
+        Ok(true)
     }
 }
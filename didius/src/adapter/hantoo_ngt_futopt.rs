@@ -1,17 +1,16 @@
-use crate::adapter::Adapter;
+use crate::adapter::{runtime, AdapterError, AdapterResult, AsyncAdapter, BrokerExecution, BrokerOrder};
 use crate::oms::account::{AccountState, Position};
-use crate::oms::order::{Order, OrderSide, OrderType, OrderState};
+use crate::oms::order::{Order, OrderSide, OrderType};
 use crate::oms::order_book::OrderBook;
 use anyhow::{anyhow, Result};
-use log::{error, info};
+use async_trait::async_trait;
+use log::{error, info, warn};
 // use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use crate::adapter::hantoo::HantooAdapter;
-use tungstenite::{connect, Message};
-use url::Url;
-use std::thread;
+use crate::adapter::hantoo::{notice_state, DailyOrder, HantooAdapter, HantooConfig, KisWebSocket};
 use std::sync::mpsc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use crate::adapter::{IncomingMessage, Trade};
@@ -24,16 +23,18 @@ use chrono::Local;
 const NIGHT_ORDER_TR_ID: &str = "STTN1101U"; // Night Future Order (Real)
 const NIGHT_CANCEL_TR_ID: &str = "STTN1103U"; // Night Future Cancel (Assumed)
 const NIGHT_BALANCE_TR_ID: &str = "CTFN6118R"; // Night Balance
+const NIGHT_CCNL_TR_ID: &str = "STTN5201R"; // Night Order/Execution Inquiry
 const URL_ORDER: &str = "/uapi/domestic-futureoption/v1/trading/order";
 const URL_CANCEL: &str = "/uapi/domestic-futureoption/v1/trading/order-rvsecncl";
 const URL_BALANCE: &str = "/uapi/domestic-futureoption/v1/trading/inquire-ngt-balance";
+const URL_CCNL: &str = "/uapi/domestic-futureoption/v1/trading/inquire-ngt-ccnl";
 const URL_LIST_FUTURE: &str = "/uapi/domestic-futureoption/v1/quotations/display-board-futures";
 const URL_LIST_OPTION: &str = "/uapi/domestic-futureoption/v1/quotations/display-board-option-list";
 
 const TR_ID_LIST_FUTURE: &str = "FHPIF05030200";
 const TR_ID_LIST_OPTION: &str = "FHPIO056104C0";
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct NightOrderInfo {
     org_no: String,
     order_no: String,
@@ -49,6 +50,10 @@ enum NightIncomingEvent {
 #[derive(Debug)]
 struct NightNotice {
     order_no: String,
+    /// Order a modify or cancel revises
+    orig_order_no: String,
+    /// 0: order, 1: modify, 2: cancel
+    rctf_cls: String,
     cntg_yn: String,
     fill_qty: String,
     fill_price: String,
@@ -58,24 +63,32 @@ struct NightNotice {
 pub struct HantooNightAdapter {
     inner: HantooAdapter,
     order_map: Arc<Mutex<HashMap<String, NightOrderInfo>>>,
-    ws_thread: Mutex<Option<thread::JoinHandle<()>>>,
+    /// Single connection shared by every subscribed symbol
+    ws: Mutex<Option<KisWebSocket>>,
     sender: Mutex<Option<mpsc::Sender<IncomingMessage>>>,
     debug_ws: Arc<AtomicBool>,
 }
 
 impl HantooNightAdapter {
     pub fn new(config_path: &str) -> Result<Self> {
-        let inner = HantooAdapter::new(config_path)?;
+        Ok(Self::with_inner(HantooAdapter::new(config_path)?))
+    }
+
+    pub fn from_config(config: HantooConfig) -> Self {
+        Self::with_inner(HantooAdapter::from_config(config))
+    }
+
+    fn with_inner(inner: HantooAdapter) -> Self {
         let acct = inner.config().my_acct.clone().unwrap_or_default();
         let prod = inner.config().my_prod.clone().unwrap_or_default();
         println!("HantooNightAdapter initialized with Account: {}, Prod: {}", acct, prod);
-        Ok(HantooNightAdapter {
+        HantooNightAdapter {
             inner,
             order_map: Arc::new(Mutex::new(HashMap::new())),
-            ws_thread: Mutex::new(None),
+            ws: Mutex::new(None),
             sender: Mutex::new(None),
             debug_ws: Arc::new(AtomicBool::new(false)),
-        })
+        }
     }
     
     pub fn set_debug_mode(&self, enabled: bool) {
@@ -88,116 +101,54 @@ impl HantooNightAdapter {
     }
 
     pub fn subscribe(&self, symbol: &str) -> Result<()> {
-        let symbol = symbol.to_string();
-        self.start_ws_thread(symbol)?;
+        runtime::block_on(self.subscribe_symbols(&[symbol.to_string()]))?;
         Ok(())
     }
 
-    fn start_ws_thread(&self, symbol: String) -> Result<()> {
-        let config = self.inner.config().clone();
+    async fn subscribe_symbols(&self, symbols: &[String]) -> AdapterResult<()> {
+        self.start_ws().await?;
+        let guard = self.ws.lock().unwrap();
+        if let Some(ws) = guard.as_ref() {
+            for symbol in symbols {
+                ws.subscribe("H0MFCNT0", symbol); // Realtime Night Future Conclusion
+                ws.subscribe("H0MFASP0", symbol); // Night Future Asking Price
+                info!("Subscribed to Night Future Trade/Ask {}", symbol);
+            }
+        }
+        Ok(())
+    }
+
+    /// Open the WebSocket on first use, with the private execution notices (H0MFCNI0).
+    async fn start_ws(&self) -> Result<()> {
+        if self.ws.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let config = self.inner.config();
         let ws_url_str = config.ops.clone().ok_or(anyhow!("No WebSocket URL (ops) in config"))?;
-        let approval_key = self.inner.get_ws_approval_key()?;
-        
+        let approval_key = self.inner.get_ws_approval_key().await?;
+
+        let mut guard = self.ws.lock().unwrap();
+        if guard.is_some() {
+            return Ok(());
+        }
         let sender = self.sender.lock().unwrap().clone();
-        let debug_ws = self.debug_ws.clone();
         let order_map_clone = self.order_map.clone();
-
-        let handle = thread::spawn(move || {
-            let full_url = format!("{}/tryitout/H0STCNT0", ws_url_str); 
-            let url = Url::parse(&full_url).expect("Invalid WS URL");
-
-            info!("NightAdapter connecting to WebSocket: {}", url);
-            match connect(url) {
-                Ok((mut socket, _)) => {
-                    info!("Night WS Connected.");
-
-                    // Subscribe to Night Future Trade (H0MFCNT0)
-                    // Note: TR_ID in body should be H0MFCNT0 for Night Future Trade?
-                    // The example uses `H0MFCNT0` (Realtime Night Future Conclusion)
-                    let tr_id = "H0MFCNT0";
-                    let sub_body = serde_json::json!({
-                        "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                        "body": {"input": {"tr_id": tr_id, "tr_key": symbol}} 
-                    });
-                    
-                    if let Err(e) = socket.send(Message::Text(sub_body.to_string())) {
-                        error!("Failed to subscribe to trade: {}", e);
-                        return;
-                    }
-                    info!("Subscribed to Night Future Trade {} (H0MFCNT0)", symbol);
-
-                    // Subscribe to Night Future Asking Price (H0MFASP0)
-                    let tr_id_ask = "H0MFASP0";
-                    let sub_body_ask = serde_json::json!({
-                        "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                        "body": {"input": {"tr_id": tr_id_ask, "tr_key": symbol}} 
-                    });
-                    
-                    if let Err(e) = socket.send(Message::Text(sub_body_ask.to_string())) {
-                        error!("Failed to subscribe to ask: {}", e);
-                        // Continue even if ask fails
-                    } else {
-                        info!("Subscribed to Night Future Ask {} (H0MFASP0)", symbol);
+        let ws = KisWebSocket::spawn(&ws_url_str, approval_key, self.debug_ws.clone(), sender.clone(), move |text, _| {
+            if let Some(s) = &sender {
+                if let Some(event) = Self::parse_ws_message(text) {
+                    if let Some(m) = Self::process_event(event, &order_map_clone) {
+                        let _ = s.send(m);
                     }
-
-                    // Subscribe to Private Execution Notices (H0MFCNI0)
-                    let my_htsid = config.my_htsid.clone().unwrap_or_default();
-                    if !my_htsid.is_empty() {
-                         let tr_id_notice = "H0MFCNI0";
-                         let sub_body_notice = serde_json::json!({
-                            "header": {"approval_key": approval_key, "custtype": "P", "tr_type": "1", "content-type": "utf-8"},
-                            "body": {"input": {"tr_id": tr_id_notice, "tr_key": my_htsid}}
-                         });
-                         if let Err(e) = socket.send(Message::Text(sub_body_notice.to_string())) {
-                             error!("Failed to subscribe to notice: {}", e);
-                         } else {
-                             info!("Subscribed to Night Future Notice (H0MFCNI0) for {}", my_htsid);
-                         }
-                    }
-
-                    loop {
-                        match socket.read() {
-                            Ok(msg) => {
-                                match msg {
-                                    Message::Text(text) => {
-                                        if debug_ws.load(Ordering::Relaxed) {
-                                            println!("[{}] WS_RECV: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), text);
-                                        }
-                                        if text.contains("PINGPONG") {
-                                            let _ = socket.send(Message::Text(text)); 
-                                            continue;
-                                        }
-                                        
-                                        if let Some(first) = text.chars().next() {
-                                            if first == '0' || first == '1' {
-                                                if let Some(s) = &sender {
-                                                    if let Some(event) = Self::parse_ws_message(&text) {
-                                                        if let Some(m) = Self::process_event(event, &order_map_clone) {
-                                                            let _ = s.send(m);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    },
-                                    Message::Close(_) => break,
-                                    _ => {}
-                                }
-                            },
-                            Err(e) => {
-                                error!("WS Error: {}", e);
-                                break;
-                            }
-                        }
-                    }
-                },
-                Err(e) => error!("Connection failed: {}", e),
+                }
             }
         });
 
-        let mut thread_guard = self.ws_thread.lock().unwrap();
-        *thread_guard = Some(handle);
-        
+        let my_htsid = config.my_htsid.clone().unwrap_or_default();
+        if !my_htsid.is_empty() {
+            ws.subscribe("H0MFCNI0", &my_htsid);
+            info!("Subscribed to Night Future Notice (H0MFCNI0) for {}", my_htsid);
+        }
+        *guard = Some(ws);
         Ok(())
     }
 
@@ -230,6 +181,7 @@ impl HantooNightAdapter {
                         price,
                         quantity: qty,
                         timestamp: Local::now().timestamp_millis() as f64 / 1000.0,
+                        venue: "hantoo_night".to_string(),
                     }));
                 }
             },
@@ -266,6 +218,8 @@ impl HantooNightAdapter {
             "H0MFCNI0" => { // Night Future Execution/Order Notice
                 if fields.len() > 13 {
                     let order_no = fields[2].to_string();
+                    let orig_order_no = fields[3].to_string();
+                    let rctf_cls = fields[5].to_string();
                     let cntg_yn = fields[13].to_string();
                     let fill_qty = fields[9].to_string();
                     let fill_price = fields[10].to_string();
@@ -273,6 +227,8 @@ impl HantooNightAdapter {
                     
                     return Some(NightIncomingEvent::Notice(NightNotice {
                         order_no,
+                        orig_order_no,
+                        rctf_cls,
                         cntg_yn,
                         fill_qty,
                         fill_price,
@@ -298,7 +254,8 @@ impl HantooNightAdapter {
             NightIncomingEvent::Snapshot(s) => Some(IncomingMessage::OrderBookSnapshot(s)),
             NightIncomingEvent::Notice(n) => {
                 let map = order_map.lock().unwrap();
-                if let Some((client_id, _)) = map.iter().find(|(_, info)| info.order_no == n.order_no) {
+                let revises = |info: &NightOrderInfo| !n.orig_order_no.is_empty() && info.order_no == n.orig_order_no;
+                if let Some((client_id, _)) = map.iter().find(|(_, info)| info.order_no == n.order_no || revises(info)) {
                     if n.cntg_yn == "2" { // Execution
                         let fill_qty = n.fill_qty.parse::<i64>().unwrap_or(0);
                         let fill_price = Decimal::from_str(&n.fill_price).unwrap_or_default();
@@ -310,17 +267,17 @@ impl HantooNightAdapter {
                             fill_price,
                         });
                     } else { // Accept/Modify/Cancel/Reject
-                         // Use n.rfus_yn if needed
-                         let _ = n.rfus_yn; 
-                         
-                         let state = OrderState::NEW; // Default to NEW/OPEN
+                         let Some((state, msg)) = notice_state(&n.rctf_cls, &n.rfus_yn) else {
+                             warn!("Night: Revision of {} refused (rctf_cls={})", client_id, n.rctf_cls);
+                             return None;
+                         };
                          
                          return Some(IncomingMessage::OrderStatus {
                              order_id: client_id.clone(),
                              state,
                              filled_qty: 0,
                              filled_price: None,
-                             msg: None,
+                             msg,
                              updated_at: Local::now().timestamp_millis() as f64 / 1000.0,
                          });
                     }
@@ -332,118 +289,176 @@ impl HantooNightAdapter {
         }
     }
 
-    pub fn get_night_future_list(&self) -> Result<Vec<Value>> {
-        let token = self.inner.get_token()?;
-        let client = self.inner.client();
+    /// Orders of the current night session. The session opens the evening before the
+    /// trading date, so the inquiry spans yesterday and today.
+    async fn night_orders(&self) -> AdapterResult<Vec<DailyOrder>> {
         let config = self.inner.config();
-
-        let url = format!("{}{}", config.prod, URL_LIST_FUTURE);
-
+        let today = Local::now();
         let params = [
-            ("FID_COND_MRKT_DIV_CODE", "F"),
-            ("FID_COND_SCR_DIV_CODE", "20503"),
-            ("FID_COND_MRKT_CLS_CODE", "MKI")
+            ("CANO", config.my_acct_future.clone().unwrap_or_default()),
+            ("ACNT_PRDT_CD", config.my_prod_future.clone().unwrap_or("01".to_string())),
+            ("STRT_ORD_DT", (today - chrono::Duration::days(1)).format("%Y%m%d").to_string()),
+            ("END_ORD_DT", today.format("%Y%m%d").to_string()),
+            ("SLL_BUY_DVSN_CD", "00".to_string()),
+            ("CCLD_NCCS_DVSN", "00".to_string()),
+            ("SORT_SQN", "DS".to_string()),
+            ("STRT_ODNO", "".to_string()),
+            ("PDNO", "".to_string()),
+            ("MKET_ID_CD", "".to_string()),
+            ("FUOP_DVSN_CD", "".to_string()),
+            ("SCRN_DVSN", "02".to_string()),
         ];
+        let rows = self.inner.inquire_pages(URL_CCNL, NIGHT_CCNL_TR_ID, &params, ("CTX_AREA_FK200", "CTX_AREA_NK200")).await?;
+        Ok(DailyOrder::merge_revisions(rows.iter().map(|r| DailyOrder::from_row(r, true)).collect()))
+    }
 
-        let resp = client.get(&url)
-            .header("content-type", "application/json")
-            .header("authorization", format!("Bearer {}", token))
-            .header("appkey", &config.my_app)
-            .header("appsecret", &config.my_sec)
-            .header("tr_id", TR_ID_LIST_FUTURE)
-            .header("custtype", "P")
-            .query(&params)
-            .send()?;
+    fn client_id_of(&self, order: &DailyOrder) -> Option<String> {
+        let map = self.order_map.lock().unwrap();
+        map.iter().find(|(_, info)| order.order_nos.contains(&info.order_no)).map(|(id, _)| id.clone())
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-             let text = resp.text().unwrap_or_default();
-             return Err(anyhow!("List Future API failed: {} - {}", status, text));
-        }
+    pub fn get_night_future_list(&self) -> Result<Vec<Value>> {
+        runtime::block_on(async {
+            let token = self.inner.get_token().await?;
+            let client = self.inner.client();
+            let config = self.inner.config();
+
+            let url = format!("{}{}", config.prod, URL_LIST_FUTURE);
+
+            let params = [
+                ("FID_COND_MRKT_DIV_CODE", "F"),
+                ("FID_COND_SCR_DIV_CODE", "20503"),
+                ("FID_COND_MRKT_CLS_CODE", "MKI")
+            ];
+
+            let resp = client.get(&url)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .header("appkey", &config.my_app)
+                .header("appsecret", &config.my_sec)
+                .header("tr_id", TR_ID_LIST_FUTURE)
+                .header("custtype", "P")
+                .query(&params)
+                .send().await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                 let text = resp.text().await.unwrap_or_default();
+                 return Err(anyhow!("List Future API failed: {} - {}", status, text));
+            }
 
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
-        }
+            let data: Value = resp.json().await?;
+            if data["rt_cd"].as_str().unwrap_or("") != "0" {
+                 return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
+            }
 
-        // Return output (array)
-        if let Some(list) = data["output"].as_array() {
-            Ok(list.clone())
-        } else {
-            Ok(vec![])
-        }
+            // Return output (array)
+            if let Some(list) = data["output"].as_array() {
+                Ok(list.clone())
+            } else {
+                Ok(vec![])
+            }
+        })
     }
 
     pub fn get_night_option_list(&self) -> Result<Vec<Value>> {
-        let token = self.inner.get_token()?;
-        let client = self.inner.client();
-        let config = self.inner.config();
+        runtime::block_on(async {
+            let token = self.inner.get_token().await?;
+            let client = self.inner.client();
+            let config = self.inner.config();
+
+            let url = format!("{}{}", config.prod, URL_LIST_OPTION);
+
+            let params = [
+                ("FID_COND_SCR_DIV_CODE", "509"),
+                // Optional params, seems empty string in example is fine or omitted?
+                // "FID_COND_MRKT_DIV_CODE": "",
+                // "FID_COND_MRKT_CLS_CODE": ""
+            ];
+
+            let resp = client.get(&url)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .header("appkey", &config.my_app)
+                .header("appsecret", &config.my_sec)
+                .header("tr_id", TR_ID_LIST_OPTION)
+                .header("custtype", "P")
+                .query(&params)
+                .send().await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                 let text = resp.text().await.unwrap_or_default();
+                 return Err(anyhow!("List Option API failed: {} - {}", status, text));
+            }
 
-        let url = format!("{}{}", config.prod, URL_LIST_OPTION);
+            let data: Value = resp.json().await?;
+            if data["rt_cd"].as_str().unwrap_or("") != "0" {
+                 return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
+            }
 
-        let params = [
-            ("FID_COND_SCR_DIV_CODE", "509"),
-            // Optional params, seems empty string in example is fine or omitted?
-            // "FID_COND_MRKT_DIV_CODE": "",
-            // "FID_COND_MRKT_CLS_CODE": ""
-        ];
+            // Return output (array)
+            if let Some(list) = data["output"].as_array() {
+                Ok(list.clone())
+            } else {
+                Ok(vec![])
+            }
+        })
+    }
+}
 
-        let resp = client.get(&url)
-            .header("content-type", "application/json")
-            .header("authorization", format!("Bearer {}", token))
-            .header("appkey", &config.my_app)
-            .header("appsecret", &config.my_sec)
-            .header("tr_id", TR_ID_LIST_OPTION)
-            .header("custtype", "P")
-            .query(&params)
-            .send()?;
+#[async_trait]
+impl AsyncAdapter for HantooNightAdapter {
+    fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>) {
+        self.set_monitor_internal(sender);
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-             let text = resp.text().unwrap_or_default();
-             return Err(anyhow!("List Option API failed: {} - {}", status, text));
-        }
+    fn get_order_ref(&self, order_id: &str) -> Option<Value> {
+        let map = self.order_map.lock().unwrap();
+        map.get(order_id).and_then(|info| serde_json::to_value(info).ok())
+    }
 
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
-        }
+    fn restore_order_ref(&self, order_id: &str, order_ref: Value) -> AdapterResult<()> {
+        let info: NightOrderInfo = serde_json::from_value(order_ref)?;
+        self.order_map.lock().unwrap().insert(order_id.to_string(), info);
+        Ok(())
+    }
 
-        // Return output (array)
-        if let Some(list) = data["output"].as_array() {
-            Ok(list.clone())
-        } else {
-            Ok(vec![])
-        }
+    async fn get_open_orders(&self) -> AdapterResult<Vec<BrokerOrder>> {
+        Ok(self.night_orders().await?.into_iter()
+            .filter(|o| o.remaining > 0)
+            .map(|o| {
+                let info = NightOrderInfo { org_no: o.org_no.clone(), order_no: o.order_no.clone() };
+                o.to_broker_order(self.client_id_of(&o), serde_json::to_value(info).unwrap_or_default())
+            })
+            .collect())
     }
-}
 
-impl Adapter for HantooNightAdapter {
-    fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>) {
-        self.set_monitor_internal(sender);
+    async fn get_executions(&self) -> AdapterResult<Vec<BrokerExecution>> {
+        Ok(self.night_orders().await?.into_iter()
+            .filter(|o| o.filled > 0)
+            .map(|o| o.to_execution(self.client_id_of(&o)))
+            .collect())
     }
-    fn connect(&self) -> Result<()> {
+    async fn connect(&self) -> AdapterResult<()> {
         // Reuse inner logic to verify token
-        let _ = self.inner.get_token()?;
+        let _ = self.inner.get_token().await?;
         info!("HantooNightAdapter connected (Token valid)");
-        // No WS for Night for now
+        // The WebSocket opens on the first subscribe
         Ok(())
     }
 
-    fn subscribe(&self, symbols: &[String]) -> Result<()> {
-        for s in symbols {
-            self.subscribe(s)?;
-        }
-        Ok(())
+    async fn subscribe(&self, symbols: &[String]) -> AdapterResult<()> {
+        self.subscribe_symbols(symbols).await
     }
 
-    fn disconnect(&self) -> Result<()> {
+    async fn disconnect(&self) -> AdapterResult<()> {
+        self.ws.lock().unwrap().take();
         info!("HantooNightAdapter disconnected");
         Ok(())
     }
 
-    fn place_order(&self, order: &Order) -> Result<bool> {
-        let token = self.inner.get_token()?;
+    async fn place_order(&self, order: &Order) -> AdapterResult<bool> {
         let client = self.inner.client();
         let config = self.inner.config();
         
@@ -487,50 +502,35 @@ impl Adapter for HantooNightAdapter {
         let body_str = serde_json::to_string_pretty(&body).unwrap_or_default();
         println!("Night Order Request: URL={} Body={}", url, body_str);
 
-        let resp = client.post(&url)
+        let data = self.inner.send_authorized(|token| client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &config.my_app)
             .header("appsecret", &config.my_sec)
             .header("tr_id", NIGHT_ORDER_TR_ID)
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-            
-        let status = resp.status();
-        let text = resp.text().unwrap_or_default();
-        println!("Night Order Response: Status={} Body={}", status, text);
-
-        if status.is_success() {
-             let data: Value = serde_json::from_str(&text).map_err(|e| anyhow!("Parse error: {}", e))?;
-             
-             if let Some(output) = data.get("output") {
-                 let org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("").to_string();
-                 let order_no = output["ODNO"].as_str().unwrap_or("").to_string();
-                 
-                 if !org_no.is_empty() && !order_no.is_empty() {
-                     println!("Night Order Placed: Org={}, No={}", org_no, order_no);
-                     
-                     if let Some(client_id) = &order.order_id {
-                         let mut map = self.order_map.lock().unwrap();
-                         map.insert(client_id.clone(), NightOrderInfo { org_no, order_no });
-                     }
-                 }
-                 Ok(true)
-             } else {
-                 // Check if rt_cd != 0
-                 let msg = data["msg1"].as_str().unwrap_or("Unknown");
-                 println!("Night Order Failed (RT!=0): {}", msg);
-                 Ok(false)
-             }
+            .json(&body)).await.inspect_err(|e| println!("Night Order Failed: {}", e))?;
+        println!("Night Order Response: {}", data);
+
+        if let Some(output) = data.get("output") {
+            let org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("").to_string();
+            let order_no = output["ODNO"].as_str().unwrap_or("").to_string();
+
+            if !org_no.is_empty() && !order_no.is_empty() {
+                println!("Night Order Placed: Org={}, No={}", org_no, order_no);
+
+                if let Some(client_id) = &order.order_id {
+                    let mut map = self.order_map.lock().unwrap();
+                    map.insert(client_id.clone(), NightOrderInfo { org_no, order_no });
+                }
+            }
+            Ok(true)
         } else {
-             println!("Night Order Request Failed: {}", text);
-             Ok(false)
+            Err(AdapterError::InvalidResponse(format!("No output in order response: {}", data)))
         }
     }
 
-    fn cancel_order(&self, order_id: &str) -> Result<bool> {
-        let token = self.inner.get_token()?;
+    async fn cancel_order(&self, order_id: &str) -> AdapterResult<bool> {
         let client = self.inner.client();
         let config = self.inner.config();
         
@@ -538,7 +538,7 @@ impl Adapter for HantooNightAdapter {
             let map = self.order_map.lock().unwrap();
             match map.get(order_id) {
                 Some(i) => (i.org_no.clone(), i.order_no.clone()),
-                None => return Err(anyhow!("Order not found in map")),
+                None => return Err(AdapterError::UnknownOrder(order_id.to_string())),
             }
         };
 
@@ -558,28 +558,19 @@ impl Adapter for HantooNightAdapter {
             "EXCG_ID_DVSN_CD": "KRX" // Assume this is still required?
         });
 
-        let resp = client.post(&url)
+        self.inner.send_authorized(|token| client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &config.my_app)
             .header("appsecret", &config.my_sec)
             .header("tr_id", NIGHT_CANCEL_TR_ID)
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-            
-        if resp.status().is_success() {
-             info!("Night Cancel Success for {}", order_id);
-             Ok(true)
-        } else {
-             let t = resp.text().unwrap_or_default();
-             error!("Night Cancel Failed: {}", t);
-             Ok(false)
-        }
+            .json(&body)).await.inspect_err(|e| error!("Night Cancel Failed: {}", e))?;
+        info!("Night Cancel Success for {}", order_id);
+        Ok(true)
     }
 
-    fn get_order_book_snapshot(&self, symbol: &str) -> Result<OrderBook> {
-        let token = self.inner.get_token()?;
+    async fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook> {
         let client = self.inner.client();
         let config = self.inner.config();
         
@@ -591,26 +582,14 @@ impl Adapter for HantooNightAdapter {
             ("FID_INPUT_ISCD", symbol)
         ];
 
-        let resp = client.get(&url)
+        let data = self.inner.send_authorized(|token| client.get(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &config.my_app)
             .header("appsecret", &config.my_sec)
             .header("tr_id", "FHMIF10010000") // ASK PRICE TR_ID
             .header("custtype", "P")
-            .query(&params)
-            .send()?;
-            
-        if !resp.status().is_success() {
-             let status = resp.status();
-             let t = resp.text().unwrap_or_default();
-             return Err(anyhow!("Snapshot API Failed {}: {}", status, t));
-        }
-        
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
-        }
+            .query(&params)).await?;
         
         let mut ob = OrderBook::new(symbol.to_string());
         ob.timestamp = Local::now().timestamp_millis() as f64 / 1000.0;
@@ -660,8 +639,7 @@ impl Adapter for HantooNightAdapter {
         Ok(ob)
     }
 
-    fn get_account_snapshot(&self, _account_id: &str) -> Result<AccountState> {
-        let token = self.inner.get_token()?;
+    async fn get_account_snapshot(&self, _account_id: &str) -> AdapterResult<AccountState> {
         let client = self.inner.client();
         let config = self.inner.config();
         
@@ -680,23 +658,13 @@ impl Adapter for HantooNightAdapter {
             ("CTX_AREA_NK200", "")
         ];
 
-        let resp = client.get(&url)
+        let data = self.inner.send_authorized(|token| client.get(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &config.my_app)
             .header("appsecret", &config.my_sec)
             .header("tr_id", NIGHT_BALANCE_TR_ID)
-            .query(&params)
-            .send()?;
-
-        if !resp.status().is_success() {
-            return Err(anyhow!("Balance API failed: {}", resp.status()));
-        }
-
-        let data: Value = resp.json()?;
-        if data["rt_cd"].as_str().unwrap_or("") != "0" {
-             return Err(anyhow!("API Error: {}", data["msg1"].as_str().unwrap_or("")));
-        }
+            .query(&params)).await?;
 
         let mut acct = AccountState::new();
         
@@ -740,8 +708,7 @@ impl Adapter for HantooNightAdapter {
         Ok(acct)
     }
 
-    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> Result<bool> {
-        let token = self.inner.get_token()?;
+    async fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool> {
         let client = self.inner.client();
         let config = self.inner.config();
         
@@ -749,7 +716,7 @@ impl Adapter for HantooNightAdapter {
             let map = self.order_map.lock().unwrap();
             match map.get(order_id) {
                 Some(i) => (i.org_no.clone(), i.order_no.clone()),
-                None => return Err(anyhow!("Order not found in map")),
+                None => return Err(AdapterError::UnknownOrder(order_id.to_string())),
             }
         };
 
@@ -776,42 +743,31 @@ impl Adapter for HantooNightAdapter {
             "EXCG_ID_DVSN_CD": "KRX" 
         });
 
-        let resp = client.post(&url)
+        let data = self.inner.send_authorized(|token| client.post(&url)
             .header("content-type", "application/json")
             .header("authorization", format!("Bearer {}", token))
             .header("appkey", &config.my_app)
             .header("appsecret", &config.my_sec)
             .header("tr_id", NIGHT_CANCEL_TR_ID) 
             .header("custtype", "P")
-            .json(&body)
-            .send()?;
-            
-        if resp.status().is_success() {
-             info!("Night Modify Success for {}", order_id);
-             
-             let text = resp.text().unwrap_or_default();
-             if let Ok(data) = serde_json::from_str::<Value>(&text) {
-                 if let Some(output) = data.get("output") {
-                     let new_order_no = output["ODNO"].as_str().unwrap_or("");
-                     let new_org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("");
-                     if !new_order_no.is_empty() {
-                         let mut map = self.order_map.lock().unwrap();
-                         if let Some(info) = map.get_mut(order_id) {
-                             info.order_no = new_order_no.to_string();
-                             if !new_org_no.is_empty() {
-                                 info.org_no = new_org_no.to_string();
-                             }
-                         }
-                     }
-                 }
-             }
-             
-             Ok(true)
-        } else {
-             let t = resp.text().unwrap_or_default();
-             error!("Night Modify Failed: {}", t);
-             Ok(false)
+            .json(&body)).await.inspect_err(|e| error!("Night Modify Failed: {}", e))?;
+        info!("Night Modify Success for {}", order_id);
+
+        if let Some(output) = data.get("output") {
+            let new_order_no = output["ODNO"].as_str().unwrap_or("");
+            let new_org_no = output["KRX_FWDG_ORD_ORGNO"].as_str().unwrap_or("");
+            if !new_order_no.is_empty() {
+                let mut map = self.order_map.lock().unwrap();
+                if let Some(info) = map.get_mut(order_id) {
+                    info.order_no = new_order_no.to_string();
+                    if !new_org_no.is_empty() {
+                        info.org_no = new_org_no.to_string();
+                    }
+                }
+            }
         }
+
+        Ok(true)
     }
 }
 
@@ -0,0 +1,689 @@
+//! In-process stand-in for the KIS (Hantoo) OpenAPI, for adapter tests without network.
+//!
+//! Serves the REST endpoints used by `HantooAdapter` / `HantooNightAdapter` on `prod`
+//! and a WebSocket feed on `ops`. Orders are acknowledged through the execution notice
+//! stream (AES-256-CBC encrypted for `H0STCNI0`, like the real service).
+use crate::adapter::hantoo::HantooConfig;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use block_padding::Pkcs7;
+use cbc::cipher::{BlockEncryptMut, KeyIvInit};
+use chrono::{Duration as ChronoDuration, Local};
+use log::{info, warn};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::Message;
+
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+
+pub const STUB_APP_KEY: &str = "stub-app-key";
+pub const STUB_APP_SECRET: &str = "stub-app-secret";
+pub const STUB_HTS_ID: &str = "stubuser";
+const STUB_AES_KEY: &str = "0123456789abcdef0123456789abcdef";
+const STUB_AES_IV: &str = "fedcba9876543210";
+const STUB_ORG_NO: &str = "91252";
+
+/// A REST call received by the stub.
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    pub path: String,
+    pub tr_id: Option<String>,
+    pub authorization: Option<String>,
+    pub query: HashMap<String, String>,
+    pub body: Value,
+}
+
+/// An order accepted by the stub.
+#[derive(Debug, Clone)]
+pub struct StubOrder {
+    pub order_no: String,
+    pub symbol: String,
+    pub side: String, // "BUY" / "SELL"
+    pub quantity: i64,
+    pub price: Decimal,
+    pub filled: i64,
+    pub filled_amount: Decimal,
+    pub canceled: bool,
+    pub night: bool,
+    /// Order this one revises (empty for a new order)
+    pub orig_order_no: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StubPosition {
+    pub symbol: String,
+    pub quantity: i64,
+    pub average_price: Decimal,
+    pub current_price: Decimal,
+}
+
+type Levels = Vec<(Decimal, i64)>;
+
+struct Frame {
+    tr_id: String,
+    key: String,
+    text: String,
+}
+
+struct WsClient {
+    subscriptions: Arc<Mutex<HashSet<(String, String)>>>,
+    sender: mpsc::Sender<Frame>,
+}
+
+#[derive(Default)]
+struct StubState {
+    requests: Vec<StubRequest>,
+    tokens: HashSet<String>,
+    token_requests: usize,
+    token_seq: usize,
+    order_seq: u64,
+    orders: Vec<StubOrder>,
+    order_reject: Option<(String, String)>,
+    mute_notices: bool,
+    books: HashMap<String, (Levels, Levels)>,
+    cash: Decimal,
+    positions: Vec<StubPosition>,
+}
+
+pub struct HantooStubServer {
+    rest_addr: String,
+    ws_addr: String,
+    state: Arc<Mutex<StubState>>,
+    clients: Arc<Mutex<Vec<WsClient>>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl HantooStubServer {
+    /// Bind REST and WebSocket listeners on ephemeral localhost ports.
+    pub fn start() -> Result<Self> {
+        let rest = TcpListener::bind("127.0.0.1:0")?;
+        let ws = TcpListener::bind("127.0.0.1:0")?;
+        let server = HantooStubServer {
+            rest_addr: format!("http://{}", rest.local_addr()?),
+            ws_addr: format!("ws://{}", ws.local_addr()?),
+            state: Arc::new(Mutex::new(StubState::default())),
+            clients: Arc::new(Mutex::new(Vec::new())),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        };
+
+        let state = server.state.clone();
+        let clients = server.clients.clone();
+        let shutdown = server.shutdown.clone();
+        thread::spawn(move || {
+            for stream in rest.incoming() {
+                if shutdown.load(Ordering::Relaxed) { break; }
+                let Ok(stream) = stream else { continue };
+                let state = state.clone();
+                let clients = clients.clone();
+                thread::spawn(move || {
+                    if let Err(e) = Self::serve_http(stream, &state, &clients) {
+                        warn!("Stub HTTP error: {}", e);
+                    }
+                });
+            }
+        });
+
+        let clients = server.clients.clone();
+        let shutdown = server.shutdown.clone();
+        thread::spawn(move || {
+            for stream in ws.incoming() {
+                if shutdown.load(Ordering::Relaxed) { break; }
+                let Ok(stream) = stream else { continue };
+                let (tx, rx) = mpsc::channel();
+                let subscriptions = Arc::new(Mutex::new(HashSet::new()));
+                clients.lock().unwrap().push(WsClient { subscriptions: subscriptions.clone(), sender: tx });
+                let shutdown = shutdown.clone();
+                thread::spawn(move || Self::serve_ws(stream, subscriptions, rx, shutdown));
+            }
+        });
+
+        info!("Hantoo stub listening: REST {} WS {}", server.rest_addr, server.ws_addr);
+        Ok(server)
+    }
+
+    /// Config pointing both adapters at this stub. `auth_dir` receives the token cache.
+    pub fn config(&self, auth_dir: &str) -> HantooConfig {
+        HantooConfig {
+            my_app: STUB_APP_KEY.to_string(),
+            my_sec: STUB_APP_SECRET.to_string(),
+            prod: self.rest_addr.clone(),
+            my_acct: Some("12345678".to_string()),
+            my_acct_future: Some("87654321".to_string()),
+            my_prod: Some("01".to_string()),
+            my_prod_future: Some("03".to_string()),
+            my_htsid: Some(STUB_HTS_ID.to_string()),
+            ops: Some(self.ws_addr.clone()),
+            auth_dir: Some(auth_dir.to_string()),
+        }
+    }
+
+    pub fn rest_url(&self) -> &str {
+        &self.rest_addr
+    }
+
+    pub fn ws_url(&self) -> &str {
+        &self.ws_addr
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn token_requests(&self) -> usize {
+        self.state.lock().unwrap().token_requests
+    }
+
+    /// Invalidate every issued access token (requests then fail with EGW00123).
+    pub fn expire_tokens(&self) {
+        self.state.lock().unwrap().tokens.clear();
+    }
+
+    pub fn orders(&self) -> Vec<StubOrder> {
+        self.state.lock().unwrap().orders.clone()
+    }
+
+    /// Reject subsequent orders with the given KIS `msg_cd` / `msg1` (None to accept again).
+    pub fn set_order_reject(&self, reject: Option<(&str, &str)>) {
+        self.state.lock().unwrap().order_reject = reject.map(|(c, m)| (c.to_string(), m.to_string()));
+    }
+
+    pub fn set_order_book(&self, symbol: &str, bids: Vec<(Decimal, i64)>, asks: Vec<(Decimal, i64)>) {
+        self.state.lock().unwrap().books.insert(symbol.to_string(), (bids, asks));
+    }
+
+    pub fn set_balance(&self, cash: Decimal, positions: Vec<StubPosition>) {
+        let mut st = self.state.lock().unwrap();
+        st.cash = cash;
+        st.positions = positions;
+    }
+
+    /// Wait until some WebSocket client subscribed to (tr_id, key).
+    pub fn wait_for_subscription(&self, tr_id: &str, key: &str, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            let found = self.clients.lock().unwrap().iter()
+                .any(|c| c.subscriptions.lock().unwrap().contains(&(tr_id.to_string(), key.to_string())));
+            if found { return true; }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    /// Number of WebSocket clients currently subscribed to (tr_id, key).
+    pub fn subscription_count(&self, tr_id: &str, key: &str) -> usize {
+        let key = (tr_id.to_string(), key.to_string());
+        self.clients.lock().unwrap().iter().filter(|c| c.subscriptions.lock().unwrap().contains(&key)).count()
+    }
+
+    /// Send a raw frame to subscribers of (tr_id, key).
+    pub fn push_frame(&self, tr_id: &str, key: &str, text: String) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|c| c.sender.send(Frame { tr_id: tr_id.to_string(), key: key.to_string(), text: text.clone() }).is_ok());
+    }
+
+    /// Realtime stock trade (H0STCNT0 / H0SCCNT0).
+    pub fn push_trade(&self, symbol: &str, price: Decimal, qty: i64) {
+        let mut f = vec!["0".to_string(); 46];
+        f[0] = symbol.to_string();
+        f[1] = Local::now().format("%H%M%S").to_string();
+        f[2] = price.to_string();
+        f[12] = qty.to_string();
+        for tr_id in ["H0STCNT0", "H0SCCNT0"] {
+            self.push_frame(tr_id, symbol, format!("0|{}|001|{}", tr_id, f.join("^")));
+        }
+    }
+
+    /// Realtime 10-level asking price (H0UNASP0).
+    pub fn push_asking_price(&self, symbol: &str, bids: &[(Decimal, i64)], asks: &[(Decimal, i64)]) {
+        let mut f = vec!["0".to_string(); 59];
+        f[0] = symbol.to_string();
+        f[1] = Local::now().format("%H%M%S").to_string();
+        for (i, (p, q)) in asks.iter().take(10).enumerate() {
+            f[3 + i] = p.to_string();
+            f[23 + i] = q.to_string();
+        }
+        for (i, (p, q)) in bids.iter().take(10).enumerate() {
+            f[13 + i] = p.to_string();
+            f[33 + i] = q.to_string();
+        }
+        self.push_frame("H0UNASP0", symbol, format!("0|H0UNASP0|001|{}", f.join("^")));
+    }
+
+    /// Realtime night future trade (H0MFCNT0).
+    pub fn push_night_trade(&self, symbol: &str, price: Decimal, qty: i64) {
+        let mut f = vec!["0".to_string(); 20];
+        f[0] = symbol.to_string();
+        f[5] = price.to_string();
+        f[9] = qty.to_string();
+        self.push_frame("H0MFCNT0", symbol, format!("0|H0MFCNT0|001|{}", f.join("^")));
+    }
+
+    /// Realtime 5-level night future asking price (H0MFASP0).
+    pub fn push_night_asking_price(&self, symbol: &str, bids: &[(Decimal, i64)], asks: &[(Decimal, i64)]) {
+        let mut f = vec!["0".to_string(); 40];
+        f[0] = symbol.to_string();
+        for (i, (p, q)) in asks.iter().take(5).enumerate() {
+            f[2 + i] = p.to_string();
+            f[22 + i] = q.to_string();
+        }
+        for (i, (p, q)) in bids.iter().take(5).enumerate() {
+            f[7 + i] = p.to_string();
+            f[27 + i] = q.to_string();
+        }
+        self.push_frame("H0MFASP0", symbol, format!("0|H0MFASP0|001|{}", f.join("^")));
+    }
+
+    /// Drop execution notices until re-enabled, like a WebSocket gap. Orders and fills
+    /// still show up in the order/execution inquiries.
+    pub fn set_notices_muted(&self, muted: bool) {
+        self.state.lock().unwrap().mute_notices = muted;
+    }
+
+    /// Execute `qty` of an accepted order and publish the execution notice.
+    pub fn fill(&self, order_no: &str, qty: i64, price: Decimal) -> Result<()> {
+        let order = {
+            let mut st = self.state.lock().unwrap();
+            let o = st.orders.iter_mut().find(|o| o.order_no == order_no)
+                .ok_or_else(|| anyhow!("Unknown order_no {}", order_no))?;
+            o.filled += qty;
+            o.filled_amount += price * Decimal::from(qty);
+            o.clone()
+        };
+        self.notify(&order, "2", "N", qty, price);
+        Ok(())
+    }
+
+    /// Cancel an order from outside the OMS (e.g. HTS) and publish the notice.
+    pub fn cancel(&self, order_no: &str) -> Result<()> {
+        let order = {
+            let mut st = self.state.lock().unwrap();
+            let o = st.orders.iter_mut().find(|o| o.order_no == order_no)
+                .ok_or_else(|| anyhow!("Unknown order_no {}", order_no))?;
+            o.canceled = true;
+            o.clone()
+        };
+        self.notify(&order, "1", "N", 0, Decimal::ZERO);
+        Ok(())
+    }
+
+    /// An order entered from another terminal, unknown to the adapters. Returns its order number.
+    pub fn add_external_order(&self, symbol: &str, side: &str, quantity: i64, price: Decimal, night: bool) -> String {
+        let mut st = self.state.lock().unwrap();
+        st.order_seq += 1;
+        let order = StubOrder {
+            order_no: format!("{:010}", st.order_seq),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity,
+            price,
+            filled: 0,
+            filled_amount: Decimal::ZERO,
+            canceled: false,
+            night,
+            orig_order_no: String::new(),
+        };
+        st.orders.push(order.clone());
+        order.order_no
+    }
+
+    fn notify(&self, order: &StubOrder, cntg_yn: &str, rfus_yn: &str, qty: i64, price: Decimal) {
+        if !self.state.lock().unwrap().mute_notices {
+            Self::publish_notice(&self.clients, order, cntg_yn, rfus_yn, qty, price);
+        }
+    }
+
+    /// Exchange refusal notice for an accepted order.
+    pub fn refuse(&self, order_no: &str) -> Result<()> {
+        let order = {
+            let st = self.state.lock().unwrap();
+            st.orders.iter().find(|o| o.order_no == order_no).cloned()
+                .ok_or_else(|| anyhow!("Unknown order_no {}", order_no))?
+        };
+        self.notify(&order, "1", "Y", 0, Decimal::ZERO);
+        Ok(())
+    }
+
+    fn publish_notice(clients: &Mutex<Vec<WsClient>>, o: &StubOrder, cntg_yn: &str, rfus_yn: &str, qty: i64, price: Decimal) {
+        let side_cd = if o.side == "SELL" { "01" } else { "02" };
+        // RCTF_CLS: 0 order, 1 modify, 2 cancel
+        let rctf_cls = if o.canceled { "2" } else if o.orig_order_no.is_empty() { "0" } else { "1" };
+        let (tr_id, text) = if o.night {
+            let mut f = vec!["".to_string(); 20];
+            f[0] = STUB_HTS_ID.to_string();
+            f[2] = o.order_no.clone();
+            f[3] = o.orig_order_no.clone();
+            f[4] = side_cd.to_string();
+            f[5] = rctf_cls.to_string();
+            f[7] = o.symbol.clone();
+            f[9] = qty.to_string();
+            f[10] = price.to_string();
+            f[11] = rfus_yn.to_string();
+            f[13] = cntg_yn.to_string();
+            ("H0MFCNI0", format!("0|H0MFCNI0|001|{}", f.join("^")))
+        } else {
+            // 0: CUST_ID, 1: ACNT_NO, 2: ODER_NO, 3: OODER_NO, 4: SELN_BYOV_CLS, 5: RCTF_CLS, 8: STCK_SHRN_ISCD,
+            // 9: CNTG_QTY, 10: CNTG_UNPR, 11: STCK_CNTG_HOUR, 12: RFUS_YN, 13: CNTG_YN, 16: ODER_QTY
+            let mut f = vec!["".to_string(); 23];
+            f[0] = STUB_HTS_ID.to_string();
+            f[1] = "1234567801".to_string();
+            f[2] = o.order_no.clone();
+            f[3] = o.orig_order_no.clone();
+            f[4] = side_cd.to_string();
+            f[5] = rctf_cls.to_string();
+            f[8] = o.symbol.clone();
+            f[9] = qty.to_string();
+            f[10] = price.to_string();
+            f[11] = Local::now().format("%H%M%S").to_string();
+            f[12] = rfus_yn.to_string();
+            f[13] = cntg_yn.to_string();
+            f[14] = "Y".to_string();
+            f[16] = o.quantity.to_string();
+            ("H0STCNI0", format!("1|H0STCNI0|001|{}", Self::encrypt(&f.join("^"))))
+        };
+        let mut clients = clients.lock().unwrap();
+        clients.retain(|c| c.sender.send(Frame { tr_id: tr_id.to_string(), key: STUB_HTS_ID.to_string(), text: text.clone() }).is_ok());
+    }
+
+    fn encrypt(plaintext: &str) -> String {
+        let bytes = plaintext.as_bytes();
+        let mut buf = vec![0u8; bytes.len() + 16];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        let enc = Aes256CbcEnc::new_from_slices(STUB_AES_KEY.as_bytes(), STUB_AES_IV.as_bytes()).unwrap();
+        let ct = enc.encrypt_padded_mut::<Pkcs7>(&mut buf, bytes.len()).unwrap();
+        BASE64.encode(ct)
+    }
+
+    fn serve_ws(stream: TcpStream, subscriptions: Arc<Mutex<HashSet<(String, String)>>>, frames: mpsc::Receiver<Frame>, shutdown: Arc<AtomicBool>) {
+        let mut socket = match tungstenite::accept(stream) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Stub WS handshake failed: {}", e);
+                return;
+            }
+        };
+        let _ = socket.get_ref().set_read_timeout(Some(Duration::from_millis(10)));
+
+        while !shutdown.load(Ordering::Relaxed) {
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    let Ok(req) = serde_json::from_str::<Value>(&text) else { continue };
+                    let tr_id = req["body"]["input"]["tr_id"].as_str().unwrap_or("").to_string();
+                    let tr_key = req["body"]["input"]["tr_key"].as_str().unwrap_or("").to_string();
+                    let tr_type = req["header"]["tr_type"].as_str().unwrap_or("1");
+                    if tr_type == "1" {
+                        subscriptions.lock().unwrap().insert((tr_id.clone(), tr_key.clone()));
+                    } else {
+                        subscriptions.lock().unwrap().remove(&(tr_id.clone(), tr_key.clone()));
+                    }
+                    let mut output = json!({});
+                    if tr_id.ends_with("CNI0") || tr_id.ends_with("CNI9") {
+                        output = json!({"iv": STUB_AES_IV, "key": STUB_AES_KEY});
+                    }
+                    let resp = json!({
+                        "header": {"tr_id": tr_id, "tr_key": tr_key, "encrypt": "N"},
+                        "body": {"rt_cd": "0", "msg_cd": "OPSP0000", "msg1": "SUBSCRIBE SUCCESS", "output": output}
+                    });
+                    if socket.send(Message::Text(resp.to_string())).is_err() { break; }
+                }
+                Ok(Message::Close(_)) => break,
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => break,
+            }
+
+            while let Ok(frame) = frames.try_recv() {
+                let subscribed = subscriptions.lock().unwrap().contains(&(frame.tr_id.clone(), frame.key.clone()));
+                if subscribed && socket.send(Message::Text(frame.text)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn serve_http(stream: TcpStream, state: &Mutex<StubState>, clients: &Mutex<Vec<WsClient>>) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("").to_string();
+        let target = parts.next().unwrap_or("").to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 { break; }
+            let line = line.trim_end();
+            if line.is_empty() { break; }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+            }
+        }
+        let len: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body)?;
+
+        let (path, query_str) = target.split_once('?').unwrap_or((target.as_str(), ""));
+        let query = url::form_urlencoded::parse(query_str.as_bytes()).into_owned().collect();
+        let req = StubRequest {
+            method,
+            path: path.to_string(),
+            tr_id: headers.get("tr_id").cloned(),
+            authorization: headers.get("authorization").cloned(),
+            query,
+            body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+        };
+
+        let (status, resp, notice) = Self::route(&req, state);
+        state.lock().unwrap().requests.push(req);
+
+        let text = resp.to_string();
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\ncontent-type: application/json; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            if status == 200 { "OK" } else { "Internal Server Error" },
+            text.len(),
+            text
+        )?;
+        stream.flush()?;
+
+        // The accept notice trails the REST response, as on the real service
+        if let Some(order) = notice {
+            thread::sleep(Duration::from_millis(20));
+            if !state.lock().unwrap().mute_notices {
+                Self::publish_notice(clients, &order, "1", "N", 0, Decimal::ZERO);
+            }
+        }
+        Ok(())
+    }
+
+    fn route(req: &StubRequest, state: &Mutex<StubState>) -> (u16, Value, Option<StubOrder>) {
+        let path = req.path.as_str();
+
+        if path == "/oauth2/tokenP" || path == "/oauth2/Approval" {
+            let secret_field = if path == "/oauth2/tokenP" { "appsecret" } else { "secretkey" };
+            if req.body["appkey"].as_str() != Some(STUB_APP_KEY) || req.body[secret_field].as_str() != Some(STUB_APP_SECRET) {
+                return (403, json!({"error_code": "EGW00105", "error_description": "유효하지 않은 AppSecret입니다."}), None);
+            }
+            if path == "/oauth2/Approval" {
+                return (200, json!({"approval_key": "stub-approval-key"}), None);
+            }
+            let mut st = state.lock().unwrap();
+            st.token_requests += 1;
+            st.token_seq += 1;
+            let token = format!("stub-token-{}", st.token_seq);
+            st.tokens.insert(token.clone());
+            let expires = (Local::now() + ChronoDuration::hours(24)).format("%Y-%m-%d %H:%M:%S").to_string();
+            return (200, json!({
+                "access_token": token,
+                "access_token_token_expired": expires,
+                "token_type": "Bearer",
+                "expires_in": 86400
+            }), None);
+        }
+
+        // Everything under /uapi requires a live token
+        let token = req.authorization.as_deref().and_then(|a| a.strip_prefix("Bearer ")).unwrap_or("");
+        if !state.lock().unwrap().tokens.contains(token) {
+            return (500, json!({"rt_cd": "1", "msg_cd": "EGW00123", "msg1": "기간이 만료된 token 입니다."}), None);
+        }
+
+        if path.ends_with("/trading/order-cash") || path.ends_with("/trading/order") {
+            Self::handle_order(req, state)
+        } else if path.ends_with("/trading/order-rvsecncl") {
+            Self::handle_revise_cancel(req, state)
+        } else if path.ends_with("/trading/inquire-balance") || path.ends_with("/trading/inquire-ngt-balance") {
+            let st = state.lock().unwrap();
+            let positions: Vec<Value> = st.positions.iter().map(|p| json!({
+                "pdno": p.symbol,
+                "hldg_qty": p.quantity.to_string(),
+                "pchs_avg_pric": p.average_price.to_string(),
+                "prpr": p.current_price.to_string(),
+            })).collect();
+            let summary = json!({"dnca_tot_amt": st.cash.to_string()});
+            // Stock balance returns output2 as an array, night balance as an object
+            let output2 = if path.ends_with("inquire-balance") { json!([summary]) } else { summary };
+            (200, json!({"rt_cd": "0", "msg_cd": "KIOK0510", "msg1": "조회가 완료되었습니다", "output1": positions, "output2": output2}), None)
+        } else if path.ends_with("/trading/inquire-daily-ccld") || path.ends_with("/trading/inquire-ngt-ccnl") {
+            let night = path.ends_with("inquire-ngt-ccnl");
+            let st = state.lock().unwrap();
+            let rows: Vec<Value> = st.orders.iter().filter(|o| o.night == night).map(|o| {
+                let remaining = if o.canceled { 0 } else { o.quantity - o.filled };
+                let avg = if o.filled > 0 { o.filled_amount / Decimal::from(o.filled) } else { Decimal::ZERO };
+                let side_cd = if o.side == "SELL" { "01" } else { "02" };
+                if night {
+                    json!({
+                        "ord_gno_brno": STUB_ORG_NO, "odno": o.order_no, "orgn_odno": o.orig_order_no,
+                        "sll_buy_dvsn_cd": side_cd, "pdno": o.symbol, "ord_dvsn_cd": "01",
+                        "ord_qty": o.quantity.to_string(), "ord_idx": o.price.to_string(),
+                        "tot_ccld_qty": o.filled.to_string(), "avg_idx": avg.to_string(),
+                        "qty": remaining.to_string()
+                    })
+                } else {
+                    json!({
+                        "ord_gno_brno": STUB_ORG_NO, "odno": o.order_no, "orgn_odno": o.orig_order_no,
+                        "sll_buy_dvsn_cd": side_cd, "pdno": o.symbol, "ord_dvsn_cd": "00",
+                        "ord_qty": o.quantity.to_string(), "ord_unpr": o.price.to_string(),
+                        "tot_ccld_qty": o.filled.to_string(), "tot_ccld_amt": o.filled_amount.to_string(),
+                        "avg_prvs": avg.to_string(), "rmn_qty": remaining.to_string(),
+                        "cncl_yn": if o.canceled { "Y" } else { "N" }, "excg_id_dvsn_cd": "KRX"
+                    })
+                }
+            }).collect();
+            (200, json!({"rt_cd": "0", "msg_cd": "KIOK0460", "msg1": "조회 되었습니다.", "output1": rows, "output2": {}}), None)
+        } else if path.ends_with("/quotations/inquire-asking-price-exp-ccn") || path.ends_with("/quotations/inquire-asking-price") {
+            let symbol = req.query.get("FID_INPUT_ISCD").cloned().unwrap_or_default();
+            let st = state.lock().unwrap();
+            let mut out = serde_json::Map::new();
+            if let Some((bids, asks)) = st.books.get(&symbol) {
+                for (i, (p, q)) in asks.iter().take(10).enumerate() {
+                    out.insert(format!("askp{}", i + 1), json!(p.to_string()));
+                    out.insert(format!("askp_rsqn{}", i + 1), json!(q.to_string()));
+                }
+                for (i, (p, q)) in bids.iter().take(10).enumerate() {
+                    out.insert(format!("bidp{}", i + 1), json!(p.to_string()));
+                    out.insert(format!("bidp_rsqn{}", i + 1), json!(q.to_string()));
+                }
+            }
+            (200, json!({"rt_cd": "0", "msg_cd": "MCA00000", "msg1": "정상처리 되었습니다.", "output1": out, "output2": {}}), None)
+        } else {
+            (404, json!({"rt_cd": "1", "msg_cd": "EGW00001", "msg1": format!("Unknown path {}", path)}), None)
+        }
+    }
+
+    fn handle_order(req: &StubRequest, state: &Mutex<StubState>) -> (u16, Value, Option<StubOrder>) {
+        let night = req.path.contains("domestic-futureoption");
+        let b = &req.body;
+        let order = {
+            let mut st = state.lock().unwrap();
+            if let Some((code, msg)) = st.order_reject.clone() {
+                return (200, json!({"rt_cd": "1", "msg_cd": code, "msg1": msg}), None);
+            }
+            st.order_seq += 1;
+            let (symbol, price, side) = if night {
+                let side = if b["SLL_BUY_DVSN_CD"].as_str() == Some("01") { "SELL" } else { "BUY" };
+                (b["SHTN_PDNO"].as_str(), b["UNIT_PRICE"].as_str(), side)
+            } else {
+                // TTTC0011U / VTTC0011U: sell, TTTC0012U / VTTC0012U: buy
+                let side = if req.tr_id.as_deref().unwrap_or("").ends_with("0011U") { "SELL" } else { "BUY" };
+                (b["PDNO"].as_str(), b["ORD_UNPR"].as_str(), side)
+            };
+            let order = StubOrder {
+                order_no: format!("{:010}", st.order_seq),
+                symbol: symbol.unwrap_or("").to_string(),
+                side: side.to_string(),
+                quantity: b["ORD_QTY"].as_str().and_then(|q| q.parse().ok()).unwrap_or(0),
+                price: price.and_then(|p| p.parse().ok()).unwrap_or_default(),
+                filled: 0,
+                filled_amount: Decimal::ZERO,
+                canceled: false,
+                night,
+                orig_order_no: String::new(),
+            };
+            st.orders.push(order.clone());
+            order
+        };
+        (200, json!({
+            "rt_cd": "0", "msg_cd": "APBK0013", "msg1": "주문 전송 완료 되었습니다.",
+            "output": {"KRX_FWDG_ORD_ORGNO": STUB_ORG_NO, "ODNO": order.order_no, "ORD_TMD": Local::now().format("%H%M%S").to_string()}
+        }), Some(order))
+    }
+
+    fn handle_revise_cancel(req: &StubRequest, state: &Mutex<StubState>) -> (u16, Value, Option<StubOrder>) {
+        let b = &req.body;
+        let orig = b["ORGN_ODNO"].as_str().unwrap_or("");
+        let mut st = state.lock().unwrap();
+        let Some(idx) = st.orders.iter().position(|o| o.order_no == orig && !o.canceled) else {
+            return (200, json!({"rt_cd": "1", "msg_cd": "APBK0915", "msg1": "원주문번호가 존재하지 않습니다."}), None);
+        };
+        st.order_seq += 1;
+        let new_no = format!("{:010}", st.order_seq);
+
+        let is_cancel = b["RVSE_CNCL_DVSN_CD"].as_str() == Some("02");
+        let notice = {
+            let o = &mut st.orders[idx];
+            if is_cancel {
+                o.canceled = true;
+                o.clone()
+            } else {
+                // A revision is a new exchange order carrying the open quantity
+                let mut revised = o.clone();
+                o.canceled = true;
+                revised.order_no = new_no.clone();
+                revised.orig_order_no = orig.to_string();
+                revised.filled_amount = Decimal::ZERO;
+                revised.quantity = b["ORD_QTY"].as_str().and_then(|q| q.parse().ok()).filter(|q| *q > 0).unwrap_or(o.quantity - o.filled);
+                revised.filled = 0;
+                revised.price = b["ORD_UNPR"].as_str().and_then(|p| p.parse().ok()).unwrap_or_default();
+                revised
+            }
+        };
+        if !is_cancel {
+            st.orders.push(notice.clone());
+        }
+        drop(st);
+
+        (200, json!({
+            "rt_cd": "0", "msg_cd": "APBK0013", "msg1": "주문 전송 완료 되었습니다.",
+            "output": {"KRX_FWDG_ORD_ORGNO": STUB_ORG_NO, "ODNO": new_no, "ORD_TMD": Local::now().format("%H%M%S").to_string()}
+        }), Some(notice))
+    }
+}
+
+impl Drop for HantooStubServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // Wake the accept loops so they observe the flag
+        let _ = TcpStream::connect(self.rest_addr.trim_start_matches("http://"));
+        let _ = TcpStream::connect(self.ws_addr.trim_start_matches("ws://"));
+    }
+}
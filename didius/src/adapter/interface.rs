@@ -1,3 +1,34 @@
-// This file is intentionally left empty as the adapter interface is now managed internally by Client.
-// Previous Python wrappers (PyHantooAdapter, PyHantooNightAdapter) have been removed
-// in favor of the factory pattern in Client::new.
+use pyo3::prelude::*;
+use std::sync::Arc;
+use crate::adapter::Adapter;
+use crate::adapter::error::to_py_err;
+
+/// Build the adapter of a venue kind for the Python entry points (`Client`, `OMSEngine`).
+/// `config_path` is the auth config for Hantoo and the log file or directory for replay.
+pub fn build_adapter(venue: &str, config_path: Option<String>) -> PyResult<Arc<dyn Adapter>> {
+    let adapter: Arc<dyn Adapter> = match venue {
+        "hantoo" => {
+            let config = config_path.ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Config path required for Hantoo"))?;
+            let a = crate::adapter::hantoo::HantooAdapter::new(&config)
+                .map_err(to_py_err)?;
+            Arc::new(a)
+        },
+        "hantoo_night" => {
+            let config = config_path.ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Config path required for Hantoo Night"))?;
+            let a = crate::adapter::hantoo_ngt_futopt::HantooNightAdapter::new(&config)
+                 .map_err(to_py_err)?;
+            Arc::new(a)
+        },
+        "mock" => {
+            Arc::new(crate::adapter::mock::MockAdapter::new())
+        },
+        "replay" => {
+            let path = config_path.ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Log file or directory path required for Replay"))?;
+            let a = crate::adapter::replay::ReplayAdapter::new(&path, crate::adapter::replay::ReplayPace::Recorded { speed: 1.0 })
+                .map_err(to_py_err)?;
+            Arc::new(a)
+        },
+        _ => return Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown venue: {}", venue))),
+    };
+    Ok(adapter)
+}
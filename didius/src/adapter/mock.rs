@@ -1,71 +1,494 @@
-use crate::oms::order::Order;
-use crate::oms::order_book::OrderBook;
+use crate::oms::order::{Order, OrderSide, OrderType, OrderState};
+use crate::oms::order_book::{OrderBook, OrderBookSnapshot};
 use crate::oms::account::{AccountState};
-use crate::adapter::Adapter;
-use anyhow::Result;
-use std::sync::Mutex;
+use crate::adapter::{Adapter, AdapterError, AdapterResult};
+use anyhow::anyhow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{mpsc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use rust_decimal::Decimal;
-// use std::collections::HashMap;
+use chrono::Local;
 use crate::adapter::IncomingMessage;
+use crate::message::ConnectionStatus;
+
+/// An order resting in the simulated book.
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    order_id: String,
+    remaining: i64,
+}
+
+/// Per-symbol simulated exchange book.
+/// `bids`/`asks` hold our resting orders in price-time priority,
+/// `depth` holds injected market liquidity (other participants).
+#[derive(Debug, Clone)]
+struct SimBook {
+    bids: BTreeMap<Decimal, VecDeque<RestingOrder>>,
+    asks: BTreeMap<Decimal, VecDeque<RestingOrder>>,
+    depth: OrderBook,
+}
+
+impl SimBook {
+    fn new(symbol: &str) -> Self {
+        SimBook {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            depth: OrderBook::new(symbol.to_string()),
+        }
+    }
+
+    /// Aggregated view of injected depth plus resting orders.
+    fn to_order_book(&self) -> OrderBook {
+        let mut ob = self.depth.clone();
+        for (p, q) in &self.bids {
+            *ob.bids.entry(*p).or_insert(0) += q.iter().map(|o| o.remaining).sum::<i64>();
+        }
+        for (p, q) in &self.asks {
+            *ob.asks.entry(*p).or_insert(0) += q.iter().map(|o| o.remaining).sum::<i64>();
+        }
+        ob
+    }
+}
+
+/// Simulated order tracked by the mock exchange.
+#[derive(Debug, Clone)]
+struct SimOrder {
+    symbol: String,
+    side: OrderSide,
+    order_type: OrderType,
+    price: Option<Decimal>,
+    quantity: i64,
+    filled: i64,
+    state: OrderState,
+}
+
+#[derive(Debug, Default)]
+struct Exchange {
+    books: HashMap<String, SimBook>,
+    orders: HashMap<String, SimOrder>,
+}
+
+/// Fill produced by the matcher: (order_id, qty, price).
+type Fill = (String, i64, Decimal);
 
 pub struct MockAdapter {
     account_state: Mutex<AccountState>,
+    exchange: Mutex<Exchange>,
+    sender: Mutex<Option<mpsc::Sender<IncomingMessage>>>,
+    // When set, every new order is refused by the "exchange" with this reason.
+    reject_reason: Mutex<Option<String>>,
+    publish_market_data: AtomicBool,
 }
 
 impl MockAdapter {
     pub fn new() -> Self {
         MockAdapter {
             account_state: Mutex::new(AccountState::new()),
+            exchange: Mutex::new(Exchange::default()),
+            sender: Mutex::new(None),
+            reject_reason: Mutex::new(None),
+            publish_market_data: AtomicBool::new(true),
         }
     }
-    
+
     pub fn with_account_state(state: AccountState) -> Self {
-        MockAdapter {
-            account_state: Mutex::new(state),
-        }
+        let adapter = Self::new();
+        adapter.set_account_state(state);
+        adapter
     }
-    
+
     pub fn set_account_state(&self, state: AccountState) {
         let mut guard = self.account_state.lock().unwrap();
         *guard = state;
     }
+
+    /// Refuse every subsequent order with `reason` (None to accept again).
+    pub fn set_reject_reason(&self, reason: Option<String>) {
+        *self.reject_reason.lock().unwrap() = reason;
+    }
+
+    /// Replace the market depth of a symbol (liquidity from other participants).
+    /// Resting orders crossed by the new depth are filled at their limit price,
+    /// and the resulting book is published to the monitor.
+    pub fn push_order_book(&self, snapshot: OrderBookSnapshot) {
+        let mut out = Vec::new();
+        {
+            let mut ex = self.exchange.lock().unwrap();
+            let book = ex.books.entry(snapshot.symbol.clone()).or_insert_with(|| SimBook::new(&snapshot.symbol));
+            book.depth.rebuild(snapshot.bids, snapshot.asks, snapshot.update_id, snapshot.timestamp);
+
+            let fills = Self::match_resting_against_depth(book);
+            Self::apply_fills(&mut ex, &fills, &mut out);
+            out.push(Self::book_message(&ex, &snapshot.symbol));
+        }
+        self.apply_account(&out);
+        self.emit(out);
+    }
+
+    /// Enable/disable publishing of order book snapshots to the monitor.
+    /// Disabled when the market data itself is delivered by someone else (e.g. `ReplayAdapter`).
+    pub fn set_publish_market_data(&self, enabled: bool) {
+        self.publish_market_data.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Print a market trade on the tape.
+    pub fn push_trade(&self, symbol: &str, price: Decimal, quantity: i64) {
+        self.emit(vec![IncomingMessage::MarketTrade {
+            symbol: symbol.to_string(),
+            price,
+            quantity,
+            timestamp: now(),
+        }]);
+    }
+
+    /// Report a gateway connection state change to the monitor.
+    pub fn push_connection_status(&self, status: ConnectionStatus) {
+        self.emit(vec![IncomingMessage::ConnectionStatus(status)]);
+    }
+
+    /// Remaining quantity of a working order, if any.
+    pub fn get_open_quantity(&self, order_id: &str) -> Option<i64> {
+        let ex = self.exchange.lock().unwrap();
+        ex.orders.get(order_id)
+            .filter(|o| matches!(o.state, OrderState::NEW | OrderState::PARTIALLY_FILLED))
+            .map(|o| o.quantity - o.filled)
+    }
+
+    fn emit(&self, msgs: Vec<IncomingMessage>) {
+        let publish_books = self.publish_market_data.load(Ordering::Relaxed);
+        if let Some(s) = self.sender.lock().unwrap().as_ref() {
+            for m in msgs {
+                if !publish_books && matches!(m, IncomingMessage::OrderBookSnapshot(_)) {
+                    continue;
+                }
+                let _ = s.send(m);
+            }
+        }
+    }
+
+    fn apply_account(&self, msgs: &[IncomingMessage]) {
+        let ex = self.exchange.lock().unwrap();
+        let mut acct = self.account_state.lock().unwrap();
+        for m in msgs {
+            if let IncomingMessage::Execution { order_id, fill_qty, fill_price } = m {
+                if let Some(o) = ex.orders.get(order_id) {
+                    let side = match o.side { OrderSide::BUY => "BUY", OrderSide::SELL => "SELL" };
+                    acct.on_execution(o.symbol.clone(), side.to_string(), *fill_qty, *fill_price, Decimal::ZERO);
+                }
+            }
+        }
+    }
+
+    fn book_message(ex: &Exchange, symbol: &str) -> IncomingMessage {
+        let ob = ex.books.get(symbol).map(|b| b.to_order_book()).unwrap_or_else(|| OrderBook::new(symbol.to_string()));
+        IncomingMessage::OrderBookSnapshot(OrderBookSnapshot {
+            symbol: symbol.to_string(),
+            bids: ob.bids.iter().rev().map(|(p, q)| (*p, *q)).collect(),
+            asks: ob.asks.iter().map(|(p, q)| (*p, *q)).collect(),
+            update_id: ob.last_update_id,
+            timestamp: now(),
+        })
+    }
+
+    fn status_message(order_id: &str, o: &SimOrder, msg: Option<String>) -> IncomingMessage {
+        IncomingMessage::OrderStatus {
+            order_id: order_id.to_string(),
+            state: o.state.clone(),
+            filled_qty: o.filled,
+            filled_price: None,
+            msg,
+            updated_at: now(),
+        }
+    }
+
+    /// Book executions on the simulated orders and queue Execution reports.
+    fn apply_fills(ex: &mut Exchange, fills: &[Fill], out: &mut Vec<IncomingMessage>) {
+        for (oid, qty, price) in fills {
+            if let Some(o) = ex.orders.get_mut(oid) {
+                o.filled += qty;
+                o.state = if o.filled >= o.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+            }
+            out.push(IncomingMessage::Execution {
+                order_id: oid.clone(),
+                fill_qty: *qty,
+                fill_price: *price,
+            });
+        }
+    }
+
+    /// Match an incoming order against resting orders and depth.
+    /// Returns fills (including those of resting counterparties) and the unfilled remainder.
+    fn match_incoming(book: &mut SimBook, order_id: &str, side: &OrderSide, limit: Option<Decimal>, mut qty: i64) -> (Vec<Fill>, i64) {
+        let mut fills = Vec::new();
+
+        while qty > 0 {
+            // Best opposite resting price and best opposite depth price
+            let (resting_best, depth_best) = match side {
+                OrderSide::BUY => (book.asks.keys().next().copied(), book.depth.get_best_ask().map(|(p, _)| p)),
+                OrderSide::SELL => (book.bids.keys().next_back().copied(), book.depth.get_best_bid().map(|(p, _)| p)),
+            };
+            let better = |a: Decimal, b: Decimal| match side { OrderSide::BUY => a <= b, OrderSide::SELL => a >= b };
+
+            // Prefer resting orders at equal price (they were on the book first)
+            let (price, from_resting) = match (resting_best, depth_best) {
+                (Some(r), Some(d)) => if better(r, d) { (r, true) } else { (d, false) },
+                (Some(r), None) => (r, true),
+                (None, Some(d)) => (d, false),
+                (None, None) => break,
+            };
+
+            if let Some(lp) = limit {
+                let crosses = match side { OrderSide::BUY => price <= lp, OrderSide::SELL => price >= lp };
+                if !crosses { break; }
+            }
+
+            if from_resting {
+                let levels = match side { OrderSide::BUY => &mut book.asks, OrderSide::SELL => &mut book.bids };
+                let queue = levels.get_mut(&price).unwrap();
+                let head = queue.front_mut().unwrap();
+                let traded = qty.min(head.remaining);
+                head.remaining -= traded;
+                qty -= traded;
+                fills.push((head.order_id.clone(), traded, price));
+                fills.push((order_id.to_string(), traded, price));
+                if head.remaining == 0 {
+                    queue.pop_front();
+                }
+                if queue.is_empty() {
+                    levels.remove(&price);
+                }
+            } else {
+                let levels = match side { OrderSide::BUY => &mut book.depth.asks, OrderSide::SELL => &mut book.depth.bids };
+                let avail = levels.get_mut(&price).unwrap();
+                let traded = qty.min(*avail);
+                *avail -= traded;
+                qty -= traded;
+                if *avail <= 0 {
+                    levels.remove(&price);
+                }
+                if traded > 0 {
+                    fills.push((order_id.to_string(), traded, price));
+                }
+            }
+        }
+
+        (fills, qty)
+    }
+
+    /// Fill resting orders that are crossed by freshly injected depth.
+    fn match_resting_against_depth(book: &mut SimBook) -> Vec<Fill> {
+        let mut fills = Vec::new();
+
+        // Resting bids vs depth asks
+        while let (Some((&bp, _)), Some((ap, _))) = (book.bids.iter().next_back(), book.depth.get_best_ask()) {
+            if ap > bp { break; }
+            let queue = book.bids.get_mut(&bp).unwrap();
+            let head = queue.front_mut().unwrap();
+            let avail = book.depth.asks.get_mut(&ap).unwrap();
+            let traded = head.remaining.min(*avail);
+            head.remaining -= traded;
+            *avail -= traded;
+            if traded > 0 {
+                fills.push((head.order_id.clone(), traded, bp));
+            }
+            if *avail <= 0 { book.depth.asks.remove(&ap); }
+            if head.remaining == 0 { queue.pop_front(); }
+            if queue.is_empty() { book.bids.remove(&bp); }
+        }
+
+        // Resting asks vs depth bids
+        while let (Some((&ap, _)), Some((bp, _))) = (book.asks.iter().next(), book.depth.get_best_bid()) {
+            if bp < ap { break; }
+            let queue = book.asks.get_mut(&ap).unwrap();
+            let head = queue.front_mut().unwrap();
+            let avail = book.depth.bids.get_mut(&bp).unwrap();
+            let traded = head.remaining.min(*avail);
+            head.remaining -= traded;
+            *avail -= traded;
+            if traded > 0 {
+                fills.push((head.order_id.clone(), traded, ap));
+            }
+            if *avail <= 0 { book.depth.bids.remove(&bp); }
+            if head.remaining == 0 { queue.pop_front(); }
+            if queue.is_empty() { book.asks.remove(&ap); }
+        }
+
+        fills
+    }
+
+    /// Remove a resting order from its price level.
+    fn unlink(book: &mut SimBook, order_id: &str, side: &OrderSide, price: Decimal) {
+        let levels = match side { OrderSide::BUY => &mut book.bids, OrderSide::SELL => &mut book.asks };
+        if let Some(queue) = levels.get_mut(&price) {
+            queue.retain(|r| r.order_id != order_id);
+            if queue.is_empty() {
+                levels.remove(&price);
+            }
+        }
+    }
+
+    /// Match `order_id` with `remaining` quantity, then rest or expire the remainder.
+    fn execute(ex: &mut Exchange, order_id: &str, remaining: i64, out: &mut Vec<IncomingMessage>) {
+        let o = ex.orders.get(order_id).unwrap().clone();
+        let limit = match o.order_type { OrderType::LIMIT => o.price, OrderType::MARKET => None };
+
+        let book = ex.books.entry(o.symbol.clone()).or_insert_with(|| SimBook::new(&o.symbol));
+        let (fills, left) = Self::match_incoming(book, order_id, &o.side, limit, remaining);
+
+        if left > 0 {
+            if let Some(p) = limit {
+                let levels = match o.side { OrderSide::BUY => &mut book.bids, OrderSide::SELL => &mut book.asks };
+                levels.entry(p).or_default().push_back(RestingOrder { order_id: order_id.to_string(), remaining: left });
+            }
+        }
+
+        Self::apply_fills(ex, &fills, out);
+
+        // Market orders never rest: the unfilled part expires
+        if left > 0 && limit.is_none() {
+            if let Some(so) = ex.orders.get_mut(order_id) {
+                so.state = OrderState::CANCELED;
+                out.push(Self::status_message(order_id, so, Some("Market order remainder canceled".into())));
+            }
+        }
+
+        if !fills.is_empty() {
+            out.push(Self::book_message(ex, &o.symbol));
+        }
+    }
+}
+
+fn now() -> f64 {
+    Local::now().timestamp_millis() as f64 / 1000.0
 }
 
 impl Adapter for MockAdapter {
-    fn connect(&self) -> Result<()> {
+    fn connect(&self) -> AdapterResult<()> {
         Ok(())
     }
 
-    fn disconnect(&self) -> Result<()> {
+    fn disconnect(&self) -> AdapterResult<()> {
         Ok(())
     }
 
-    fn place_order(&self, _order: &Order) -> Result<bool> {
+    fn place_order(&self, order: &Order) -> AdapterResult<bool> {
+        let order_id = order.order_id.clone().ok_or_else(|| anyhow!("Order has no order_id"))?;
+
+        // Request validation (API level error, like a non-zero rt_cd)
+        if order.quantity <= 0 || (order.order_type == OrderType::LIMIT && order.price.is_none()) {
+            return Ok(false);
+        }
+
+        let mut out = Vec::new();
+        {
+            let mut ex = self.exchange.lock().unwrap();
+            if ex.orders.contains_key(&order_id) {
+                return Ok(false);
+            }
+
+            let mut so = SimOrder {
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                order_type: order.order_type.clone(),
+                price: order.price,
+                quantity: order.quantity,
+                filled: 0,
+                state: OrderState::NEW,
+            };
+
+            // Exchange refusal (rfus_yn == "Y")
+            if let Some(reason) = self.reject_reason.lock().unwrap().clone() {
+                so.state = OrderState::REJECTED;
+                out.push(Self::status_message(&order_id, &so, Some(reason)));
+                ex.orders.insert(order_id, so);
+            } else {
+                out.push(Self::status_message(&order_id, &so, None));
+                ex.orders.insert(order_id.clone(), so);
+                Self::execute(&mut ex, &order_id, order.quantity, &mut out);
+            }
+        }
+        self.apply_account(&out);
+        self.emit(out);
         Ok(true)
     }
 
-    fn cancel_order(&self, _order_id: &str) -> Result<bool> {
+    fn cancel_order(&self, order_id: &str) -> AdapterResult<bool> {
+        let mut out = Vec::new();
+        {
+            let mut ex = self.exchange.lock().unwrap();
+            let o = ex.orders.get(order_id).cloned()
+                .ok_or_else(|| AdapterError::UnknownOrder(order_id.to_string()))?;
+            if !matches!(o.state, OrderState::NEW | OrderState::PARTIALLY_FILLED) {
+                return Ok(false);
+            }
+
+            if let (Some(book), Some(p)) = (ex.books.get_mut(&o.symbol), o.price) {
+                Self::unlink(book, order_id, &o.side, p);
+            }
+            let so = ex.orders.get_mut(order_id).unwrap();
+            so.state = OrderState::CANCELED;
+            out.push(Self::status_message(order_id, so, None));
+            out.push(Self::book_message(&ex, &o.symbol));
+        }
+        self.emit(out);
         Ok(true)
     }
 
-    fn get_order_book_snapshot(&self, symbol: &str) -> Result<OrderBook> {
-        Ok(OrderBook::new(symbol.to_string()))
+    fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook> {
+        let ex = self.exchange.lock().unwrap();
+        let mut ob = ex.books.get(symbol).map(|b| b.to_order_book()).unwrap_or_else(|| OrderBook::new(symbol.to_string()));
+        ob.timestamp = now();
+        Ok(ob)
     }
 
-    fn get_account_snapshot(&self, _account_id: &str) -> Result<AccountState> {
+    fn get_account_snapshot(&self, _account_id: &str) -> AdapterResult<AccountState> {
         // Return cloned state
         Ok(self.account_state.lock().unwrap().clone())
     }
 
-    fn modify_order(&self, _order_id: &str, _price: Option<Decimal>, _qty: Option<i64>) -> Result<bool> {
-        Ok(true) //TODO: what if the user want to modify whole remaining orders? Does the API support partial modify?
+    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool> {
+        let mut out = Vec::new();
+        {
+            let mut ex = self.exchange.lock().unwrap();
+            let o = ex.orders.get(order_id).cloned()
+                .ok_or_else(|| AdapterError::UnknownOrder(order_id.to_string()))?;
+            if !matches!(o.state, OrderState::NEW | OrderState::PARTIALLY_FILLED) {
+                return Ok(false);
+            }
+
+            // Re-pricing loses time priority: pull the order and re-enter it.
+            if let (Some(book), Some(p)) = (ex.books.get_mut(&o.symbol), o.price) {
+                Self::unlink(book, order_id, &o.side, p);
+            }
+
+            let open = o.quantity - o.filled;
+            let remaining = match qty {
+                Some(q) if q > 0 => q.min(open),
+                _ => open,
+            };
+            {
+                let so = ex.orders.get_mut(order_id).unwrap();
+                so.quantity = so.filled + remaining;
+                so.price = price;
+                so.order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+            }
+            // The replacement is acknowledged before it trades
+            out.push(Self::status_message(order_id, &o, Some("Replaced".to_string())));
+            Self::execute(&mut ex, order_id, remaining, &mut out);
+            if out.len() == 1 {
+                out.push(Self::book_message(&ex, &o.symbol));
+            }
+        }
+        self.apply_account(&out);
+        self.emit(out);
+        Ok(true)
     }
 
-    fn subscribe(&self, _symbols: &[String]) -> Result<()> {
+    fn subscribe(&self, _symbols: &[String]) -> AdapterResult<()> {
         Ok(())
     }
 
-    fn set_monitor(&self, _sender: std::sync::mpsc::Sender<IncomingMessage>) {
-        // Mock adapter currently ignores the monitor, or we could store it to send mock messages later
+    fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>) {
+        *self.sender.lock().unwrap() = Some(sender);
     }
 }
@@ -1,9 +1,9 @@
-use crate::oms::order::Order;
+use crate::oms::order::{Order, OrderSide, OrderType};
 use crate::oms::order_book::OrderBook;
 use crate::oms::account::AccountState;
-use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use async_trait::async_trait;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
@@ -11,25 +11,154 @@ pub struct Trade {
     pub price: Decimal,
     pub quantity: i64,
     pub timestamp: f64,
+    #[serde(default)]
+    pub venue: String,
+}
+
+/// An order working at the broker, as returned by `Adapter::get_open_orders`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerOrder {
+    /// Client order id, if the adapter knows this broker order.
+    pub order_id: Option<String>,
+    /// Broker order number.
+    pub exchange_order_id: String,
+    /// Reference accepted by `Adapter::restore_order_ref`.
+    pub order_ref: serde_json::Value,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub quantity: i64,
+    pub price: Option<Decimal>,
+    pub filled_quantity: i64,
+}
+
+/// Cumulative fills of one broker order for the trading day, as returned by `Adapter::get_executions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerExecution {
+    pub order_id: Option<String>,
+    pub exchange_order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub filled_quantity: i64,
+    pub average_price: Decimal,
 }
 
 pub use crate::message::Message as IncomingMessage;
+pub use error::{AdapterError, AdapterResult, BrokerStatus};
 use crate::message::Message;
 
 
 pub trait Adapter: Send + Sync {
-    fn connect(&self) -> Result<()>;
-    fn disconnect(&self) -> Result<()>;
-    fn place_order(&self, order: &Order) -> Result<bool>;
-    fn cancel_order(&self, order_id: &str) -> Result<bool>;
-    fn get_order_book_snapshot(&self, symbol: &str) -> Result<OrderBook>;
-    fn get_account_snapshot(&self, account_id: &str) -> Result<AccountState>;
-    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> Result<bool>;
-    fn subscribe(&self, symbols: &[String]) -> Result<()>;
+    fn connect(&self) -> AdapterResult<()>;
+    fn disconnect(&self) -> AdapterResult<()>;
+    fn place_order(&self, order: &Order) -> AdapterResult<bool>;
+    fn cancel_order(&self, order_id: &str) -> AdapterResult<bool>;
+    fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook>;
+    fn get_account_snapshot(&self, account_id: &str) -> AdapterResult<AccountState>;
+    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool>;
+    fn subscribe(&self, symbols: &[String]) -> AdapterResult<()>;
     fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>);
+
+    /// Broker-side reference kept for a client order id (persisted by the OMS journal).
+    fn get_order_ref(&self, _order_id: &str) -> Option<serde_json::Value> {
+        None
+    }
+    /// Restore a reference previously returned by `get_order_ref` after a restart.
+    fn restore_order_ref(&self, _order_id: &str, _order_ref: serde_json::Value) -> AdapterResult<()> {
+        Ok(())
+    }
+    /// Orders still working at the broker (used by `OMSEngine::reconcile_internal`).
+    fn get_open_orders(&self) -> AdapterResult<Vec<BrokerOrder>> {
+        Err(AdapterError::Unsupported("Open order inquiry".to_string()))
+    }
+    /// Today's fills per broker order.
+    fn get_executions(&self) -> AdapterResult<Vec<BrokerExecution>> {
+        Err(AdapterError::Unsupported("Execution inquiry".to_string()))
+    }
 }
 
+/// Async variant of `Adapter`, run on the shared runtime in `adapter::runtime`.
+/// Every implementor is also an `Adapter`: the blocking methods wait on the shared runtime.
+#[async_trait]
+pub trait AsyncAdapter: Send + Sync {
+    async fn connect(&self) -> AdapterResult<()>;
+    async fn disconnect(&self) -> AdapterResult<()>;
+    async fn place_order(&self, order: &Order) -> AdapterResult<bool>;
+    async fn cancel_order(&self, order_id: &str) -> AdapterResult<bool>;
+    async fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook>;
+    async fn get_account_snapshot(&self, account_id: &str) -> AdapterResult<AccountState>;
+    async fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool>;
+    /// Takes effect immediately on a connected WebSocket session.
+    async fn subscribe(&self, symbols: &[String]) -> AdapterResult<()>;
+    fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>);
+
+    fn get_order_ref(&self, _order_id: &str) -> Option<serde_json::Value> {
+        None
+    }
+    fn restore_order_ref(&self, _order_id: &str, _order_ref: serde_json::Value) -> AdapterResult<()> {
+        Ok(())
+    }
+    async fn get_open_orders(&self) -> AdapterResult<Vec<BrokerOrder>> {
+        Err(AdapterError::Unsupported("Open order inquiry".to_string()))
+    }
+    async fn get_executions(&self) -> AdapterResult<Vec<BrokerExecution>> {
+        Err(AdapterError::Unsupported("Execution inquiry".to_string()))
+    }
+}
+
+impl<T: AsyncAdapter> Adapter for T {
+    fn connect(&self) -> AdapterResult<()> {
+        runtime::block_on(AsyncAdapter::connect(self))
+    }
+    fn disconnect(&self) -> AdapterResult<()> {
+        runtime::block_on(AsyncAdapter::disconnect(self))
+    }
+    fn place_order(&self, order: &Order) -> AdapterResult<bool> {
+        runtime::block_on(AsyncAdapter::place_order(self, order))
+    }
+    fn cancel_order(&self, order_id: &str) -> AdapterResult<bool> {
+        runtime::block_on(AsyncAdapter::cancel_order(self, order_id))
+    }
+    fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook> {
+        runtime::block_on(AsyncAdapter::get_order_book_snapshot(self, symbol))
+    }
+    fn get_account_snapshot(&self, account_id: &str) -> AdapterResult<AccountState> {
+        runtime::block_on(AsyncAdapter::get_account_snapshot(self, account_id))
+    }
+    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool> {
+        runtime::block_on(AsyncAdapter::modify_order(self, order_id, price, qty))
+    }
+    fn subscribe(&self, symbols: &[String]) -> AdapterResult<()> {
+        runtime::block_on(AsyncAdapter::subscribe(self, symbols))
+    }
+    fn set_monitor(&self, sender: std::sync::mpsc::Sender<IncomingMessage>) {
+        AsyncAdapter::set_monitor(self, sender)
+    }
+    fn get_order_ref(&self, order_id: &str) -> Option<serde_json::Value> {
+        AsyncAdapter::get_order_ref(self, order_id)
+    }
+    fn restore_order_ref(&self, order_id: &str, order_ref: serde_json::Value) -> AdapterResult<()> {
+        AsyncAdapter::restore_order_ref(self, order_id, order_ref)
+    }
+    fn get_open_orders(&self) -> AdapterResult<Vec<BrokerOrder>> {
+        runtime::block_on(AsyncAdapter::get_open_orders(self))
+    }
+    fn get_executions(&self) -> AdapterResult<Vec<BrokerExecution>> {
+        runtime::block_on(AsyncAdapter::get_executions(self))
+    }
+}
+
+pub mod error;
+pub mod runtime;
 pub mod mock;
+pub mod replay;
 pub mod hantoo;
 pub mod hantoo_ngt_futopt;
+pub mod hantoo_stub;
 pub mod interface;
+
+use pyo3::prelude::*;
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    error::register(m)
+}
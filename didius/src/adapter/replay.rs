@@ -0,0 +1,346 @@
+use crate::adapter::mock::MockAdapter;
+use crate::adapter::{Adapter, AdapterResult, IncomingMessage};
+use crate::oms::account::AccountState;
+use crate::oms::order::{Order, OrderState};
+use crate::oms::order_book::{OrderBook, OrderBookDelta, OrderBookSnapshot};
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How fast recorded events are re-played.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayPace {
+    /// Emit events back-to-back.
+    AsFastAsPossible,
+    /// Sleep between events according to recorded timestamps, scaled by `speed` (2.0 = twice as fast).
+    Recorded { speed: f64 },
+}
+
+/// Re-plays `MARKET_DATA` logs written by `OMSEngine::start_gateway_listener`.
+///
+/// Recorded messages are emitted to the monitor in file order. Orders sent to this adapter
+/// are matched by an internal `MockAdapter` against the replayed order book, so strategies
+/// can be run over a recorded tape.
+pub struct ReplayAdapter {
+    files: Vec<PathBuf>,
+    pace: ReplayPace,
+    exchange: Arc<MockAdapter>,
+    books: Arc<Mutex<HashMap<String, OrderBook>>>,
+    sender: Mutex<Option<mpsc::Sender<IncomingMessage>>>,
+    symbols: Mutex<Vec<String>>,
+    stop_flag: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+    replay_thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl ReplayAdapter {
+    /// `path` is a single `.jsonl`/`.jsonl.zstd` file or a directory of them (replayed in file name order).
+    pub fn new(path: &str, pace: ReplayPace) -> Result<Self> {
+        let p = Path::new(path);
+        let files = if p.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(p)?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|f| Self::is_log_file(f))
+                .collect();
+            files.sort();
+            files
+        } else if p.is_file() {
+            vec![p.to_path_buf()]
+        } else {
+            return Err(anyhow!("Replay path not found: {}", path));
+        };
+        Ok(Self::with_files(files, pace))
+    }
+
+    pub fn with_files(files: Vec<PathBuf>, pace: ReplayPace) -> Self {
+        let exchange = Arc::new(MockAdapter::new());
+        // Market data comes from the tape, not from the simulated exchange
+        exchange.set_publish_market_data(false);
+        ReplayAdapter {
+            files,
+            pace,
+            exchange,
+            books: Arc::new(Mutex::new(HashMap::new())),
+            sender: Mutex::new(None),
+            symbols: Mutex::new(Vec::new()),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+            replay_thread: Mutex::new(None),
+        }
+    }
+
+    /// Simulated exchange used for order entry during the replay.
+    pub fn exchange(&self) -> &MockAdapter {
+        &self.exchange
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Block until every recorded event has been emitted (or the replay was stopped).
+    pub fn join(&self) {
+        if let Some(h) = self.replay_thread.lock().unwrap().take() {
+            let _ = h.join();
+        }
+    }
+
+    fn is_log_file(path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        name.ends_with(".jsonl") || name.ends_with(".jsonl.zstd") || name.ends_with(".log")
+    }
+
+    fn open(path: &Path) -> Result<Box<dyn BufRead>> {
+        let file = File::open(path).map_err(|e| anyhow!("Failed to open replay file {}: {}", path.display(), e))?;
+        let reader: Box<dyn Read> = if path.extension().and_then(|e| e.to_str()) == Some("zstd") {
+            Box::new(zstd::stream::read::Decoder::new(file)?)
+        } else {
+            Box::new(file)
+        };
+        Ok(Box::new(BufReader::new(reader)))
+    }
+
+    /// Parse one log line. Returns the log timestamp and the reconstructed message,
+    /// or None if the line is not a replayable `MARKET_DATA` record.
+    pub fn parse_line(line: &str) -> Option<(f64, IncomingMessage)> {
+        let record: Value = serde_json::from_str(line).ok()?;
+        if record["log_type"].as_str() != Some("MARKET_DATA") {
+            return None;
+        }
+        let ts = record["timestamp"].as_f64().unwrap_or(0.0);
+        let body = &record["log_body"];
+        let msg = Self::parse_body(body, ts)?;
+        Some((ts, msg))
+    }
+
+    fn parse_body(body: &Value, ts: f64) -> Option<IncomingMessage> {
+        let symbol = || body["symbol"].as_str().map(|s| s.to_string());
+        let dec = |v: &Value| v.as_str().and_then(|s| Decimal::from_str(s).ok());
+        let levels = |prices: &Value, qtys: &Value| -> Vec<(Decimal, i64)> {
+            let prices = prices.as_array().cloned().unwrap_or_default();
+            let qtys = qtys.as_array().cloned().unwrap_or_default();
+            prices.iter().zip(qtys.iter())
+                .filter_map(|(p, q)| Some((dec(p)?, q.as_i64()?)))
+                .collect()
+        };
+
+        match body["type"].as_str()? {
+            "OrderBookDelta" => {
+                let data = &body["data"];
+                let symbol = symbol()?;
+                Some(IncomingMessage::OrderBookUpdate {
+                    delta: OrderBookDelta {
+                        symbol: symbol.clone(),
+                        bids: levels(&data["bp"], &data["bv"]),
+                        asks: levels(&data["ap"], &data["av"]),
+                        update_id: body["update_id"].as_i64().unwrap_or(0),
+                        timestamp: body["timestamp"].as_f64().unwrap_or(ts),
+                    },
+                    symbol,
+                })
+            }
+            "OrderBookSnapshot" => Some(IncomingMessage::OrderBookSnapshot(OrderBookSnapshot {
+                symbol: symbol()?,
+                bids: serde_json::from_value(body["bids"].clone()).ok()?,
+                asks: serde_json::from_value(body["asks"].clone()).ok()?,
+                update_id: body["update_id"].as_i64().unwrap_or(0),
+                timestamp: body["timestamp"].as_f64().unwrap_or(ts),
+            })),
+            "Trade" => Some(IncomingMessage::MarketTrade {
+                symbol: symbol()?,
+                price: dec(&body["price"])?,
+                quantity: body["qty"].as_i64().unwrap_or(0),
+                timestamp: body["timestamp"].as_f64().unwrap_or(ts),
+            }),
+            "Execution" => Some(IncomingMessage::Execution {
+                order_id: body["order_id"].as_str()?.to_string(),
+                fill_qty: body["qty"].as_i64().unwrap_or(0),
+                // Older logs did not record the fill price
+                fill_price: dec(&body["price"]).unwrap_or_default(),
+            }),
+            "OrderUpdate" => Some(IncomingMessage::OrderStatus {
+                order_id: body["order_id"].as_str()?.to_string(),
+                state: serde_json::from_value::<OrderState>(body["state"].clone()).ok()?,
+                filled_qty: body["filled_qty"].as_i64().unwrap_or(0),
+                filled_price: dec(&body["filled_price"]),
+                msg: body["msg"].as_str().map(|s| s.to_string()),
+                updated_at: body["updated_at"].as_f64().unwrap_or(ts),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Keep the simulated exchange depth in sync with the replayed book.
+    fn sync_exchange(books: &Mutex<HashMap<String, OrderBook>>, exchange: &MockAdapter, msg: &IncomingMessage) {
+        let snapshot = {
+            let mut books = books.lock().unwrap();
+            let symbol = match msg {
+                IncomingMessage::OrderBookUpdate { symbol, delta } => {
+                    let book = books.entry(symbol.clone()).or_insert_with(|| OrderBook::new(symbol.clone()));
+                    book.apply_delta(delta);
+                    symbol.clone()
+                }
+                IncomingMessage::OrderBookSnapshot(s) => {
+                    let book = books.entry(s.symbol.clone()).or_insert_with(|| OrderBook::new(s.symbol.clone()));
+                    book.rebuild(s.bids.clone(), s.asks.clone(), s.update_id, s.timestamp);
+                    s.symbol.clone()
+                }
+                _ => return,
+            };
+            let book = books.get(&symbol).unwrap();
+            OrderBookSnapshot {
+                symbol,
+                bids: book.bids.iter().rev().map(|(p, q)| (*p, *q)).collect(),
+                asks: book.asks.iter().map(|(p, q)| (*p, *q)).collect(),
+                update_id: book.last_update_id,
+                timestamp: book.timestamp,
+            }
+        };
+        exchange.push_order_book(snapshot);
+    }
+
+    fn message_symbol(msg: &IncomingMessage) -> Option<&str> {
+        match msg {
+            IncomingMessage::OrderBookUpdate { symbol, .. } | IncomingMessage::MarketTrade { symbol, .. } => Some(symbol),
+            IncomingMessage::OrderBookSnapshot(s) => Some(&s.symbol),
+            _ => None,
+        }
+    }
+
+    fn start_replay_thread(&self) {
+        let files = self.files.clone();
+        let pace = self.pace.clone();
+        let sender = self.sender.lock().unwrap().clone();
+        let symbols = self.symbols.lock().unwrap().clone();
+        let exchange = self.exchange.clone();
+        let books = self.books.clone();
+        let stop_flag = self.stop_flag.clone();
+        let finished = self.finished.clone();
+
+        let handle = thread::spawn(move || {
+            let started = Instant::now();
+            let mut first_ts: Option<f64> = None;
+            let mut count = 0usize;
+
+            'files: for path in &files {
+                let reader = match Self::open(path) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        warn!("{}", e);
+                        continue;
+                    }
+                };
+                info!("Replaying {}", path.display());
+
+                for line in reader.lines() {
+                    if stop_flag.load(Ordering::Relaxed) {
+                        break 'files;
+                    }
+                    let line = match line {
+                        Ok(l) => l,
+                        Err(e) => {
+                            warn!("Replay read error in {}: {}", path.display(), e);
+                            break;
+                        }
+                    };
+                    let (ts, msg) = match Self::parse_line(&line) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+
+                    if !symbols.is_empty() {
+                        if let Some(s) = Self::message_symbol(&msg) {
+                            if !symbols.iter().any(|x| x == s) {
+                                continue;
+                            }
+                        }
+                    }
+
+                    if let ReplayPace::Recorded { speed } = pace {
+                        let t0 = *first_ts.get_or_insert(ts);
+                        let offset = ((ts - t0) / speed.max(f64::EPSILON)).max(0.0);
+                        let due = Duration::from_secs_f64(offset);
+                        let elapsed = started.elapsed();
+                        if due > elapsed {
+                            thread::sleep(due - elapsed);
+                        }
+                    }
+
+                    if let Some(s) = &sender {
+                        let _ = s.send(msg.clone());
+                    }
+                    // Fills produced by the simulated exchange follow the market data that caused them
+                    Self::sync_exchange(&books, &exchange, &msg);
+                    count += 1;
+                }
+            }
+            info!("Replay finished ({} events)", count);
+            finished.store(true, Ordering::Relaxed);
+        });
+
+        *self.replay_thread.lock().unwrap() = Some(handle);
+    }
+}
+
+impl Adapter for ReplayAdapter {
+    fn connect(&self) -> AdapterResult<()> {
+        if self.replay_thread.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        self.stop_flag.store(false, Ordering::Relaxed);
+        self.finished.store(false, Ordering::Relaxed);
+        self.start_replay_thread();
+        Ok(())
+    }
+
+    fn disconnect(&self) -> AdapterResult<()> {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn place_order(&self, order: &Order) -> AdapterResult<bool> {
+        self.exchange.place_order(order)
+    }
+
+    fn cancel_order(&self, order_id: &str) -> AdapterResult<bool> {
+        self.exchange.cancel_order(order_id)
+    }
+
+    fn get_order_book_snapshot(&self, symbol: &str) -> AdapterResult<OrderBook> {
+        self.exchange.get_order_book_snapshot(symbol)
+    }
+
+    fn get_account_snapshot(&self, account_id: &str) -> AdapterResult<AccountState> {
+        self.exchange.get_account_snapshot(account_id)
+    }
+
+    fn modify_order(&self, order_id: &str, price: Option<Decimal>, qty: Option<i64>) -> AdapterResult<bool> {
+        self.exchange.modify_order(order_id, price, qty)
+    }
+
+    /// Restrict market data to the given symbols (all symbols if never called).
+    fn subscribe(&self, symbols: &[String]) -> AdapterResult<()> {
+        let mut guard = self.symbols.lock().unwrap();
+        for s in symbols {
+            if !guard.contains(s) {
+                guard.push(s.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn set_monitor(&self, sender: mpsc::Sender<IncomingMessage>) {
+        self.exchange.set_monitor(sender.clone());
+        *self.sender.lock().unwrap() = Some(sender);
+    }
+}
@@ -0,0 +1,27 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Multi-thread runtime shared by the async adapters (REST calls and WebSocket sessions).
+pub fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .thread_name("didius-adapter")
+            .enable_all()
+            .build()
+            .expect("Failed to build adapter runtime")
+    })
+}
+
+/// Run a future to completion on the shared runtime from synchronous code.
+/// Also usable from inside a multi-thread tokio runtime, where the worker is handed off while blocking.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| runtime().block_on(future))
+        }
+        _ => runtime().block_on(future),
+    }
+}
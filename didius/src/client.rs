@@ -22,24 +22,7 @@ impl Client {
     #[new]
     #[pyo3(signature = (venue, config_path=None, s3_bucket=None, s3_region=None, s3_prefix=None))]
     fn new(venue: String, config_path: Option<String>, s3_bucket: Option<String>, s3_region: Option<String>, s3_prefix: Option<String>) -> PyResult<Self> {
-        let adapter: Arc<dyn Adapter> = match venue.as_str() {
-            "hantoo" => {
-                let config = config_path.ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Config path required for Hantoo"))?;
-                let a = crate::adapter::hantoo::HantooAdapter::new(&config)
-                    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-                Arc::new(a)
-            },
-            "hantoo_night" => {
-                let config = config_path.ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Config path required for Hantoo Night"))?;
-                let a = crate::adapter::hantoo_ngt_futopt::HantooNightAdapter::new(&config)
-                     .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-                Arc::new(a)
-            },
-            "mock" => {
-                Arc::new(crate::adapter::mock::MockAdapter::new())
-            },
-            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown venue: {}", venue))),
-        };
+        let adapter = crate::adapter::interface::build_adapter(&venue, config_path)?;
         
         let (sender, receiver) = mpsc::channel();
         
@@ -74,19 +57,19 @@ impl Client {
     }
 
     fn connect(&self) -> PyResult<()> {
-        self.adapter.connect().map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.connect().map_err(PyErr::from)
     }
     
     fn disconnect(&self) -> PyResult<()> {
-        self.adapter.disconnect().map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.disconnect().map_err(PyErr::from)
     }
 
     fn place_order(&self, order: &Order) -> PyResult<bool> {
-        self.adapter.place_order(order).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.place_order(order).map_err(PyErr::from)
     }
     
     fn cancel_order(&self, order_id: &str) -> PyResult<bool> {
-        self.adapter.cancel_order(order_id).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.cancel_order(order_id).map_err(PyErr::from)
     }
 
     fn update_order(&self, order_id: &str, price: Option<String>, qty: Option<i64>) -> PyResult<bool> {
@@ -96,11 +79,11 @@ impl Client {
             None
         };
         
-        self.adapter.modify_order(order_id, price_dec, qty).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.modify_order(order_id, price_dec, qty).map_err(PyErr::from)
     }
     
     fn subscribe(&self, symbols: Vec<String>) -> PyResult<()> {
-        self.adapter.subscribe(&symbols).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.adapter.subscribe(&symbols).map_err(PyErr::from)
     }
 
     fn fetch_message(&self, timeout_sec: f64) -> PyResult<Option<String>> {
@@ -14,6 +14,8 @@ use pyo3::types::PyModule;
 #[pymodule]
 fn core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     oms::register(m)?;
+    adapter::register(m)?;
+    strategy::register(m)?;
     utils::register(m)?;
     m.add_class::<client::Client>()?;
     Ok(())
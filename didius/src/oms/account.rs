@@ -28,6 +28,9 @@ impl Position {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountState {
+    /// Venue the account is held at; empty when not tied to one
+    #[serde(default)]
+    pub venue: String,
     pub balance: Decimal,
     pub locked: Decimal,
     pub positions: HashMap<String, Position>,
@@ -36,12 +39,17 @@ pub struct AccountState {
 impl AccountState {
     pub fn new() -> Self {
         AccountState {
+            venue: String::new(),
             balance: Decimal::ZERO,
             locked: Decimal::ZERO,
             positions: HashMap::new(),
         }
     }
 
+    pub fn for_venue(venue: &str) -> Self {
+        AccountState { venue: venue.to_string(), ..Self::new() }
+    }
+
     pub fn rebuild(&mut self, balance: Decimal, locked: Decimal, positions: Vec<Position>) {
         self.balance = balance;
         self.locked = locked;
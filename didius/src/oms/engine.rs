@@ -4,50 +4,403 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
-use crate::oms::order::{Order, OrderState, ExecutionStrategy, OrderSide, OrderType};
+use crate::oms::order::{Order, OrderState, ExecutionStrategy, OrderSide, OrderType, IllegalTransition};
 use crate::oms::order_book::OrderBook;
+use crate::oms::queue::{QueuePosition, QueueTracker};
 use crate::oms::account::AccountState;
-use crate::adapter::Adapter;
+use crate::oms::risk::RiskLimits;
+use crate::oms::journal::{JournalEvent, OrderJournal, RecoveredState};
+use crate::oms::reconcile::{self, ReconciliationBreak, ReconciliationReport};
+use crate::oms::session::{SessionSchedule, TradingPhase};
+use crate::adapter::{Adapter, AdapterError};
+use crate::adapter::error::to_py_err;
 use crate::logger::Logger;
 use crate::logger::message::Message;
 use uuid::Uuid;
-use chrono::Local;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{self, Receiver};
 use crate::adapter::{IncomingMessage};
+use crate::message::ConnectionStatus;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::{FromPrimitive, FromStr};
-use crate::strategy::base::StrategyAction;
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::registry::{StrategyFactory, StrategyRegistry};
 // use anyhow::anyhow;
 
+/// Venue key used by `OMSEngine::new` for its single adapter.
+pub const DEFAULT_VENUE: &str = "default";
+
+/// Whether `strategy` runs in `phase`; all strategies run without a session schedule.
+fn runs_in(strategy: &(dyn Strategy + Send + Sync), phase: Option<TradingPhase>) -> bool {
+    match (phase, strategy.active_phases()) {
+        (Some(phase), Some(active)) => active.contains(&phase),
+        _ => true,
+    }
+}
+
 #[derive(Clone)]
 pub struct OMSEngine {
-    adapter: Arc<dyn Adapter>,
-    order_books: Arc<Mutex<HashMap<String, OrderBook>>>,
-    account: Arc<Mutex<AccountState>>,
+    adapters: HashMap<String, Arc<dyn Adapter>>,
+    default_venue: String,
+    /// Books by (venue, symbol)
+    order_books: Arc<Mutex<HashMap<(String, String), OrderBook>>>,
+    /// Account of each venue
+    accounts: Arc<Mutex<HashMap<String, AccountState>>>,
     orders: Arc<Mutex<HashMap<String, Order>>>,
     is_running: Arc<Mutex<bool>>,
     // margin_requirement: Decimal,
 
     active_strategies: Arc<Mutex<Vec<Box<dyn crate::strategy::base::Strategy + Send + Sync>>>>,
+    strategy_registry: Arc<Mutex<StrategyRegistry>>,
+    risk_limits: Arc<Mutex<RiskLimits>>,
+    journal: Arc<Mutex<Option<OrderJournal>>>,
+    session_schedule: Arc<Mutex<Option<SessionSchedule>>>,
+    /// Phase seen by the last strategy check; `None` without a schedule
+    session_phase: Arc<Mutex<Option<TradingPhase>>>,
+    /// Estimated queue position of each resting limit order
+    queue_positions: Arc<Mutex<QueueTracker>>,
     logger: Arc<Mutex<Logger>>,
 }
 
 impl OMSEngine {
     pub fn new(adapter: Arc<dyn Adapter>, logger: Arc<Mutex<Logger>>) -> Self {
+        let mut adapters = HashMap::new();
+        adapters.insert(DEFAULT_VENUE.to_string(), adapter);
+        Self::with_venues(adapters, DEFAULT_VENUE, logger)
+    }
+
+    /// Engine trading several venues at once. Orders are routed by `Order::venue`,
+    /// orders without a venue go to `default_venue`.
+    pub fn with_venues(adapters: HashMap<String, Arc<dyn Adapter>>, default_venue: &str, logger: Arc<Mutex<Logger>>) -> Self {
         OMSEngine {
-            adapter,
+            adapters,
+            default_venue: default_venue.to_string(),
             order_books: Arc::new(Mutex::new(HashMap::new())),
-            account: Arc::new(Mutex::new(AccountState::new())),
+            accounts: Arc::new(Mutex::new(HashMap::new())),
             orders: Arc::new(Mutex::new(HashMap::new())),
             is_running: Arc::new(Mutex::new(false)),
             // margin_requirement: Decimal::from_f64(margin_requirement).unwrap_or(Decimal::ONE),
             active_strategies: Arc::new(Mutex::new(Vec::new())),
+            strategy_registry: Arc::new(Mutex::new(StrategyRegistry::default())),
+            risk_limits: Arc::new(Mutex::new(RiskLimits::default())),
+            journal: Arc::new(Mutex::new(None)),
+            session_schedule: Arc::new(Mutex::new(None)),
+            session_phase: Arc::new(Mutex::new(None)),
+            queue_positions: Arc::new(Mutex::new(QueueTracker::new())),
             logger,
         }
     }
 
+    pub fn venues(&self) -> Vec<String> {
+        self.adapters.keys().cloned().collect()
+    }
+
+    /// `venue`, or the default venue for orders and books without one.
+    fn venue_or_default<'a>(&'a self, venue: &'a str) -> &'a str {
+        if venue.is_empty() { self.default_venue.as_str() } else { venue }
+    }
+
+    fn adapter_for(&self, venue: &str) -> anyhow::Result<&Arc<dyn Adapter>> {
+        let venue = self.venue_or_default(venue);
+        self.adapters.get(venue).ok_or_else(|| anyhow::anyhow!("Unknown venue: {}", venue))
+    }
+
+    pub fn set_risk_limits(&self, limits: RiskLimits) {
+        *self.risk_limits.lock().unwrap() = limits;
+    }
+
+    pub fn get_risk_limits(&self) -> RiskLimits {
+        self.risk_limits.lock().unwrap().clone()
+    }
+
+    /// Pause strategies outside their `Strategy::active_phases` of `schedule` and tell them about
+    /// phase changes. Without a schedule (the default) strategies run around the clock.
+    pub fn set_session_schedule(&self, schedule: Option<SessionSchedule>) {
+        *self.session_schedule.lock().unwrap() = schedule;
+        *self.session_phase.lock().unwrap() = None;
+    }
+
+    /// Phase seen by the last strategy check; `None` without a session schedule.
+    pub fn get_session_phase(&self) -> Option<TradingPhase> {
+        *self.session_phase.lock().unwrap()
+    }
+
+    /// Move to the phase of the session schedule at `now`, returning it and the transition
+    /// when it changed. The engine starts out `CLOSED`.
+    fn advance_session(&self, now: f64) -> (Option<TradingPhase>, Option<(TradingPhase, TradingPhase)>) {
+        let Some(phase) = self.session_schedule.lock().unwrap().as_ref().map(|s| s.phase_at(now)) else {
+            return (None, None);
+        };
+        let previous = self.session_phase.lock().unwrap().replace(phase).unwrap_or(TradingPhase::CLOSED);
+        (Some(phase), (previous != phase).then_some((previous, phase)))
+    }
+
+    /// Persist order lifecycle events to `journal`. Orders found in it are recovered by `start_internal`.
+    pub fn set_journal(&self, journal: OrderJournal) {
+        *self.journal.lock().unwrap() = Some(journal);
+    }
+
+    fn write_journal(&self, event: JournalEvent) -> anyhow::Result<()> {
+        let mut journal = self.journal.lock().unwrap();
+        match journal.as_mut() {
+            Some(j) => j.append(event),
+            None => Ok(()),
+        }
+    }
+
+    fn journal_order(&self, order_id: &str) {
+        let order = self.orders.lock().unwrap().get(order_id).cloned();
+        if let Some(order) = order {
+            if let Err(e) = self.write_journal(JournalEvent::Order { order: Box::new(order) }) {
+                log::error!("Journal write failed for {}: {}", order_id, e);
+            }
+        }
+    }
+
+    fn journal_order_ref(&self, venue: &str, order_id: &str) {
+        let Ok(adapter) = self.adapter_for(venue) else { return };
+        if let Some(order_ref) = adapter.get_order_ref(order_id) {
+            let event = JournalEvent::OrderRef { order_id: order_id.to_string(), venue: venue.to_string(), order_ref };
+            if let Err(e) = self.write_journal(event) {
+                log::error!("Journal write failed for {}: {}", order_id, e);
+            }
+        }
+    }
+
+    /// Rebuild orders, strategies and adapter order maps from the journal, then compact it.
+    /// Returns the ids of orders that were still working.
+    pub fn recover_from_journal(&self) -> anyhow::Result<Vec<String>> {
+        let state = {
+            let journal = self.journal.lock().unwrap();
+            match journal.as_ref() {
+                Some(j) => j.recover()?,
+                None => return Ok(Vec::new()),
+            }
+        };
+
+        for (order_id, (venue, order_ref)) in &state.order_refs {
+            let restored = self.adapter_for(venue).and_then(|a| a.restore_order_ref(order_id, order_ref.clone()).map_err(anyhow::Error::from));
+            if let Err(e) = restored {
+                log::warn!("Could not restore broker reference for {}: {}", order_id, e);
+            }
+        }
+
+        let mut active: Vec<String> = state.orders.values()
+            .filter(|o| o.is_active() || o.state == OrderState::CREATED)
+            .filter_map(|o| o.order_id.clone())
+            .collect();
+        active.sort();
+
+        self.orders.lock().unwrap().extend(state.orders.clone());
+        for oid in &active {
+            if let Some(order) = state.orders.get(oid) {
+                if let Err(e) = self.attach_strategy(order, true) {
+                    log::warn!("Could not restore strategy for {}: {}", oid, e);
+                }
+            }
+        }
+
+        if let Some(j) = self.journal.lock().unwrap().as_mut() {
+            j.compact(&RecoveredState { orders: self.get_orders(), order_refs: state.order_refs.clone() })?;
+        }
+
+        let msg = Message::new(
+            "JOURNAL_RECOVERY".to_string(),
+            serde_json::json!({
+                "orders": state.orders.len(),
+                "active": active,
+                "order_refs": state.order_refs.len()
+            })
+        );
+        self.logger.lock().unwrap().log(msg);
+        Ok(active)
+    }
+
+    /// Compare every venue's open orders and executions with the OMS and repair the differences:
+    /// unknown working orders are adopted, missed fills are booked as executions and stale
+    /// states are corrected. Venues without order inquiry are skipped.
+    /// Run while the execution stream is quiet (startup, after a reconnect); fills arriving
+    /// during the run may otherwise be booked twice.
+    pub fn reconcile_internal(&self) -> anyhow::Result<ReconciliationReport> {
+        let mut report = ReconciliationReport::default();
+        let mut venues = self.venues();
+        venues.sort();
+
+        for venue in venues {
+            let adapter = self.adapter_for(&venue)?.clone();
+            let inquiry = adapter.get_open_orders().and_then(|open| Ok((open, adapter.get_executions()?)));
+            let (open, executions) = match inquiry {
+                Ok(r) => r,
+                Err(e) => {
+                    log::warn!("Skipping reconciliation of {}: {}", venue, e);
+                    report.skipped.push((venue.clone(), e.to_string()));
+                    continue;
+                }
+            };
+
+            let local: Vec<Order> = self.orders.lock().unwrap().values()
+                .filter(|o| o.venue == venue || (o.venue.is_empty() && venue == self.default_venue))
+                // Parent orders never reach the broker
+                .filter(|o| !o.strategy.is_parent())
+                .cloned()
+                .collect();
+
+            for brk in reconcile::find_breaks(&venue, &local, &open, &executions) {
+                match &brk {
+                    ReconciliationBreak::UnknownOrder { broker_order, .. } => {
+                        let average = executions.iter()
+                            .find(|e| e.exchange_order_id == broker_order.exchange_order_id)
+                            .map(|e| e.average_price);
+                        let order_id = self.adopt_order(&venue, broker_order, average);
+                        report.adopted.push(order_id);
+                    }
+                    ReconciliationBreak::MissedFill { order_id, quantity, price, .. } => {
+                        self.on_trade_update(order_id, *quantity, *price);
+                    }
+                    ReconciliationBreak::StaleState { order_id, from, to, .. } => {
+                        let msg = Some("Reconciled with broker".to_string());
+                        if from.can_transition_to(to) {
+                            self.on_order_status_update(order_id, to.clone(), msg);
+                        } else {
+                            // The broker is authoritative, even against the transition table
+                            self.force_order_state(order_id, to.clone(), msg);
+                        }
+                    }
+                    ReconciliationBreak::UnknownExecution { .. } => {}
+                }
+                report.breaks.push(brk);
+            }
+            report.venues.push(venue);
+        }
+
+        let msg = Message::new("RECONCILIATION".to_string(), serde_json::to_value(&report)?);
+        self.logger.lock().unwrap().log(msg);
+        Ok(report)
+    }
+
+    fn force_order_state(&self, order_id: &str, state: OrderState, msg: Option<String>) {
+        let order = self.orders.lock().unwrap().get_mut(order_id).map(|o| {
+            o.force_state(state, msg);
+            o.clone()
+        });
+        if let Some(order) = order {
+            self.journal_order(order_id);
+            self.notify_strategies_and_process_actions(&order);
+        }
+    }
+
+    /// Log a state change refused by the transition table. `source` names the event that asked for it.
+    fn log_illegal_transition(&self, e: &IllegalTransition, source: &str) {
+        log::warn!("{} ({})", e, source);
+        let msg = Message::new(
+            "ILLEGAL_TRANSITION".to_string(),
+            serde_json::json!({
+                "order_id": e.order_id,
+                "from": format!("{:?}", e.from),
+                "to": format!("{:?}", e.to),
+                "source": source
+            })
+        );
+        self.logger.lock().unwrap().log(msg);
+    }
+
+    fn log_adapter_error(&self, venue: &str, operation: &str, order_id: &str, e: &AdapterError) {
+        log::error!("{} on {} failed for {}: {}", operation, venue, order_id, e);
+        let msg = Message::new(
+            "ADAPTER_ERROR".to_string(),
+            serde_json::json!({
+                "venue": venue,
+                "operation": operation,
+                "order_id": order_id,
+                "kind": e.kind(),
+                "retryable": e.is_retryable(),
+                "http_status": e.http_status(),
+                "broker_status": e.broker_status(),
+                "message": e.to_string()
+            })
+        );
+        self.logger.lock().unwrap().log(msg);
+    }
+
+    /// Take over an order found working at the broker. Returns its new client order id.
+    fn adopt_order(&self, venue: &str, broker_order: &crate::adapter::BrokerOrder, average_price: Option<Decimal>) -> String {
+        let order_id = Uuid::new_v4().to_string();
+        let mut order = Order::new(
+            broker_order.symbol.clone(),
+            broker_order.side.clone(),
+            broker_order.order_type.clone(),
+            broker_order.quantity,
+            broker_order.price.map(|p| p.to_string()),
+            None,
+            None,
+            None,
+            "KRX".to_string(),
+            Some(venue.to_string()),
+        );
+        if let Some(exchange) = broker_order.order_ref["exchange"].as_str() {
+            order.exchange = exchange.to_string();
+        }
+        order.order_id = Some(order_id.clone());
+        order.exchange_order_id = Some(broker_order.exchange_order_id.clone());
+        order.filled_quantity = broker_order.filled_quantity;
+        order.average_fill_price = average_price.unwrap_or_default();
+        order.state = if broker_order.filled_quantity > 0 { OrderState::PARTIALLY_FILLED } else { OrderState::NEW };
+
+        if let Err(e) = self.adapter_for(venue).and_then(|a| a.restore_order_ref(&order_id, broker_order.order_ref.clone()).map_err(anyhow::Error::from)) {
+            log::warn!("Could not map adopted order {} to {}: {}", order_id, broker_order.exchange_order_id, e);
+        }
+        self.orders.lock().unwrap().insert(order_id.clone(), order);
+        self.journal_order(&order_id);
+        self.journal_order_ref(venue, &order_id);
+        order_id
+    }
+
+    /// Run the pre-trade checks. On failure the order is stored as REJECTED and the reason is logged.
+    fn check_risk(&self, order: &mut Order) -> bool {
+        let limits = self.risk_limits.lock().unwrap().clone();
+        let venue = self.venue_or_default(&order.venue).to_string();
+        // Positions and balance are per venue, and so is the exposure of the working orders
+        let open_orders: Vec<Order> = self.orders.lock().unwrap().values()
+            .filter(|o| o.is_active() && o.order_id != order.order_id)
+            .filter(|o| self.venue_or_default(&o.venue) == venue)
+            // A parent's exposure is carried by its children
+            .filter(|o| !o.strategy.is_parent())
+            .cloned()
+            .collect();
+        let book = self.get_venue_order_book(&venue, &order.symbol);
+        let account = self.get_venue_account(&venue);
+
+        let Err(rejection) = limits.check(order, &account, book.as_ref(), &open_orders) else {
+            return true;
+        };
+
+        if let Err(e) = order.update_state(OrderState::REJECTED, Some(rejection.reason.clone())) {
+            self.log_illegal_transition(&e, "risk");
+            return false;
+        }
+        let oid = order.order_id.clone().unwrap_or_default();
+        self.orders.lock().unwrap().insert(oid.clone(), order.clone());
+        self.journal_order(&oid);
+        // A parent strategy waiting on this child learns it will never work
+        self.notify_strategies_and_process_actions(order);
+
+        let msg = Message::new(
+            "RISK_REJECT".to_string(),
+            serde_json::json!({
+                "order_id": oid,
+                "symbol": order.symbol,
+                "venue": order.venue,
+                "side": format!("{:?}", order.side),
+                "quantity": order.quantity,
+                "price": order.price.map(|p| p.to_string()),
+                "check": rejection.check,
+                "reason": rejection.reason
+            })
+        );
+        self.logger.lock().unwrap().log(msg);
+        false
+    }
+
     pub fn start(&self, _py: Python, account_id: Option<String>) -> PyResult<()> {
-        self.start_internal(account_id).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.start_internal(account_id).map_err(to_py_err)
     }
 
     pub fn start_internal(&self, account_id: Option<String>) -> anyhow::Result<()> {
@@ -60,10 +413,8 @@ impl OMSEngine {
             true
         };
         
-        self.adapter.connect().map_err(|e| anyhow::anyhow!(e.to_string()))?;
-        
-        if let Some(acc) = account_id {
-            self.initialize_account_internal(acc).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        for adapter in self.adapters.values() {
+            adapter.connect().map_err(|e| anyhow::anyhow!(e.to_string()))?;
         }
 
         // Start logger
@@ -71,7 +422,32 @@ impl OMSEngine {
             let mut l = self.logger.lock().unwrap();
             l.start();
         }
+
+        let recovered = self.recover_from_journal()?;
+        // Before the account snapshot, which already contains any missed fills
+        let report = self.reconcile_internal()?;
         
+        if let Some(acc) = account_id {
+            self.initialize_account_internal(acc).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        }
+
+        // Resync books of recovered and adopted orders before strategies run again
+        let mut symbols: Vec<(String, String)> = {
+            let orders = self.orders.lock().unwrap();
+            recovered.iter().chain(report.adopted.iter())
+                .filter_map(|oid| orders.get(oid))
+                .filter(|o| o.is_active() || o.state == OrderState::CREATED)
+                .map(|o| (o.venue.clone(), o.symbol.clone()))
+                .collect()
+        };
+        symbols.sort();
+        symbols.dedup();
+        for (venue, symbol) in symbols {
+            if let Err(e) = self.initialize_symbol_on_venue_internal(&venue, symbol.clone()) {
+                log::warn!("Could not resync book for recovered symbol {}: {}", symbol, e);
+            }
+        }
+
         // Background Thread with Periodic Strategy Check
         let engine = self.clone();
         
@@ -87,7 +463,9 @@ impl OMSEngine {
                 // Periodic Strategy Check
                 engine.check_strategies();
                 
-                thread::sleep(Duration::from_millis(100)); // 100ms interval
+                // 100ms interval, 1s while the session is closed
+                let idle = engine.get_session_phase() == Some(TradingPhase::CLOSED);
+                thread::sleep(Duration::from_millis(if idle { 1000 } else { 100 }));
             }
         });
 
@@ -95,14 +473,39 @@ impl OMSEngine {
     }
     
     pub fn check_strategies(&self) {
+        self.check_strategies_at(chrono::Local::now().timestamp_millis() as f64 / 1000.0);
+    }
+
+    /// Strategy check at `now` (unix seconds): drop completed strategies, pass on a session phase
+    /// change, then run the timer of the strategies active in the current phase.
+    pub fn check_strategies_at(&self, now: f64) {
+        let (phase, change) = self.advance_session(now);
         let mut strats = self.active_strategies.lock().unwrap();
         
         // Remove completed strategies
+        let reports: Vec<serde_json::Value> = strats.iter()
+            .filter(|s| s.is_completed())
+            .filter_map(|s| s.report())
+            .collect();
+        let finished: Vec<String> = strats.iter()
+            .filter(|s| s.is_completed())
+            .filter_map(|s| s.get_origin_order_id())
+            .collect();
         strats.retain(|s| !s.is_completed());
         
         let mut actions = Vec::new();
+
+        if let Some((from, to)) = change {
+            for strat in strats.iter_mut() {
+                if let Ok(action) = strat.on_session_change(from, to) {
+                    if !matches!(action, StrategyAction::None) {
+                        actions.push(action);
+                    }
+                }
+            }
+        }
         
-        for strat in strats.iter_mut() {
+        for strat in strats.iter_mut().filter(|s| runs_in(s.as_ref(), phase)) {
             if let Ok(action) = strat.on_timer() {
                 if !matches!(action, StrategyAction::None) {
                      actions.push(action);
@@ -110,18 +513,103 @@ impl OMSEngine {
             }
         }
         drop(strats);
+
+        if let Some((from, to)) = change {
+            self.logger.lock().unwrap().log(Message::new("SESSION_CHANGE".to_string(), serde_json::json!({
+                "from": from.to_string(),
+                "to": to.to_string(),
+                "timestamp": now
+            })));
+        }
+        for report in reports {
+            self.logger.lock().unwrap().log(Message::new("STRATEGY_REPORT".to_string(), report));
+        }
+        for order_id in finished {
+            self.close_parent(&order_id);
+        }
         
+        self.process_actions(actions);
+    }
+
+    /// Cancel what is left of a parent order whose strategy has stopped (rejected child, end of
+    /// its window), so the parent ends in a terminal state like any order sent to a venue.
+    fn close_parent(&self, parent_id: &str) {
+        let mut orders = self.orders.lock().unwrap();
+        let Some(parent) = orders.get_mut(parent_id).filter(|o| o.strategy.is_parent() && !o.state.is_terminal()) else { return };
+        let result = parent.update_state(OrderState::CANCELED, Some("Strategy stopped".to_string())).map(|_| parent.clone());
+        drop(orders);
+        match result {
+            Ok(parent) => {
+                self.journal_order(parent_id);
+                self.notify_strategies_and_process_actions(&parent);
+            }
+            Err(e) => self.log_illegal_transition(&e, "strategy"),
+        }
+    }
+
+    /// Execute the actions returned by strategy callbacks, in order.
+    fn process_actions(&self, actions: Vec<StrategyAction>) {
         for action in actions {
-             match action {
-                  StrategyAction::PlaceOrder(o) => { let _ = self.send_order_internal(o); },
-                  StrategyAction::CancelOrder(oid) => { let _ = self.cancel_order_internal(oid); },
-                  StrategyAction::ModifyPrice(oid, price) => { let _ = self.modify_order_internal(oid, price); },
-                  StrategyAction::RemoveOrder(oid) => { let _ = self.remove_order_internal(oid); },
-                  StrategyAction::None => {}
-             }
+            let _ = self.process_action(action);
+        }
+    }
+
+    fn process_action(&self, action: StrategyAction) -> anyhow::Result<()> {
+        match action {
+            StrategyAction::PlaceOrder(o) => {
+                // Refused orders (risk, adapter) are stored as REJECTED rather than returned as errors
+                let oid = self.send_order_internal(o)?;
+                match self.orders.lock().unwrap().get(&oid).filter(|o| o.state == OrderState::REJECTED) {
+                    Some(o) => Err(anyhow::anyhow!("Order {} rejected: {}", oid, o.error_message.as_deref().unwrap_or(""))),
+                    None => Ok(()),
+                }
+            }
+            StrategyAction::CancelOrder(oid) => self.cancel_order_internal(oid),
+            StrategyAction::ModifyPrice(oid, price) => self.modify_order_internal(oid, price),
+            StrategyAction::RemoveOrder(oid) => self.remove_order_internal(oid),
+            StrategyAction::Batch(batch) => {
+                let total = batch.len();
+                for (i, action) in batch.into_iter().enumerate() {
+                    if let Err(e) = self.process_action(action) {
+                        log::warn!("Strategy batch stopped at action {} of {}: {}", i + 1, total, e);
+                        return Err(e);
+                    }
+                }
+                Ok(())
+            }
+            StrategyAction::Each(actions) => {
+                let mut result = Ok(());
+                for action in actions {
+                    if let Err(e) = self.process_action(action) {
+                        log::warn!("Strategy action failed: {}", e);
+                        result = Err(e);
+                    }
+                }
+                result
+            }
+            StrategyAction::None => Ok(()),
         }
     }
     
+    /// Register a strategy built outside the engine. It gets the same callbacks as the
+    /// strategies of `ExecutionStrategy` orders and is dropped once it completes.
+    pub fn add_strategy(&self, strategy: Box<dyn Strategy + Send + Sync>) {
+        self.active_strategies.lock().unwrap().push(strategy);
+    }
+
+    /// Build the strategy of `strategy` orders with `factory` from now on, replacing a built-in one.
+    pub fn register_strategy(&self, strategy: ExecutionStrategy, factory: StrategyFactory) {
+        self.strategy_registry.lock().unwrap().register(strategy, factory);
+    }
+
+    /// Register a strategy written in Python (see `crate::strategy::python::PyStrategy`).
+    pub fn add_python_strategy(&self, py: Python, strategy: PyObject) -> PyResult<()> {
+        let strategy = crate::strategy::python::PyStrategy::new(py, strategy)?;
+        // The timer thread calls into Python while it holds the strategies
+        py.allow_threads(|| self.add_strategy(Box::new(strategy)));
+        Ok(())
+    }
+
     pub fn get_active_strategy_order_ids(&self) -> Vec<String> {
         let strats = self.active_strategies.lock().unwrap();
         strats.iter().filter_map(|s| s.get_origin_order_id()).collect()
@@ -130,39 +618,78 @@ impl OMSEngine {
     pub fn remove_order_internal(&self, order_id: String) -> anyhow::Result<()> {
         let mut orders = self.orders.lock().unwrap();
         orders.remove(&order_id);
+        drop(orders);
+        self.write_journal(JournalEvent::Removed { order_id })?;
         Ok(())
     }
 
     pub fn modify_order_internal(&self, order_id: String, price: Option<Decimal>) -> anyhow::Result<()> {
         let mut orders = self.orders.lock().unwrap();
-        let (qty, _symbol) = if let Some(order) = orders.get(&order_id) {
-             let remaining = order.quantity - order.filled_quantity; 
-             if remaining <= 0 { return Ok(()); }
-             (remaining, order.symbol.clone())
-        } else {
-             return Err(anyhow::anyhow!("Order not found"));
+        let Some(order) = orders.get_mut(&order_id) else {
+            return Err(anyhow::anyhow!("Order not found"));
         };
-        // Update local order state? 
-        if let Some(order) = orders.get_mut(&order_id) {
-            order.price = price;
-            if price.is_none() { order.order_type = OrderType::MARKET; }
-            // If price is Some, keep it as LIMIT (or update to LIMIT if it was MARKET?)
-            // Usually Stop Strategy is Limit -> Market or Limit -> Different Limit.
-            // If price is Some, it is Limit.
-            else { order.order_type = OrderType::LIMIT; }
+        let qty = order.quantity - order.filled_quantity;
+        if qty <= 0 { return Ok(()); }
+        if order.state.is_terminal() {
+            return Err(anyhow::anyhow!("Order {} is {:?} and cannot be modified", order_id, order.state));
+        }
+        // Put back if the venue refuses the replacement
+        let prior = (order.state.clone(), order.price, order.order_type.clone());
+        if let Err(e) = order.update_state(OrderState::PENDING_REPLACE, None) {
+            drop(orders);
+            self.log_illegal_transition(&e, "modify");
+            return Err(e.into());
         }
+        order.price = price;
+        // A price makes it a limit order, none a market order (a stop triggering)
+        order.order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+        let venue = order.venue.clone();
         drop(orders);
-        
-        // Ensure book exists? modify_order doesn't need book.
-        
-        self.adapter.modify_order(&order_id, price, Some(qty))
-            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        self.journal_order(&order_id);
+
+        // The venue's acknowledgement of the replacement puts the order back to work
+        let result = self.adapter_for(&venue)?
+            .modify_order(&order_id, price, Some(qty))
+            .inspect_err(|e| self.log_adapter_error(&venue, "modify_order", &order_id, e));
+        let refused = match &result {
+            Ok(success) => !success,
+            // After a timeout the replacement may be live; reconciliation settles it
+            Err(e) => !e.is_outcome_unknown(),
+        };
+        if refused {
+            self.restore_order(&order_id, OrderState::PENDING_REPLACE, prior);
+            result?;
+            return Err(anyhow::anyhow!("Modify of {} refused by {}", order_id, venue));
+        }
+        result?;
+        // Brokers may assign a new order number on modify
+        self.journal_order_ref(&venue, &order_id);
+        // A new price goes to the back of its level
+        let order = self.orders.lock().unwrap().get(&order_id).cloned();
+        if let Some(order) = order {
+            self.track_queue(&order);
+        }
             
         Ok(())
     }
 
+    /// Put an order whose cancel or modify the venue refused back to the state, price and type it had,
+    /// unless a fill or venue notice has moved it on from `pending` meanwhile.
+    fn restore_order(&self, order_id: &str, pending: OrderState, prior: (OrderState, Option<Decimal>, OrderType)) {
+        let mut orders = self.orders.lock().unwrap();
+        let Some(order) = orders.get_mut(order_id).filter(|o| o.state == pending) else { return };
+        let (state, price, order_type) = prior;
+        order.force_state(state, Some(format!("{:?} refused", pending)));
+        order.price = price;
+        order.order_type = order_type;
+        let order = order.clone();
+        drop(orders);
+        self.journal_order(order_id);
+        self.notify_strategies_and_process_actions(&order);
+    }
+
     pub fn stop(&self, _py: Python) -> PyResult<()> {
-        self.stop_internal().map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+        self.stop_internal().map_err(to_py_err)
     }
 
     pub fn stop_internal(&self) -> anyhow::Result<()> {
@@ -176,7 +703,9 @@ impl OMSEngine {
             l.stop();
         }
 
-        self.adapter.disconnect().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        for adapter in self.adapters.values() {
+            adapter.disconnect().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        }
         Ok(())
     }
 
@@ -185,9 +714,15 @@ impl OMSEngine {
     }
 
     pub fn initialize_symbol_internal(&self, symbol: String) -> anyhow::Result<()> {
-        let snapshot = self.adapter.get_order_book_snapshot(&symbol)?;
+        self.initialize_symbol_on_venue_internal(&self.default_venue.clone(), symbol)
+    }
+
+    pub fn initialize_symbol_on_venue_internal(&self, venue: &str, symbol: String) -> anyhow::Result<()> {
+        let venue = self.venue_or_default(venue).to_string();
+        let mut snapshot = self.adapter_for(&venue)?.get_order_book_snapshot(&symbol)?;
+        snapshot.venue = venue.clone();
         let mut books = self.order_books.lock().unwrap();
-        books.insert(symbol.clone(), snapshot);
+        books.insert((venue, symbol), snapshot);
         Ok(())
     }
     
@@ -195,15 +730,28 @@ impl OMSEngine {
         self.initialize_account_internal(account_id).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
     }
 
+    /// Load the account `account_id` from every venue.
     pub fn initialize_account_internal(&self, account_id: String) -> anyhow::Result<()> {
-        let snapshot = self.adapter.get_account_snapshot(&account_id)?;
-        let mut acct = self.account.lock().unwrap();
-        *acct = snapshot;
+        let mut venues = self.venues();
+        venues.sort();
+        for venue in venues {
+            self.initialize_venue_account_internal(&venue, &account_id)?;
+        }
+        Ok(())
+    }
+
+    pub fn initialize_venue_account_internal(&self, venue: &str, account_id: &str) -> anyhow::Result<()> {
+        let venue = self.venue_or_default(venue).to_string();
+        let mut snapshot = self.adapter_for(&venue)?.get_account_snapshot(account_id)?;
+        snapshot.venue = venue.clone();
+        self.accounts.lock().unwrap().insert(venue.clone(), snapshot);
+        self.notify_account_update(&venue);
         Ok(())
     }
 
-    pub fn send_order(&self, _py: Python, order: Order) -> PyResult<String> {
-        self.send_order_internal(order).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    pub fn send_order(&self, py: Python, order: Order) -> PyResult<String> {
+        // Strategy callbacks on the engine threads need the GIL while the order is processed
+        py.allow_threads(|| self.send_order_internal(order)).map_err(to_py_err)
     }
 
     pub fn send_order_internal(&self, mut order: Order) -> anyhow::Result<String> {
@@ -211,99 +759,68 @@ impl OMSEngine {
             order.order_id = Some(Uuid::new_v4().to_string());
         }
         
+        if order.venue.is_empty() {
+            order.venue = self.default_venue.clone();
+        }
+        let adapter = self.adapter_for(&order.venue)?.clone();
+        // A submitted order starts its life cycle over
+        order.state = OrderState::CREATED;
+
         let order_id_clone = order.order_id.clone();
+
+        // A pegged order goes out, and is risk-checked, at the current peg price
+        if order.strategy == ExecutionStrategy::PEG {
+            if let Err(e) = self.price_pegged_order(&mut order) {
+                return self.reject_order(order, e);
+            }
+        }
+        // The decision price of an arrival-price order is the mid as it reaches the OMS
+        if order.strategy == ExecutionStrategy::IS && !order.strategy_params.contains_key("arrival_price") {
+            if let Some(mid) = self.get_venue_order_book(&order.venue, &order.symbol).and_then(|book| book.get_mid_price()) {
+                order.strategy_params.insert("arrival_price".to_string(), mid.normalize().to_string());
+            }
+        }
+
+        if !self.check_risk(&mut order) {
+            return Ok(order_id_clone.unwrap_or_default());
+        }
         
         // Strategy Handling
-        match order.strategy {
-            ExecutionStrategy::STOP => {
-                order.state = OrderState::CREATED;
-                
-                // Parse Strategy Params
-                 if let Some(price_str) = order.strategy_params.get("trigger_price") {
-                    if let Ok(trigger_price) = Decimal::from_str(price_str) {
-                         let side_str = order.strategy_params.get("trigger_side").map(|s| s.as_str()).unwrap_or("BUY");
-                         let trigger_side = match side_str {
-                             "SELL" => OrderSide::SELL,
-                             _ => OrderSide::BUY,
-                         };
-                         let ts_str = order.strategy_params.get("trigger_timestamp").map(|s| s.as_str()).unwrap_or("0");
-                         let trigger_timestamp = ts_str.parse::<f64>().unwrap_or(0.0);
-                         
-                         let stop_price = order.strategy_params.get("chained_price").and_then(|p| Decimal::from_str(p).ok());
-
-                         let strat = crate::strategy::stop::StopStrategy::new(
-                             order.order_id.clone().unwrap(),
-                             order.symbol.clone(),
-                             order.side.clone(), // Clone to avoid move
-                             order.quantity,
-                             trigger_side,
-                             trigger_price,
-                             trigger_timestamp,
-                             stop_price
-                         );
-                         
-                         {
-                             let mut strats = self.active_strategies.lock().unwrap();
-                             strats.push(Box::new(strat));
-                         }
-                    } else {
-                        println!("Failed to parse trigger price for Stop Order");
-                    }
-                 } else {
-                     println!("Missing trigger params for Stop Order");
-                 }
-
-                let mut orders = self.orders.lock().unwrap();
-                let oid = order.order_id.clone().unwrap_or_default();
-                orders.insert(oid.clone(), order.clone());
-            },
-            ExecutionStrategy::LIMIT => {
-                order.state = OrderState::CREATED;
-                
-                // For Limit Strategy, we just need the limit price from the order itself or params?
-                // The order.price IS the limit price usually for a standard limit order.
-                // But if this is a strategy triggered limit order, maybe it's different?
-                // Let's assume order.price is the target price.
-                
-                let limit_price = order.price.unwrap_or(Decimal::ZERO);
-                
-                let strat = crate::strategy::limit::LimitStrategy::new(
-                    order.order_id.clone().unwrap(),
-                    order.symbol.clone(),
-                    order.side.clone(),
-                    order.quantity,
-                    limit_price
-                );
-                
-                {
-                    let mut strats = self.active_strategies.lock().unwrap();
-                    strats.push(Box::new(strat));
-                }
-                
-                let mut orders = self.orders.lock().unwrap();
-                let oid = order.order_id.clone().unwrap_or_default();
-                orders.insert(oid.clone(), order.clone());
-            },
-            _ => {}
+        if order.strategy.is_parent() {
+            return self.accept_parent_order(order);
+        }
+        if self.strategy_registry.lock().unwrap().contains(&order.strategy) {
+            if let Err(e) = self.attach_strategy(&order, false) {
+                return self.reject_order(order, e);
+            }
+            let oid = order.order_id.clone().unwrap_or_default();
+            self.orders.lock().unwrap().insert(oid, order.clone());
         }
 
         {
              let mut orders = self.orders.lock().unwrap();
-             if let Some(oid) = &order.order_id {
-                 order.state = OrderState::PENDING_NEW;
-                 orders.insert(oid.clone(), order.clone());
+             if let Some(oid) = order.order_id.clone() {
+                 order.update_state(OrderState::PENDING_NEW, None)?;
+                 orders.insert(oid, order.clone());
              }
         }
+        // Write-ahead: the order is on disk before the broker sees it
+        self.write_journal(JournalEvent::Order { order: Box::new(order.clone()) })?;
         
-        let success = self.adapter.place_order(&order)?;
-        
-        if !success {
-             let mut orders = self.orders.lock().unwrap();
-             if let Some(oid) = &order.order_id {
-                 if let Some(o) = orders.get_mut(oid) {
-                     o.update_state(OrderState::REJECTED, Some("Adapter Send Failed".into()));
-                 }
-             }
+        let success = match adapter.place_order(&order) {
+            Ok(success) => success,
+            Err(e) => {
+                let oid = order_id_clone.unwrap_or_default();
+                self.log_adapter_error(&order.venue, "place_order", &oid, &e);
+                // After a timeout the order may be live; reconciliation settles it
+                if !e.is_outcome_unknown() {
+                    self.on_order_status_update(&oid, OrderState::REJECTED, Some(e.to_string()));
+                }
+                return Err(e.into());
+            }
+        };
+        if success {
+            self.journal_order_ref(&order.venue, order.order_id.as_deref().unwrap_or_default());
         }
         
         if !success {
@@ -315,20 +832,127 @@ impl OMSEngine {
         Ok(order_id_clone.unwrap_or_default())
     }
 
-    pub fn cancel_order(&self, _py: Python, order_id: String) -> PyResult<()> {
-        self.cancel_order_internal(order_id).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    /// Keep a parent order (TWAP) in the OMS and let its strategy send the children.
+    /// The parent is acknowledged locally and never reaches the adapter.
+    fn accept_parent_order(&self, mut order: Order) -> anyhow::Result<String> {
+        let oid = order.order_id.clone().unwrap_or_default();
+        if let Err(e) = self.attach_strategy(&order, false) {
+            return self.reject_order(order, e);
+        }
+
+        order.update_state(OrderState::PENDING_NEW, None)?;
+        order.update_state(OrderState::NEW, None)?;
+        self.orders.lock().unwrap().insert(oid.clone(), order.clone());
+        self.write_journal(JournalEvent::Order { order: Box::new(order) })?;
+        Ok(oid)
+    }
+
+    /// Price a PEG order off the current book. Without a book the given price is kept.
+    fn price_pegged_order(&self, order: &mut Order) -> anyhow::Result<()> {
+        let strat = crate::strategy::peg::PegStrategy::from_order(order)?;
+        let price = self.get_venue_order_book(&order.venue, &order.symbol)
+            .and_then(|book| strat.target_price(&book))
+            .or(order.price)
+            .ok_or_else(|| anyhow::anyhow!("No book for {} to peg to and no price given", order.symbol))?;
+        order.order_type = OrderType::LIMIT;
+        order.price = Some(price);
+        Ok(())
+    }
+
+    /// Keep an order refused by its strategy (bad `strategy_params`) as REJECTED and return the error.
+    fn reject_order(&self, mut order: Order, e: anyhow::Error) -> anyhow::Result<String> {
+        let oid = order.order_id.clone().unwrap_or_default();
+        order.update_state(OrderState::REJECTED, Some(e.to_string()))?;
+        self.orders.lock().unwrap().insert(oid.clone(), order);
+        self.journal_order(&oid);
+        Err(e)
+    }
+
+    /// Child orders a parent strategy sent for `parent_order_id`, oldest first.
+    pub fn get_child_orders(&self, parent_order_id: &str) -> Vec<Order> {
+        let mut children: Vec<Order> = self.orders.lock().unwrap().values()
+            .filter(|o| o.parent_order_id.as_deref() == Some(parent_order_id))
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| a.created_at.total_cmp(&b.created_at));
+        children
+    }
+
+    /// Create the execution strategy for `order` from the registry and register it.
+    /// `recovered` marks orders rebuilt from the journal, whose strategy may already have fired;
+    /// a recovered parent takes over its journaled children.
+    fn attach_strategy(&self, order: &Order, recovered: bool) -> anyhow::Result<()> {
+        let Some(mut strat) = self.strategy_registry.lock().unwrap().create(order, recovered)? else {
+            return Ok(());
+        };
+        if recovered && order.strategy.is_parent() {
+            for child in self.get_child_orders(order.order_id.as_deref().unwrap_or_default()) {
+                strat.restore_child(&child);
+            }
+        }
+        // Hand over the current books (the order's and the strategy's other legs) so children and pegs
+        // can be priced at once. Not for a stop: a trigger fired here would be lost, it waits for the
+        // next book from the gateway.
+        if order.strategy != ExecutionStrategy::STOP {
+            if let Some(book) = self.get_venue_order_book(&order.venue, &order.symbol) {
+                strat.on_order_book_update(&book)?;
+            }
+            // Other legs may trade on another venue
+            for symbol in strat.symbols() {
+                if let Some(book) = self.get_venue_order_book(&order.venue, &symbol).or_else(|| self.get_order_book(&symbol)) {
+                    strat.on_order_book_update(&book)?;
+                }
+            }
+        }
+        strat.on_account_update(&self.get_venue_account(&order.venue))?;
+        self.active_strategies.lock().unwrap().push(strat);
+        Ok(())
+    }
+
+    pub fn cancel_order(&self, py: Python, order_id: String) -> PyResult<()> {
+        py.allow_threads(|| self.cancel_order_internal(order_id)).map_err(to_py_err)
     }
 
     pub fn cancel_order_internal(&self, order_id: String) -> anyhow::Result<()> {
         let mut orders = self.orders.lock().unwrap();
-        if let Some(order) = orders.get_mut(&order_id) {
-            order.update_state(OrderState::PENDING_CANCEL, None);
+        // A parent is canceled locally; its strategy pulls the working child
+        if let Some(parent) = orders.get_mut(&order_id).filter(|o| o.strategy.is_parent()) {
+            if let Err(e) = parent.update_state(OrderState::CANCELED, Some("Canceled by user".to_string())) {
+                drop(orders);
+                self.log_illegal_transition(&e, "cancel");
+                return Err(e.into());
+            }
+            let parent = parent.clone();
+            drop(orders);
+            self.journal_order(&order_id);
+            self.notify_strategies_and_process_actions(&parent);
+            return Ok(());
+        }
+        let (venue, prior) = if let Some(order) = orders.get_mut(&order_id) {
+            let prior = (order.state.clone(), order.price, order.order_type.clone());
+            if let Err(e) = order.update_state(OrderState::PENDING_CANCEL, None) {
+                drop(orders);
+                self.log_illegal_transition(&e, "cancel");
+                return Err(e.into());
+            }
+            (order.venue.clone(), prior)
         } else {
              return Err(anyhow::anyhow!("Order not found"));
-        }
+        };
         drop(orders);
+        self.journal_order(&order_id);
         
-        let success = self.adapter.cancel_order(&order_id)?;
+        let success = match self.adapter_for(&venue)?.cancel_order(&order_id) {
+            Ok(success) => success,
+            Err(e) => {
+                self.log_adapter_error(&venue, "cancel_order", &order_id, &e);
+                // After a timeout the cancel may have gone through; reconciliation settles it
+                if !e.is_outcome_unknown() {
+                    self.restore_order(&order_id, OrderState::PENDING_CANCEL, prior);
+                }
+                return Err(e.into());
+            }
+        };
             
         let msg = Message::new(
             "ORDER_CANCEL_REQ".to_string(),
@@ -340,7 +964,8 @@ impl OMSEngine {
         self.logger.lock().unwrap().log(msg);
             
         if !success {
-             // Handle failure
+            self.restore_order(&order_id, OrderState::PENDING_CANCEL, prior);
+            return Err(anyhow::anyhow!("Cancel of {} refused by {}", order_id, venue));
         }
         
         Ok(())
@@ -350,26 +975,13 @@ impl OMSEngine {
         let mut orders = self.orders.lock().unwrap();
         
         if let Some(order) = orders.get_mut(order_id) {
-             let old_filled = order.filled_quantity;
-             let new_filled = old_filled + fill_qty;
-             let total_qty = order.quantity;
-             
-             order.filled_quantity = new_filled;
-             let old_qty_dec = Decimal::from_i64(old_filled).unwrap_or_default();
-             let fill_qty_dec = Decimal::from_i64(fill_qty).unwrap_or_default();
-             let new_qty_dec = Decimal::from_i64(new_filled).unwrap_or_default();
-             
-             if new_qty_dec > Decimal::ZERO {
-                 let old_val = old_qty_dec * order.average_fill_price;
-                 let fill_val = fill_qty_dec * fill_price;
-                 order.average_fill_price = (old_val + fill_val) / new_qty_dec;
-             }
-             
-             order.state = if new_filled >= total_qty { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
-             order.updated_at = Local::now().timestamp_millis() as f64 / 1000.0;
+             // The fill is booked either way; a fill on a closed order keeps its state
+             let illegal = order.apply_fill(fill_qty, fill_price).err();
              
+             let venue = self.venue_or_default(&order.venue).to_string();
              {
-                 let mut acct = self.account.lock().unwrap();
+                 let mut accounts = self.accounts.lock().unwrap();
+                 let acct = accounts.entry(venue.clone()).or_insert_with(|| AccountState::for_venue(&venue));
                  let symbol = order.symbol.clone();
                  let side = match order.side { OrderSide::BUY => "BUY", OrderSide::SELL => "SELL" };
                  
@@ -378,12 +990,104 @@ impl OMSEngine {
              
              // Notify Strategies
              let order_clone = order.clone();
+             let parent_id = Self::parent_of(&orders, &order_clone);
              drop(orders); // Drop lock before notifying strategies
-             
+             if let Some(e) = illegal {
+                 self.log_illegal_transition(&e, "execution");
+             }
+             self.journal_order(order_id);
+
+             // The parent is up to date before its strategy hears of the fill (and may finish)
+             if let Some(parent_id) = parent_id {
+                 self.aggregate_child_fill(&parent_id, fill_qty, fill_price);
+             }
+             self.queue_positions.lock().unwrap().on_fill(order_id, order_clone.quantity - order_clone.filled_quantity);
+             self.track_queue(&order_clone);
+             self.notify_account_update(&venue);
              self.notify_strategies_and_process_actions(&order_clone);
+        }
+    }
+
+    /// Parent order a child's fills roll up into: its `parent_order_id`, when that is a parent order
+    /// on the same symbol and side.
+    /// Exits of a bracket are on the other side and stay out of the entry, the hedge leg of a spread
+    /// is another symbol, and the quotes of a market maker, on both sides, never fill its parent.
+    fn parent_of(orders: &HashMap<String, Order>, child: &Order) -> Option<String> {
+        let parent_id = child.parent_order_id.as_deref()?;
+        orders.get(parent_id)
+            .filter(|p| p.strategy.is_parent() && p.strategy != ExecutionStrategy::MM && p.symbol == child.symbol && p.side == child.side)
+            .map(|_| parent_id.to_string())
+    }
+
+    /// Roll a child fill up into its parent (quantity, average price, state).
+    /// The account has already been booked with the child fill.
+    fn aggregate_child_fill(&self, parent_id: &str, fill_qty: i64, fill_price: Decimal) {
+        let mut orders = self.orders.lock().unwrap();
+        let Some(parent) = orders.get_mut(parent_id) else { return };
+        let illegal = parent.apply_fill(fill_qty, fill_price).err();
+        let parent = parent.clone();
+        drop(orders);
+        if let Some(e) = illegal {
+            self.log_illegal_transition(&e, "execution");
+        }
+        self.journal_order(parent_id);
+        self.notify_strategies_and_process_actions(&parent);
+    }
+
+    /// Pass a market trade print of `venue` to the strategies trading there.
+    pub fn on_market_trade(&self, venue: &str, symbol: &str, price: Decimal, quantity: i64, timestamp: f64) {
+        let venue = self.venue_or_default(venue);
+        self.queue_positions.lock().unwrap().on_trade(venue, symbol, price, quantity);
+        let phase = self.get_session_phase();
+        let mut strats = self.active_strategies.lock().unwrap();
+        let mut actions = Vec::new();
+        for strat in strats.iter_mut().filter(|s| runs_in(s.as_ref(), phase) && s.venue().is_none_or(|v| v == venue)) {
+             if let Ok(action) = strat.on_trade_update(symbol, price, quantity, timestamp) {
+                 if !matches!(action, StrategyAction::None) {
+                     actions.push(action);
+                 }
+             }
+        }
+        drop(strats);
+
+        self.process_actions(actions);
+    }
 
-             return;
+    /// Pass the account of `venue`, as booked so far, to the strategies.
+    fn notify_account_update(&self, venue: &str) {
+        let account = self.get_venue_account(venue);
+        let mut strats = self.active_strategies.lock().unwrap();
+        let mut actions = Vec::new();
+        for strat in strats.iter_mut() {
+             if let Ok(action) = strat.on_account_update(&account) {
+                 if !matches!(action, StrategyAction::None) {
+                     actions.push(action);
+                 }
+             }
         }
+        drop(strats);
+
+        self.process_actions(actions);
+    }
+
+    /// A venue gateway connected, dropped or is reconnecting; strategies quoting there pull or resume.
+    pub fn on_connection_status(&self, venue: &str, status: &ConnectionStatus) {
+        self.logger.lock().unwrap().log(Message::new("CONNECTION_STATUS".to_string(), serde_json::json!({
+            "venue": venue,
+            "status": format!("{:?}", status)
+        })));
+        let mut strats = self.active_strategies.lock().unwrap();
+        let mut actions = Vec::new();
+        for strat in strats.iter_mut() {
+             if let Ok(action) = strat.on_connection_status(venue, status) {
+                 if !matches!(action, StrategyAction::None) {
+                     actions.push(action);
+                 }
+             }
+        }
+        drop(strats);
+
+        self.process_actions(actions);
     }
 
     fn notify_strategies_and_process_actions(&self, order: &Order) {
@@ -398,32 +1102,46 @@ impl OMSEngine {
         }
         drop(strats);
         
-        for action in actions {
-            match action {
-                  StrategyAction::PlaceOrder(o) => { let _ = self.send_order_internal(o); },
-                  StrategyAction::CancelOrder(oid) => { let _ = self.cancel_order_internal(oid); },
-                  StrategyAction::ModifyPrice(oid, price) => { let _ = self.modify_order_internal(oid, price); },
-                  StrategyAction::RemoveOrder(oid) => { let _ = self.remove_order_internal(oid); },
-                  StrategyAction::None => {}
-             }
-        }
+        self.process_actions(actions);
     }
 
     pub fn on_order_status_update(&self, order_id: &str, state: OrderState, msg: Option<String>) {
         let mut orders = self.orders.lock().unwrap();
-        let order_ref = if let Some(order) = orders.get_mut(order_id) {
-             order.update_state(state.clone(), msg);
-             Some(order.clone()) 
-        } else {
-            None
+        let order_ref = match orders.get_mut(order_id).map(|order| order.update_state(state.clone(), msg).map(|_| order.clone())) {
+            Some(Ok(order)) => Some(order),
+            Some(Err(e)) => {
+                drop(orders);
+                self.log_illegal_transition(&e, "status");
+                return;
+            }
+            None => None,
         };
         drop(orders);
         
         if let Some(order) = order_ref {
+            self.journal_order(order_id);
+            self.track_queue(&order);
             self.notify_strategies_and_process_actions(&order);
         }
     }
 
+    /// Start, move or stop the queue estimate of an order after its state or price changed.
+    fn track_queue(&self, order: &Order) {
+        let book = self.get_venue_order_book(&order.venue, &order.symbol);
+        let now = chrono::Local::now().timestamp_millis() as f64 / 1000.0;
+        self.queue_positions.lock().unwrap().on_order(order, book.as_ref(), now);
+    }
+
+    /// Estimated queue position of a resting limit order; `None` for orders not resting at a venue.
+    pub fn get_queue_position(&self, order_id: &str) -> Option<QueuePosition> {
+        self.queue_positions.lock().unwrap().get(order_id).cloned()
+    }
+
+    /// Estimated queue positions of all resting limit orders.
+    pub fn get_queue_positions(&self) -> Vec<QueuePosition> {
+        self.queue_positions.lock().unwrap().positions()
+    }
+
     pub fn on_market_data(&self, _py: Python, _data: PyObject) -> PyResult<()> {
         Ok(())
     }
@@ -432,12 +1150,30 @@ impl OMSEngine {
         Ok(())
     }
     
+    /// Account of the default venue.
     pub fn get_account(&self) -> AccountState {
-        self.account.lock().unwrap().clone()
+        self.get_venue_account(&self.default_venue)
     }
 
+    pub fn get_venue_account(&self, venue: &str) -> AccountState {
+        let venue = self.venue_or_default(venue);
+        self.accounts.lock().unwrap().get(venue).cloned().unwrap_or_else(|| AccountState::for_venue(venue))
+    }
+
+    /// Book of `symbol` on the default venue, or on the first venue (by name) that has one.
     pub fn get_order_book(&self, symbol: &str) -> Option<OrderBook> {
-        self.order_books.lock().unwrap().get(symbol).cloned()
+        let books = self.order_books.lock().unwrap();
+        books.get(&(self.default_venue.clone(), symbol.to_string())).cloned().or_else(|| {
+            books.iter()
+                .filter(|((_, s), _)| s == symbol)
+                .min_by(|((a, _), _), ((b, _), _)| a.cmp(b))
+                .map(|(_, book)| book.clone())
+        })
+    }
+
+    pub fn get_venue_order_book(&self, venue: &str, symbol: &str) -> Option<OrderBook> {
+        let key = (self.venue_or_default(venue).to_string(), symbol.to_string());
+        self.order_books.lock().unwrap().get(&key).cloned()
     }
     
     pub fn get_orders(&self) -> HashMap<String, Order> {
@@ -445,14 +1181,20 @@ impl OMSEngine {
     }
 
     pub fn on_order_book_information(&self, msg: IncomingMessage) -> PyResult<()> {
+        self.on_venue_order_book_information(&self.default_venue.clone(), msg)
+    }
+
+    pub fn on_venue_order_book_information(&self, venue: &str, msg: IncomingMessage) -> PyResult<()> {
         let (symbol, delta_opt, snapshot_opt) = match msg {
             IncomingMessage::OrderBookUpdate{symbol, delta} => (symbol, Some(delta), None),
             IncomingMessage::OrderBookSnapshot(s) => (s.symbol.clone(), None, Some(s)),
             _ => return Ok(()),
         };
         
+        let venue = self.venue_or_default(venue).to_string();
         let mut books = self.order_books.lock().unwrap();
-        let book = books.entry(symbol.clone()).or_insert_with(|| OrderBook::new(symbol.clone()));
+        let book = books.entry((venue.clone(), symbol.clone())).or_insert_with(|| OrderBook::new(symbol.clone()));
+        book.venue = venue.clone();
         
         if let Some(delta) = delta_opt {
             book.apply_delta(&delta);
@@ -462,15 +1204,20 @@ impl OMSEngine {
         
         if !book.validate() {
             drop(books); 
-            self.reconcile_orderbook(&symbol)?;
+            self.reconcile_venue_orderbook(&venue, &symbol)?;
             return Ok(()); 
         }
+        // Actions may place orders, which read the books again
+        let book = book.clone();
+        drop(books);
+        self.queue_positions.lock().unwrap().on_book(&book);
         
         {
+            let phase = self.get_session_phase();
             let mut strats = self.active_strategies.lock().unwrap();
             let mut actions = Vec::new();
             
-            for strat in strats.iter_mut() {
+            for strat in strats.iter_mut().filter(|s| runs_in(s.as_ref(), phase)) {
                 if let Ok(action) = strat.on_order_book_update(&book) {
                     if !matches!(action, StrategyAction::None) {
                         actions.push(action);
@@ -479,48 +1226,68 @@ impl OMSEngine {
             }
             drop(strats); 
             
-            for action in actions {
-                match action {
-                    StrategyAction::PlaceOrder(o) => {
-                         let _ = self.send_order_internal(o);
-                    },
-                    StrategyAction::CancelOrder(oid) => {
-                         let _ = self.cancel_order_internal(oid);
-                    },
-                    StrategyAction::ModifyPrice(oid, price) => {
-                         let _ = self.modify_order_internal(oid, price);
-                    },
-                    StrategyAction::RemoveOrder(oid) => {
-                         let _ = self.remove_order_internal(oid);
-                    },
-                    StrategyAction::None => {}
-                }
-            }
+            self.process_actions(actions);
         }
         
         Ok(())
     }
     
     pub fn reconcile_orderbook(&self, symbol: &str) -> PyResult<()> {
-        eprintln!("OrderBook for {} is being reconciled.", symbol);
-        let snapshot = self.adapter.get_order_book_snapshot(symbol)
-             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        self.reconcile_venue_orderbook(&self.default_venue.clone(), symbol)
+    }
+
+    pub fn reconcile_venue_orderbook(&self, venue: &str, symbol: &str) -> PyResult<()> {
+        eprintln!("OrderBook for {} on {} is being reconciled.", symbol, venue);
+        let venue = self.venue_or_default(venue).to_string();
+        let mut snapshot = self.adapter_for(&venue)
+             .and_then(|a| a.get_order_book_snapshot(symbol).map_err(anyhow::Error::from))
+             .map_err(to_py_err)?;
+        snapshot.venue = venue.clone();
              
         let mut books = self.order_books.lock().unwrap();
-        let book = books.entry(symbol.to_string()).or_insert_with(|| OrderBook::new(symbol.to_string()));
-        *book = snapshot;
+        books.insert((venue, symbol.to_string()), snapshot);
         Ok(())
     }
 
     pub fn start_gateway_listener(&self, receiver: Receiver<IncomingMessage>) -> PyResult<()> {
+        let (tx, rx) = mpsc::channel();
+        let venue = self.default_venue.clone();
+        thread::spawn(move || {
+            for msg in receiver {
+                if tx.send((venue.clone(), msg)).is_err() { break; }
+            }
+        });
+        self.start_venue_listener(rx)
+    }
+
+    /// Attach every venue adapter's monitor and merge the streams into one gateway listener.
+    pub fn start_venue_gateways(&self) -> PyResult<()> {
+        let (tx, rx) = mpsc::channel();
+        for (venue, adapter) in &self.adapters {
+            let (adapter_tx, adapter_rx) = mpsc::channel();
+            adapter.set_monitor(adapter_tx);
+
+            let tx = tx.clone();
+            let venue = venue.clone();
+            thread::spawn(move || {
+                for msg in adapter_rx {
+                    if tx.send((venue.clone(), msg)).is_err() { break; }
+                }
+            });
+        }
+        self.start_venue_listener(rx)
+    }
+
+    fn start_venue_listener(&self, receiver: Receiver<(String, IncomingMessage)>) -> PyResult<()> {
         let engine = self.clone();
     
         thread::spawn(move || {
-            for msg in receiver {
+            for (venue, msg) in receiver {
                 {
                      let msg_clone = msg.clone();
+                     let venue_clone = venue.clone();
                      engine.logger.lock().unwrap().log_lazy("MARKET_DATA".to_string(), Box::new(move || {
-                        match &msg_clone {
+                        let mut body = match &msg_clone {
                             IncomingMessage::OrderBookUpdate{symbol, delta} => {
                                 let (bp, bv): (Vec<_>, Vec<_>) = delta.bids.iter().map(|(p, q)| (p.to_string(), *q)).unzip();
                                 let (ap, av): (Vec<_>, Vec<_>) = delta.asks.iter().map(|(p, q)| (p.to_string(), *q)).unzip();
@@ -529,6 +1296,7 @@ impl OMSEngine {
                                     "type": "OrderBookDelta", 
                                     "symbol": symbol, 
                                     "update_id": delta.update_id,
+                                    "timestamp": delta.timestamp,
                                     "data": {
                                         "bp": bp,
                                         "bv": bv,
@@ -537,25 +1305,37 @@ impl OMSEngine {
                                     }
                                 })
                             },
-                            IncomingMessage::MarketTrade{symbol, price, quantity, ..} => serde_json::json!({"type": "Trade", "symbol": symbol, "price": price.to_string(), "qty": quantity}),
-                            IncomingMessage::Execution{order_id, fill_qty, ..} => serde_json::json!({"type": "Execution", "order_id": order_id, "qty": fill_qty}),
+                            IncomingMessage::MarketTrade{symbol, price, quantity, timestamp} => serde_json::json!({"type": "Trade", "symbol": symbol, "price": price.to_string(), "qty": quantity, "timestamp": timestamp}),
+                            IncomingMessage::Execution{order_id, fill_qty, fill_price} => serde_json::json!({"type": "Execution", "order_id": order_id, "qty": fill_qty, "price": fill_price.to_string()}),
                             IncomingMessage::OrderBookSnapshot(s) => serde_json::json!({
                                 "type": "OrderBookSnapshot", 
                                 "symbol": s.symbol,
+                                "update_id": s.update_id,
+                                "timestamp": s.timestamp,
                                 "bids": s.bids,
                                 "asks": s.asks 
                             }),
-                            IncomingMessage::OrderStatus{order_id, state, ..} => serde_json::json!({"type": "OrderUpdate", "order_id": order_id, "state": format!("{:?}", state)}),
+                            IncomingMessage::OrderStatus{order_id, state, filled_qty, filled_price, msg, updated_at} => serde_json::json!({
+                                "type": "OrderUpdate", 
+                                "order_id": order_id, 
+                                "state": format!("{:?}", state),
+                                "filled_qty": filled_qty,
+                                "filled_price": filled_price.map(|p| p.to_string()),
+                                "msg": msg,
+                                "updated_at": updated_at
+                            }),
                             _ => serde_json::json!({"type": "Unknown"}),
-                        }
+                        };
+                        body["venue"] = serde_json::json!(venue_clone);
+                        body
                     }));
                 }
                 match msg {
                     IncomingMessage::OrderBookUpdate{..} | IncomingMessage::OrderBookSnapshot(_) => {
-                         let _ = engine.on_order_book_information(msg);
+                         let _ = engine.on_venue_order_book_information(&venue, msg);
                     },
-                    IncomingMessage::MarketTrade{..} => {
-                        // Handle market trade if needed
+                    IncomingMessage::MarketTrade{symbol, price, quantity, timestamp} => {
+                        engine.on_market_trade(&venue, &symbol, price, quantity, timestamp);
                     },
                     IncomingMessage::Execution{order_id, fill_qty, fill_price} => {
                          engine.on_trade_update(&order_id, fill_qty, fill_price);
@@ -563,6 +1343,9 @@ impl OMSEngine {
                     IncomingMessage::OrderStatus{order_id, state, msg, ..} => {
                         engine.on_order_status_update(&order_id, state, msg);
                     },
+                    IncomingMessage::ConnectionStatus(status) => {
+                        engine.on_connection_status(&venue, &status);
+                    },
                     _ => {}
                 }
             }
@@ -1,16 +1,38 @@
 use pyo3::prelude::*;
-use pyo3::types::{PyAny, PyDict};
+use pyo3::types::PyDict;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::sync::mpsc;
 use crate::oms::engine::OMSEngine;
+use crate::oms::journal::OrderJournal;
 use crate::oms::order::Order;
-use crate::adapter::mock::MockAdapter;
-use crate::adapter::interface::{extract_adapter, initialize_monitor};
+use crate::oms::risk::RiskLimits;
+use crate::oms::session::{SessionSchedule, TradingPhase};
+use crate::adapter::interface::build_adapter;
 use crate::logger::config::{LoggerConfig, LogDestinationInfo};
 use crate::logger::Logger;
 use crate::adapter::Adapter;
 
+fn start_logger(s3_bucket: Option<String>, s3_region: Option<String>, s3_prefix: Option<String>) -> Arc<Mutex<Logger>> {
+    let destination = if let (Some(bucket), Some(region)) = (s3_bucket, s3_region) {
+        LogDestinationInfo::AmazonS3 { 
+            bucket, 
+            key_prefix: s3_prefix.unwrap_or_else(|| "logs".to_string()), 
+            region 
+        }
+    } else {
+         LogDestinationInfo::Console 
+    };
+
+    let config = LoggerConfig {
+        destination,
+        flush_interval_seconds: 60,
+        batch_size: 8192,
+    };
+    let logger = Arc::new(Mutex::new(Logger::new(config)));
+    logger.lock().unwrap().start();
+    logger
+}
+
 #[pyclass(name = "OMSEngine")]
 pub struct Interface {
     engine: Arc<OMSEngine>,
@@ -18,51 +40,77 @@ pub struct Interface {
 
 #[pymethods]
 impl Interface {
+    /// Engine on one venue: "hantoo", "hantoo_night", "mock" or "replay" (see `Client`).
     #[new]
-    #[pyo3(signature = (adapter=None, s3_bucket=None, s3_region=None, s3_prefix=None))]
-    fn new(adapter: Option<&Bound<'_, PyAny>>, s3_bucket: Option<String>, s3_region: Option<String>, s3_prefix: Option<String>) -> PyResult<Self> {
-        
-        let destination = if let (Some(bucket), Some(region)) = (s3_bucket, s3_region) {
-            LogDestinationInfo::AmazonS3 { 
-                bucket, 
-                key_prefix: s3_prefix.unwrap_or_else(|| "logs".to_string()), 
-                region 
-            }
-        } else {
-             LogDestinationInfo::Console 
-        };
+    #[pyo3(signature = (venue="mock".to_string(), config_path=None, s3_bucket=None, s3_region=None, s3_prefix=None))]
+    fn new(venue: String, config_path: Option<String>, s3_bucket: Option<String>, s3_region: Option<String>, s3_prefix: Option<String>) -> PyResult<Self> {
+        let adapter = build_adapter(&venue, config_path)?;
+        Ok(Interface {
+            engine: Arc::new(OMSEngine::new(adapter, start_logger(s3_bucket, s3_region, s3_prefix))),
+        })
+    }
 
-        let config = LoggerConfig {
-            destination,
-            flush_interval_seconds: 60,
-            batch_size: 8192,
-        };
-        let logger = Arc::new(Mutex::new(Logger::new(config)));
-        logger.lock().unwrap().start();
-        
-        // Resolve Adapter
-        let adapter_arc: Arc<dyn Adapter> = if let Some(py_any) = adapter {
-            if py_any.is_none() {
-                 Arc::new(MockAdapter::new()) as Arc<dyn Adapter>
-            } else {
-                 extract_adapter(py_any)?
-            }
-        } else {
-            Arc::new(MockAdapter::new()) as Arc<dyn Adapter>
-        };
-        
+    /// Engine on several venues, `{name: (kind, config_path)}`. Orders are routed by `Order.venue`,
+    /// orders without one go to `default_venue`.
+    #[staticmethod]
+    #[pyo3(signature = (venues, default_venue, s3_bucket=None, s3_region=None, s3_prefix=None))]
+    fn with_venues(venues: HashMap<String, (String, Option<String>)>, default_venue: String, s3_bucket: Option<String>, s3_region: Option<String>, s3_prefix: Option<String>) -> PyResult<Self> {
+        if !venues.contains_key(&default_venue) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown venue: {}", default_venue)));
+        }
+        let mut adapters: HashMap<String, Arc<dyn Adapter>> = HashMap::new();
+        for (name, (kind, config_path)) in venues {
+            adapters.insert(name, build_adapter(&kind, config_path)?);
+        }
+        let logger = start_logger(s3_bucket, s3_region, s3_prefix);
         Ok(Interface {
-            engine: Arc::new(OMSEngine::new(adapter_arc, logger)),
+            engine: Arc::new(OMSEngine::with_venues(adapters, &default_venue, logger)),
         })
     }
 
-    fn start_gateway(&self, adapter: &Bound<'_, PyAny>) -> PyResult<()> {
-        let (tx, rx) = mpsc::channel();
-        initialize_monitor(adapter, tx)?;
-        self.engine.start_gateway_listener(rx)?;
+    fn venues(&self) -> Vec<String> {
+        let mut venues = self.engine.venues();
+        venues.sort();
+        venues
+    }
+
+    /// Listen to the market data and order updates of every venue.
+    fn start_venue_gateways(&self) -> PyResult<()> {
+        self.engine.start_venue_gateways()
+    }
+
+    /// Same as `start_venue_gateways`.
+    fn start_gateway(&self) -> PyResult<()> {
+        self.engine.start_venue_gateways()
+    }
+
+    /// Journal order events to the file at `path`; `start` recovers the orders in it.
+    fn set_journal(&self, path: String) -> PyResult<()> {
+        let journal = OrderJournal::open(&path).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        self.engine.set_journal(journal);
         Ok(())
     }
 
+    /// Pre-trade limits checked before every order reaches its adapter (see `RiskLimits`).
+    fn set_risk_limits(&self, limits: RiskLimits) {
+        self.engine.set_risk_limits(limits);
+    }
+
+    fn get_risk_limits(&self) -> RiskLimits {
+        self.engine.get_risk_limits()
+    }
+
+    /// Trading-session calendar strategies are paused outside of (see `SessionSchedule`); `None` turns it off.
+    #[pyo3(signature = (schedule=None))]
+    fn set_session_schedule(&self, schedule: Option<SessionSchedule>) {
+        self.engine.set_session_schedule(schedule);
+    }
+
+    /// Phase seen by the last strategy check; `None` without a session schedule.
+    fn get_session_phase(&self) -> Option<TradingPhase> {
+        self.engine.get_session_phase()
+    }
+
     #[pyo3(signature = (account_id=None))]
     fn start(&self, py: Python, account_id: Option<String>) -> PyResult<()> {
         self.engine.start(py, account_id)
@@ -81,10 +129,17 @@ impl Interface {
         self.engine.cancel_order(py, order_id)
     }
     
-    fn get_order_book(&self, py: Python, symbol: String) -> PyResult<PyObject> {
-        if let Some(book) = self.engine.get_order_book(&symbol) {
+    /// Book of `symbol` on `venue`; without a venue, on the default venue or any venue that has one.
+    #[pyo3(signature = (symbol, venue=None))]
+    fn get_order_book(&self, py: Python, symbol: String, venue: Option<String>) -> PyResult<PyObject> {
+        let book = match venue {
+            Some(venue) => self.engine.get_venue_order_book(&venue, &symbol),
+            None => self.engine.get_order_book(&symbol),
+        };
+        if let Some(book) = book {
             let dict = PyDict::new(py);
             dict.set_item("symbol", book.symbol)?;
+            dict.set_item("venue", book.venue)?;
             dict.set_item("last_update_id", book.last_update_id)?;
             dict.set_item("timestamp", book.timestamp)?;
             
@@ -106,9 +161,12 @@ impl Interface {
         }
     }
     
-    fn get_account(&self, py: Python) -> PyResult<PyObject> {
-        let acc = self.engine.get_account();
+    /// Account of `venue`, the default venue if not given.
+    #[pyo3(signature = (venue=None))]
+    fn get_account(&self, py: Python, venue: Option<String>) -> PyResult<PyObject> {
+        let acc = self.engine.get_venue_account(venue.as_deref().unwrap_or_default());
         let dict = PyDict::new(py);
+        dict.set_item("venue", acc.venue.clone())?;
         dict.set_item("balance", acc.balance.to_string())?;
         dict.set_item("locked", acc.locked.to_string())?;
         
@@ -128,15 +186,17 @@ impl Interface {
         Ok(dict.into())
     }
     
-    fn get_balance(&self, py: Python) -> PyResult<PyObject> {
-        self.get_account(py)
+    #[pyo3(signature = (venue=None))]
+    fn get_balance(&self, py: Python, venue: Option<String>) -> PyResult<PyObject> {
+        self.get_account(py, venue)
     }
 
-    fn get_balance_api(&self, py: Python, account_id: String) -> PyResult<PyObject> {
+    #[pyo3(signature = (account_id, venue=None))]
+    fn get_balance_api(&self, py: Python, account_id: String, venue: Option<String>) -> PyResult<PyObject> {
         // Trigger update from API
         self.engine.initialize_account(py, account_id)?;
         // Return updated state
-        self.get_account(py)
+        self.get_account(py, venue)
     }
 
     fn get_orders(&self, _py: Python) -> PyResult<HashMap<String, Order>> {
@@ -144,6 +204,38 @@ impl Interface {
         // PyO3 converts HashMap<String, Order> to Dict[str, Order] automatically if Order is PyClass.
         Ok(self.engine.get_orders())
     }
+
+    /// Register a Python strategy object (`on_order_book_update`, `on_trade_update`,
+    /// `on_order_status_update`, `on_timer`) with the engine.
+    fn add_strategy(&self, py: Python, strategy: PyObject) -> PyResult<()> {
+        self.engine.add_python_strategy(py, strategy)
+    }
+
+    /// Child orders a parent strategy (TWAP, VWAP, ...) sent for `parent_order_id`, oldest first.
+    fn get_child_orders(&self, _py: Python, parent_order_id: String) -> PyResult<Vec<Order>> {
+        Ok(self.engine.get_child_orders(&parent_order_id))
+    }
+
+    /// Estimated queue position of a resting limit order, with the probability of the queue ahead
+    /// being gone within `horizon` seconds; `None` for orders not resting at a venue.
+    #[pyo3(signature = (order_id, horizon=60.0))]
+    fn get_queue_position(&self, py: Python, order_id: String, horizon: f64) -> PyResult<PyObject> {
+        let Some(position) = self.engine.get_queue_position(&order_id) else {
+            return Ok(py.None());
+        };
+        let now = chrono::Local::now().timestamp_millis() as f64 / 1000.0;
+        let dict = PyDict::new(py);
+        dict.set_item("order_id", &position.order_id)?;
+        dict.set_item("symbol", &position.symbol)?;
+        dict.set_item("price", position.price.to_string())?;
+        dict.set_item("open_quantity", position.open_quantity)?;
+        dict.set_item("queue_ahead", position.queue_ahead)?;
+        dict.set_item("level_quantity", position.level_quantity)?;
+        dict.set_item("traded_quantity", position.traded_quantity)?;
+        dict.set_item("depletion_rate", position.depletion_rate(now))?;
+        dict.set_item("fill_probability", position.fill_probability(now, horizon))?;
+        Ok(dict.into())
+    }
     
     fn get_oms_status(&self, _py: Python) -> PyResult<String> {
         // Simple status report
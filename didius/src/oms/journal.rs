@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use log::warn;
+use anyhow::Result;
+use crate::oms::order::{Order, OrderState};
+
+/// One order lifecycle event in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum JournalEvent {
+    /// Full order snapshot after a change (submission, fill, status, modify).
+    Order { order: Box<Order> },
+    /// Broker-side reference of an order (e.g. Hantoo org_no / order_no).
+    OrderRef { order_id: String, venue: String, order_ref: serde_json::Value },
+    /// Order dropped from the OMS.
+    Removed { order_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// State rebuilt from a journal.
+#[derive(Debug, Clone, Default)]
+pub struct RecoveredState {
+    pub orders: HashMap<String, Order>,
+    /// order_id -> (venue, broker reference)
+    pub order_refs: HashMap<String, (String, serde_json::Value)>,
+}
+
+/// Append-only JSON-lines journal of order events. Every entry is flushed to disk
+/// before the engine acts on it, so the file is a write-ahead log of the OMS.
+pub struct OrderJournal {
+    path: PathBuf,
+    file: File,
+    seq: u64,
+}
+
+impl OrderJournal {
+    pub fn open(path: &str) -> Result<Self> {
+        let path = PathBuf::from(path);
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        let seq = Self::read_entries(&path)?.last().map(|e| e.seq).unwrap_or(0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(OrderJournal { path, file, seq })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&mut self, event: JournalEvent) -> Result<()> {
+        self.seq += 1;
+        let entry = JournalEntry {
+            seq: self.seq,
+            timestamp: Local::now().timestamp_millis() as f64 / 1000.0,
+            event,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Replay the journal into the last known state of every order.
+    pub fn recover(&self) -> Result<RecoveredState> {
+        let mut state = RecoveredState::default();
+        for entry in Self::read_entries(&self.path)? {
+            match entry.event {
+                JournalEvent::Order { order } => {
+                    if let Some(oid) = order.order_id.clone() {
+                        state.orders.insert(oid, *order);
+                    }
+                }
+                JournalEvent::OrderRef { order_id, venue, order_ref } => {
+                    state.order_refs.insert(order_id, (venue, order_ref));
+                }
+                JournalEvent::Removed { order_id } => {
+                    state.orders.remove(&order_id);
+                    state.order_refs.remove(&order_id);
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Rewrite the journal so it holds only the orders of `state` a recovery still needs, and their
+    /// references: working orders, and the children of working parents, whose strategy takes over
+    /// their fills. Closed orders are dropped. Written to a temp file and renamed.
+    pub fn compact(&mut self, state: &RecoveredState) -> Result<()> {
+        let working = |o: &Order| o.is_active() || o.state == OrderState::CREATED;
+        let needed = |o: &Order| working(o)
+            || o.parent_order_id.as_ref().and_then(|p| state.orders.get(p)).is_some_and(working);
+        let tmp = self.path.with_extension("compact");
+        {
+            let mut out = File::create(&tmp)?;
+            let now = Local::now().timestamp_millis() as f64 / 1000.0;
+            let mut seq = 0;
+            let mut events: Vec<JournalEvent> = state.orders.values()
+                .filter(|o| needed(o))
+                .map(|o| JournalEvent::Order { order: Box::new(o.clone()) })
+                .collect();
+            for (order_id, (venue, order_ref)) in &state.order_refs {
+                if !state.orders.get(order_id).is_some_and(needed) {
+                    continue;
+                }
+                events.push(JournalEvent::OrderRef { order_id: order_id.clone(), venue: venue.clone(), order_ref: order_ref.clone() });
+            }
+            for event in events {
+                seq += 1;
+                let mut line = serde_json::to_string(&JournalEntry { seq, timestamp: now, event })?;
+                line.push('\n');
+                out.write_all(line.as_bytes())?;
+            }
+            out.sync_all()?;
+            self.seq = seq;
+        }
+        fs::rename(&tmp, &self.path)?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        Ok(())
+    }
+
+    fn read_entries(path: &Path) -> Result<Vec<JournalEntry>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(e) => entries.push(e),
+                // A torn last write after a crash is expected; anything else is logged and skipped
+                Err(e) => warn!("Skipping journal line {} in {}: {}", i + 1, path.display(), e),
+            }
+        }
+        Ok(entries)
+    }
+}
@@ -2,7 +2,12 @@ pub mod order;
 pub mod order_book;
 pub mod account;
 pub mod engine;
-// pub mod interface;
+pub mod risk;
+pub mod journal;
+pub mod reconcile;
+pub mod session;
+pub mod queue;
+pub mod interface;
 
 use pyo3::prelude::*;
 
@@ -12,11 +17,14 @@ pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<order::OrderState>()?;
     m.add_class::<order::ExecutionStrategy>()?;
     m.add_class::<order::Order>()?;
+    m.add_class::<session::TradingPhase>()?;
+    m.add_class::<session::SessionSchedule>()?;
+    m.add_class::<risk::RiskLimits>()?;
     
     // OrderBook and AccountState are no longer exposed directly.
     // They are accessed via Interface returning Dicts.
 
-    // m.add_class::<interface::Interface>()?;
+    m.add_class::<interface::Interface>()?;
     // m.add_class::<crate::adapter::interface::PyHantooAdapter>()?;
     // m.add_class::<crate::adapter::interface::PyHantooNightAdapter>()?;
     Ok(())
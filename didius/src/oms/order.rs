@@ -13,6 +13,33 @@ pub enum OrderSide {
     SELL,
 }
 
+impl OrderSide {
+    pub fn opposite(&self) -> OrderSide {
+        match self {
+            OrderSide::BUY => OrderSide::SELL,
+            OrderSide::SELL => OrderSide::BUY,
+        }
+    }
+}
+
+impl FromStr for OrderSide {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "BUY" => Ok(OrderSide::BUY),
+            "SELL" => Ok(OrderSide::SELL),
+            other => Err(anyhow::anyhow!("Unknown side: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
 #[pyclass(eq, eq_int)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
@@ -36,6 +63,59 @@ pub enum OrderState {
     PENDING_REPLACE,
 }
 
+#[pymethods]
+impl OrderState {
+    /// FILLED, CANCELED and REJECTED: no transition leaves them.
+    #[getter]
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderState::FILLED | OrderState::CANCELED | OrderState::REJECTED)
+    }
+
+    /// Transition table shared by `Order::update_state`, `OMSEngine` and `State`.
+    /// Repeating the current state is allowed (duplicate notices, further partial fills).
+    pub fn can_transition_to(&self, next: &OrderState) -> bool {
+        use OrderState::*;
+        if self == next {
+            return true;
+        }
+        match self {
+            CREATED => matches!(next, PENDING_NEW | NEW | REJECTED | CANCELED),
+            // A cancel may go out before the venue has acknowledged the order
+            PENDING_NEW => matches!(next, NEW | PARTIALLY_FILLED | FILLED | REJECTED | CANCELED | PENDING_CANCEL),
+            NEW => matches!(next, PARTIALLY_FILLED | FILLED | CANCELED | REJECTED | PENDING_CANCEL | PENDING_REPLACE),
+            PARTIALLY_FILLED => matches!(next, FILLED | CANCELED | PENDING_CANCEL | PENDING_REPLACE),
+            // Fills may race a cancel. A late acknowledgement does not undo it; a refused cancel is
+            // put back by the engine, which knows the state it came from
+            PENDING_CANCEL => matches!(next, PARTIALLY_FILLED | FILLED | CANCELED),
+            // A cancel may chase a replacement the venue has not acknowledged yet
+            PENDING_REPLACE => matches!(next, NEW | PARTIALLY_FILLED | FILLED | CANCELED | REJECTED | PENDING_CANCEL),
+            FILLED | CANCELED | REJECTED => false,
+        }
+    }
+}
+
+/// A state change refused by the transition table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IllegalTransition {
+    pub order_id: Option<String>,
+    pub from: OrderState,
+    pub to: OrderState,
+}
+
+impl std::fmt::Display for IllegalTransition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Illegal order state transition {:?} -> {:?} for {}", self.from, self.to, self.order_id.as_deref().unwrap_or("<unassigned>"))
+    }
+}
+
+impl std::error::Error for IllegalTransition {}
+
+impl From<IllegalTransition> for PyErr {
+    fn from(e: IllegalTransition) -> PyErr {
+        pyo3::exceptions::PyValueError::new_err(e.to_string())
+    }
+}
+
 #[pyclass(eq, eq_int)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
@@ -44,6 +124,31 @@ pub enum ExecutionStrategy {
     LIMIT,
     STOP,
     NONE,
+    TWAP,
+    VWAP,
+    ICEBERG,
+    OCO,
+    BRACKET,
+    PEG,
+    POV,
+    /// Implementation shortfall: front-loaded execution against the arrival price
+    IS,
+    /// Market making: a bid and an ask around the fair value, skewed by inventory
+    MM,
+    /// Two-leg spread: one leg worked passively, the other hedged aggressively on its fills
+    SPREAD,
+}
+
+#[pymethods]
+impl ExecutionStrategy {
+    /// Parent strategies keep the order inside the OMS and trade it through child orders.
+    #[getter]
+    pub fn is_parent(&self) -> bool {
+        matches!(self,
+            ExecutionStrategy::TWAP | ExecutionStrategy::VWAP | ExecutionStrategy::ICEBERG
+            | ExecutionStrategy::POV | ExecutionStrategy::IS | ExecutionStrategy::MM | ExecutionStrategy::SPREAD
+            | ExecutionStrategy::OCO | ExecutionStrategy::BRACKET)
+    }
 }
 
 #[pyclass]
@@ -88,12 +193,20 @@ pub struct Order {
     pub error_message: Option<String>,
     #[pyo3(get, set)]
     pub exchange: String,
+    /// Adapter the OMS routes this order to (empty: engine default venue)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub venue: String,
+    /// Parent order a strategy sent this order for (`None` for orders sent directly)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub parent_order_id: Option<String>,
 }
 
 #[pymethods]
 impl Order {
     #[new]
-    #[pyo3(signature = (symbol, side, order_type, quantity, price=None, strategy=None, strategy_params=None, stop_price=None, exchange="SOR".to_string()))]
+    #[pyo3(signature = (symbol, side, order_type, quantity, price=None, strategy=None, strategy_params=None, stop_price=None, exchange="SOR".to_string(), venue=None))]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         symbol: String,
@@ -104,7 +217,8 @@ impl Order {
         strategy: Option<ExecutionStrategy>,
         strategy_params: Option<HashMap<String, String>>,
         stop_price: Option<String>,
-        exchange: String
+        exchange: String,
+        venue: Option<String>,
     ) -> Self {
         let now = Local::now().timestamp_millis() as f64 / 1000.0;
         
@@ -129,7 +243,9 @@ impl Order {
             created_at: now,
             updated_at: now,
             error_message: None,
-            exchange: exchange
+            exchange: exchange,
+            venue: venue.unwrap_or_default(),
+            parent_order_id: None,
         }
     }
 
@@ -161,17 +277,18 @@ impl Order {
         self.stop_price = value.and_then(|s| Decimal::from_str(&s).ok());
     }
 
+    /// Move to `new_state` if the transition table allows it (`ValueError` in Python otherwise).
     #[pyo3(signature = (new_state, msg=None))]
-    pub fn update_state(&mut self, new_state: OrderState, msg: Option<String>) {
-        self.state = new_state;
-        self.updated_at = Local::now().timestamp_millis() as f64 / 1000.0;
-        if let Some(m) = msg {
-            self.error_message = Some(m);
+    pub fn update_state(&mut self, new_state: OrderState, msg: Option<String>) -> Result<(), IllegalTransition> {
+        if !self.state.can_transition_to(&new_state) {
+            return Err(IllegalTransition { order_id: self.order_id.clone(), from: self.state.clone(), to: new_state });
         }
+        self.force_state(new_state, msg);
+        Ok(())
     }
 
     #[getter]
-    fn is_active(&self) -> bool {
+    pub fn is_active(&self) -> bool {
         matches!(
             self.state,
             OrderState::PENDING_NEW
@@ -186,3 +303,35 @@ impl Order {
         format!("{:?}", self)
     }
 }
+
+impl Order {
+    /// Set the state without consulting the transition table. Only for corrections
+    /// the broker is authoritative on (reconciliation).
+    pub fn force_state(&mut self, new_state: OrderState, msg: Option<String>) {
+        self.state = new_state;
+        self.updated_at = Local::now().timestamp_millis() as f64 / 1000.0;
+        if let Some(m) = msg {
+            self.error_message = Some(m);
+        }
+    }
+
+    /// Book a fill: filled quantity, average price and `PARTIALLY_FILLED`/`FILLED`.
+    /// The fill is booked either way; a fill on a closed order keeps its state and returns the refused transition.
+    pub fn apply_fill(&mut self, fill_qty: i64, fill_price: Decimal) -> Result<(), IllegalTransition> {
+        let old_filled = self.filled_quantity;
+        let new_filled = old_filled + fill_qty;
+        self.filled_quantity = new_filled;
+
+        let new_qty_dec = Decimal::from(new_filled);
+        if new_qty_dec > Decimal::ZERO {
+            let old_val = Decimal::from(old_filled) * self.average_fill_price;
+            let fill_val = Decimal::from(fill_qty) * fill_price;
+            self.average_fill_price = (old_val + fill_val) / new_qty_dec;
+        }
+
+        let next = if new_filled >= self.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+        let result = self.update_state(next, None);
+        self.updated_at = Local::now().timestamp_millis() as f64 / 1000.0;
+        result
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
+use crate::oms::order::OrderSide;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceLevel {
@@ -40,6 +41,9 @@ pub struct OrderBook {
 
     pub last_update_id: i64,
     pub timestamp: f64,
+    // Venue the book was sourced from (empty when unknown)
+    #[serde(default)]
+    pub venue: String,
 }
 
 impl OrderBook {
@@ -50,6 +54,7 @@ impl OrderBook {
             asks: std::collections::BTreeMap::new(),
             last_update_id: 0,
             timestamp: 0.0,
+            venue: String::new(),
         }
     }
 
@@ -120,7 +125,25 @@ impl OrderBook {
             _ => None,
         }
     }
-    
+
+    /// Quantity resting at `price` on the `side` orders rest on (bids for BUY); 0 when the level is empty.
+    pub fn get_level_quantity(&self, side: &OrderSide, price: Decimal) -> i64 {
+        let levels = match side {
+            OrderSide::BUY => &self.bids,
+            OrderSide::SELL => &self.asks,
+        };
+        levels.get(&price).copied().unwrap_or(0)
+    }
+
+    /// Touch prices weighted by the opposite size: leans toward the side about to be taken out.
+    pub fn get_microprice(&self) -> Option<Decimal> {
+        let ((bid, bid_qty), (ask, ask_qty)) = (self.get_best_bid()?, self.get_best_ask()?);
+        if bid_qty + ask_qty <= 0 {
+            return self.get_mid_price();
+        }
+        Some((bid * Decimal::from(ask_qty) + ask * Decimal::from(bid_qty)) / Decimal::from(bid_qty + ask_qty))
+    }
+
     pub fn validate(&self) -> bool {
         // Check crossed book
         let _bb = self.get_best_bid();
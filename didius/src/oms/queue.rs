@@ -0,0 +1,173 @@
+use crate::oms::order::{Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Estimated place of a resting limit order in the queue of its price level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuePosition {
+    pub order_id: String,
+    pub symbol: String,
+    pub venue: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    /// Open quantity of the order
+    pub open_quantity: i64,
+    /// Estimated quantity ahead of the order at its level
+    pub queue_ahead: f64,
+    /// Queue ahead when the order joined the level
+    pub initial_queue: f64,
+    /// Level quantity, the order's own included, as of the last book update or trade print
+    pub level_quantity: i64,
+    /// Quantity printed at the order's price since it joined
+    pub traded_quantity: i64,
+    /// When the order joined the level (unix seconds)
+    pub joined_at: f64,
+}
+
+impl QueuePosition {
+    /// Shares per second the queue ahead has shrunk by since the order joined.
+    pub fn depletion_rate(&self, now: f64) -> f64 {
+        let elapsed = now - self.joined_at;
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        (self.initial_queue - self.queue_ahead).max(0.0) / elapsed
+    }
+
+    /// Probability that the queue ahead is gone within `horizon` seconds, so the next print at the
+    /// level fills the order. Depletion is taken as a Poisson flow of shares at `depletion_rate`;
+    /// with none observed yet only an order at the front has a chance.
+    pub fn fill_probability(&self, now: f64, horizon: f64) -> f64 {
+        let ahead = self.queue_ahead.ceil();
+        if ahead <= 0.0 {
+            return 1.0;
+        }
+        let mean = self.depletion_rate(now) * horizon.max(0.0);
+        if mean <= 0.0 {
+            return 0.0;
+        }
+        // P(N > ahead) = 1 - sum of the Poisson terms up to `ahead`, summed in log space
+        let (mut log_term, mut cdf) = (-mean, 0.0);
+        for k in 0..=(ahead as u64) {
+            if k > 0 {
+                log_term += mean.ln() - (k as f64).ln();
+            }
+            cdf += log_term.exp();
+        }
+        (1.0 - cdf).clamp(0.0, 1.0)
+    }
+}
+
+/// Queue positions of the working limit orders, kept from the books and the tape.
+///
+/// The level quantity is taken to include the order itself, so an order joins behind the level
+/// shown when it is acknowledged less its own open quantity. Prints at its price
+/// take from the front of the queue; a print through its price means the level is gone. A level
+/// that shrinks by more than the prints is taken to have lost cancellations spread evenly over the
+/// other orders at it, so the queue ahead loses its share. Quantity joining the level queues behind.
+#[derive(Debug, Clone, Default)]
+pub struct QueueTracker {
+    positions: HashMap<String, QueuePosition>,
+}
+
+impl QueueTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow an order status: an acknowledged limit order at a new price (new order or modify) joins
+    /// the back of its level in `book` at `now`; an order no longer resting is dropped.
+    pub fn on_order(&mut self, order: &Order, book: Option<&OrderBook>, now: f64) {
+        let Some(order_id) = order.order_id.clone() else { return };
+        let acknowledged = matches!(order.state, OrderState::NEW | OrderState::PARTIALLY_FILLED);
+        let resting = order.order_type == OrderType::LIMIT && !order.strategy.is_parent()
+            && (acknowledged || matches!(order.state, OrderState::PENDING_CANCEL | OrderState::PENDING_REPLACE));
+        let Some(price) = order.price.filter(|_| resting) else {
+            self.positions.remove(&order_id);
+            return;
+        };
+        let open_quantity = order.quantity - order.filled_quantity;
+        if let Some(position) = self.positions.get_mut(&order_id).filter(|p| p.price == price) {
+            position.open_quantity = open_quantity;
+            return;
+        }
+        // A pending order keeps its place until the venue confirms the new price
+        if !acknowledged {
+            return;
+        }
+        let level = book.filter(|b| b.symbol == order.symbol && b.venue == order.venue).map_or(0, |b| b.get_level_quantity(&order.side, price));
+        let ahead = (level - open_quantity).max(0) as f64;
+        self.positions.insert(order_id.clone(), QueuePosition {
+            order_id,
+            symbol: order.symbol.clone(),
+            venue: order.venue.clone(),
+            side: order.side.clone(),
+            price,
+            open_quantity,
+            queue_ahead: ahead,
+            initial_queue: ahead,
+            level_quantity: level,
+            traded_quantity: 0,
+            joined_at: now,
+        });
+    }
+
+    /// A fill of the order itself: nothing is ahead of it any more.
+    pub fn on_fill(&mut self, order_id: &str, open_quantity: i64) {
+        if let Some(position) = self.positions.get_mut(order_id) {
+            position.queue_ahead = 0.0;
+            position.open_quantity = open_quantity;
+        }
+    }
+
+    /// A print on the tape of `venue`.
+    pub fn on_trade(&mut self, venue: &str, symbol: &str, price: Decimal, quantity: i64) {
+        for position in self.positions.values_mut().filter(|p| p.symbol == symbol && p.venue == venue) {
+            let through = match position.side {
+                OrderSide::BUY => price < position.price,
+                OrderSide::SELL => price > position.price,
+            };
+            if through {
+                position.queue_ahead = 0.0;
+            } else if price == position.price {
+                position.queue_ahead = (position.queue_ahead - quantity as f64).max(0.0);
+                position.level_quantity = (position.level_quantity - quantity).max(0);
+                position.traded_quantity += quantity;
+            }
+        }
+    }
+
+    /// A book update. Levels beyond the depth the book shows are left alone.
+    pub fn on_book(&mut self, book: &OrderBook) {
+        for position in self.positions.values_mut().filter(|p| p.symbol == book.symbol && p.venue == book.venue) {
+            let shown = match position.side {
+                OrderSide::BUY => book.bids.keys().next().is_some_and(|lowest| *lowest <= position.price),
+                OrderSide::SELL => book.asks.keys().next_back().is_some_and(|highest| *highest >= position.price),
+            };
+            if !shown {
+                continue;
+            }
+            let level = book.get_level_quantity(&position.side, position.price);
+            if level < position.level_quantity {
+                // Cancellations come from the others at the level, spread evenly over them
+                let dropped = (position.level_quantity - level) as f64;
+                let others = position.level_quantity - position.open_quantity;
+                if others > 0 {
+                    position.queue_ahead -= dropped * position.queue_ahead / others as f64;
+                }
+            }
+            position.queue_ahead = position.queue_ahead.clamp(0.0, (level - position.open_quantity).max(0) as f64);
+            position.level_quantity = level;
+        }
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<&QueuePosition> {
+        self.positions.get(order_id)
+    }
+
+    /// Positions of all tracked orders.
+    pub fn positions(&self) -> Vec<QueuePosition> {
+        self.positions.values().cloned().collect()
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use crate::adapter::{BrokerExecution, BrokerOrder};
+use crate::oms::order::{Order, OrderState};
+
+/// A difference between the OMS and the broker found by `OMSEngine::reconcile_internal`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum ReconciliationBreak {
+    /// Working at the broker but unknown to the OMS. The engine adopts it as a new order.
+    UnknownOrder { venue: String, broker_order: BrokerOrder },
+    /// Fills the OMS did not see. The engine books a synthesized execution.
+    MissedFill { venue: String, order_id: String, quantity: i64, price: Decimal },
+    /// Local state contradicts the broker. The engine moves the order to `to`.
+    StaleState { venue: String, order_id: String, from: OrderState, to: OrderState },
+    /// Fills of an order unknown to the OMS that is no longer working. Reported only.
+    UnknownExecution { venue: String, execution: BrokerExecution },
+}
+
+/// Outcome of a reconciliation run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconciliationReport {
+    /// Venues whose broker state was compared.
+    pub venues: Vec<String>,
+    /// Venues that could not be inquired, with the error.
+    pub skipped: Vec<(String, String)>,
+    /// Breaks found, in the order they were applied.
+    pub breaks: Vec<ReconciliationBreak>,
+    /// Ids given to adopted orders, in the order of the `UnknownOrder` breaks.
+    pub adopted: Vec<String>,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.breaks.is_empty()
+    }
+}
+
+/// Compare the local orders of one venue with the broker's open orders and executions.
+pub fn find_breaks(venue: &str, local: &[Order], open: &[BrokerOrder], executions: &[BrokerExecution]) -> Vec<ReconciliationBreak> {
+    let open_by_id: HashMap<&str, &BrokerOrder> = open.iter()
+        .filter_map(|o| o.order_id.as_deref().map(|id| (id, o)))
+        .collect();
+    let exec_by_id: HashMap<&str, &BrokerExecution> = executions.iter()
+        .filter_map(|e| e.order_id.as_deref().map(|id| (id, e)))
+        .collect();
+    let local_ids: HashMap<&str, &Order> = local.iter()
+        .filter_map(|o| o.order_id.as_deref().map(|id| (id, o)))
+        .collect();
+
+    let mut breaks = Vec::new();
+    let mut ids: Vec<&&str> = local_ids.keys().collect();
+    ids.sort();
+    for id in ids {
+        let order = local_ids[*id];
+        let broker_open = open_by_id.get(*id);
+        let execution = exec_by_id.get(*id);
+        let broker_filled = execution.map(|e| e.filled_quantity).unwrap_or(0)
+            .max(broker_open.map(|o| o.filled_quantity).unwrap_or(0));
+
+        let mut filled = order.filled_quantity;
+        let mut state = order.state.clone();
+        if broker_filled > filled {
+            let quantity = broker_filled - filled;
+            let average = execution.map(|e| e.average_price).unwrap_or_default();
+            breaks.push(ReconciliationBreak::MissedFill {
+                venue: venue.to_string(),
+                order_id: id.to_string(),
+                quantity,
+                price: missed_fill_price(average, broker_filled, order.average_fill_price, filled, quantity),
+            });
+            filled = broker_filled;
+            // Late fills on a closed order do not reopen it (see `OrderState::can_transition_to`)
+            if !state.is_terminal() {
+                state = if filled >= order.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+            }
+        }
+
+        let expected = if broker_open.is_some() {
+            match state {
+                // A cancel or replace still in flight is left alone
+                OrderState::PENDING_CANCEL | OrderState::PENDING_REPLACE => None,
+                _ if filled > 0 => Some(OrderState::PARTIALLY_FILLED),
+                _ => Some(OrderState::NEW),
+            }
+        } else {
+            match state {
+                // Sent but never acknowledged. CREATED orders are held by strategies and never sent.
+                OrderState::PENDING_NEW => Some(OrderState::REJECTED),
+                OrderState::NEW | OrderState::PARTIALLY_FILLED | OrderState::PENDING_CANCEL | OrderState::PENDING_REPLACE => Some(OrderState::CANCELED),
+                _ => None,
+            }
+        };
+        if let Some(to) = expected {
+            if to != state {
+                breaks.push(ReconciliationBreak::StaleState { venue: venue.to_string(), order_id: id.to_string(), from: state, to });
+            }
+        }
+    }
+
+    for broker_order in open {
+        let known = broker_order.order_id.as_deref().map(|id| local_ids.contains_key(id)).unwrap_or(false);
+        if !known {
+            breaks.push(ReconciliationBreak::UnknownOrder { venue: venue.to_string(), broker_order: broker_order.clone() });
+        }
+    }
+
+    for execution in executions {
+        let known = execution.order_id.as_deref().map(|id| local_ids.contains_key(id)).unwrap_or(false);
+        let working = open.iter().any(|o| o.exchange_order_id == execution.exchange_order_id);
+        if !known && !working {
+            breaks.push(ReconciliationBreak::UnknownExecution { venue: venue.to_string(), execution: execution.clone() });
+        }
+    }
+    breaks
+}
+
+/// Price of the fills missing locally, backed out of the broker's average over all fills.
+fn missed_fill_price(broker_average: Decimal, broker_filled: i64, local_average: Decimal, local_filled: i64, missed: i64) -> Decimal {
+    let to_dec = |q: i64| Decimal::from_i64(q).unwrap_or_default();
+    if local_filled == 0 || local_average.is_zero() {
+        return broker_average;
+    }
+    let price = (broker_average * to_dec(broker_filled) - local_average * to_dec(local_filled)) / to_dec(missed);
+    if price > Decimal::ZERO { price } else { broker_average }
+}
+
@@ -0,0 +1,211 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use crate::oms::order::{Order, OrderSide, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::account::AccountState;
+
+/// Pre-trade limits checked by `OMSEngine` before an order reaches the adapter.
+/// Every limit is optional; the default configuration lets everything through.
+#[pyclass]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// Largest quantity for a single order.
+    pub max_order_qty: Option<i64>,
+    /// Largest `quantity * price` for a single order.
+    pub max_notional: Option<Decimal>,
+    /// Absolute position limit per symbol, including working orders.
+    pub max_position: HashMap<String, i64>,
+    /// Position limit for symbols not listed in `max_position`.
+    pub default_max_position: Option<i64>,
+    /// Require `balance - locked` to cover buy orders (working buys included).
+    pub check_cash: bool,
+    /// Maximum number of working orders in the engine.
+    pub max_open_orders: Option<usize>,
+    /// Maximum distance of a limit price from the book mid, as a fraction (0.05 = 5%).
+    pub price_collar: Option<Decimal>,
+}
+
+/// A failed check: which limit fired and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskRejection {
+    pub check: &'static str,
+    pub reason: String,
+}
+
+impl RiskRejection {
+    fn new(check: &'static str, reason: String) -> Self {
+        RiskRejection { check, reason }
+    }
+}
+
+#[pymethods]
+impl RiskLimits {
+    /// Decimal limits are given as strings, like order prices.
+    #[new]
+    #[pyo3(signature = (max_order_qty=None, max_notional=None, max_position=None, default_max_position=None, check_cash=false, max_open_orders=None, price_collar=None))]
+    fn py_new(
+        max_order_qty: Option<i64>,
+        max_notional: Option<String>,
+        max_position: Option<HashMap<String, i64>>,
+        default_max_position: Option<i64>,
+        check_cash: bool,
+        max_open_orders: Option<usize>,
+        price_collar: Option<String>,
+    ) -> PyResult<Self> {
+        let decimal = |value: Option<String>| value
+            .map(|v| crate::utils::parse_decimal(&v))
+            .transpose()
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()));
+        Ok(RiskLimits {
+            max_order_qty,
+            max_notional: decimal(max_notional)?,
+            max_position: max_position.unwrap_or_default(),
+            default_max_position,
+            check_cash,
+            max_open_orders,
+            price_collar: decimal(price_collar)?,
+        })
+    }
+
+    /// Limits from a YAML file.
+    #[staticmethod]
+    #[pyo3(name = "load")]
+    fn py_load(path: &str) -> PyResult<Self> {
+        Self::load(path).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn max_order_qty(&self) -> Option<i64> {
+        self.max_order_qty
+    }
+
+    #[getter]
+    fn max_notional(&self) -> Option<String> {
+        self.max_notional.map(|d| d.to_string())
+    }
+
+    #[getter]
+    fn max_position(&self) -> HashMap<String, i64> {
+        self.max_position.clone()
+    }
+
+    #[getter]
+    fn default_max_position(&self) -> Option<i64> {
+        self.default_max_position
+    }
+
+    #[getter]
+    fn check_cash(&self) -> bool {
+        self.check_cash
+    }
+
+    #[getter]
+    fn max_open_orders(&self) -> Option<usize> {
+        self.max_open_orders
+    }
+
+    #[getter]
+    fn price_collar(&self) -> Option<String> {
+        self.price_collar.map(|d| d.to_string())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl RiskLimits {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&text)?)
+    }
+
+    /// Check `order` against the current account, book and working orders (excluding `order` itself).
+    pub fn check(&self, order: &Order, account: &AccountState, book: Option<&OrderBook>, open_orders: &[Order]) -> Result<(), RiskRejection> {
+        let qty = Decimal::from_i64(order.quantity).unwrap_or_default();
+
+        if let Some(max_qty) = self.max_order_qty {
+            if order.quantity > max_qty {
+                return Err(RiskRejection::new("max_order_qty", format!("Quantity {} exceeds limit {}", order.quantity, max_qty)));
+            }
+        }
+
+        if let Some(max_open) = self.max_open_orders {
+            if open_orders.len() >= max_open {
+                return Err(RiskRejection::new("max_open_orders", format!("{} open orders, limit {}", open_orders.len(), max_open)));
+            }
+        }
+
+        let mid = book.and_then(|b| b.get_mid_price());
+        if let (Some(collar), Some(price), Some(mid)) = (self.price_collar, order.price, mid) {
+            if order.order_type == OrderType::LIMIT && mid > Decimal::ZERO {
+                let distance = (price - mid).abs() / mid;
+                if distance > collar {
+                    return Err(RiskRejection::new("price_collar", format!("Price {} is {:.2}% away from mid {}, collar {}%", price, distance * Decimal::ONE_HUNDRED, mid, collar * Decimal::ONE_HUNDRED)));
+                }
+            }
+        }
+
+        // Market orders are valued at the touch they would take
+        let reference_price = match order.order_type {
+            OrderType::LIMIT => order.price,
+            OrderType::MARKET => match order.side {
+                OrderSide::BUY => book.and_then(|b| b.get_best_ask()).map(|(p, _)| p),
+                OrderSide::SELL => book.and_then(|b| b.get_best_bid()).map(|(p, _)| p),
+            },
+        };
+
+        if let Some(max_notional) = self.max_notional {
+            let Some(price) = reference_price else {
+                return Err(RiskRejection::new("max_notional", "No reference price to value the order".to_string()));
+            };
+            let notional = qty * price;
+            if notional > max_notional {
+                return Err(RiskRejection::new("max_notional", format!("Notional {} exceeds limit {}", notional, max_notional)));
+            }
+        }
+
+        let limit = self.max_position.get(&order.symbol).copied().or(self.default_max_position);
+        if let Some(limit) = limit {
+            let current = account.positions.get(&order.symbol).map(|p| p.quantity).unwrap_or(0);
+            // Working orders on the other side may never fill: the worst case of a side counts its own only
+            let working: i64 = open_orders.iter()
+                .filter(|o| o.symbol == order.symbol && o.side == order.side)
+                .map(|o| o.quantity - o.filled_quantity)
+                .sum();
+            let projected = current + signed(&order.side, working + order.quantity);
+            // A position already over the limit can still be cut
+            if projected.abs() > limit && projected.abs() > current.abs() {
+                return Err(RiskRejection::new("max_position", format!("Projected position {} in {} exceeds limit {}", projected, order.symbol, limit)));
+            }
+        }
+
+        if self.check_cash && order.side == OrderSide::BUY {
+            let Some(price) = reference_price else {
+                return Err(RiskRejection::new("cash", "No reference price to value the order".to_string()));
+            };
+            let committed: Decimal = open_orders.iter()
+                .filter(|o| o.side == OrderSide::BUY)
+                .filter_map(|o| o.price.map(|p| p * Decimal::from_i64(o.quantity - o.filled_quantity).unwrap_or_default()))
+                .sum();
+            let available = account.balance - account.locked - committed;
+            let notional = qty * price;
+            if notional > available {
+                return Err(RiskRejection::new("cash", format!("Notional {} exceeds available cash {}", notional, available)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn signed(side: &OrderSide, qty: i64) -> i64 {
+    match side {
+        OrderSide::BUY => qty,
+        OrderSide::SELL => -qty,
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, Weekday};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// KST is UTC+9 all year
+pub(crate) const KST_OFFSET_SECONDS: i32 = 9 * 3600;
+const DAY: f64 = 86400.0;
+
+/// Market state of a KRX session at a point in time.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum TradingPhase {
+    CLOSED,
+    /// Orders accepted, nothing matched yet
+    PRE_OPEN,
+    /// Single-price call auction at the open
+    OPENING_AUCTION,
+    CONTINUOUS,
+    /// Single-price call auction at the close (15:20 for equities)
+    CLOSING_AUCTION,
+    /// Post-close closing-price and single-price trading
+    AFTER_HOURS,
+    /// KRX night derivatives session (18:00 to 05:00 the next morning)
+    NIGHT_SESSION,
+}
+
+/// Default `active_phases` of the slicing strategies: continuous trading, day and night.
+pub const CONTINUOUS_PHASES: &[TradingPhase] = &[TradingPhase::CONTINUOUS, TradingPhase::NIGHT_SESSION];
+
+/// `active_phases` as a `strategy_params` value.
+pub fn join_phases(phases: &[TradingPhase]) -> String {
+    phases.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
+}
+
+impl FromStr for TradingPhase {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "CLOSED" => Ok(TradingPhase::CLOSED),
+            "PRE_OPEN" => Ok(TradingPhase::PRE_OPEN),
+            "OPENING_AUCTION" => Ok(TradingPhase::OPENING_AUCTION),
+            "CONTINUOUS" => Ok(TradingPhase::CONTINUOUS),
+            "CLOSING_AUCTION" => Ok(TradingPhase::CLOSING_AUCTION),
+            "AFTER_HOURS" => Ok(TradingPhase::AFTER_HOURS),
+            "NIGHT_SESSION" => Ok(TradingPhase::NIGHT_SESSION),
+            other => Err(anyhow!("Unknown trading phase: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for TradingPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// One phase of a trading day, in seconds after midnight KST. `end` may pass midnight
+/// (86400 and above) for a session that runs into the next morning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionWindow {
+    pub phase: TradingPhase,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl SessionWindow {
+    pub fn new(phase: TradingPhase, start: &str, end: &str) -> Self {
+        let start = clock(start);
+        let mut end = clock(end);
+        if end <= start {
+            end += DAY;
+        }
+        SessionWindow { phase, start, end }
+    }
+}
+
+/// `"HH:MM"` as seconds after midnight.
+fn clock(hhmm: &str) -> f64 {
+    parse_clock(hhmm).expect("HH:MM")
+}
+
+fn parse_clock(hhmm: &str) -> Result<f64> {
+    let parsed = hhmm.split_once(':').and_then(|(h, m)| Some((h.trim().parse::<u32>().ok()?, m.trim().parse::<u32>().ok()?)));
+    match parsed {
+        Some((h, m)) if h <= 24 && m < 60 => Ok((h * 3600 + m * 60) as f64),
+        _ => Err(anyhow!("Invalid time {:?}, expected HH:MM", hhmm)),
+    }
+}
+
+/// Trading-session calendar: the phases of a trading day (Monday to Friday, holidays excluded).
+/// Anything outside the windows is `CLOSED`. A window running past midnight belongs to the
+/// trading day it starts on, so Friday's night session ends on Saturday morning.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSchedule {
+    pub windows: Vec<SessionWindow>,
+    pub holidays: HashSet<NaiveDate>,
+}
+
+impl SessionSchedule {
+    pub fn new(windows: Vec<SessionWindow>) -> Self {
+        SessionSchedule { windows, holidays: HashSet::new() }
+    }
+
+    /// KOSPI / KOSDAQ equities.
+    pub fn krx_equity() -> Self {
+        Self::new(vec![
+            SessionWindow::new(TradingPhase::PRE_OPEN, "08:00", "08:30"),
+            SessionWindow::new(TradingPhase::OPENING_AUCTION, "08:30", "09:00"),
+            SessionWindow::new(TradingPhase::CONTINUOUS, "09:00", "15:20"),
+            SessionWindow::new(TradingPhase::CLOSING_AUCTION, "15:20", "15:30"),
+            SessionWindow::new(TradingPhase::AFTER_HOURS, "15:30", "18:00"),
+        ])
+    }
+
+    /// KOSPI200 futures and options: the day session and the night session.
+    pub fn krx_derivatives() -> Self {
+        Self::new(vec![
+            SessionWindow::new(TradingPhase::OPENING_AUCTION, "08:30", "08:45"),
+            SessionWindow::new(TradingPhase::CONTINUOUS, "08:45", "15:35"),
+            SessionWindow::new(TradingPhase::CLOSING_AUCTION, "15:35", "15:45"),
+            SessionWindow::new(TradingPhase::NIGHT_SESSION, "18:00", "05:00"),
+        ])
+    }
+
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// Phase at `timestamp` (unix seconds).
+    pub fn phase_at(&self, timestamp: f64) -> TradingPhase {
+        let kst = FixedOffset::east_opt(KST_OFFSET_SECONDS).expect("KST offset");
+        let Some(time) = DateTime::from_timestamp_millis((timestamp * 1000.0) as i64) else {
+            return TradingPhase::CLOSED;
+        };
+        let local = time.with_timezone(&kst);
+        let today = local.date_naive();
+        let seconds = (local.timestamp() + KST_OFFSET_SECONDS as i64).rem_euclid(86400) as f64
+            + local.timestamp_subsec_millis() as f64 / 1000.0;
+
+        // Today's windows first, then yesterday's that run past midnight
+        for (day, offset) in [(today, 0.0), (today - Duration::days(1), DAY)] {
+            if !self.is_trading_day(day) {
+                continue;
+            }
+            let t = seconds + offset;
+            if let Some(w) = self.windows.iter().find(|w| w.start <= t && t < w.end) {
+                return w.phase;
+            }
+        }
+        TradingPhase::CLOSED
+    }
+}
+
+fn value_err(e: anyhow::Error) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(e.to_string())
+}
+
+#[pymethods]
+impl SessionSchedule {
+    /// Custom calendar from `(phase, "HH:MM", "HH:MM")` windows and `"YYYY-MM-DD"` holidays.
+    #[new]
+    #[pyo3(signature = (windows, holidays=None))]
+    fn py_new(windows: Vec<(TradingPhase, String, String)>, holidays: Option<Vec<String>>) -> PyResult<Self> {
+        let mut parsed = Vec::new();
+        for (phase, start, end) in windows {
+            parse_clock(&start).and(parse_clock(&end)).map_err(value_err)?;
+            parsed.push(SessionWindow::new(phase, &start, &end));
+        }
+        Self::new(parsed).py_with_holidays(holidays.unwrap_or_default())
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "krx_equity")]
+    fn py_krx_equity() -> Self {
+        Self::krx_equity()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "krx_derivatives")]
+    fn py_krx_derivatives() -> Self {
+        Self::krx_derivatives()
+    }
+
+    /// Copy with `"YYYY-MM-DD"` holidays added.
+    #[pyo3(name = "with_holidays")]
+    fn py_with_holidays(&self, holidays: Vec<String>) -> PyResult<Self> {
+        let dates = holidays.iter()
+            .map(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| anyhow!("Invalid holiday {:?}, expected YYYY-MM-DD", d)))
+            .collect::<Result<Vec<_>>>()
+            .map_err(value_err)?;
+        Ok(self.clone().with_holidays(dates))
+    }
+
+    /// Phase at `timestamp` (unix seconds).
+    #[pyo3(name = "phase_at")]
+    fn py_phase_at(&self, timestamp: f64) -> TradingPhase {
+        self.phase_at(timestamp)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
@@ -3,6 +3,7 @@ use crate::message::{Message, ConnectionStatus};
 use crate::oms::order::{Order, OrderState};
 use crate::oms::order_book::OrderBook;
 use crate::oms::account::AccountState;
+use log::warn;
 
 #[derive(Debug, Clone)]
 pub struct State {
@@ -40,7 +41,10 @@ impl State {
             }
             Message::OrderStatus { order_id, state, filled_qty, filled_price, .. } => {
                  if let Some(order) = self.orders.get_mut(order_id) {
-                     order.state = state.clone();
+                     if let Err(e) = order.update_state(state.clone(), None) {
+                         warn!("State: {}", e);
+                         return;
+                     }
                      order.filled_quantity = *filled_qty;
                      if let Some(price) = filled_price {
                          order.average_fill_price = *price; // Simplified
@@ -63,6 +67,10 @@ impl State {
                      // For now just logging or simple update logic if State manages orders.
                      order.filled_quantity += fill_qty; 
                      // Update avg price... logic omitted for brevity but should be here.
+                     let next = if order.filled_quantity >= order.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+                     if let Err(e) = order.update_state(next, None) {
+                         warn!("State: {}", e);
+                     }
                  }
             }
             Message::Error { .. } => {
@@ -1,5 +1,8 @@
+use crate::message::ConnectionStatus;
+use crate::oms::account::AccountState;
 use crate::oms::order::Order;
 use crate::oms::order_book::OrderBook;
+use crate::oms::session::TradingPhase;
 use anyhow::Result;
 use rust_decimal::Decimal;
 
@@ -9,13 +12,38 @@ pub enum StrategyAction {
     CancelOrder(String), // order_id
     ModifyPrice(String, Option<Decimal>), // order_id, new_price
     RemoveOrder(String), // order_id
+    /// Several actions from one callback, executed in order; the first that fails drops the rest
+    Batch(Vec<StrategyAction>),
+    /// Independent actions from one callback (the two sides of a quote), each executed whatever became of the others
+    Each(Vec<StrategyAction>),
     None,
 }
 
+impl StrategyAction {
+    /// Combine `actions` into one, leaving out `None`s: nothing is `None`, a single action is returned as is.
+    pub fn batch(actions: Vec<StrategyAction>) -> StrategyAction {
+        let mut actions: Vec<StrategyAction> = actions.into_iter().filter(|a| !matches!(a, StrategyAction::None)).collect();
+        match actions.len() {
+            0 => StrategyAction::None,
+            1 => actions.remove(0),
+            _ => StrategyAction::Batch(actions),
+        }
+    }
+
+    /// Like `batch`, for actions that do not depend on each other.
+    pub fn each(actions: Vec<StrategyAction>) -> StrategyAction {
+        match StrategyAction::batch(actions) {
+            StrategyAction::Batch(actions) => StrategyAction::Each(actions),
+            action => action,
+        }
+    }
+}
+
 pub trait Strategy {
     // Check if the strategy should trigger based on market data (OrderBook updates, Trade updates, etc.)
     fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction>;
-    fn on_trade_update(&mut self, price: f64) -> Result<StrategyAction>;
+    /// A market trade print (`Message::MarketTrade`) of any symbol.
+    fn on_trade_update(&mut self, symbol: &str, price: Decimal, quantity: i64, timestamp: f64) -> Result<StrategyAction>;
     fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
         Ok(StrategyAction::None)
     }
@@ -24,13 +52,54 @@ pub trait Strategy {
         Ok(StrategyAction::None)
     }
 
+    /// Session phases the strategy runs in; `None` runs in all of them. Outside these phases the
+    /// engine holds back its timer and market data, order updates still come through.
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        None
+    }
+
+    /// The engine's session schedule moved from one phase to the next; called on every strategy.
+    fn on_session_change(&mut self, _from: TradingPhase, _to: TradingPhase) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    /// The account after a fill was booked, and once when the strategy is attached.
+    fn on_account_update(&mut self, _account: &AccountState) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    /// A venue gateway reported its connection state; called on every strategy.
+    fn on_connection_status(&mut self, _venue: &str, _status: &ConnectionStatus) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
     fn is_completed(&self) -> bool {
         false
     }
 
+    /// Symbols besides its order's whose books the strategy reads (the other legs of a spread);
+    /// the engine hands their current books over when it attaches the strategy.
+    fn symbols(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Venue the strategy trades on; market trades of other venues are not passed to it.
+    /// `None` gets the trades of every venue.
+    fn venue(&self) -> Option<&str> {
+        None
+    }
+
     fn get_origin_order_id(&self) -> Option<String> {
         None
     }
     
     fn update_order_id(&mut self, _new_id: String) {}
+
+    /// Take over a child order recovered from the journal (parent strategies).
+    fn restore_child(&mut self, _child: &Order) {}
+
+    /// Execution summary, logged as a `STRATEGY_REPORT` event once the strategy completes.
+    fn report(&self) -> Option<serde_json::Value> {
+        None
+    }
 }
@@ -0,0 +1,316 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// `strategy_params` of an `OCO` or `BRACKET` order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BracketParams {
+    pub stop_price: Decimal,
+    /// Price the exit is moved to when the stop fires; to market when unset
+    pub stop_limit_price: Option<Decimal>,
+    /// Bracket only: an OCO takes its take-profit from the order price
+    pub take_profit_price: Option<Decimal>,
+}
+
+impl StrategyParams for BracketParams {
+    const KEYS: &'static [&'static str] = &["stop_price", "stop_limit_price", "take_profit_price"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let bracket = BracketParams {
+            stop_price: params.require("stop_price")?,
+            stop_limit_price: params.get("stop_limit_price")?,
+            take_profit_price: params.get("take_profit_price")?,
+        };
+        match (params.strategy(), bracket.take_profit_price) {
+            (ExecutionStrategy::BRACKET, None) => Err(anyhow!("Bracket requires take_profit_price")),
+            (ExecutionStrategy::OCO, Some(_)) => Err(anyhow!("OCO takes the take-profit from the order price, not take_profit_price")),
+            _ => Ok(bracket),
+        }
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("stop_price", self.stop_price)
+            .set_opt("stop_limit_price", self.stop_limit_price)
+            .set_opt("take_profit_price", self.take_profit_price)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl BracketParams {
+    /// With `take_profit_price` the params are those of a `BRACKET`, without it of an `OCO`.
+    #[new]
+    #[pyo3(signature = (stop_price, take_profit_price=None, stop_limit_price=None))]
+    fn py_new(stop_price: String, take_profit_price: Option<String>, stop_limit_price: Option<String>) -> PyResult<Self> {
+        let strategy = if take_profit_price.is_some() { ExecutionStrategy::BRACKET } else { ExecutionStrategy::OCO };
+        params::py_params(strategy, ParamWriter::default()
+            .set("stop_price", stop_price)
+            .set_opt("take_profit_price", take_profit_price)
+            .set_opt("stop_limit_price", stop_limit_price))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        if self.take_profit_price.is_some() { ExecutionStrategy::BRACKET } else { ExecutionStrategy::OCO }
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("BracketParams", &self.to_params())
+    }
+}
+
+/// One-cancels-other exit (take-profit limit plus stop), optionally behind an entry.
+///
+/// - `OCO`: the parent is the exit. Its LIMIT price is the take-profit leg, `stop_price` the stop leg.
+/// - `BRACKET`: the parent is the entry. Once it fills, exits on the other side cover the filled quantity.
+///
+/// Only the take-profit leg rests at the venue. The stop leg is watched here, and when the market
+/// reaches `stop_price` the resting leg is re-priced to `stop_limit_price` (market when unset), so both
+/// legs can never fill. A take-profit fill shrinks the stop with it; an entry fill replaces the exit
+/// with one sized to the new position.
+pub struct BracketStrategy {
+    pub parent_order_id: String,
+    /// Entry children (`"{parent}-{n}"`), `None` for an OCO
+    pub entry: Option<ChildOrders>,
+    /// Exit legs: `"{parent}-{n}"` for an OCO, `"{parent}-x-{n}"` for a bracket. Bracket exits are on the
+    /// other side of the parent, so the engine does not roll them into the entry.
+    pub exits: ChildOrders,
+    pub take_profit_price: Decimal,
+    pub stop_price: Decimal,
+    pub stop_limit_price: Option<Decimal>,
+
+    pub stop_triggered: bool,
+    /// Pull the working legs and place nothing new (parent canceled, leg rejected or exit canceled elsewhere)
+    pub stopping: bool,
+    pub finished: bool,
+
+    /// Whether the working exit already rests at the stop price
+    exit_repriced: bool,
+}
+
+impl BracketStrategy {
+    /// Build from an `OCO` or `BRACKET` order. `strategy_params`: `stop_price` (required),
+    /// `stop_limit_price` (optional, market otherwise) and, for a bracket, `take_profit_price`.
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = BracketParams::from_params(&order.strategy, &order.strategy_params)?;
+        let (stop_price, stop_limit_price) = (params.stop_price, params.stop_limit_price);
+        let parent_order_id = order.order_id.clone().unwrap_or_default();
+
+        let (entry, exits, take_profit_price) = match order.strategy {
+            ExecutionStrategy::OCO => {
+                let take_profit = order.price.filter(|_| order.order_type == OrderType::LIMIT)
+                    .ok_or_else(|| anyhow!("OCO requires a LIMIT order, its price is the take-profit leg"))?;
+                (None, ChildOrders::new(order, ChildPricing::Passive), take_profit)
+            }
+            ExecutionStrategy::BRACKET => {
+                let take_profit = params.take_profit_price.ok_or_else(|| anyhow!("Bracket requires take_profit_price"))?;
+                let mut exit = order.clone();
+                exit.side = match order.side { OrderSide::BUY => OrderSide::SELL, OrderSide::SELL => OrderSide::BUY };
+                exit.order_type = OrderType::MARKET;
+                exit.price = None;
+                let mut exits = ChildOrders::new(&exit, ChildPricing::Passive);
+                exits.id_prefix = format!("{}-x", parent_order_id);
+                (Some(ChildOrders::new(order, ChildPricing::Passive)), exits, take_profit)
+            }
+            _ => return Err(anyhow!("{:?} is not an OCO or bracket order", order.strategy)),
+        };
+        let ordered = match exits.side {
+            OrderSide::SELL => stop_price < take_profit_price,
+            OrderSide::BUY => stop_price > take_profit_price,
+        };
+        if !ordered {
+            return Err(anyhow!("stop_price {} must be on the losing side of the take-profit {}", stop_price, take_profit_price));
+        }
+
+        Ok(BracketStrategy {
+            parent_order_id,
+            entry,
+            exits,
+            take_profit_price,
+            stop_price,
+            stop_limit_price,
+            stop_triggered: false,
+            stopping: false,
+            finished: false,
+            exit_repriced: false,
+        })
+    }
+
+    /// Quantity the exits have to cover: the filled entry (the whole order for an OCO) less the exit fills.
+    pub fn position(&self) -> i64 {
+        let held = match &self.entry {
+            Some(entry) => entry.filled_quantity(),
+            None => self.exits.quantity,
+        };
+        (held - self.exits.filled_quantity()).max(0)
+    }
+
+    /// Same trigger as `StopStrategy`: best ask at or below the stop for a SELL exit, best bid at or above for a BUY exit.
+    fn stop_crossed(&self, book: &OrderBook) -> bool {
+        match self.exits.side {
+            OrderSide::SELL => book.get_best_ask().is_some_and(|(p, _)| p <= self.stop_price),
+            OrderSide::BUY => book.get_best_bid().is_some_and(|(p, _)| p >= self.stop_price),
+        }
+    }
+
+    /// Next actions for the current legs: the exit first, then pulling the entry, as one batch.
+    fn step(&mut self) -> StrategyAction {
+        if self.finished {
+            return StrategyAction::None;
+        }
+        if let Some(entry) = self.entry.as_mut() {
+            if !self.stopping && entry.child_order_ids().is_empty() {
+                let (quantity, price) = (entry.quantity, entry.limit_price);
+                return entry.place_at(quantity, price, f64::INFINITY);
+            }
+        }
+
+        let mut actions = Vec::new();
+        let position = self.position();
+        if self.exits.is_working() {
+            if !self.exits.working_acknowledged() {
+                // Not at the venue yet: nothing to cancel or re-price
+            } else if self.stopping || self.exits.working_open_quantity() != position {
+                // Resize by replacing: the new exit goes out once the venue confirms the cancel
+                actions.push(self.exits.pull_working());
+            } else if self.stop_triggered && !self.exit_repriced {
+                self.exit_repriced = true;
+                let exit_id = self.exits.working_order_id().unwrap_or_default().to_string();
+                actions.push(StrategyAction::ModifyPrice(exit_id, self.stop_limit_price));
+            }
+        } else if !self.stopping && position > 0 {
+            self.exit_repriced = self.stop_triggered;
+            let price = if self.stop_triggered { self.stop_limit_price } else { Some(self.take_profit_price) };
+            actions.push(self.exits.place_at(position, price, f64::INFINITY));
+        }
+
+        // Once the stop fires the entry must not add to the position
+        let entry_working = self.entry.as_ref().is_some_and(|e| e.is_working());
+        if entry_working && (self.stopping || self.stop_triggered) {
+            if let Some(entry) = self.entry.as_mut().filter(|e| e.working_acknowledged()) {
+                actions.push(entry.pull_working());
+            }
+        }
+        if !entry_working && !self.exits.is_working() {
+            self.finished = true;
+        }
+        StrategyAction::batch(actions)
+    }
+}
+
+impl Strategy for BracketStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.exits.on_book(book);
+        if let Some(entry) = self.entry.as_mut() {
+            entry.on_book(book);
+        }
+        if self.finished || book.symbol != self.exits.symbol {
+            return Ok(StrategyAction::None);
+        }
+        // Legs are sent from the timer and status updates; a book only fires the stop
+        if self.stop_triggered || self.stopping || self.position() == 0 || !self.stop_crossed(book) {
+            return Ok(StrategyAction::None);
+        }
+        log::info!("{} {}: stop {} reached", if self.entry.is_some() { "Bracket" } else { "OCO" }, self.parent_order_id, self.stop_price);
+        self.stop_triggered = true;
+        Ok(self.step())
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.step())
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        let oid = order.order_id.as_deref().unwrap_or("");
+        if oid == self.parent_order_id {
+            if order.state == OrderState::CANCELED && !self.stopping {
+                self.stopping = true;
+                return Ok(self.step());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        let pulled = |c: &ChildOrders| c.working_order_id() == Some(oid) && c.cancel_requested();
+        let mut was_pulled = pulled(&self.exits);
+        let mut update = self.exits.on_child_update(order);
+        let mut from_entry = false;
+        if update == ChildUpdate::Unrelated {
+            if let Some(entry) = self.entry.as_mut() {
+                was_pulled = pulled(entry);
+                update = entry.on_child_update(order);
+                from_entry = true;
+            }
+        }
+        match update {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected if !self.stopping => {
+                log::warn!("Bracket {}: leg {} rejected ({}), pulling the rest", self.parent_order_id, oid, order.error_message.as_deref().unwrap_or(""));
+                self.stopping = true;
+            }
+            // The venue ending the entry (e.g. the rest of a day order) leaves what was filled to the exits
+            ChildUpdate::Updated if order.state == OrderState::CANCELED && !was_pulled && from_entry => {
+                log::info!("Bracket {}: entry {} canceled outside the strategy with {} filled, keeping the exits", self.parent_order_id, oid, order.filled_quantity);
+            }
+            ChildUpdate::Updated if order.state == OrderState::CANCELED && !was_pulled && !self.stopping => {
+                log::warn!("Bracket {}: exit {} canceled outside the strategy, pulling the rest", self.parent_order_id, oid);
+                self.stopping = true;
+            }
+            _ => {}
+        }
+        Ok(self.step())
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id.clone())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": if self.entry.is_some() { "BRACKET" } else { "OCO" },
+            "parent_order_id": self.parent_order_id,
+            "entry_filled_quantity": self.entry.as_ref().map(|e| e.filled_quantity()),
+            "entry_average_price": self.entry.as_ref().and_then(|e| e.average_fill_price()).map(|p| p.to_string()),
+            "exit_filled_quantity": self.exits.filled_quantity(),
+            "exit_average_price": self.exits.average_fill_price().map(|p| p.to_string()),
+            "stop_triggered": self.stop_triggered
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        let oid = child.order_id.as_deref().unwrap_or("");
+        let is_exit = self.entry.is_none() || oid.starts_with(&format!("{}-", self.exits.id_prefix));
+        match self.entry.as_mut() {
+            Some(entry) if !is_exit => entry.restore_child(child, f64::INFINITY),
+            _ => {
+                self.exits.restore_child(child, f64::INFINITY);
+                // A working exit away from the take-profit was re-priced by the stop
+                if child.is_active() && child.price != Some(self.take_profit_price) {
+                    self.stop_triggered = true;
+                    self.exit_repriced = true;
+                }
+            }
+        }
+    }
+}
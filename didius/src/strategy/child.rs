@@ -0,0 +1,303 @@
+use crate::oms::order::{Order, OrderSide, OrderState, OrderType, ExecutionStrategy};
+use crate::oms::order_book::OrderBook;
+use crate::strategy::base::StrategyAction;
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Where child orders of a parent strategy are priced in the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildPricing {
+    /// Join the own touch (best bid for a buy)
+    Passive,
+    /// Take the opposite touch (best ask for a buy)
+    Aggressive,
+}
+
+impl FromStr for ChildPricing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "AGGRESSIVE" => Ok(ChildPricing::Aggressive),
+            "PASSIVE" => Ok(ChildPricing::Passive),
+            other => Err(anyhow!("Unknown pricing: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for ChildPricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChildPricing::Passive => "PASSIVE",
+            ChildPricing::Aggressive => "AGGRESSIVE",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorkingChild {
+    order_id: String,
+    quantity: i64,
+    /// Unix seconds after which the child is pulled
+    deadline: f64,
+    /// Acknowledged by the venue; before that it cannot be canceled or modified
+    acknowledged: bool,
+    cancel_requested: bool,
+    /// Pulled before the venue acknowledged it; the cancel goes out with the acknowledgement
+    pull_on_ack: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ChildFill {
+    quantity: i64,
+    average_price: Decimal,
+}
+
+/// Child order bookkeeping shared by the parent strategies (TWAP, VWAP, Iceberg, POV, OCO, Bracket).
+/// Children are named `"{parent}-{n}"` and carry the parent in `Order::parent_order_id`. One works
+/// at a time, and each has a deadline after which it is canceled so the rest can be sent with the next child.
+pub struct ChildOrders {
+    pub parent_order_id: String,
+    /// Children are named `"{id_prefix}-{n}"`; the parent ID unless set otherwise
+    pub id_prefix: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: i64,
+    /// Parent limit price; children are never priced through it
+    pub limit_price: Option<Decimal>,
+    pub venue: String,
+    pub exchange: String,
+    pub pricing: ChildPricing,
+
+    book: Option<OrderBook>,
+    fills: BTreeMap<String, ChildFill>,
+    working: Option<WorkingChild>,
+}
+
+/// What a child status update meant for the parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildUpdate {
+    /// Not one of our children
+    Unrelated,
+    Updated,
+    Rejected,
+}
+
+impl ChildOrders {
+    pub fn new(parent: &Order, pricing: ChildPricing) -> Self {
+        ChildOrders {
+            parent_order_id: parent.order_id.clone().unwrap_or_default(),
+            id_prefix: parent.order_id.clone().unwrap_or_default(),
+            symbol: parent.symbol.clone(),
+            side: parent.side.clone(),
+            quantity: parent.quantity,
+            limit_price: if parent.order_type == OrderType::LIMIT { parent.price } else { None },
+            venue: parent.venue.clone(),
+            exchange: parent.exchange.clone(),
+            pricing,
+            book: None,
+            fills: BTreeMap::new(),
+            working: None,
+        }
+    }
+
+    /// Keep the latest book of the parent symbol.
+    pub fn on_book(&mut self, book: &OrderBook) {
+        if book.symbol == self.symbol {
+            self.book = Some(book.clone());
+        }
+    }
+
+    pub fn book(&self) -> Option<&OrderBook> {
+        self.book.as_ref()
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.fills.values().map(|f| f.quantity).sum()
+    }
+
+    pub fn remaining_quantity(&self) -> i64 {
+        (self.quantity - self.filled_quantity()).max(0)
+    }
+
+    /// Average price over all child fills.
+    pub fn average_fill_price(&self) -> Option<Decimal> {
+        let filled = self.filled_quantity();
+        if filled == 0 {
+            return None;
+        }
+        let value: Decimal = self.fills.values()
+            .map(|f| f.average_price * Decimal::from_i64(f.quantity).unwrap_or_default())
+            .sum();
+        Some(value / Decimal::from_i64(filled).unwrap_or(Decimal::ONE))
+    }
+
+    pub fn child_order_ids(&self) -> Vec<String> {
+        self.fills.keys().cloned().collect()
+    }
+
+    pub fn is_working(&self) -> bool {
+        self.working.is_some()
+    }
+
+    pub fn working_order_id(&self) -> Option<&str> {
+        self.working.as_ref().map(|w| w.order_id.as_str())
+    }
+
+    /// Unfilled quantity of the working child.
+    pub fn working_open_quantity(&self) -> i64 {
+        self.working.as_ref()
+            .map(|w| w.quantity - self.fills.get(&w.order_id).map_or(0, |f| f.quantity))
+            .unwrap_or(0)
+    }
+
+    pub fn working_acknowledged(&self) -> bool {
+        self.working.as_ref().is_some_and(|w| w.acknowledged)
+    }
+
+    /// Whether a cancel of the working child is in flight.
+    pub fn cancel_requested(&self) -> bool {
+        self.working.as_ref().is_some_and(|w| w.cancel_requested)
+    }
+
+    /// Whether a child can be priced now. Without a book a passive child has nothing to join;
+    /// an aggressive one goes to market.
+    pub fn can_price(&self) -> bool {
+        self.book.is_some() || self.limit_price.is_some() || self.pricing == ChildPricing::Aggressive
+    }
+
+    fn child_price(&self) -> Option<Decimal> {
+        let touch = self.book.as_ref().and_then(|b| {
+            let (bid, ask) = (b.get_best_bid(), b.get_best_ask());
+            match (&self.side, self.pricing) {
+                (OrderSide::BUY, ChildPricing::Passive) | (OrderSide::SELL, ChildPricing::Aggressive) => bid,
+                (OrderSide::BUY, ChildPricing::Aggressive) | (OrderSide::SELL, ChildPricing::Passive) => ask,
+            }
+        }).map(|(p, _)| p);
+
+        match (touch, self.limit_price) {
+            (Some(p), Some(limit)) => Some(match self.side {
+                OrderSide::BUY => p.min(limit),
+                OrderSide::SELL => p.max(limit),
+            }),
+            (Some(p), None) => Some(p),
+            (None, limit) => limit,
+        }
+    }
+
+    /// Send a child for `quantity` priced off the book, to be pulled after `deadline`.
+    pub fn place(&mut self, quantity: i64, deadline: f64) -> StrategyAction {
+        self.place_at(quantity, self.child_price(), deadline)
+    }
+
+    /// Send a child for `quantity` at `price` (market when `None`).
+    pub fn place_at(&mut self, quantity: i64, price: Option<Decimal>, deadline: f64) -> StrategyAction {
+        let order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+        let mut child = Order::new(
+            self.symbol.clone(),
+            self.side.clone(),
+            order_type,
+            quantity,
+            price.map(|p| p.to_string()),
+            Some(ExecutionStrategy::NONE),
+            None,
+            None,
+            self.exchange.clone(),
+            Some(self.venue.clone()),
+        );
+        let oid = format!("{}-{}", self.id_prefix, self.fills.len() + 1);
+        child.order_id = Some(oid.clone());
+        child.parent_order_id = Some(self.parent_order_id.clone());
+        self.fills.insert(oid.clone(), ChildFill::default());
+        self.working = Some(WorkingChild { order_id: oid, quantity, deadline, acknowledged: false, cancel_requested: false, pull_on_ack: false });
+        StrategyAction::PlaceOrder(child)
+    }
+
+    /// Cancel the working child once its deadline has passed.
+    pub fn expire(&mut self, now: f64) -> StrategyAction {
+        match self.working.as_ref() {
+            Some(w) if now > w.deadline => self.pull_working(),
+            _ => StrategyAction::None,
+        }
+    }
+
+    /// Cancel the working child but keep it as working until the venue confirms,
+    /// so nothing replaces it while it can still fill. A child the venue has not
+    /// acknowledged yet is canceled once it is (see `resume_pull`).
+    pub fn pull_working(&mut self) -> StrategyAction {
+        match self.working.as_mut() {
+            Some(w) if !w.acknowledged => {
+                w.pull_on_ack = true;
+                StrategyAction::None
+            }
+            Some(w) if !w.cancel_requested => {
+                w.cancel_requested = true;
+                w.pull_on_ack = false;
+                StrategyAction::CancelOrder(w.order_id.clone())
+            }
+            _ => StrategyAction::None,
+        }
+    }
+
+    /// Send the cancel of a child pulled before its acknowledgement, once it is acknowledged.
+    pub fn resume_pull(&mut self) -> StrategyAction {
+        match self.working.as_ref() {
+            Some(w) if w.pull_on_ack && w.acknowledged => self.pull_working(),
+            _ => StrategyAction::None,
+        }
+    }
+
+    /// Cancel the working child now (parent canceled).
+    pub fn cancel_working(&mut self) -> StrategyAction {
+        match self.working.take() {
+            Some(w) => StrategyAction::CancelOrder(w.order_id),
+            None => StrategyAction::None,
+        }
+    }
+
+    /// Book a status update of a child order.
+    pub fn on_child_update(&mut self, order: &Order) -> ChildUpdate {
+        let oid = order.order_id.as_deref().unwrap_or("");
+        let Some(fill) = self.fills.get_mut(oid) else {
+            return ChildUpdate::Unrelated;
+        };
+        let new_fill = order.filled_quantity != fill.quantity;
+        fill.quantity = order.filled_quantity;
+        fill.average_price = order.average_fill_price;
+        if let Some(w) = self.working.as_mut().filter(|w| w.order_id == oid) {
+            w.acknowledged |= !matches!(order.state, OrderState::CREATED | OrderState::PENDING_NEW);
+            // The engine puts a child back as it was when the venue refuses the cancel
+            if w.cancel_requested && !new_fill && matches!(order.state, OrderState::NEW | OrderState::PARTIALLY_FILLED) {
+                w.cancel_requested = false;
+            }
+            if order.state.is_terminal() {
+                self.working = None;
+            }
+        }
+        if order.state == OrderState::REJECTED {
+            ChildUpdate::Rejected
+        } else {
+            ChildUpdate::Updated
+        }
+    }
+
+    /// Take over a child order recovered from the journal.
+    pub fn restore_child(&mut self, child: &Order, deadline: f64) {
+        let Some(oid) = child.order_id.clone() else { return };
+        self.fills.insert(oid.clone(), ChildFill { quantity: child.filled_quantity, average_price: child.average_fill_price });
+        if child.is_active() {
+            self.working = Some(WorkingChild {
+                order_id: oid,
+                quantity: child.quantity,
+                deadline,
+                acknowledged: !matches!(child.state, OrderState::CREATED | OrderState::PENDING_NEW),
+                cancel_requested: child.state == OrderState::PENDING_CANCEL,
+                pull_on_ack: false,
+            });
+        }
+    }
+}
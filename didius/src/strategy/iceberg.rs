@@ -0,0 +1,244 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// `strategy_params` of an ICEBERG order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcebergParams {
+    pub display_quantity: i64,
+    /// Price distance from the own touch after which the clip is re-priced
+    pub max_drift: Option<Decimal>,
+}
+
+impl StrategyParams for IcebergParams {
+    const KEYS: &'static [&'static str] = &["display_quantity", "max_drift"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let display_quantity = params.require::<i64>("display_quantity")?;
+        if display_quantity < 1 {
+            return Err(anyhow!("display_quantity must be between 1 and the order quantity"));
+        }
+        let max_drift = params.get::<Decimal>("max_drift")?;
+        if max_drift.is_some_and(|d| d < Decimal::ZERO) {
+            return Err(anyhow!("max_drift must not be negative"));
+        }
+        Ok(IcebergParams { display_quantity, max_drift })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("display_quantity", self.display_quantity)
+            .set_opt("max_drift", self.max_drift)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl IcebergParams {
+    #[new]
+    #[pyo3(signature = (display_quantity, max_drift=None))]
+    fn py_new(display_quantity: i64, max_drift: Option<String>) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::ICEBERG, ParamWriter::default()
+            .set("display_quantity", display_quantity)
+            .set_opt("max_drift", max_drift))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::ICEBERG
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("IcebergParams", &self.to_params())
+    }
+}
+
+/// Reserve order: only `display_quantity` of the parent rests at a time. When the visible clip
+/// is filled the next one is sent from the hidden reserve. With `max_drift`, a clip the own touch
+/// has moved away from by more than `max_drift` is re-priced to the touch, never through the limit.
+pub struct IcebergStrategy {
+    pub display_quantity: i64,
+    pub max_drift: Option<Decimal>,
+    pub children: ChildOrders,
+    pub finished: bool,
+
+    /// Price of the working clip, as confirmed by the venue
+    clip_price: Option<Decimal>,
+    /// Price of a re-price the venue has not confirmed yet
+    pending_price: Option<Decimal>,
+    reprices: i64,
+}
+
+impl IcebergStrategy {
+    pub fn new(parent: &Order, display_quantity: i64, max_drift: Option<Decimal>) -> Self {
+        IcebergStrategy {
+            display_quantity: display_quantity.max(1),
+            max_drift,
+            children: ChildOrders::new(parent, ChildPricing::Passive),
+            finished: false,
+            clip_price: None,
+            pending_price: None,
+            reprices: 0,
+        }
+    }
+
+    /// Build from `strategy_params`: `display_quantity` (required) and `max_drift` (price distance, optional).
+    /// The parent must be a LIMIT order.
+    pub fn from_order(order: &Order) -> Result<Self> {
+        if order.order_type != OrderType::LIMIT || order.price.is_none() {
+            return Err(anyhow!("Iceberg requires a LIMIT order with a price"));
+        }
+        let params = IcebergParams::from_params(&order.strategy, &order.strategy_params)?;
+        if params.display_quantity > order.quantity {
+            return Err(anyhow!("display_quantity must be between 1 and the order quantity"));
+        }
+        Ok(Self::new(order, params.display_quantity, params.max_drift))
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.children.parent_order_id
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.children.filled_quantity()
+    }
+
+    /// Own touch (best bid for a buy) capped at the parent limit.
+    fn touch_price(&self) -> Option<Decimal> {
+        let book = self.children.book()?;
+        let limit = self.children.limit_price?;
+        match self.children.side {
+            OrderSide::BUY => book.get_best_bid().map(|(p, _)| p.min(limit)),
+            OrderSide::SELL => book.get_best_ask().map(|(p, _)| p.max(limit)),
+        }
+    }
+
+    /// Send the next clip from the reserve.
+    fn next_clip(&mut self) -> StrategyAction {
+        let quantity = self.display_quantity.min(self.children.remaining_quantity());
+        if self.finished || quantity <= 0 || self.children.is_working() {
+            return StrategyAction::None;
+        }
+        let price = match self.max_drift {
+            Some(_) => self.touch_price().or(self.children.limit_price),
+            None => self.children.limit_price,
+        };
+        self.clip_price = price;
+        self.pending_price = None;
+        self.children.place_at(quantity, price, f64::INFINITY)
+    }
+}
+
+impl Strategy for IcebergStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.children.on_book(book);
+        // Only a clip the venue holds can be re-priced, one re-price at a time
+        if self.finished || book.symbol != self.children.symbol || !self.children.working_acknowledged()
+            || self.children.cancel_requested() || self.pending_price.is_some() {
+            return Ok(StrategyAction::None);
+        }
+        let (Some(drift), Some(clip_id), Some(clip_price), Some(touch)) =
+            (self.max_drift, self.children.working_order_id(), self.clip_price, self.touch_price()) else {
+            return Ok(StrategyAction::None);
+        };
+        if (touch - clip_price).abs() <= drift {
+            return Ok(StrategyAction::None);
+        }
+        let clip_id = clip_id.to_string();
+        self.pending_price = Some(touch);
+        Ok(StrategyAction::ModifyPrice(clip_id, Some(touch)))
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.next_clip())
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.children.cancel_working());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        // The venue confirmed the re-price, or refused it and the engine put the old price back
+        let working = order.order_id.is_some() && self.children.working_order_id() == order.order_id.as_deref();
+        if working && order.state != OrderState::PENDING_REPLACE {
+            if let Some(pending) = self.pending_price.take() {
+                if order.price == Some(pending) {
+                    self.clip_price = Some(pending);
+                    self.reprices += 1;
+                }
+            }
+        }
+
+        match self.children.on_child_update(order) {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected => {
+                log::warn!("Iceberg {}: clip {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            ChildUpdate::Updated if order.state == OrderState::CANCELED && !self.finished => {
+                // Only the parent cancels clips; anything else (venue, user) stops the reserve
+                log::warn!("Iceberg {}: clip {:?} canceled outside the strategy, stopping", self.parent_order_id(), order.order_id);
+                self.finished = true;
+            }
+            ChildUpdate::Updated => {}
+        }
+        if self.children.remaining_quantity() == 0 {
+            self.finished = true;
+        }
+        // Visible clip done: replenish from the reserve
+        if order.state == OrderState::FILLED {
+            return Ok(self.next_clip());
+        }
+        Ok(StrategyAction::None)
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "ICEBERG",
+            "parent_order_id": self.parent_order_id(),
+            "quantity": self.children.quantity,
+            "filled_quantity": self.children.filled_quantity(),
+            "average_price": self.children.average_fill_price().map(|p| p.to_string()),
+            "clips": self.children.child_order_ids().len(),
+            "reprices": self.reprices
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        self.children.restore_child(child, f64::INFINITY);
+        if child.is_active() {
+            self.clip_price = child.price;
+            self.pending_price = None;
+        }
+        self.finished = self.children.remaining_quantity() == 0;
+    }
+}
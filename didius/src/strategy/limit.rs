@@ -1,6 +1,7 @@
 use crate::oms::order::{Order, OrderState, OrderSide, OrderType, ExecutionStrategy};
 use crate::oms::order_book::OrderBook;
 use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::params::ParamReader;
 use anyhow::Result;
 use rust_decimal::Decimal;
 
@@ -31,6 +32,18 @@ impl LimitStrategy {
             finished: false,
         }
     }
+
+    /// Build from a LIMIT order, which takes no `strategy_params`.
+    pub fn from_order(order: &Order) -> Result<Self> {
+        ParamReader::new(&order.strategy, &order.strategy_params).expect_keys(&[])?;
+        Ok(Self::new(
+            order.order_id.clone().unwrap_or_default(),
+            order.symbol.clone(),
+            order.side.clone(),
+            order.quantity,
+            order.price.unwrap_or(Decimal::ZERO),
+        ))
+    }
 }
 
 impl Strategy for LimitStrategy {
@@ -38,7 +51,7 @@ impl Strategy for LimitStrategy {
         Ok(StrategyAction::None)
     }
     
-    fn on_trade_update(&mut self, _price: f64) -> Result<StrategyAction> {
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
         Ok(StrategyAction::None)
     }
     
@@ -0,0 +1,483 @@
+use crate::message::ConnectionStatus;
+use crate::oms::account::AccountState;
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use crate::strategy::quote::{Quote, QuoteSlot, QuoteUpdate};
+use crate::strategy::shortfall::BookEstimator;
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a market maker centers its quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FairValue {
+    /// Halfway between the touches
+    Mid,
+    /// Touches weighted by the opposite size (`OrderBook::get_microprice`)
+    Microprice,
+}
+
+impl FromStr for FairValue {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "MID" => Ok(FairValue::Mid),
+            "MICROPRICE" => Ok(FairValue::Microprice),
+            other => Err(anyhow!("Unknown fair_value: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for FairValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FairValue::Mid => "MID",
+            FairValue::Microprice => "MICROPRICE",
+        })
+    }
+}
+
+/// `strategy_params` of a market-making order. The order quantity is the size of each quote.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketMakerParams {
+    /// Largest position, long or short, the quotes may build; the side that would pass it is pulled
+    pub max_inventory: i64,
+    pub tick_size: Decimal,
+    /// Distance of each quote from the fair value with a calm book and a flat position
+    pub half_spread_ticks: f64,
+    /// Shift of both quotes at full inventory, away from adding to it
+    pub skew_ticks: f64,
+    /// Half spread added per unit of volatility (relative, per square-root second) times the fair value
+    pub volatility_multiplier: f64,
+    /// Book updates the volatility is estimated over
+    pub estimation_window: usize,
+    pub fair_value: FairValue,
+    /// Quotes are pulled when no book has come in for this long
+    pub stale_seconds: f64,
+    /// Session phases the order quotes in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl StrategyParams for MarketMakerParams {
+    const KEYS: &'static [&'static str] = &[
+        "max_inventory", "tick_size", "half_spread_ticks", "skew_ticks", "volatility_multiplier",
+        "estimation_window", "fair_value", "stale_seconds", "active_phases",
+    ];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let max_inventory = params.require::<i64>("max_inventory")?;
+        if max_inventory <= 0 {
+            return Err(anyhow!("max_inventory must be positive"));
+        }
+        let tick_size = params.get::<Decimal>("tick_size")?.unwrap_or(Decimal::ONE);
+        if tick_size <= Decimal::ZERO {
+            return Err(anyhow!("tick_size must be positive"));
+        }
+        let half_spread_ticks = params.get::<f64>("half_spread_ticks")?.unwrap_or(1.0);
+        if !half_spread_ticks.is_finite() || half_spread_ticks < 0.0 {
+            return Err(anyhow!("half_spread_ticks must be zero or positive"));
+        }
+        let skew_ticks = params.get::<f64>("skew_ticks")?.unwrap_or(half_spread_ticks);
+        if !skew_ticks.is_finite() || skew_ticks < 0.0 {
+            return Err(anyhow!("skew_ticks must be zero or positive"));
+        }
+        let volatility_multiplier = params.get::<f64>("volatility_multiplier")?.unwrap_or(1.0);
+        if !volatility_multiplier.is_finite() || volatility_multiplier < 0.0 {
+            return Err(anyhow!("volatility_multiplier must be zero or positive"));
+        }
+        let estimation_window = params.get::<usize>("estimation_window")?.unwrap_or(100);
+        if estimation_window < 2 {
+            return Err(anyhow!("estimation_window must be at least 2"));
+        }
+        let stale_seconds = params.get::<f64>("stale_seconds")?.unwrap_or(5.0);
+        if stale_seconds.is_nan() || stale_seconds <= 0.0 {
+            return Err(anyhow!("stale_seconds must be positive"));
+        }
+        Ok(MarketMakerParams {
+            max_inventory,
+            tick_size,
+            half_spread_ticks,
+            skew_ticks,
+            volatility_multiplier,
+            estimation_window,
+            fair_value: params.get("fair_value")?.unwrap_or(FairValue::Mid),
+            stale_seconds,
+            active_phases: params.list("active_phases")?,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("max_inventory", self.max_inventory)
+            .set("tick_size", self.tick_size)
+            .set("half_spread_ticks", self.half_spread_ticks)
+            .set("skew_ticks", self.skew_ticks)
+            .set("volatility_multiplier", self.volatility_multiplier)
+            .set("estimation_window", self.estimation_window)
+            .set("fair_value", self.fair_value)
+            .set("stale_seconds", self.stale_seconds)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl MarketMakerParams {
+    #[new]
+    #[pyo3(signature = (max_inventory, tick_size=None, half_spread_ticks=None, skew_ticks=None, volatility_multiplier=None, estimation_window=None, fair_value=None, stale_seconds=None, active_phases=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        max_inventory: i64,
+        tick_size: Option<String>,
+        half_spread_ticks: Option<f64>,
+        skew_ticks: Option<f64>,
+        volatility_multiplier: Option<f64>,
+        estimation_window: Option<usize>,
+        fair_value: Option<String>,
+        stale_seconds: Option<f64>,
+        active_phases: Option<Vec<TradingPhase>>,
+    ) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::MM, ParamWriter::default()
+            .set("max_inventory", max_inventory)
+            .set_opt("tick_size", tick_size)
+            .set_opt("half_spread_ticks", half_spread_ticks)
+            .set_opt("skew_ticks", skew_ticks)
+            .set_opt("volatility_multiplier", volatility_multiplier)
+            .set_opt("estimation_window", estimation_window)
+            .set_opt("fair_value", fair_value)
+            .set_opt("stale_seconds", stale_seconds)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::MM
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("MarketMakerParams", &self.to_params())
+    }
+}
+
+/// Two-sided quoting around a fair value taken from the book (mid or microprice). The bid and the ask
+/// sit `half_spread_ticks` away, widened by the mid's volatility, and both are shifted against the
+/// position in the account: long inventory lowers the quotes to sell more and buy less. A side whose
+/// fill could take the position past `max_inventory` is sized down or pulled.
+///
+/// Each side is a `QuoteSlot`, moved by cancel/replace as the targets change. Quoting starts with the
+/// first timer tick; quotes are pulled when the book goes stale, when the venue disconnects (until it
+/// is back and a new book has come in), outside `active_phases` and when the parent is canceled.
+/// Quotes are named `"{parent}-{n}"` and, being on both sides, never fill the parent.
+pub struct MarketMakerStrategy {
+    pub parent_order_id: String,
+    pub symbol: String,
+    pub venue: String,
+    pub exchange: String,
+    /// Size of each quote: the parent order quantity
+    pub quote_size: i64,
+    pub params: MarketMakerParams,
+    pub bid: QuoteSlot,
+    pub ask: QuoteSlot,
+    /// Signed position in `symbol`, from the account
+    pub inventory: i64,
+    pub estimator: BookEstimator,
+    pub active_phases: Vec<TradingPhase>,
+    pub finished: bool,
+
+    book: Option<OrderBook>,
+    /// Local time the last book came in
+    last_book_at: Option<f64>,
+    /// Latest time seen by a callback; callbacks without one quote at this or the wall clock
+    clock: f64,
+    connected: bool,
+    in_session: bool,
+    started: bool,
+    next_quote: usize,
+}
+
+impl MarketMakerStrategy {
+    pub fn new(parent: &Order, params: MarketMakerParams) -> Self {
+        MarketMakerStrategy {
+            parent_order_id: parent.order_id.clone().unwrap_or_default(),
+            symbol: parent.symbol.clone(),
+            venue: parent.venue.clone(),
+            exchange: parent.exchange.clone(),
+            quote_size: parent.quantity,
+            estimator: BookEstimator::new(params.estimation_window),
+            active_phases: params.active_phases.clone().unwrap_or_else(|| session::CONTINUOUS_PHASES.to_vec()),
+            params,
+            bid: QuoteSlot::new(OrderSide::BUY),
+            ask: QuoteSlot::new(OrderSide::SELL),
+            inventory: 0,
+            finished: false,
+            book: None,
+            last_book_at: None,
+            clock: 0.0,
+            connected: true,
+            in_session: true,
+            started: false,
+            next_quote: 1,
+        }
+    }
+
+    /// Build from `strategy_params` (see `MarketMakerParams`): `max_inventory` (required), `tick_size`
+    /// (default 1), `half_spread_ticks` (default 1), `skew_ticks` (default the half spread),
+    /// `volatility_multiplier` (default 1), `estimation_window` (default 100 books), `fair_value`
+    /// (default `MID`), `stale_seconds` (default 5) and `active_phases` (default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = MarketMakerParams::from_params(&order.strategy, &order.strategy_params)?;
+        if order.quantity <= 0 {
+            return Err(anyhow!("MM quote size (the order quantity) must be positive"));
+        }
+        Ok(Self::new(order, params))
+    }
+
+    pub fn fair_value(&self) -> Option<Decimal> {
+        let book = self.book.as_ref()?;
+        match self.params.fair_value {
+            FairValue::Mid => book.get_mid_price(),
+            FairValue::Microprice => book.get_microprice(),
+        }
+    }
+
+    /// Distance of each quote from the fair value: the configured ticks plus the volatility widening.
+    pub fn half_spread(&self, fair: Decimal) -> Decimal {
+        let base = Decimal::from_f64(self.params.half_spread_ticks).unwrap_or_default() * self.params.tick_size;
+        let widening = self.estimator.volatility()
+            .and_then(|vol| Decimal::from_f64(vol * self.params.volatility_multiplier))
+            .unwrap_or_default() * fair;
+        base + widening
+    }
+
+    /// Shift of both quotes for the current inventory, positive when long.
+    pub fn skew(&self) -> Decimal {
+        let share = (self.inventory as f64 / self.params.max_inventory as f64).clamp(-1.0, 1.0);
+        Decimal::from_f64(self.params.skew_ticks * share).unwrap_or_default() * self.params.tick_size
+    }
+
+    /// Bid and ask (price, quantity) the strategy wants working now; `None` for a side it does not quote.
+    pub fn targets(&self, now: f64) -> (Option<Quote>, Option<Quote>) {
+        let stale = self.last_book_at.is_none_or(|t| now - t > self.params.stale_seconds);
+        if self.finished || !self.started || !self.connected || !self.in_session || stale {
+            return (None, None);
+        }
+        let Some(fair) = self.fair_value() else { return (None, None) };
+        let tick = self.params.tick_size;
+        let (half, skew) = (self.half_spread(fair), self.skew());
+        let mut bid = ((fair - half - skew) / tick).floor() * tick;
+        let mut ask = ((fair + half - skew) / tick).ceil() * tick;
+        // Stay passive: never cross the other side's touch
+        let book = self.book.as_ref();
+        if let Some((best_ask, _)) = book.and_then(|b| b.get_best_ask()) {
+            bid = bid.min(best_ask - tick);
+        }
+        if let Some((best_bid, _)) = book.and_then(|b| b.get_best_bid()) {
+            ask = ask.max(best_bid + tick);
+        }
+        if ask <= bid {
+            ask = bid + tick;
+        }
+
+        let max = self.params.max_inventory;
+        let bid_size = self.quote_size.min(max - self.inventory);
+        let ask_size = self.quote_size.min(max + self.inventory);
+        (
+            (bid_size > 0 && bid > Decimal::ZERO).then_some((bid, bid_size)),
+            (ask_size > 0).then_some((ask, ask_size)),
+        )
+    }
+
+    /// Move both quotes to their targets at `now` (unix seconds).
+    pub fn quote_at(&mut self, now: f64) -> StrategyAction {
+        self.clock = self.clock.max(now);
+        let (bid_target, ask_target) = self.targets(self.clock);
+        let template = self.quote_template();
+        let bid = Self::requote(&mut self.bid, bid_target, &mut self.next_quote, template.clone());
+        let ask = Self::requote(&mut self.ask, ask_target, &mut self.next_quote, template);
+        StrategyAction::each(vec![bid, ask])
+    }
+
+    fn quote_template(&self) -> Order {
+        let mut order = Order::new(
+            self.symbol.clone(),
+            OrderSide::BUY,
+            OrderType::LIMIT,
+            0,
+            None,
+            Some(ExecutionStrategy::NONE),
+            None,
+            None,
+            self.exchange.clone(),
+            Some(self.venue.clone()),
+        );
+        order.parent_order_id = Some(self.parent_order_id.clone());
+        order
+    }
+
+    fn requote(slot: &mut QuoteSlot, target: Option<Quote>, next_quote: &mut usize, template: Order) -> StrategyAction {
+        let side = slot.side.clone();
+        slot.requote(target, |price, quantity| {
+            let mut order = template;
+            let parent = order.parent_order_id.clone().unwrap_or_default();
+            order.order_id = Some(format!("{}-{}", parent, next_quote));
+            *next_quote += 1;
+            order.side = side;
+            order.quantity = quantity;
+            order.price = Some(price);
+            order
+        })
+    }
+
+    /// Book update received at `now` (local unix seconds).
+    pub fn on_book_at(&mut self, book: &OrderBook, now: f64) -> StrategyAction {
+        if book.symbol != self.symbol {
+            return StrategyAction::None;
+        }
+        self.estimator.on_book(book);
+        self.book = Some(book.clone());
+        self.last_book_at = Some(now);
+        self.quote_at(now)
+    }
+
+    /// Timer step at `now` (unix seconds); the first one starts quoting.
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        self.started = true;
+        self.quote_at(now)
+    }
+
+    fn now(&self) -> f64 {
+        self.clock.max(Local::now().timestamp_millis() as f64 / 1000.0)
+    }
+
+    /// Realized spread: what the round trips so far earned per unit, times the matched quantity.
+    pub fn realized_pnl(&self) -> Option<Decimal> {
+        let (buy, sell) = (self.bid.average_price()?, self.ask.average_price()?);
+        Some((sell - buy) * Decimal::from(self.bid.filled_quantity.min(self.ask.filled_quantity)))
+    }
+}
+
+impl Strategy for MarketMakerStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        Ok(self.on_book_at(book, self.now()))
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(self.now()))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id.as_str()) {
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.quote_at(self.now()));
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        let update = match self.bid.on_order_update(order) {
+            QuoteUpdate::Unrelated => self.ask.on_order_update(order),
+            update => update,
+        };
+        match update {
+            QuoteUpdate::Unrelated => return Ok(StrategyAction::None),
+            QuoteUpdate::Rejected => {
+                log::warn!("MM {}: quote {:?} rejected ({}), stopping", self.parent_order_id, order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            QuoteUpdate::Working { .. } | QuoteUpdate::Done { .. } => {}
+        }
+        // A confirmed cancel frees the slot for the replacement; an acknowledgement lets a pending pull go out
+        Ok(self.quote_at(self.now()))
+    }
+
+    fn on_account_update(&mut self, account: &AccountState) -> Result<StrategyAction> {
+        // The inventory is held where the quotes trade
+        if !account.venue.is_empty() && account.venue != self.venue {
+            return Ok(StrategyAction::None);
+        }
+        let inventory = account.positions.get(&self.symbol).map_or(0, |p| p.quantity);
+        if inventory == self.inventory {
+            return Ok(StrategyAction::None);
+        }
+        self.inventory = inventory;
+        Ok(self.quote_at(self.now()))
+    }
+
+    fn on_connection_status(&mut self, venue: &str, status: &ConnectionStatus) -> Result<StrategyAction> {
+        if venue != self.venue {
+            return Ok(StrategyAction::None);
+        }
+        self.connected = *status == ConnectionStatus::Connected;
+        if !self.connected {
+            // Quote again only off a book received after the reconnect
+            self.last_book_at = None;
+        }
+        Ok(self.quote_at(self.now()))
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        self.in_session = self.active_phases.contains(&to);
+        Ok(self.quote_at(self.now()))
+    }
+
+    /// Done once the parent is closed and both quotes are off the venue.
+    fn is_completed(&self) -> bool {
+        self.finished && self.bid.live_order_id().is_none() && self.ask.live_order_id().is_none()
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id.clone())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "MM",
+            "parent_order_id": self.parent_order_id,
+            "quote_size": self.quote_size,
+            "quotes_sent": self.bid.quotes_sent + self.ask.quotes_sent,
+            "bought": self.bid.filled_quantity,
+            "sold": self.ask.filled_quantity,
+            "buy_average": self.bid.average_price().map(|p| p.to_string()),
+            "sell_average": self.ask.average_price().map(|p| p.to_string()),
+            "realized_pnl": self.realized_pnl().map(|p| p.to_string()),
+            "inventory": self.inventory,
+            "volatility": self.estimator.volatility()
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        match child.side {
+            OrderSide::BUY => self.bid.restore(child),
+            OrderSide::SELL => self.ask.restore(child),
+        }
+        let n = child.order_id.as_deref()
+            .and_then(|id| id.rsplit_once('-'))
+            .and_then(|(_, n)| n.parse::<usize>().ok())
+            .unwrap_or(0);
+        self.next_quote = self.next_quote.max(n + 1);
+    }
+}
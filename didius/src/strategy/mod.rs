@@ -1,3 +1,34 @@
 pub mod base;
+pub mod bracket;
+pub mod child;
+pub mod iceberg;
 pub mod limit;
-pub mod stop;
\ No newline at end of file
+pub mod market_maker;
+pub mod params;
+pub mod peg;
+pub mod pov;
+pub mod python;
+pub mod quote;
+pub mod registry;
+pub mod shortfall;
+pub mod spread;
+pub mod stop;
+pub mod twap;
+pub mod vwap;
+
+use pyo3::prelude::*;
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<python::PyStrategyAction>()?;
+    m.add_class::<stop::StopParams>()?;
+    m.add_class::<twap::TwapParams>()?;
+    m.add_class::<vwap::VwapParams>()?;
+    m.add_class::<iceberg::IcebergParams>()?;
+    m.add_class::<peg::PegParams>()?;
+    m.add_class::<pov::PovParams>()?;
+    m.add_class::<shortfall::ShortfallParams>()?;
+    m.add_class::<market_maker::MarketMakerParams>()?;
+    m.add_class::<spread::SpreadParams>()?;
+    m.add_class::<bracket::BracketParams>()?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::oms::order::ExecutionStrategy;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Typed view of `Order.strategy_params` for one strategy. Values are parsed on access and errors
+/// name the key; `expect_keys` refuses keys outside the schema, so a typo rejects the order.
+pub struct ParamReader<'a> {
+    strategy: &'a ExecutionStrategy,
+    params: &'a HashMap<String, String>,
+}
+
+impl<'a> ParamReader<'a> {
+    pub fn new(strategy: &'a ExecutionStrategy, params: &'a HashMap<String, String>) -> Self {
+        ParamReader { strategy, params }
+    }
+
+    pub fn strategy(&self) -> &ExecutionStrategy {
+        self.strategy
+    }
+
+    /// Refuse any key outside `keys`.
+    pub fn expect_keys(self, keys: &[&str]) -> Result<Self> {
+        let mut unknown: Vec<&str> = self.params.keys().map(|k| k.as_str()).filter(|k| !keys.contains(k)).collect();
+        if unknown.is_empty() {
+            return Ok(self);
+        }
+        unknown.sort();
+        if keys.is_empty() {
+            return Err(anyhow!("{:?} takes no strategy_params, got {}", self.strategy, unknown.join(", ")));
+        }
+        Err(anyhow!("Unknown {:?} strategy_params: {} (expected {})", self.strategy, unknown.join(", "), keys.join(", ")))
+    }
+
+    /// `key` parsed as `T`, `None` when it is not set.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.params.get(key)
+            .map(|s| s.trim().parse::<T>().map_err(|e| anyhow!("Invalid {}: {}", key, e)))
+            .transpose()
+    }
+
+    /// `key` parsed as `T`; the order is rejected without it.
+    pub fn require<T>(&self, key: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key)?.ok_or_else(|| anyhow!("{:?} requires {}", self.strategy, key))
+    }
+
+    /// Comma separated list, e.g. a volume profile.
+    pub fn list<T>(&self, key: &str) -> Result<Option<Vec<T>>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.params.get(key)
+            .map(|s| s.split(',')
+                .map(|v| v.trim().parse::<T>().map_err(|e| anyhow!("Invalid {}: {}", key, e)))
+                .collect::<Result<Vec<T>>>())
+            .transpose()
+    }
+}
+
+/// Parameters of one execution strategy, parsed and validated from `Order.strategy_params`.
+/// Checks that need the order itself (quantity, price) stay in the strategy's `from_order`.
+pub trait StrategyParams: Sized {
+    /// Keys the strategy reads; any other key is refused
+    const KEYS: &'static [&'static str];
+
+    fn read(params: &ParamReader) -> Result<Self>;
+
+    /// Back to `strategy_params`, leaving out unset keys.
+    fn to_params(&self) -> HashMap<String, String>;
+
+    fn from_params(strategy: &ExecutionStrategy, params: &HashMap<String, String>) -> Result<Self> {
+        Self::read(&ParamReader::new(strategy, params).expect_keys(Self::KEYS)?)
+    }
+}
+
+/// Collects the set keys of a params struct into `strategy_params`.
+#[derive(Default)]
+pub struct ParamWriter(HashMap<String, String>);
+
+impl ParamWriter {
+    pub fn set(mut self, key: &str, value: impl ToString) -> Self {
+        self.0.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn set_opt(self, key: &str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(v) => self.set(key, v),
+            None => self,
+        }
+    }
+
+    pub fn finish(self) -> HashMap<String, String> {
+        self.0
+    }
+}
+
+/// Python constructors build the `strategy_params` they were given and validate them on the way in.
+pub(crate) fn py_params<P: StrategyParams>(strategy: ExecutionStrategy, params: ParamWriter) -> pyo3::PyResult<P> {
+    P::from_params(&strategy, &params.finish()).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+/// `Name(key='value', ...)` with the keys sorted, for the `__repr__` of the Python classes.
+pub(crate) fn repr(name: &str, params: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = params.iter().collect();
+    pairs.sort();
+    let fields: Vec<String> = pairs.iter().map(|(k, v)| format!("{}='{}'", k, v)).collect();
+    format!("{}({})", name, fields.join(", "))
+}
@@ -0,0 +1,304 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState};
+use crate::oms::order_book::OrderBook;
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Price a pegged order follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PegReference {
+    /// Own touch: best bid for a buy, best ask for a sell
+    Primary,
+    /// Other side: best ask for a buy, best bid for a sell
+    Opposite,
+    /// `OrderBook::get_mid_price`
+    Mid,
+}
+
+impl FromStr for PegReference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "PRIMARY" => Ok(PegReference::Primary),
+            "OPPOSITE" => Ok(PegReference::Opposite),
+            "MID" => Ok(PegReference::Mid),
+            other => Err(anyhow!("Unknown peg: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for PegReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PegReference::Primary => "PRIMARY",
+            PegReference::Opposite => "OPPOSITE",
+            PegReference::Mid => "MID",
+        })
+    }
+}
+
+/// `strategy_params` of a PEG order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PegParams {
+    pub reference: PegReference,
+    pub offset_ticks: i64,
+    pub tick_size: Option<Decimal>,
+    pub peg_limit: Option<Decimal>,
+    pub min_requote_interval: f64,
+}
+
+impl StrategyParams for PegParams {
+    const KEYS: &'static [&'static str] = &["peg", "offset_ticks", "tick_size", "peg_limit", "min_requote_interval"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let offset_ticks = params.get::<i64>("offset_ticks")?.unwrap_or(0);
+        let tick_size = params.get::<Decimal>("tick_size")?;
+        if tick_size.is_some_and(|t| t <= Decimal::ZERO) {
+            return Err(anyhow!("tick_size must be positive"));
+        }
+        if offset_ticks != 0 && tick_size.is_none() {
+            return Err(anyhow!("offset_ticks requires tick_size"));
+        }
+        let min_requote_interval = params.get::<f64>("min_requote_interval")?.unwrap_or(1.0);
+        if min_requote_interval.is_nan() || min_requote_interval < 0.0 {
+            return Err(anyhow!("min_requote_interval must not be negative"));
+        }
+        Ok(PegParams {
+            reference: params.get("peg")?.unwrap_or(PegReference::Primary),
+            offset_ticks,
+            tick_size,
+            peg_limit: params.get("peg_limit")?,
+            min_requote_interval,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("peg", self.reference)
+            .set("offset_ticks", self.offset_ticks)
+            .set_opt("tick_size", self.tick_size)
+            .set_opt("peg_limit", self.peg_limit)
+            .set("min_requote_interval", self.min_requote_interval)
+            .finish()
+    }
+}
+
+#[pymethods]
+impl PegParams {
+    #[new]
+    #[pyo3(signature = (peg=None, offset_ticks=None, tick_size=None, peg_limit=None, min_requote_interval=None))]
+    fn py_new(peg: Option<String>, offset_ticks: Option<i64>, tick_size: Option<String>, peg_limit: Option<String>, min_requote_interval: Option<f64>) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::PEG, ParamWriter::default()
+            .set_opt("peg", peg)
+            .set_opt("offset_ticks", offset_ticks)
+            .set_opt("tick_size", tick_size)
+            .set_opt("peg_limit", peg_limit)
+            .set_opt("min_requote_interval", min_requote_interval))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::PEG
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("PegParams", &self.to_params())
+    }
+}
+
+/// Keeps a resting LIMIT order pinned to a reference price plus `offset_ticks`, never through
+/// `peg_limit`. Re-prices go out as `ModifyPrice`, at most one per `min_requote_interval`;
+/// a move inside the interval is picked up by the timer once it has passed.
+pub struct PegStrategy {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub reference: PegReference,
+    /// Ticks towards the other side of the book (negative: away from it)
+    pub offset_ticks: i64,
+    pub tick_size: Option<Decimal>,
+    /// A buy is never priced above it, a sell never below
+    pub peg_limit: Option<Decimal>,
+    /// Seconds between two re-prices
+    pub min_requote_interval: f64,
+
+    /// Price the order rests at, as last reported by the OMS
+    pub quote: Option<Decimal>,
+    pub open_quantity: i64,
+    /// Acknowledged by the venue; before that it cannot be modified
+    pub acknowledged: bool,
+    /// A modify is waiting for the venue's answer
+    pub replacing: bool,
+    pub finished: bool,
+
+    book: Option<OrderBook>,
+    /// Price of a re-price the venue has not confirmed yet
+    pending_price: Option<Decimal>,
+    /// Unix seconds of the last price sent (placement or re-price)
+    last_requote: f64,
+    requotes: i64,
+}
+
+impl PegStrategy {
+    /// Build from `strategy_params` (see `PegParams`): `peg` (PRIMARY, OPPOSITE or MID, default PRIMARY),
+    /// `offset_ticks` (needs `tick_size`), `tick_size`, `peg_limit` and `min_requote_interval` (seconds, default 1).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = PegParams::from_params(&order.strategy, &order.strategy_params)?;
+        Ok(PegStrategy {
+            order_id: order.order_id.clone().unwrap_or_default(),
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            reference: params.reference,
+            offset_ticks: params.offset_ticks,
+            tick_size: params.tick_size,
+            peg_limit: params.peg_limit,
+            min_requote_interval: params.min_requote_interval,
+            quote: order.price,
+            open_quantity: order.quantity - order.filled_quantity,
+            acknowledged: !matches!(order.state, OrderState::CREATED | OrderState::PENDING_NEW),
+            replacing: order.state == OrderState::PENDING_REPLACE,
+            finished: false,
+            book: None,
+            pending_price: None,
+            last_requote: order.updated_at,
+            requotes: 0,
+        })
+    }
+
+    /// Best price on our own side, skipping our order when it is all there is at its level
+    /// (otherwise a peg with a positive offset would chase itself).
+    fn primary_touch(&self, book: &OrderBook) -> Option<Decimal> {
+        let mine = |p: &Decimal, q: &i64| Some(*p) == self.quote && *q <= self.open_quantity;
+        match self.side {
+            OrderSide::BUY => book.bids.iter().rev().find(|(p, q)| !mine(p, q)).map(|(p, _)| *p),
+            OrderSide::SELL => book.asks.iter().find(|(p, q)| !mine(p, q)).map(|(p, _)| *p),
+        }
+    }
+
+    /// Peg price for `book`: reference plus offset, rounded to the tick away from the other side, capped at `peg_limit`.
+    pub fn target_price(&self, book: &OrderBook) -> Option<Decimal> {
+        let reference = match (self.reference, &self.side) {
+            (PegReference::Primary, _) => self.primary_touch(book)?,
+            (PegReference::Opposite, OrderSide::BUY) => book.get_best_ask()?.0,
+            (PegReference::Opposite, OrderSide::SELL) => book.get_best_bid()?.0,
+            (PegReference::Mid, _) => book.get_mid_price()?,
+        };
+        let offset = Decimal::from(self.offset_ticks) * self.tick_size.unwrap_or_default();
+        let mut price = match self.side {
+            OrderSide::BUY => reference + offset,
+            OrderSide::SELL => reference - offset,
+        };
+        if let Some(tick) = self.tick_size {
+            price = match self.side {
+                OrderSide::BUY => (price / tick).floor() * tick,
+                OrderSide::SELL => (price / tick).ceil() * tick,
+            };
+        }
+        if let Some(limit) = self.peg_limit {
+            price = match self.side {
+                OrderSide::BUY => price.min(limit),
+                OrderSide::SELL => price.max(limit),
+            };
+        }
+        Some(price).filter(|p| *p > Decimal::ZERO)
+    }
+
+    /// Re-price to the peg if it moved and the interval since the last price has passed.
+    /// One re-price at a time: nothing goes out while a modify is unanswered.
+    pub fn requote_at(&mut self, now: f64) -> StrategyAction {
+        if self.finished || !self.acknowledged || self.replacing || self.pending_price.is_some()
+            || now - self.last_requote < self.min_requote_interval {
+            return StrategyAction::None;
+        }
+        let Some(target) = self.book.as_ref().and_then(|b| self.target_price(b)) else {
+            return StrategyAction::None;
+        };
+        if self.quote == Some(target) {
+            return StrategyAction::None;
+        }
+        self.pending_price = Some(target);
+        self.last_requote = now;
+        StrategyAction::ModifyPrice(self.order_id.clone(), Some(target))
+    }
+
+    fn now() -> f64 {
+        Local::now().timestamp_millis() as f64 / 1000.0
+    }
+}
+
+impl Strategy for PegStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        if self.finished || book.symbol != self.symbol {
+            return Ok(StrategyAction::None);
+        }
+        self.book = Some(book.clone());
+        Ok(self.requote_at(Self::now()))
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.requote_at(Self::now()))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() != Some(self.order_id.as_str()) {
+            return Ok(StrategyAction::None);
+        }
+        self.open_quantity = order.quantity - order.filled_quantity;
+        self.acknowledged |= !matches!(order.state, OrderState::CREATED | OrderState::PENDING_NEW);
+        self.replacing = order.state == OrderState::PENDING_REPLACE;
+        self.quote = order.price;
+        // The venue confirmed the re-price, or refused it and the engine put the old price back
+        if !self.replacing {
+            if let Some(pending) = self.pending_price.take() {
+                if order.price == Some(pending) {
+                    self.requotes += 1;
+                }
+            }
+        }
+        if order.state.is_terminal() {
+            self.finished = true;
+            return Ok(StrategyAction::None);
+        }
+        Ok(self.requote_at(Self::now()))
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.order_id.clone())
+    }
+
+    fn update_order_id(&mut self, new_id: String) {
+        self.order_id = new_id;
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "PEG",
+            "order_id": self.order_id,
+            "reference": format!("{:?}", self.reference),
+            "quote": self.quote.map(|p| p.to_string()),
+            "requotes": self.requotes
+        }))
+    }
+}
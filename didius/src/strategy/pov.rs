@@ -0,0 +1,391 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// What a POV order does with its remainder once `end_time` is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PovEndAction {
+    /// Send the remainder as aggressive children until the parent is filled
+    Complete,
+    /// Pull the working child and cancel the parent
+    Cancel,
+}
+
+impl FromStr for PovEndAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "COMPLETE" => Ok(PovEndAction::Complete),
+            "CANCEL" => Ok(PovEndAction::Cancel),
+            other => Err(anyhow!("Unknown end_action: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for PovEndAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PovEndAction::Complete => "COMPLETE",
+            PovEndAction::Cancel => "CANCEL",
+        })
+    }
+}
+
+/// `strategy_params` of a POV order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PovParams {
+    pub participation: f64,
+    pub min_participation: f64,
+    pub max_participation: f64,
+    /// Unix seconds; the order creation time when unset
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub duration: Option<f64>,
+    pub end_action: PovEndAction,
+    pub interval: f64,
+    pub pricing: ChildPricing,
+    /// Session phases the POV trades and counts volume in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl StrategyParams for PovParams {
+    const KEYS: &'static [&'static str] = &[
+        "participation", "min_participation", "max_participation", "start_time", "end_time",
+        "duration_seconds", "end_action", "interval_seconds", "pricing", "active_phases",
+    ];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let participation = params.require::<f64>("participation")?;
+        if !(participation > 0.0 && participation <= 1.0) {
+            return Err(anyhow!("participation must be in (0, 1]"));
+        }
+        let min_participation = params.get::<f64>("min_participation")?.unwrap_or(participation);
+        let max_participation = params.get::<f64>("max_participation")?.unwrap_or(participation);
+        if !(0.0..=participation).contains(&min_participation) || !(participation..=1.0).contains(&max_participation) {
+            return Err(anyhow!("Participation bands must satisfy 0 <= min_participation <= participation <= max_participation <= 1"));
+        }
+        let interval = params.get::<f64>("interval_seconds")?.unwrap_or(10.0);
+        if interval.is_nan() || interval <= 0.0 {
+            return Err(anyhow!("interval_seconds must be positive"));
+        }
+        Ok(PovParams {
+            participation,
+            min_participation,
+            max_participation,
+            start_time: params.get("start_time")?,
+            end_time: params.get("end_time")?,
+            duration: params.get("duration_seconds")?,
+            end_action: params.get("end_action")?.unwrap_or(PovEndAction::Cancel),
+            interval,
+            pricing: params.get("pricing")?.unwrap_or(ChildPricing::Passive),
+            active_phases: params.list("active_phases")?,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("participation", self.participation)
+            .set("min_participation", self.min_participation)
+            .set("max_participation", self.max_participation)
+            .set_opt("start_time", self.start_time)
+            .set_opt("end_time", self.end_time)
+            .set_opt("duration_seconds", self.duration)
+            .set("end_action", self.end_action)
+            .set("interval_seconds", self.interval)
+            .set("pricing", self.pricing)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl PovParams {
+    #[new]
+    #[pyo3(signature = (participation, min_participation=None, max_participation=None, start_time=None, end_time=None, duration_seconds=None, end_action=None, interval_seconds=None, pricing=None, active_phases=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        participation: f64,
+        min_participation: Option<f64>,
+        max_participation: Option<f64>,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        duration_seconds: Option<f64>,
+        end_action: Option<String>,
+        interval_seconds: Option<f64>,
+        pricing: Option<String>,
+        active_phases: Option<Vec<TradingPhase>>,
+    ) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::POV, ParamWriter::default()
+            .set("participation", participation)
+            .set_opt("min_participation", min_participation)
+            .set_opt("max_participation", max_participation)
+            .set_opt("start_time", start_time)
+            .set_opt("end_time", end_time)
+            .set_opt("duration_seconds", duration_seconds)
+            .set_opt("end_action", end_action)
+            .set_opt("interval_seconds", interval_seconds)
+            .set_opt("pricing", pricing)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::POV
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("PovParams", &self.to_params())
+    }
+}
+
+/// Percentage-of-volume execution: trades a target share of the market volume printed on the
+/// symbol since `start_time`. Market volume includes our own fills, as the tape does.
+///
+/// Every `interval` seconds a child is sized against the bands:
+/// - below `min_participation` it takes the opposite touch for what brings it back to `participation`;
+/// - otherwise it rests (`pricing`, passive by default) for what the volume allows up to `max_participation`.
+///
+/// A resting child the market has left behind the minimum is pulled and replaced by an aggressive one.
+/// Outside `active_phases` the working child is pulled and auction volume is not counted.
+pub struct PovStrategy {
+    /// Target share of the market volume, in (0, 1]
+    pub participation: f64,
+    pub min_participation: f64,
+    pub max_participation: f64,
+    pub start_time: f64,
+    pub end_time: Option<f64>,
+    pub end_action: PovEndAction,
+    pub interval: f64,
+    /// Pricing of children sent inside the bands
+    pub pricing: ChildPricing,
+    pub children: ChildOrders,
+    pub active_phases: Vec<TradingPhase>,
+    pub finished: bool,
+
+    market_volume: i64,
+    next_child_time: f64,
+}
+
+impl PovStrategy {
+    pub fn new(parent: &Order, participation: f64, start_time: f64, interval: f64, pricing: ChildPricing) -> Self {
+        PovStrategy {
+            participation,
+            min_participation: participation,
+            max_participation: participation,
+            start_time,
+            end_time: None,
+            end_action: PovEndAction::Cancel,
+            interval,
+            pricing,
+            children: ChildOrders::new(parent, pricing),
+            active_phases: session::CONTINUOUS_PHASES.to_vec(),
+            finished: false,
+            market_volume: 0,
+            next_child_time: start_time,
+        }
+    }
+
+    /// Build from `strategy_params` (see `PovParams`): `participation` (required, in (0, 1]), `min_participation` and
+    /// `max_participation` (default `participation`), `start_time` (default the order creation time),
+    /// `end_time` (unix seconds) or `duration_seconds`, `end_action` (`COMPLETE` or `CANCEL`, default
+    /// `CANCEL`), `interval_seconds` (default 10), `pricing` (default `PASSIVE`) and `active_phases`
+    /// (default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = PovParams::from_params(&order.strategy, &order.strategy_params)?;
+        let start_time = params.start_time.unwrap_or(order.created_at);
+        let end_time = match (params.end_time, params.duration) {
+            (Some(end), _) => Some(end),
+            (None, Some(duration)) => Some(start_time + duration),
+            (None, None) => None,
+        };
+        if end_time.is_some_and(|end| end <= start_time) {
+            return Err(anyhow!("POV window must end after it starts"));
+        }
+        if order.order_type == OrderType::LIMIT && order.price.is_none() {
+            return Err(anyhow!("LIMIT POV requires a price"));
+        }
+
+        let mut strat = Self::new(order, params.participation, start_time, params.interval, params.pricing);
+        strat.min_participation = params.min_participation;
+        strat.max_participation = params.max_participation;
+        strat.end_time = end_time;
+        strat.end_action = params.end_action;
+        if let Some(phases) = params.active_phases {
+            strat.active_phases = phases;
+        }
+        Ok(strat)
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.children.parent_order_id
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.children.filled_quantity()
+    }
+
+    /// Market volume printed on the symbol since `start_time`.
+    pub fn market_volume(&self) -> i64 {
+        self.market_volume
+    }
+
+    /// Share of the market volume filled so far.
+    pub fn realized_participation(&self) -> Option<f64> {
+        (self.market_volume > 0).then(|| self.children.filled_quantity() as f64 / self.market_volume as f64)
+    }
+
+    /// Filled quantity `rate` of the market volume calls for, capped at the parent quantity.
+    pub fn quantity_at(&self, rate: f64) -> i64 {
+        ((self.market_volume as f64 * rate).floor() as i64).min(self.children.quantity)
+    }
+
+    fn ended(&self, now: f64) -> bool {
+        self.end_time.is_some_and(|end| now >= end)
+    }
+
+    /// Timer step at `now` (unix seconds).
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        if self.finished || now < self.start_time {
+            return StrategyAction::None;
+        }
+        let ended = self.ended(now);
+        if ended && self.end_action == PovEndAction::Cancel {
+            if self.children.is_working() {
+                return self.children.pull_working();
+            }
+            log::info!("POV {}: end time reached with {} of {} filled, canceling", self.parent_order_id(), self.filled_quantity(), self.children.quantity);
+            self.finished = true;
+            return StrategyAction::CancelOrder(self.parent_order_id().to_string());
+        }
+
+        let filled = self.children.filled_quantity();
+        let behind = ended || filled < self.quantity_at(self.min_participation);
+        if self.children.is_working() {
+            // A resting child is no use once the market has left us behind the minimum
+            if behind && self.children.pricing != ChildPricing::Aggressive {
+                return self.children.pull_working();
+            }
+            return self.children.expire(now);
+        }
+        if now < self.next_child_time && !behind {
+            return StrategyAction::None;
+        }
+
+        let (target, pricing) = if ended {
+            (self.children.quantity, ChildPricing::Aggressive)
+        } else if behind {
+            (self.quantity_at(self.participation), ChildPricing::Aggressive)
+        } else {
+            (self.quantity_at(self.max_participation), self.pricing)
+        };
+        let need = target - filled;
+        self.children.pricing = pricing;
+        if need <= 0 || !self.children.can_price() {
+            return StrategyAction::None;
+        }
+        self.next_child_time = now + self.interval;
+        self.children.place(need, now + self.interval)
+    }
+}
+
+impl Strategy for PovStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.children.on_book(book);
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, symbol: &str, _price: Decimal, quantity: i64, timestamp: f64) -> Result<StrategyAction> {
+        let in_window = timestamp >= self.start_time && !self.ended(timestamp);
+        if symbol == self.children.symbol && in_window && !self.finished {
+            self.market_volume += quantity;
+        }
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(Local::now().timestamp_millis() as f64 / 1000.0))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.children.cancel_working());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        match self.children.on_child_update(order) {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected => {
+                log::warn!("POV {}: child {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            ChildUpdate::Updated => {}
+        }
+        if self.children.remaining_quantity() == 0 {
+            self.finished = true;
+        }
+        Ok(self.children.resume_pull())
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        if self.finished || self.active_phases.contains(&to) {
+            return Ok(StrategyAction::None);
+        }
+        Ok(self.children.pull_working())
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn venue(&self) -> Option<&str> {
+        Some(&self.children.venue)
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "POV",
+            "parent_order_id": self.parent_order_id(),
+            "quantity": self.children.quantity,
+            "filled_quantity": self.children.filled_quantity(),
+            "average_price": self.children.average_fill_price().map(|p| p.to_string()),
+            "market_volume": self.market_volume,
+            "participation": self.participation,
+            "realized_participation": self.realized_participation()
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        self.children.restore_child(child, child.created_at + self.interval);
+        self.next_child_time = self.next_child_time.max(child.created_at + self.interval);
+        self.finished = self.children.remaining_quantity() == 0;
+    }
+}
@@ -0,0 +1,260 @@
+use crate::message::ConnectionStatus;
+use crate::oms::order::Order;
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::TradingPhase;
+use crate::strategy::base::{Strategy, StrategyAction};
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyDict, PyList, PyTuple};
+use rust_decimal::Decimal;
+
+/// Callbacks a Python strategy has to implement.
+const CALLBACKS: [&str; 4] = ["on_order_book_update", "on_trade_update", "on_order_status_update", "on_timer"];
+
+/// Consecutive failing callbacks after which a Python strategy is stopped.
+pub const DEFAULT_MAX_ERRORS: usize = 10;
+
+/// An action returned by a Python strategy, built with `StrategyAction.place(order)`,
+/// `.cancel(order_id)`, `.modify(order_id, price=None)` or `.remove(order_id)`.
+#[pyclass(name = "StrategyAction")]
+#[derive(Debug, Clone)]
+pub struct PyStrategyAction {
+    pub action: StrategyAction,
+}
+
+#[pymethods]
+impl PyStrategyAction {
+    #[staticmethod]
+    fn place(order: Order) -> Self {
+        PyStrategyAction { action: StrategyAction::PlaceOrder(order) }
+    }
+
+    #[staticmethod]
+    fn cancel(order_id: String) -> Self {
+        PyStrategyAction { action: StrategyAction::CancelOrder(order_id) }
+    }
+
+    /// Re-price `order_id`; without a price it goes to market.
+    #[staticmethod]
+    #[pyo3(signature = (order_id, price=None))]
+    fn modify(order_id: String, price: Option<String>) -> PyResult<Self> {
+        let price = price
+            .map(|p| crate::utils::parse_decimal(&p).map_err(|e| PyValueError::new_err(format!("Invalid price {}: {}", p, e))))
+            .transpose()?;
+        Ok(PyStrategyAction { action: StrategyAction::ModifyPrice(order_id, price) })
+    }
+
+    #[staticmethod]
+    fn remove(order_id: String) -> Self {
+        PyStrategyAction { action: StrategyAction::RemoveOrder(order_id) }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.action)
+    }
+}
+
+/// Convert what a Python callback returned: `None`, a `StrategyAction`, an `Order` (placed as is)
+/// or a list / tuple of those, which becomes a `StrategyAction::Batch`.
+fn to_action(value: &Bound<'_, PyAny>) -> PyResult<StrategyAction> {
+    if value.is_none() {
+        return Ok(StrategyAction::None);
+    }
+    if let Ok(action) = value.downcast::<PyStrategyAction>() {
+        return Ok(action.borrow().action.clone());
+    }
+    if let Ok(order) = value.extract::<Order>() {
+        return Ok(StrategyAction::PlaceOrder(order));
+    }
+    if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        let actions = value.try_iter()?
+            .map(|item| to_action(&item?))
+            .collect::<PyResult<Vec<_>>>()?;
+        return Ok(StrategyAction::batch(actions));
+    }
+    Err(PyTypeError::new_err(format!("Expected StrategyAction, Order, a list of them or None, got {}", value.get_type().name()?)))
+}
+
+/// Book as handed to Python: levels are `(price, quantity)` with the best price first.
+fn book_to_py<'py>(py: Python<'py>, book: &OrderBook) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("symbol", &book.symbol)?;
+    dict.set_item("venue", &book.venue)?;
+    dict.set_item("update_id", book.last_update_id)?;
+    dict.set_item("timestamp", book.timestamp)?;
+    let bids: Vec<(String, i64)> = book.bids.iter().rev().map(|(p, q)| (p.to_string(), *q)).collect();
+    let asks: Vec<(String, i64)> = book.asks.iter().map(|(p, q)| (p.to_string(), *q)).collect();
+    dict.set_item("bids", bids)?;
+    dict.set_item("asks", asks)?;
+    Ok(dict)
+}
+
+/// A strategy written in Python, called from the engine threads.
+///
+/// Every callback takes the GIL for its duration. An exception is logged with its traceback and
+/// returned as an error, which the engine skips like any failing strategy; after `max_errors`
+/// failures in a row the strategy is stopped. The Python object must not call back into the
+/// engine from a callback: the engine holds its strategy lock while it runs, so actions are returned instead.
+pub struct PyStrategy {
+    strategy: PyObject,
+    pub name: String,
+    pub max_errors: usize,
+    /// Failing callbacks in a row
+    errors: usize,
+    stopped: bool,
+    /// Read once from the optional `active_phases` attribute
+    active_phases: Option<Vec<TradingPhase>>,
+}
+
+/// `active_phases` given as `TradingPhase`s or their names.
+fn extract_phases(value: &Bound<'_, PyAny>) -> PyResult<Vec<TradingPhase>> {
+    value.try_iter()?
+        .map(|item| {
+            let item = item?;
+            match item.extract::<TradingPhase>() {
+                Ok(phase) => Ok(phase),
+                Err(_) => item.extract::<String>()?.parse().map_err(|e: anyhow::Error| PyValueError::new_err(e.to_string())),
+            }
+        })
+        .collect()
+}
+
+impl PyStrategy {
+    /// Wrap `strategy`, which must have the four callbacks. `is_completed()`, `get_origin_order_id()`,
+    /// `report()` (a dict), `on_session_change(from, to)` and `on_connection_status(venue, status)`
+    /// (the status name, e.g. `"Disconnected"`) are used when present, and an
+    /// `active_phases` attribute (a list of `TradingPhase`s or their names) is read once here.
+    pub fn new(py: Python<'_>, strategy: PyObject) -> PyResult<Self> {
+        let bound = strategy.bind(py);
+        for callback in CALLBACKS {
+            if !bound.getattr(callback).is_ok_and(|m| m.is_callable()) {
+                return Err(PyTypeError::new_err(format!("Strategy {} has no {} method", bound.get_type().name()?, callback)));
+            }
+        }
+        let name = bound.get_type().name()?.to_string();
+        let active_phases = match bound.getattr("active_phases") {
+            Ok(value) if !value.is_none() => Some(extract_phases(&value)?),
+            _ => None,
+        };
+        Ok(PyStrategy { strategy, name, max_errors: DEFAULT_MAX_ERRORS, errors: 0, stopped: false, active_phases })
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Call `callback` with the arguments built by `args` and convert what it returns.
+    fn call<F>(&mut self, callback: &str, args: F) -> Result<StrategyAction>
+    where
+        F: for<'py> FnOnce(Python<'py>) -> PyResult<Bound<'py, PyTuple>>,
+    {
+        if self.stopped {
+            return Ok(StrategyAction::None);
+        }
+        let result = Python::with_gil(|py| {
+            let returned = self.strategy.call_method1(py, callback, args(py)?)?;
+            to_action(returned.bind(py))
+        });
+        match result {
+            Ok(action) => {
+                self.errors = 0;
+                Ok(action)
+            }
+            Err(e) => Err(self.failed(callback, e)),
+        }
+    }
+
+    /// Log a Python exception and stop the strategy once it has failed `max_errors` times in a row.
+    fn failed(&mut self, callback: &str, e: PyErr) -> anyhow::Error {
+        let traceback = Python::with_gil(|py| e.traceback(py).and_then(|tb| tb.format().ok())).unwrap_or_default();
+        log::error!("Python strategy {}: {} raised {}\n{}", self.name, callback, e, traceback);
+        self.errors += 1;
+        if self.errors >= self.max_errors && !self.stopped {
+            log::error!("Python strategy {}: stopped after {} failing callbacks in a row", self.name, self.errors);
+            self.stopped = true;
+        }
+        anyhow!("{}.{} raised {}", self.name, callback, e)
+    }
+
+    /// Call an optional method without arguments; `None` when the object does not have it.
+    fn call_optional<T, F>(&self, method: &str, convert: F) -> Option<T>
+    where
+        F: for<'py> FnOnce(&Bound<'py, PyAny>) -> PyResult<T>,
+    {
+        Python::with_gil(|py| {
+            let bound = self.strategy.bind(py);
+            if !bound.hasattr(method).unwrap_or(false) {
+                return None;
+            }
+            match bound.call_method0(method).and_then(|value| convert(&value)) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    log::error!("Python strategy {}: {} raised {}", self.name, method, e);
+                    None
+                }
+            }
+        })
+    }
+}
+
+impl Strategy for PyStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.call("on_order_book_update", |py| PyTuple::new(py, [book_to_py(py, book)?]))
+    }
+
+    fn on_trade_update(&mut self, symbol: &str, price: Decimal, quantity: i64, timestamp: f64) -> Result<StrategyAction> {
+        self.call("on_trade_update", |py| (symbol, price.to_string(), quantity, timestamp).into_pyobject(py))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        self.call("on_order_status_update", |py| PyTuple::new(py, [order.clone()]))
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        self.call("on_timer", |py| Ok(PyTuple::empty(py)))
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        self.active_phases.as_deref()
+    }
+
+    fn on_session_change(&mut self, from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        let defined = Python::with_gil(|py| self.strategy.bind(py).hasattr("on_session_change").unwrap_or(false));
+        if !defined {
+            return Ok(StrategyAction::None);
+        }
+        self.call("on_session_change", |py| (from, to).into_pyobject(py))
+    }
+
+    fn on_connection_status(&mut self, venue: &str, status: &ConnectionStatus) -> Result<StrategyAction> {
+        let defined = Python::with_gil(|py| self.strategy.bind(py).hasattr("on_connection_status").unwrap_or(false));
+        if !defined {
+            return Ok(StrategyAction::None);
+        }
+        self.call("on_connection_status", |py| (venue, format!("{:?}", status)).into_pyobject(py))
+    }
+
+    fn is_completed(&self) -> bool {
+        self.stopped || self.call_optional("is_completed", |v| v.is_truthy()).unwrap_or(false)
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        self.call_optional("get_origin_order_id", |v| v.extract::<Option<String>>()).flatten()
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        let json = self.call_optional("report", |v| {
+            if v.is_none() {
+                return Ok(None);
+            }
+            let dumps = v.py().import("json")?.getattr("dumps")?;
+            dumps.call1((v,))?.extract::<String>().map(Some)
+        })??;
+        let mut report: serde_json::Value = serde_json::from_str(&json).ok()?;
+        if let Some(body) = report.as_object_mut() {
+            body.entry("strategy").or_insert_with(|| serde_json::json!(self.name));
+        }
+        Some(report)
+    }
+}
@@ -0,0 +1,166 @@
+use crate::oms::order::{Order, OrderSide, OrderState};
+use crate::strategy::base::StrategyAction;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone)]
+struct LiveQuote {
+    order_id: String,
+    price: Decimal,
+    quantity: i64,
+    filled: i64,
+    filled_value: Decimal,
+    /// Acknowledged by the venue; before that it cannot be canceled
+    acknowledged: bool,
+    cancel_requested: bool,
+}
+
+impl LiveQuote {
+    fn open_quantity(&self) -> i64 {
+        self.quantity - self.filled
+    }
+}
+
+/// Price and quantity of a quote.
+pub type Quote = (Decimal, i64);
+
+/// What a status update meant for a quote slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteUpdate {
+    /// Not the slot's order
+    Unrelated,
+    /// Still live; `filled` is the quantity filled since the last update
+    Working { filled: i64 },
+    /// Filled, canceled or expired: the slot is free for the next quote
+    Done { filled: i64 },
+    Rejected,
+}
+
+/// One side of a two-sided quote. The slot holds at most one live order and moves it by
+/// cancel/replace: a quote that no longer matches the target is canceled, and the replacement
+/// goes out only once the venue has confirmed the cancel, so both never work at once.
+#[derive(Debug, Clone)]
+pub struct QuoteSlot {
+    pub side: OrderSide,
+    /// Quantity and value of all fills of the slot's orders
+    pub filled_quantity: i64,
+    pub filled_value: Decimal,
+    /// Orders the slot has sent
+    pub quotes_sent: usize,
+
+    live: Option<LiveQuote>,
+}
+
+impl QuoteSlot {
+    pub fn new(side: OrderSide) -> Self {
+        QuoteSlot { side, filled_quantity: 0, filled_value: Decimal::ZERO, quotes_sent: 0, live: None }
+    }
+
+    pub fn live_order_id(&self) -> Option<&str> {
+        self.live.as_ref().map(|q| q.order_id.as_str())
+    }
+
+    pub fn live_price(&self) -> Option<Decimal> {
+        self.live.as_ref().map(|q| q.price)
+    }
+
+    /// Unfilled quantity of the live order.
+    pub fn open_quantity(&self) -> i64 {
+        self.live.as_ref().map_or(0, |q| q.open_quantity())
+    }
+
+    /// Whether a cancel of the live order is in flight.
+    pub fn cancel_requested(&self) -> bool {
+        self.live.as_ref().is_some_and(|q| q.cancel_requested)
+    }
+
+    /// Average price over all fills of the slot.
+    pub fn average_price(&self) -> Option<Decimal> {
+        (self.filled_quantity > 0).then(|| self.filled_value / Decimal::from(self.filled_quantity))
+    }
+
+    /// Move the slot toward `target` (price, quantity); `None` or a zero quantity pulls the quote.
+    /// A live order at the target price is kept while its open quantity fits the target. Otherwise
+    /// it is canceled, once acknowledged, and `new_order` builds the replacement when the slot is free.
+    pub fn requote(&mut self, target: Option<Quote>, new_order: impl FnOnce(Decimal, i64) -> Order) -> StrategyAction {
+        let target = target.filter(|(_, quantity)| *quantity > 0);
+        match (self.live.as_ref(), target) {
+            (Some(q), Some((price, quantity))) if q.price == price && q.open_quantity() <= quantity => StrategyAction::None,
+            (Some(_), _) => self.pull(),
+            (None, Some((price, quantity))) => {
+                let order = new_order(price, quantity);
+                self.live = Some(LiveQuote {
+                    order_id: order.order_id.clone().unwrap_or_default(),
+                    price,
+                    quantity,
+                    filled: 0,
+                    filled_value: Decimal::ZERO,
+                    acknowledged: false,
+                    cancel_requested: false,
+                });
+                self.quotes_sent += 1;
+                StrategyAction::PlaceOrder(order)
+            }
+            (None, None) => StrategyAction::None,
+        }
+    }
+
+    /// Cancel the live order. It stays live until the venue confirms, and an order not yet
+    /// acknowledged is canceled by the next `requote` or `pull` after its acknowledgement.
+    pub fn pull(&mut self) -> StrategyAction {
+        match self.live.as_mut() {
+            Some(q) if q.acknowledged && !q.cancel_requested => {
+                q.cancel_requested = true;
+                StrategyAction::CancelOrder(q.order_id.clone())
+            }
+            _ => StrategyAction::None,
+        }
+    }
+
+    /// Book a status update of the slot's live order.
+    pub fn on_order_update(&mut self, order: &Order) -> QuoteUpdate {
+        let Some(q) = self.live.as_mut().filter(|q| order.order_id.as_deref() == Some(q.order_id.as_str())) else {
+            return QuoteUpdate::Unrelated;
+        };
+        let filled = (order.filled_quantity - q.filled).max(0);
+        if filled > 0 {
+            // The order's average covers all its fills so far
+            let value = order.average_fill_price * Decimal::from(order.filled_quantity);
+            self.filled_value += value - q.filled_value;
+            self.filled_quantity += filled;
+            q.filled = order.filled_quantity;
+            q.filled_value = value;
+        }
+        q.acknowledged |= !matches!(order.state, OrderState::CREATED | OrderState::PENDING_NEW);
+        // The engine puts a quote back as it was when the venue refuses the cancel
+        if q.cancel_requested && filled == 0 && matches!(order.state, OrderState::NEW | OrderState::PARTIALLY_FILLED) {
+            q.cancel_requested = false;
+        }
+        if order.state == OrderState::REJECTED {
+            self.live = None;
+            return QuoteUpdate::Rejected;
+        }
+        if order.state.is_terminal() {
+            self.live = None;
+            return QuoteUpdate::Done { filled };
+        }
+        QuoteUpdate::Working { filled }
+    }
+
+    /// Take over a quote recovered from the journal.
+    pub fn restore(&mut self, order: &Order) {
+        self.filled_quantity += order.filled_quantity;
+        self.filled_value += order.average_fill_price * Decimal::from(order.filled_quantity);
+        self.quotes_sent += 1;
+        if order.is_active() {
+            self.live = Some(LiveQuote {
+                order_id: order.order_id.clone().unwrap_or_default(),
+                price: order.price.unwrap_or_default(),
+                quantity: order.quantity,
+                filled: order.filled_quantity,
+                filled_value: order.average_fill_price * Decimal::from(order.filled_quantity),
+                acknowledged: !matches!(order.state, OrderState::CREATED | OrderState::PENDING_NEW),
+                cancel_requested: order.state == OrderState::PENDING_CANCEL,
+            });
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::oms::order::{ExecutionStrategy, Order};
+use crate::strategy::base::Strategy;
+use crate::strategy::bracket::BracketStrategy;
+use crate::strategy::iceberg::IcebergStrategy;
+use crate::strategy::limit::LimitStrategy;
+use crate::strategy::market_maker::MarketMakerStrategy;
+use crate::strategy::peg::PegStrategy;
+use crate::strategy::pov::PovStrategy;
+use crate::strategy::shortfall::ShortfallStrategy;
+use crate::strategy::spread::SpreadStrategy;
+use crate::strategy::stop::StopStrategy;
+use crate::strategy::twap::TwapStrategy;
+use crate::strategy::vwap::VwapStrategy;
+use anyhow::Result;
+use std::collections::HashMap;
+
+pub type BoxedStrategy = Box<dyn Strategy + Send + Sync>;
+
+/// Builds the strategy of an order. `recovered` marks orders rebuilt from the journal,
+/// whose strategy may already have acted.
+pub type StrategyFactory = fn(order: &Order, recovered: bool) -> Result<BoxedStrategy>;
+
+/// Which strategy the engine attaches to an order, by `ExecutionStrategy`.
+/// Orders whose strategy has no factory (`NONE`, `MARKET`) go to the venue unmanaged.
+pub struct StrategyRegistry {
+    factories: HashMap<ExecutionStrategy, StrategyFactory>,
+}
+
+impl StrategyRegistry {
+    pub fn empty() -> Self {
+        StrategyRegistry { factories: HashMap::new() }
+    }
+
+    /// Replace the factory of `strategy`.
+    pub fn register(&mut self, strategy: ExecutionStrategy, factory: StrategyFactory) {
+        self.factories.insert(strategy, factory);
+    }
+
+    pub fn contains(&self, strategy: &ExecutionStrategy) -> bool {
+        self.factories.contains_key(strategy)
+    }
+
+    /// Strategy for `order`; `None` when its `ExecutionStrategy` has none. Invalid
+    /// `strategy_params` are an error and the engine rejects the order.
+    pub fn create(&self, order: &Order, recovered: bool) -> Result<Option<BoxedStrategy>> {
+        self.factories.get(&order.strategy).map(|factory| factory(order, recovered)).transpose()
+    }
+}
+
+impl Default for StrategyRegistry {
+    /// The built-in strategies.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(ExecutionStrategy::STOP, |order, recovered| {
+            let mut strat = StopStrategy::from_order(order)?;
+            if recovered {
+                strat.resume(order);
+            }
+            Ok(Box::new(strat))
+        });
+        registry.register(ExecutionStrategy::LIMIT, |order, _| Ok(Box::new(LimitStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::PEG, |order, _| Ok(Box::new(PegStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::TWAP, |order, _| Ok(Box::new(TwapStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::VWAP, |order, _| Ok(Box::new(VwapStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::ICEBERG, |order, _| Ok(Box::new(IcebergStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::POV, |order, _| Ok(Box::new(PovStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::IS, |order, _| Ok(Box::new(ShortfallStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::MM, |order, _| Ok(Box::new(MarketMakerStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::SPREAD, |order, _| Ok(Box::new(SpreadStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::OCO, |order, _| Ok(Box::new(BracketStrategy::from_order(order)?)));
+        registry.register(ExecutionStrategy::BRACKET, |order, _| Ok(Box::new(BracketStrategy::from_order(order)?)));
+        registry
+    }
+}
@@ -0,0 +1,402 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use std::collections::{HashMap, VecDeque};
+
+/// Above this `kappa * T` the schedule is all but a single block at the start; also keeps `sinh` finite.
+const MAX_KAPPA_T: f64 = 30.0;
+
+/// `strategy_params` of an implementation-shortfall order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortfallParams {
+    pub duration: f64,
+    /// Risk aversion: 0 trades evenly in time, higher front-loads harder
+    pub urgency: f64,
+    pub interval: f64,
+    /// Unix seconds; the order creation time when unset
+    pub start_time: Option<f64>,
+    /// Decision price; stamped from the book mid by `OMSEngine::send_order_internal` when unset
+    pub arrival_price: Option<Decimal>,
+    /// Book updates the volatility and spread estimates are taken over
+    pub estimation_window: usize,
+    pub pricing: ChildPricing,
+    /// Session phases the order trades in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl StrategyParams for ShortfallParams {
+    const KEYS: &'static [&'static str] = &[
+        "duration_seconds", "urgency", "interval_seconds", "start_time", "arrival_price",
+        "estimation_window", "pricing", "active_phases",
+    ];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let duration = params.require::<f64>("duration_seconds")?;
+        if duration.is_nan() || duration <= 0.0 {
+            return Err(anyhow!("duration_seconds must be positive"));
+        }
+        let urgency = params.get::<f64>("urgency")?.unwrap_or(1.0);
+        if !urgency.is_finite() || urgency < 0.0 {
+            return Err(anyhow!("urgency must be zero or positive"));
+        }
+        let interval = params.get::<f64>("interval_seconds")?.unwrap_or(10.0);
+        if interval.is_nan() || interval <= 0.0 {
+            return Err(anyhow!("interval_seconds must be positive"));
+        }
+        let arrival_price = params.get::<Decimal>("arrival_price")?;
+        if arrival_price.is_some_and(|p| p <= Decimal::ZERO) {
+            return Err(anyhow!("arrival_price must be positive"));
+        }
+        let estimation_window = params.get::<usize>("estimation_window")?.unwrap_or(100);
+        if estimation_window < 2 {
+            return Err(anyhow!("estimation_window must be at least 2"));
+        }
+        Ok(ShortfallParams {
+            duration,
+            urgency,
+            interval,
+            start_time: params.get("start_time")?,
+            arrival_price,
+            estimation_window,
+            pricing: params.get("pricing")?.unwrap_or(ChildPricing::Aggressive),
+            active_phases: params.list("active_phases")?,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("duration_seconds", self.duration)
+            .set("urgency", self.urgency)
+            .set("interval_seconds", self.interval)
+            .set_opt("start_time", self.start_time)
+            .set_opt("arrival_price", self.arrival_price)
+            .set("estimation_window", self.estimation_window)
+            .set("pricing", self.pricing)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl ShortfallParams {
+    #[new]
+    #[pyo3(signature = (duration_seconds, urgency=None, interval_seconds=None, start_time=None, arrival_price=None, estimation_window=None, pricing=None, active_phases=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        duration_seconds: f64,
+        urgency: Option<f64>,
+        interval_seconds: Option<f64>,
+        start_time: Option<f64>,
+        arrival_price: Option<String>,
+        estimation_window: Option<usize>,
+        pricing: Option<String>,
+        active_phases: Option<Vec<TradingPhase>>,
+    ) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::IS, ParamWriter::default()
+            .set("duration_seconds", duration_seconds)
+            .set_opt("urgency", urgency)
+            .set_opt("interval_seconds", interval_seconds)
+            .set_opt("start_time", start_time)
+            .set_opt("arrival_price", arrival_price)
+            .set_opt("estimation_window", estimation_window)
+            .set_opt("pricing", pricing)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::IS
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("ShortfallParams", &self.to_params())
+    }
+}
+
+/// Volatility and spread of the mid over the last `window` book updates of a symbol.
+#[derive(Debug, Clone)]
+pub struct BookEstimator {
+    pub window: usize,
+    /// (timestamp, mid, relative spread)
+    samples: VecDeque<(f64, f64, f64)>,
+}
+
+impl BookEstimator {
+    pub fn new(window: usize) -> Self {
+        BookEstimator { window: window.max(2), samples: VecDeque::new() }
+    }
+
+    pub fn on_book(&mut self, book: &OrderBook) {
+        let (Some((bid, _)), Some((ask, _))) = (book.get_best_bid(), book.get_best_ask()) else { return };
+        let (Some(bid), Some(ask)) = (bid.to_f64(), ask.to_f64()) else { return };
+        let mid = (bid + ask) / 2.0;
+        if mid <= 0.0 || ask < bid {
+            return;
+        }
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((book.timestamp, mid, (ask - bid) / mid));
+    }
+
+    pub fn last_mid(&self) -> Option<f64> {
+        self.samples.back().map(|s| s.1)
+    }
+
+    /// Volatility of the mid's log returns per square-root second; `None` until the books span some time.
+    pub fn volatility(&self) -> Option<f64> {
+        let (mut variance, mut elapsed) = (0.0, 0.0);
+        for (a, b) in self.samples.iter().zip(self.samples.iter().skip(1)) {
+            let dt = b.0 - a.0;
+            if dt > 0.0 {
+                variance += (b.1 / a.1).ln().powi(2);
+                elapsed += dt;
+            }
+        }
+        (elapsed > 0.0).then(|| (variance / elapsed).sqrt())
+    }
+
+    /// Mean bid-ask spread relative to the mid.
+    pub fn spread(&self) -> Option<f64> {
+        (!self.samples.is_empty()).then(|| self.samples.iter().map(|s| s.2).sum::<f64>() / self.samples.len() as f64)
+    }
+}
+
+/// Arrival-price (implementation shortfall) execution of a parent order. The decision price is the
+/// book mid when the order was sent; the strategy trades front-loaded over `duration` seconds along the
+/// Almgren-Chriss trajectory, trading the risk of the price drifting from arrival against the cost of
+/// crossing the spread. Every `interval` seconds a child is sent for what the trajectory calls for by the
+/// end of the interval; a child still open then is canceled and the rest rolls into the next one.
+/// Outside `active_phases` the working child is pulled and the schedule waits for the session to resume.
+pub struct ShortfallStrategy {
+    pub start_time: f64,
+    pub duration: f64,
+    pub urgency: f64,
+    pub interval: f64,
+    /// Decision price; the first book's mid when the order was sent without a book
+    pub arrival_price: Option<Decimal>,
+    pub estimator: BookEstimator,
+    pub children: ChildOrders,
+    pub active_phases: Vec<TradingPhase>,
+    pub finished: bool,
+
+    next_child_time: f64,
+}
+
+impl ShortfallStrategy {
+    pub fn new(parent: &Order, start_time: f64, duration: f64, urgency: f64, interval: f64, pricing: ChildPricing) -> Self {
+        ShortfallStrategy {
+            start_time,
+            duration,
+            urgency,
+            interval,
+            arrival_price: None,
+            estimator: BookEstimator::new(100),
+            children: ChildOrders::new(parent, pricing),
+            active_phases: session::CONTINUOUS_PHASES.to_vec(),
+            finished: false,
+            next_child_time: start_time,
+        }
+    }
+
+    /// Build from `strategy_params` (see `ShortfallParams`): `duration_seconds` (required), `urgency`
+    /// (default 1), `interval_seconds` (default 10), `start_time` (default the order creation time),
+    /// `arrival_price` (stamped by the engine), `estimation_window` (default 100 books), `pricing`
+    /// (default `AGGRESSIVE`) and `active_phases` (default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = ShortfallParams::from_params(&order.strategy, &order.strategy_params)?;
+        if order.order_type == OrderType::LIMIT && order.price.is_none() {
+            return Err(anyhow!("LIMIT IS requires a price"));
+        }
+        let start_time = params.start_time.unwrap_or(order.created_at);
+        let mut strat = Self::new(order, start_time, params.duration, params.urgency, params.interval, params.pricing);
+        strat.arrival_price = params.arrival_price;
+        strat.estimator = BookEstimator::new(params.estimation_window);
+        if let Some(phases) = params.active_phases {
+            strat.active_phases = phases;
+        }
+        Ok(strat)
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.children.parent_order_id
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.children.filled_quantity()
+    }
+
+    /// `kappa * T` of the trajectory: `urgency` times the price risk over the window (volatility
+    /// times the square root of the duration) per half spread. Without estimates yet, `urgency` alone.
+    pub fn kappa_t(&self) -> f64 {
+        let ratio = match (self.estimator.volatility(), self.estimator.spread()) {
+            (Some(vol), Some(spread)) if spread > 0.0 => vol * self.duration.sqrt() / (spread / 2.0),
+            _ => 1.0,
+        };
+        (self.urgency * ratio).min(MAX_KAPPA_T)
+    }
+
+    /// Share of the parent the trajectory has done by `now`: `1 - sinh(kT (1 - tau)) / sinh(kT)`,
+    /// linear in time when `kT` is 0.
+    pub fn target_fraction(&self, now: f64) -> f64 {
+        let tau = ((now - self.start_time) / self.duration).clamp(0.0, 1.0);
+        let kt = self.kappa_t();
+        if kt < 1e-6 {
+            return tau;
+        }
+        1.0 - (kt * (1.0 - tau)).sinh() / kt.sinh()
+    }
+
+    pub fn target_quantity(&self, now: f64) -> i64 {
+        let quantity = self.children.quantity;
+        ((quantity as f64 * self.target_fraction(now)).ceil() as i64).min(quantity)
+    }
+
+    /// Signed price difference that costs us: paid above arrival on a buy, sold below it on a sell.
+    fn cost(&self, price: Decimal, arrival: Decimal) -> Decimal {
+        match self.children.side {
+            OrderSide::BUY => price - arrival,
+            OrderSide::SELL => arrival - price,
+        }
+    }
+
+    /// Execution shortfall of the fills against the arrival price, in basis points; positive is a cost.
+    pub fn shortfall_bps(&self) -> Option<f64> {
+        let (average, arrival) = (self.children.average_fill_price()?, self.arrival_price?);
+        (self.cost(average, arrival) / arrival * Decimal::from(10000)).to_f64()
+    }
+
+    /// Execution shortfall in currency: the cost per share times the filled quantity.
+    pub fn shortfall_value(&self) -> Option<Decimal> {
+        let (average, arrival) = (self.children.average_fill_price()?, self.arrival_price?);
+        Some(self.cost(average, arrival) * Decimal::from(self.children.filled_quantity()))
+    }
+
+    /// Cost of the unfilled rest at the last mid against the arrival price.
+    pub fn opportunity_cost(&self) -> Option<Decimal> {
+        let (last, arrival) = (Decimal::from_f64(self.estimator.last_mid()?)?, self.arrival_price?);
+        Some(self.cost(last, arrival) * Decimal::from(self.children.remaining_quantity()))
+    }
+
+    /// Timer step at `now` (unix seconds).
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        if self.finished || now < self.start_time {
+            return StrategyAction::None;
+        }
+        if self.children.is_working() {
+            return self.children.expire(now);
+        }
+        if now < self.next_child_time {
+            return StrategyAction::None;
+        }
+
+        // Front-loaded: trade now what the trajectory wants done by the end of the interval
+        let need = self.target_quantity(now + self.interval) - self.children.filled_quantity();
+        if need <= 0 || !self.children.can_price() {
+            return StrategyAction::None;
+        }
+        self.next_child_time = now + self.interval;
+        self.children.place(need, now + self.interval)
+    }
+}
+
+impl Strategy for ShortfallStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        if book.symbol == self.children.symbol {
+            self.estimator.on_book(book);
+            if self.arrival_price.is_none() {
+                self.arrival_price = book.get_mid_price();
+            }
+        }
+        self.children.on_book(book);
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(Local::now().timestamp_millis() as f64 / 1000.0))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.children.cancel_working());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        match self.children.on_child_update(order) {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected => {
+                log::warn!("IS {}: child {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            ChildUpdate::Updated => {}
+        }
+        if self.children.remaining_quantity() == 0 {
+            self.finished = true;
+        }
+        Ok(self.children.resume_pull())
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        if self.finished || self.active_phases.contains(&to) {
+            return Ok(StrategyAction::None);
+        }
+        Ok(self.children.pull_working())
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "IS",
+            "parent_order_id": self.parent_order_id(),
+            "quantity": self.children.quantity,
+            "filled_quantity": self.children.filled_quantity(),
+            "average_price": self.children.average_fill_price().map(|p| p.to_string()),
+            "arrival_price": self.arrival_price.map(|p| p.to_string()),
+            "shortfall_bps": self.shortfall_bps(),
+            "shortfall_value": self.shortfall_value().map(|v| v.to_string()),
+            "opportunity_cost": self.opportunity_cost().map(|v| v.to_string()),
+            "urgency": self.urgency,
+            "volatility": self.estimator.volatility(),
+            "spread_bps": self.estimator.spread().map(|s| s * 10000.0)
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        self.children.restore_child(child, child.created_at + self.interval);
+        self.next_child_time = self.next_child_time.max(child.created_at + self.interval);
+        self.finished = self.children.remaining_quantity() == 0;
+    }
+}
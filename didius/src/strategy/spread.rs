@@ -0,0 +1,482 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the two legs' prices are combined into the spread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadType {
+    /// Passive leg price minus hedge leg price
+    Difference,
+    /// Passive leg price over hedge leg price
+    Ratio,
+}
+
+impl FromStr for SpreadType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "DIFFERENCE" => Ok(SpreadType::Difference),
+            "RATIO" => Ok(SpreadType::Ratio),
+            other => Err(anyhow!("Unknown spread_type: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for SpreadType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SpreadType::Difference => "DIFFERENCE",
+            SpreadType::Ratio => "RATIO",
+        })
+    }
+}
+
+/// `strategy_params` of a spread order. The order itself is the passive leg.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadParams {
+    pub hedge_symbol: String,
+    /// Side of the hedge leg; the opposite of the order's when unset
+    pub hedge_side: Option<OrderSide>,
+    /// Venue of the hedge leg (e.g. the night session gateway); the order's when unset
+    pub hedge_venue: Option<String>,
+    /// Hedge quantity per unit of the passive leg
+    pub hedge_ratio: f64,
+    pub spread_type: SpreadType,
+    /// The passive leg is worked while the spread is at or below this (buy) or at or above it (sell)
+    pub entry_spread: Decimal,
+    /// A hedge child not filled by then has failed, and the unhedged passive fills are unwound
+    pub hedge_timeout: f64,
+    /// Session phases the passive leg is worked in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl StrategyParams for SpreadParams {
+    const KEYS: &'static [&'static str] = &[
+        "hedge_symbol", "hedge_side", "hedge_venue", "hedge_ratio", "spread_type", "entry_spread",
+        "hedge_timeout_seconds", "active_phases",
+    ];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let hedge_ratio = params.get::<f64>("hedge_ratio")?.unwrap_or(1.0);
+        if !hedge_ratio.is_finite() || hedge_ratio <= 0.0 {
+            return Err(anyhow!("hedge_ratio must be positive"));
+        }
+        let hedge_timeout = params.get::<f64>("hedge_timeout_seconds")?.unwrap_or(2.0);
+        if hedge_timeout.is_nan() || hedge_timeout <= 0.0 {
+            return Err(anyhow!("hedge_timeout_seconds must be positive"));
+        }
+        let spread_type = params.get("spread_type")?.unwrap_or(SpreadType::Difference);
+        let entry_spread = params.require::<Decimal>("entry_spread")?;
+        if spread_type == SpreadType::Ratio && entry_spread <= Decimal::ZERO {
+            return Err(anyhow!("entry_spread must be positive for a RATIO spread"));
+        }
+        Ok(SpreadParams {
+            hedge_symbol: params.require("hedge_symbol")?,
+            hedge_side: params.get("hedge_side")?,
+            hedge_venue: params.get("hedge_venue")?,
+            hedge_ratio,
+            spread_type,
+            entry_spread,
+            hedge_timeout,
+            active_phases: params.list("active_phases")?,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("hedge_symbol", &self.hedge_symbol)
+            .set_opt("hedge_side", self.hedge_side.as_ref())
+            .set_opt("hedge_venue", self.hedge_venue.as_ref())
+            .set("hedge_ratio", self.hedge_ratio)
+            .set("spread_type", self.spread_type)
+            .set("entry_spread", self.entry_spread)
+            .set("hedge_timeout_seconds", self.hedge_timeout)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl SpreadParams {
+    #[new]
+    #[pyo3(signature = (hedge_symbol, entry_spread, hedge_side=None, hedge_venue=None, hedge_ratio=None, spread_type=None, hedge_timeout_seconds=None, active_phases=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        hedge_symbol: String,
+        entry_spread: String,
+        hedge_side: Option<OrderSide>,
+        hedge_venue: Option<String>,
+        hedge_ratio: Option<f64>,
+        spread_type: Option<String>,
+        hedge_timeout_seconds: Option<f64>,
+        active_phases: Option<Vec<TradingPhase>>,
+    ) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::SPREAD, ParamWriter::default()
+            .set("hedge_symbol", hedge_symbol)
+            .set("entry_spread", entry_spread)
+            .set_opt("hedge_side", hedge_side)
+            .set_opt("hedge_venue", hedge_venue)
+            .set_opt("hedge_ratio", hedge_ratio)
+            .set_opt("spread_type", spread_type)
+            .set_opt("hedge_timeout_seconds", hedge_timeout_seconds)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::SPREAD
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("SpreadParams", &self.to_params())
+    }
+}
+
+/// Two-leg spread execution. The parent order is the passive leg: while the spread between the price
+/// it would join and the price the hedge leg can be taken at meets `entry_spread`, a child rests at the
+/// passive leg's touch, and it is pulled when the spread moves away. Each passive fill is hedged at
+/// once with an aggressive child on the hedge leg, `hedge_ratio` per unit. A hedge child that is
+/// rejected, or not filled within `hedge_timeout` seconds, leaves leg risk: the strategy stops working
+/// the passive leg and unwinds the unhedged passive fills with aggressive children.
+///
+/// Passive children are named `"{parent}-{n}"`, hedges `"{parent}-H-{n}"` and unwinds `"{parent}-U-{n}"`.
+/// Only the passive fills roll up into the parent.
+pub struct SpreadStrategy {
+    pub passive: ChildOrders,
+    pub hedge: ChildOrders,
+    pub unwind: ChildOrders,
+    pub hedge_ratio: f64,
+    pub spread_type: SpreadType,
+    pub entry_spread: Decimal,
+    pub hedge_timeout: f64,
+    pub active_phases: Vec<TradingPhase>,
+    /// Hedge leg failed; unwinding what it left unhedged
+    pub unwinding: bool,
+    pub finished: bool,
+
+    /// Price of the working passive child
+    passive_price: Option<Decimal>,
+    /// Quantity sent on the hedge leg so far
+    hedge_sent: i64,
+    /// Parent canceled or passive leg refused: no new passive children, hedges still go out
+    stopping: bool,
+    in_session: bool,
+    started: bool,
+}
+
+impl SpreadStrategy {
+    pub fn new(parent: &Order, params: &SpreadParams) -> Self {
+        let parent_id = parent.order_id.clone().unwrap_or_default();
+        let mut hedge_leg = parent.clone();
+        hedge_leg.symbol = params.hedge_symbol.clone();
+        hedge_leg.side = params.hedge_side.clone().unwrap_or_else(|| parent.side.opposite());
+        hedge_leg.venue = params.hedge_venue.clone().unwrap_or_else(|| parent.venue.clone());
+        hedge_leg.quantity = (parent.quantity as f64 * params.hedge_ratio).round() as i64;
+        hedge_leg.order_type = OrderType::MARKET;
+        hedge_leg.price = None;
+        let mut hedge = ChildOrders::new(&hedge_leg, ChildPricing::Aggressive);
+        hedge.id_prefix = format!("{}-H", parent_id);
+
+        let mut unwind_leg = parent.clone();
+        unwind_leg.side = parent.side.opposite();
+        unwind_leg.order_type = OrderType::MARKET;
+        unwind_leg.price = None;
+        let mut unwind = ChildOrders::new(&unwind_leg, ChildPricing::Aggressive);
+        unwind.id_prefix = format!("{}-U", parent_id);
+
+        SpreadStrategy {
+            passive: ChildOrders::new(parent, ChildPricing::Passive),
+            hedge,
+            unwind,
+            hedge_ratio: params.hedge_ratio,
+            spread_type: params.spread_type,
+            entry_spread: params.entry_spread,
+            hedge_timeout: params.hedge_timeout,
+            active_phases: params.active_phases.clone().unwrap_or_else(|| session::CONTINUOUS_PHASES.to_vec()),
+            unwinding: false,
+            finished: false,
+            passive_price: None,
+            hedge_sent: 0,
+            stopping: false,
+            in_session: true,
+            started: false,
+        }
+    }
+
+    /// Build from `strategy_params` (see `SpreadParams`): `hedge_symbol` and `entry_spread` (required),
+    /// `hedge_side` (default the other side), `hedge_venue` (default the order's), `hedge_ratio`
+    /// (default 1), `spread_type` (default `DIFFERENCE`), `hedge_timeout_seconds` (default 2) and
+    /// `active_phases` (default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = SpreadParams::from_params(&order.strategy, &order.strategy_params)?;
+        if params.hedge_symbol == order.symbol {
+            return Err(anyhow!("SPREAD hedge_symbol must differ from the order symbol"));
+        }
+        if order.order_type == OrderType::LIMIT && order.price.is_none() {
+            return Err(anyhow!("LIMIT SPREAD requires a price"));
+        }
+        Ok(Self::new(order, &params))
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.passive.parent_order_id
+    }
+
+    /// Price a passive child joins at: the own touch of the passive leg, within the parent limit.
+    pub fn passive_price(&self) -> Option<Decimal> {
+        let book = self.passive.book()?;
+        let (touch, _) = match self.passive.side {
+            OrderSide::BUY => book.get_best_bid(),
+            OrderSide::SELL => book.get_best_ask(),
+        }?;
+        Some(match (&self.passive.side, self.passive.limit_price) {
+            (OrderSide::BUY, Some(limit)) => touch.min(limit),
+            (OrderSide::SELL, Some(limit)) => touch.max(limit),
+            (_, None) => touch,
+        })
+    }
+
+    /// Price the hedge leg can be taken at: its opposite touch.
+    pub fn hedge_price(&self) -> Option<Decimal> {
+        let book = self.hedge.book()?;
+        let (touch, _) = match self.hedge.side {
+            OrderSide::BUY => book.get_best_ask(),
+            OrderSide::SELL => book.get_best_bid(),
+        }?;
+        Some(touch)
+    }
+
+    fn combine(&self, passive: Decimal, hedge: Decimal) -> Option<Decimal> {
+        match self.spread_type {
+            SpreadType::Difference => Some(passive - hedge),
+            SpreadType::Ratio => (hedge > Decimal::ZERO).then(|| passive / hedge),
+        }
+    }
+
+    /// Spread the legs can be traded at now; `None` until both books are in.
+    pub fn spread(&self) -> Option<Decimal> {
+        self.combine(self.passive_price()?, self.hedge_price()?)
+    }
+
+    /// Whether `spread` is good enough to work the passive leg: cheap enough to buy, rich enough to sell.
+    pub fn meets_entry(&self, spread: Decimal) -> bool {
+        match self.passive.side {
+            OrderSide::BUY => spread <= self.entry_spread,
+            OrderSide::SELL => spread >= self.entry_spread,
+        }
+    }
+
+    /// Hedge quantity the passive fills call for.
+    pub fn hedge_target(&self) -> i64 {
+        (self.passive.filled_quantity() as f64 * self.hedge_ratio).round() as i64
+    }
+
+    /// Passive fills neither hedged nor unwound.
+    pub fn unhedged_quantity(&self) -> i64 {
+        let hedged = ((self.hedge.filled_quantity() as f64 / self.hedge_ratio) + 1e-9).floor() as i64;
+        (self.passive.filled_quantity() - hedged - self.unwind.filled_quantity()).max(0)
+    }
+
+    /// Spread the fills were done at, from the average prices of both legs.
+    pub fn realized_spread(&self) -> Option<Decimal> {
+        self.combine(self.passive.average_fill_price()?, self.hedge.average_fill_price()?)
+    }
+
+    fn fail_hedge(&mut self, reason: &str) {
+        if !self.unwinding {
+            log::warn!("SPREAD {}: hedge leg failed ({}), unwinding {} unhedged", self.parent_order_id(), reason, self.unhedged_quantity());
+            self.unwinding = true;
+        }
+    }
+
+    /// Move the legs at `now` (unix seconds): hedge what the passive fills call for, work or pull the
+    /// passive child as the spread allows, or unwind after a failed hedge.
+    pub fn step(&mut self, now: f64) -> StrategyAction {
+        if self.finished {
+            return StrategyAction::None;
+        }
+        if self.unwinding {
+            return self.step_unwind(now);
+        }
+
+        let mut actions = Vec::new();
+        if self.hedge.is_working() {
+            actions.push(self.hedge.expire(now));
+        } else {
+            let need = self.hedge_target() - self.hedge.filled_quantity();
+            if need > 0 {
+                self.hedge_sent += need;
+                actions.push(self.hedge.place(need, now + self.hedge_timeout));
+            }
+        }
+
+        let spread = self.spread();
+        let work = self.started && !self.stopping && self.in_session && spread.is_some_and(|s| self.meets_entry(s));
+        let price = self.passive_price();
+        if self.passive.is_working() {
+            if !work || price != self.passive_price {
+                actions.push(self.passive.pull_working());
+            }
+        } else if work && !self.hedge.is_working() && self.passive.remaining_quantity() > 0 {
+            self.passive_price = price;
+            actions.push(self.passive.place_at(self.passive.remaining_quantity(), price, f64::INFINITY));
+        }
+
+        let hedged = !self.hedge.is_working() && self.hedge.filled_quantity() >= self.hedge_target();
+        if hedged && !self.passive.is_working() && (self.stopping || self.passive.remaining_quantity() == 0) {
+            self.finished = true;
+        }
+        StrategyAction::each(actions)
+    }
+
+    fn step_unwind(&mut self, now: f64) -> StrategyAction {
+        let mut actions = vec![self.passive.pull_working(), self.hedge.pull_working()];
+        if self.unwind.is_working() {
+            actions.push(self.unwind.expire(now));
+        } else if !self.passive.is_working() && !self.hedge.is_working() {
+            let unhedged = self.unhedged_quantity();
+            if unhedged == 0 {
+                self.finished = true;
+            } else {
+                actions.push(self.unwind.place(unhedged, now + self.hedge_timeout));
+            }
+        }
+        StrategyAction::each(actions)
+    }
+
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        self.started = true;
+        self.step(now)
+    }
+
+    fn now() -> f64 {
+        Local::now().timestamp_millis() as f64 / 1000.0
+    }
+}
+
+impl Strategy for SpreadStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        if book.symbol != self.passive.symbol && book.symbol != self.hedge.symbol {
+            return Ok(StrategyAction::None);
+        }
+        self.passive.on_book(book);
+        self.hedge.on_book(book);
+        self.unwind.on_book(book);
+        Ok(self.step(Self::now()))
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(Self::now()))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            if order.state == OrderState::CANCELED && !self.stopping {
+                self.stopping = true;
+                return Ok(self.step(Self::now()));
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        if self.passive.on_child_update(order) != ChildUpdate::Unrelated {
+            if order.state == OrderState::REJECTED {
+                log::warn!("SPREAD {}: passive child {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.stopping = true;
+            }
+        } else if self.hedge.on_child_update(order) != ChildUpdate::Unrelated {
+            // A hedge child that ended short of what was sent leaves the passive fills exposed
+            if !self.hedge.is_working() && self.hedge.filled_quantity() < self.hedge_sent {
+                self.fail_hedge(order.error_message.as_deref().unwrap_or("not filled in time"));
+            }
+        } else {
+            match self.unwind.on_child_update(order) {
+                ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+                ChildUpdate::Rejected => {
+                    log::error!("SPREAD {}: unwind {:?} rejected ({}), {} left unhedged", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""), self.unhedged_quantity());
+                    self.finished = true;
+                }
+                ChildUpdate::Updated => {}
+            }
+        }
+        Ok(self.step(Self::now()))
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    /// Leaving the active phases pulls the passive child; hedges and unwinds still go out.
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        self.in_session = self.active_phases.contains(&to);
+        Ok(self.step(Self::now()))
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        vec![self.hedge.symbol.clone()]
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "SPREAD",
+            "parent_order_id": self.parent_order_id(),
+            "symbol": self.passive.symbol,
+            "hedge_symbol": self.hedge.symbol,
+            "quantity": self.passive.quantity,
+            "filled_quantity": self.passive.filled_quantity(),
+            "average_price": self.passive.average_fill_price().map(|p| p.to_string()),
+            "hedge_filled_quantity": self.hedge.filled_quantity(),
+            "hedge_average_price": self.hedge.average_fill_price().map(|p| p.to_string()),
+            "entry_spread": self.entry_spread.to_string(),
+            "realized_spread": self.realized_spread().map(|s| s.normalize().to_string()),
+            "hedge_failed": self.unwinding,
+            "unwound_quantity": self.unwind.filled_quantity(),
+            "unwind_average_price": self.unwind.average_fill_price().map(|p| p.to_string()),
+            "unhedged_quantity": self.unhedged_quantity()
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        let parent_id = self.parent_order_id().to_string();
+        let id = child.order_id.as_deref().unwrap_or("");
+        if id.starts_with(&format!("{}-H-", parent_id)) {
+            self.hedge.restore_child(child, child.created_at + self.hedge_timeout);
+            self.hedge_sent += child.quantity;
+        } else if id.starts_with(&format!("{}-U-", parent_id)) {
+            self.unwind.restore_child(child, child.created_at + self.hedge_timeout);
+            self.unwinding = true;
+        } else {
+            self.passive.restore_child(child, f64::INFINITY);
+            self.passive_price = child.price;
+        }
+    }
+}
@@ -1,9 +1,157 @@
 use crate::oms::order::{Order, OrderSide, OrderType, OrderState, ExecutionStrategy};
 use crate::oms::order_book::OrderBook;
 use crate::strategy::base::{Strategy, StrategyAction};
-use anyhow::Result;
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
 use rust_decimal::prelude::*;
 use chrono::Local;
+use std::collections::HashMap;
+
+/// Distance a trailing stop keeps from the best price seen since it was armed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trail {
+    Amount(Decimal),
+    /// Percent of the reference price (1.5 = 1.5%)
+    Percent(Decimal),
+    Ticks { ticks: i64, tick_size: Decimal },
+}
+
+impl Trail {
+    /// Parse `trail_amount`, `trail_percent` or `trail_ticks` (with `tick_size`) from `strategy_params`.
+    /// `None` for a plain stop.
+    pub fn from_params(params: &HashMap<String, String>) -> anyhow::Result<Option<Trail>> {
+        Self::read(&ParamReader::new(&ExecutionStrategy::STOP, params))
+    }
+
+    fn read(params: &ParamReader) -> anyhow::Result<Option<Trail>> {
+        let amount = params.get::<Decimal>("trail_amount")?;
+        let percent = params.get::<Decimal>("trail_percent")?;
+        let ticks = params.get::<i64>("trail_ticks")?;
+
+        let trail = match (amount, percent, ticks) {
+            (None, None, None) => return Ok(None),
+            (Some(a), None, None) => Trail::Amount(a),
+            (None, Some(p), None) => {
+                if p >= Decimal::ONE_HUNDRED {
+                    return Err(anyhow!("trail_percent must be below 100"));
+                }
+                Trail::Percent(p)
+            }
+            (None, None, Some(t)) => {
+                let tick_size = params.get::<Decimal>("tick_size")?.ok_or_else(|| anyhow!("trail_ticks requires tick_size"))?;
+                if tick_size <= Decimal::ZERO {
+                    return Err(anyhow!("tick_size must be positive"));
+                }
+                Trail::Ticks { ticks: t, tick_size }
+            }
+            _ => return Err(anyhow!("Set only one of trail_amount, trail_percent and trail_ticks")),
+        };
+        if trail.distance(Decimal::ONE_HUNDRED) <= Decimal::ZERO {
+            return Err(anyhow!("Trail must be positive"));
+        }
+        Ok(Some(trail))
+    }
+
+    /// Trail distance in price at `reference`.
+    pub fn distance(&self, reference: Decimal) -> Decimal {
+        match self {
+            Trail::Amount(a) => *a,
+            Trail::Percent(p) => reference * p / Decimal::ONE_HUNDRED,
+            Trail::Ticks { ticks, tick_size } => Decimal::from(*ticks) * tick_size,
+        }
+    }
+}
+
+/// `strategy_params` of a STOP order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopParams {
+    /// Fixed trigger; required unless the stop trails
+    pub trigger_price: Option<Decimal>,
+    /// Unix seconds at which the stop fires whatever the price (0: never)
+    pub trigger_timestamp: f64,
+    /// Price the order is moved to when the stop fires; to market when unset
+    pub chained_price: Option<Decimal>,
+    pub trail: Option<Trail>,
+}
+
+impl StrategyParams for StopParams {
+    // `trigger_side` is no longer used (the order side decides) but still accepted
+    const KEYS: &'static [&'static str] = &["trigger_price", "trigger_side", "trigger_timestamp", "chained_price", "trail_amount", "trail_percent", "trail_ticks", "tick_size"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let trail = Trail::read(params)?;
+        let trigger_price = params.get::<Decimal>("trigger_price")?;
+        if trigger_price.is_none() && trail.is_none() {
+            return Err(anyhow!("STOP requires trigger_price or a trail (trail_amount, trail_percent or trail_ticks)"));
+        }
+        if let Some(side) = params.get::<String>("trigger_side")? {
+            if !matches!(side.to_uppercase().as_str(), "BUY" | "SELL") {
+                return Err(anyhow!("Invalid trigger_side: {}", side));
+            }
+        }
+        let trigger_timestamp = params.get::<f64>("trigger_timestamp")?.unwrap_or(0.0);
+        if trigger_timestamp.is_nan() || trigger_timestamp < 0.0 {
+            return Err(anyhow!("trigger_timestamp must not be negative"));
+        }
+        Ok(StopParams { trigger_price, trigger_timestamp, chained_price: params.get("chained_price")?, trail })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        let writer = ParamWriter::default()
+            .set_opt("trigger_price", self.trigger_price)
+            .set_opt("trigger_timestamp", Some(self.trigger_timestamp).filter(|t| *t > 0.0))
+            .set_opt("chained_price", self.chained_price);
+        match &self.trail {
+            Some(Trail::Amount(a)) => writer.set("trail_amount", a),
+            Some(Trail::Percent(p)) => writer.set("trail_percent", p),
+            Some(Trail::Ticks { ticks, tick_size }) => writer.set("trail_ticks", ticks).set("tick_size", tick_size),
+            None => writer,
+        }
+        .finish()
+    }
+}
+
+#[pymethods]
+impl StopParams {
+    /// Prices are strings, as on `Order`. Invalid combinations raise `ValueError`.
+    #[new]
+    #[pyo3(signature = (trigger_price=None, trigger_timestamp=None, chained_price=None, trail_amount=None, trail_percent=None, trail_ticks=None, tick_size=None))]
+    fn py_new(
+        trigger_price: Option<String>,
+        trigger_timestamp: Option<f64>,
+        chained_price: Option<String>,
+        trail_amount: Option<String>,
+        trail_percent: Option<String>,
+        trail_ticks: Option<i64>,
+        tick_size: Option<String>,
+    ) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::STOP, ParamWriter::default()
+            .set_opt("trigger_price", trigger_price)
+            .set_opt("trigger_timestamp", trigger_timestamp)
+            .set_opt("chained_price", chained_price)
+            .set_opt("trail_amount", trail_amount)
+            .set_opt("trail_percent", trail_percent)
+            .set_opt("trail_ticks", trail_ticks)
+            .set_opt("tick_size", tick_size))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::STOP
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("StopParams", &self.to_params())
+    }
+}
 
 pub struct StopStrategy {
     pub original_order_id: String,
@@ -18,6 +166,11 @@ pub struct StopStrategy {
     pub trigger_timestamp: f64,
     
     pub stop_limit_price: Option<Decimal>, // New Order Price (or None for Market)
+
+    /// Trailing stop: `trigger_price` follows the market by this distance, never backing off
+    pub trail: Option<Trail>,
+    /// Whether `trigger_price` is set; a trailing stop without one is armed by the first book
+    pub armed: bool,
     
     pub triggered: bool,
     pub finished: bool,
@@ -42,11 +195,71 @@ impl StopStrategy {
             trigger_price,
             trigger_timestamp,
             stop_limit_price,
+            trail: None,
+            armed: true,
             triggered: false,
             finished: false,
         }
     }
 
+    /// Build from the STOP order's `strategy_params` (see `StopParams`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = StopParams::from_params(&order.strategy, &order.strategy_params)?;
+        let mut strat = Self::new(
+            order.order_id.clone().unwrap_or_default(),
+            order.symbol.clone(),
+            order.side.clone(),
+            order.quantity,
+            order.side.clone(),
+            params.trigger_price.unwrap_or_default(),
+            params.trigger_timestamp,
+            params.chained_price,
+        );
+        if let Some(trail) = params.trail {
+            strat = strat.with_trail(trail);
+            strat.armed = params.trigger_price.is_some();
+        }
+        Ok(strat)
+    }
+
+    /// Pick up a stop recovered from the journal: one that has already fired was re-priced to
+    /// the chained price (or to market) and must not fire again.
+    pub fn resume(&mut self, order: &Order) {
+        if order.order_type == OrderType::MARKET || (self.stop_limit_price.is_some() && order.price == self.stop_limit_price) {
+            self.triggered = true;
+        }
+    }
+
+    pub fn with_trail(mut self, trail: Trail) -> Self {
+        self.trail = Some(trail);
+        self
+    }
+
+    /// Move a trailing trigger with the monitored price (best ask for a SELL stop, best bid for a BUY stop).
+    /// A SELL trigger only rises and a BUY trigger only falls.
+    fn ratchet(&mut self, book: &OrderBook) {
+        let Some(trail) = &self.trail else { return };
+        let (reference, candidate) = match self.original_side {
+            OrderSide::SELL => match book.get_best_ask() {
+                Some((ask, _)) => (ask, ask - trail.distance(ask)),
+                None => return,
+            },
+            OrderSide::BUY => match book.get_best_bid() {
+                Some((bid, _)) => (bid, bid + trail.distance(bid)),
+                None => return,
+            },
+        };
+        let tighter = match self.original_side {
+            OrderSide::SELL => candidate > self.trigger_price,
+            OrderSide::BUY => candidate < self.trigger_price,
+        };
+        if !self.armed || tighter {
+            log::debug!("Trailing stop {}: trigger {} -> {} (reference {})", self.original_order_id, self.trigger_price, candidate, reference);
+            self.trigger_price = candidate;
+            self.armed = true;
+        }
+    }
+
     fn check_trigger(&mut self, book: Option<&OrderBook>) -> bool {
          if self.trigger_timestamp > 0.0 {
              let now = Local::now().timestamp_millis() as f64 / 1000.0;
@@ -55,7 +268,7 @@ impl StopStrategy {
              }
          }
          
-         if let Some(b) = book {
+         if let Some(b) = book.filter(|_| self.armed) {
               match self.original_side {
                  OrderSide::SELL => {
                      // STOP SELL: Trigger if Best Ask <= Trigger Price
@@ -78,10 +291,12 @@ impl StopStrategy {
 
 impl Strategy for StopStrategy {
     fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
-        if self.triggered || self.finished {
+        if self.triggered || self.finished || book.symbol != self.original_symbol {
             return Ok(StrategyAction::None);
         }
         
+        // Ratchet first: the new trigger always trails the price that moved it
+        self.ratchet(book);
         if self.check_trigger(Some(book)) {
             self.triggered = true;
             // Return ModifyPrice action
@@ -91,7 +306,7 @@ impl Strategy for StopStrategy {
         Ok(StrategyAction::None)
     }
     
-    fn on_trade_update(&mut self, _price: f64) -> Result<StrategyAction> {
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
         Ok(StrategyAction::None)
     }
     
@@ -0,0 +1,276 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// `strategy_params` of a TWAP order.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwapParams {
+    pub duration: f64,
+    pub slices: i64,
+    /// Unix seconds; the order creation time when unset
+    pub start_time: Option<f64>,
+    pub pricing: ChildPricing,
+    /// Session phases the TWAP slices in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl StrategyParams for TwapParams {
+    const KEYS: &'static [&'static str] = &["duration_seconds", "slices", "start_time", "pricing", "active_phases"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let duration = params.require::<f64>("duration_seconds")?;
+        if duration.is_nan() || duration <= 0.0 {
+            return Err(anyhow!("duration_seconds must be positive"));
+        }
+        let slices = params.get::<i64>("slices")?.unwrap_or(10);
+        if slices < 1 {
+            return Err(anyhow!("slices must be between 1 and the order quantity"));
+        }
+        Ok(TwapParams {
+            duration,
+            slices,
+            start_time: params.get("start_time")?,
+            pricing: params.get("pricing")?.unwrap_or(ChildPricing::Aggressive),
+            active_phases: params.list("active_phases")?,
+        })
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        ParamWriter::default()
+            .set("duration_seconds", self.duration)
+            .set("slices", self.slices)
+            .set_opt("start_time", self.start_time)
+            .set("pricing", self.pricing)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl TwapParams {
+    #[new]
+    #[pyo3(signature = (duration_seconds, slices=None, start_time=None, pricing=None, active_phases=None))]
+    fn py_new(duration_seconds: f64, slices: Option<i64>, start_time: Option<f64>, pricing: Option<String>, active_phases: Option<Vec<TradingPhase>>) -> PyResult<Self> {
+        params::py_params(ExecutionStrategy::TWAP, ParamWriter::default()
+            .set("duration_seconds", duration_seconds)
+            .set_opt("slices", slices)
+            .set_opt("start_time", start_time)
+            .set_opt("pricing", pricing)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::TWAP
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("TwapParams", &self.to_params())
+    }
+}
+
+/// Time-weighted execution of a parent order. The parent quantity is split into `slices`
+/// equal parts over `duration` seconds from `start_time`; one child order works at a time.
+/// A child still open when its slice ends is canceled and the shortfall is added to the next child.
+/// Outside `active_phases` (e.g. the closing auction) the working child is pulled and slicing pauses.
+/// After the window one last child works the rest for a slice interval, then the TWAP stops.
+pub struct TwapStrategy {
+    pub start_time: f64,
+    pub duration: f64,
+    pub slices: i64,
+    pub children: ChildOrders,
+    pub active_phases: Vec<TradingPhase>,
+    pub finished: bool,
+    /// The final child after the window has been sent
+    pub swept: bool,
+}
+
+impl TwapStrategy {
+    pub fn new(parent: &Order, start_time: f64, duration: f64, slices: i64, pricing: ChildPricing) -> Self {
+        TwapStrategy {
+            start_time,
+            duration,
+            slices: slices.max(1),
+            children: ChildOrders::new(parent, pricing),
+            active_phases: session::CONTINUOUS_PHASES.to_vec(),
+            finished: false,
+            swept: false,
+        }
+    }
+
+    /// Build from `strategy_params` (see `TwapParams`): `duration_seconds` (required), `slices` (default 10),
+    /// `start_time` (unix seconds, default the order creation time), `pricing` (`AGGRESSIVE` or `PASSIVE`)
+    /// and `active_phases` (comma separated `TradingPhase`s, default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = TwapParams::from_params(&order.strategy, &order.strategy_params)?;
+        if params.slices > order.quantity.max(1) {
+            return Err(anyhow!("slices must be between 1 and the order quantity"));
+        }
+        if order.order_type == OrderType::LIMIT && order.price.is_none() {
+            return Err(anyhow!("LIMIT TWAP requires a price"));
+        }
+        let mut strat = Self::new(order, params.start_time.unwrap_or(order.created_at), params.duration, params.slices, params.pricing);
+        if let Some(phases) = params.active_phases {
+            strat.active_phases = phases;
+        }
+        Ok(strat)
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.children.parent_order_id
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.children.filled_quantity()
+    }
+
+    /// 1-based index of the slice running at `now` (0 before the start).
+    pub fn current_slice(&self, now: f64) -> i64 {
+        if now < self.start_time {
+            return 0;
+        }
+        let interval = self.duration / self.slices as f64;
+        (((now - self.start_time) / interval) as i64 + 1).min(self.slices)
+    }
+
+    fn slice_end(&self, slice: i64) -> f64 {
+        self.start_time + self.duration * slice as f64 / self.slices as f64
+    }
+
+    /// Cumulative quantity that should be done by `now`.
+    pub fn target_quantity(&self, now: f64) -> i64 {
+        let quantity = self.children.quantity;
+        if now >= self.start_time + self.duration {
+            return quantity;
+        }
+        let k = self.current_slice(now);
+        (quantity * k + self.slices - 1) / self.slices
+    }
+
+    /// Timer step at `now` (unix seconds).
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        if self.finished {
+            return StrategyAction::None;
+        }
+        // Catch up: the slice is over, pull the rest into the next child
+        if self.children.is_working() {
+            return self.children.expire(now);
+        }
+
+        // The window is over and the final child is done: stop rather than chase the rest
+        let end = self.start_time + self.duration;
+        if now >= end && self.swept {
+            self.finished = true;
+            return StrategyAction::None;
+        }
+
+        let need = self.target_quantity(now) - self.children.filled_quantity();
+        if need <= 0 || !self.children.can_price() {
+            return StrategyAction::None;
+        }
+        let deadline = if now >= end {
+            // Past the window a slice end is already behind us; give the final child a slice interval
+            self.swept = true;
+            now + self.duration / self.slices as f64
+        } else {
+            self.slice_end(self.current_slice(now))
+        };
+        self.children.place(need, deadline)
+    }
+}
+
+impl Strategy for TwapStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.children.on_book(book);
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(Local::now().timestamp_millis() as f64 / 1000.0))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            // Parent canceled: stop slicing and pull the working child
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.children.cancel_working());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        match self.children.on_child_update(order) {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected => {
+                log::warn!("TWAP {}: child {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            ChildUpdate::Updated => {}
+        }
+        if self.children.remaining_quantity() == 0 {
+            self.finished = true;
+        }
+        Ok(self.children.resume_pull())
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        // Keep the working child out of the auction; slicing catches up when the session resumes
+        if self.finished || self.active_phases.contains(&to) {
+            return Ok(StrategyAction::None);
+        }
+        Ok(self.children.pull_working())
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "TWAP",
+            "parent_order_id": self.parent_order_id(),
+            "quantity": self.children.quantity,
+            "filled_quantity": self.children.filled_quantity(),
+            "average_price": self.children.average_fill_price().map(|p| p.to_string())
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        let end = self.start_time + self.duration;
+        let deadline = if child.created_at >= end {
+            self.swept = true;
+            child.created_at + self.duration / self.slices as f64
+        } else {
+            self.slice_end(self.current_slice(child.created_at))
+        };
+        self.children.restore_child(child, deadline);
+        self.finished = self.children.remaining_quantity() == 0;
+    }
+}
@@ -0,0 +1,417 @@
+use crate::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use crate::oms::order_book::OrderBook;
+use crate::oms::session::{self, TradingPhase};
+use crate::strategy::base::{Strategy, StrategyAction};
+use crate::strategy::child::{ChildOrders, ChildPricing, ChildUpdate};
+use crate::strategy::params::{self, ParamReader, ParamWriter, StrategyParams};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use std::collections::HashMap;
+
+/// Historical share of the day's volume per time bucket of the KRX regular session.
+/// Buckets are equal in length and cover `session_start..session_end` (seconds after midnight, KST).
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeProfile {
+    pub session_start: f64,
+    pub session_end: f64,
+    /// Normalized to sum to 1
+    pub weights: Vec<f64>,
+}
+
+impl VolumeProfile {
+    /// Weights over the regular session (09:00-15:30 KST), normalized.
+    pub fn from_weights(weights: Vec<f64>) -> Result<Self> {
+        if weights.is_empty() || weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(anyhow!("Volume profile weights must be non-negative numbers"));
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(anyhow!("Volume profile has no volume"));
+        }
+        Ok(VolumeProfile {
+            session_start: 9.0 * 3600.0,
+            session_end: 15.5 * 3600.0,
+            weights: weights.iter().map(|w| w / total).collect(),
+        })
+    }
+
+    /// Typical U-shaped KOSPI profile in 30 minute buckets; the last bucket holds the closing auction.
+    pub fn krx_equity() -> Self {
+        Self::from_weights(vec![12.0, 8.5, 7.0, 6.0, 5.5, 5.0, 4.8, 4.8, 5.0, 5.5, 6.5, 8.4, 21.0])
+            .expect("static profile")
+    }
+
+    /// Share of the day's volume traded by `timestamp` (unix seconds).
+    pub fn cumulative(&self, timestamp: f64) -> f64 {
+        let t = (timestamp + session::KST_OFFSET_SECONDS as f64).rem_euclid(86400.0);
+        if t <= self.session_start {
+            return 0.0;
+        }
+        if t >= self.session_end {
+            return 1.0;
+        }
+        let bucket = (self.session_end - self.session_start) / self.weights.len() as f64;
+        let pos = (t - self.session_start) / bucket;
+        let full = pos as usize;
+        let done: f64 = self.weights[..full].iter().sum();
+        done + self.weights.get(full).copied().unwrap_or(0.0) * (pos - full as f64)
+    }
+}
+
+/// `strategy_params` of a VWAP order. The window ends at `end_time`, or `duration` after the start.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VwapParams {
+    /// Unix seconds; the order creation time when unset
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub duration: Option<f64>,
+    pub interval: f64,
+    /// Raw weights over 09:00-15:30 KST; `VolumeProfile::krx_equity` when unset
+    pub volume_profile: Option<Vec<f64>>,
+    pub expected_volume: Option<f64>,
+    pub pricing: ChildPricing,
+    /// Session phases the VWAP trades in; continuous trading when unset
+    pub active_phases: Option<Vec<TradingPhase>>,
+}
+
+impl VwapParams {
+    pub fn profile(&self) -> Result<VolumeProfile> {
+        match &self.volume_profile {
+            Some(weights) => VolumeProfile::from_weights(weights.clone()),
+            None => Ok(VolumeProfile::krx_equity()),
+        }
+    }
+}
+
+impl StrategyParams for VwapParams {
+    const KEYS: &'static [&'static str] = &["start_time", "end_time", "duration_seconds", "interval_seconds", "volume_profile", "expected_volume", "pricing", "active_phases"];
+
+    fn read(params: &ParamReader) -> Result<Self> {
+        let vwap = VwapParams {
+            start_time: params.get("start_time")?,
+            end_time: params.get("end_time")?,
+            duration: params.get("duration_seconds")?,
+            interval: params.get("interval_seconds")?.unwrap_or(30.0),
+            volume_profile: params.list("volume_profile")?,
+            expected_volume: params.get("expected_volume")?,
+            pricing: params.get("pricing")?.unwrap_or(ChildPricing::Aggressive),
+            active_phases: params.list("active_phases")?,
+        };
+        if vwap.end_time.is_none() && vwap.duration.is_none() {
+            return Err(anyhow!("VWAP requires end_time or duration_seconds"));
+        }
+        if vwap.interval.is_nan() || vwap.interval <= 0.0 {
+            return Err(anyhow!("interval_seconds must be positive"));
+        }
+        if vwap.expected_volume.is_some_and(|v| v.is_nan() || v <= 0.0) {
+            return Err(anyhow!("expected_volume must be positive"));
+        }
+        vwap.profile()?;
+        Ok(vwap)
+    }
+
+    fn to_params(&self) -> HashMap<String, String> {
+        let profile = self.volume_profile.as_ref()
+            .map(|weights| weights.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(","));
+        ParamWriter::default()
+            .set_opt("start_time", self.start_time)
+            .set_opt("end_time", self.end_time)
+            .set_opt("duration_seconds", self.duration)
+            .set("interval_seconds", self.interval)
+            .set_opt("volume_profile", profile)
+            .set_opt("expected_volume", self.expected_volume)
+            .set("pricing", self.pricing)
+            .set_opt("active_phases", self.active_phases.as_deref().map(session::join_phases))
+            .finish()
+    }
+}
+
+#[pymethods]
+impl VwapParams {
+    #[new]
+    #[pyo3(signature = (end_time=None, duration_seconds=None, start_time=None, interval_seconds=None, volume_profile=None, expected_volume=None, pricing=None, active_phases=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        end_time: Option<f64>,
+        duration_seconds: Option<f64>,
+        start_time: Option<f64>,
+        interval_seconds: Option<f64>,
+        volume_profile: Option<Vec<f64>>,
+        expected_volume: Option<f64>,
+        pricing: Option<String>,
+        active_phases: Option<Vec<TradingPhase>>,
+    ) -> PyResult<Self> {
+        let profile = volume_profile.map(|weights| weights.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(","));
+        params::py_params(ExecutionStrategy::VWAP, ParamWriter::default()
+            .set_opt("end_time", end_time)
+            .set_opt("duration_seconds", duration_seconds)
+            .set_opt("start_time", start_time)
+            .set_opt("interval_seconds", interval_seconds)
+            .set_opt("volume_profile", profile)
+            .set_opt("expected_volume", expected_volume)
+            .set_opt("pricing", pricing)
+            .set_opt("active_phases", active_phases.as_deref().map(session::join_phases)))
+    }
+
+    /// `ExecutionStrategy` the params go with
+    #[getter]
+    fn strategy(&self) -> ExecutionStrategy {
+        ExecutionStrategy::VWAP
+    }
+
+    /// `strategy_params` for `Order`
+    fn to_dict(&self) -> HashMap<String, String> {
+        self.to_params()
+    }
+
+    fn __repr__(&self) -> String {
+        params::repr("VwapParams", &self.to_params())
+    }
+}
+
+/// Volume-weighted execution of a parent order against an intraday volume profile.
+/// Every `interval` seconds a child is sent for the quantity the schedule is behind; a child
+/// still open after its interval is canceled and the rest rolls into the next one.
+/// With `expected_volume` the schedule follows the market volume actually traded.
+/// Outside `active_phases` the working child is pulled and the schedule waits for the session to resume.
+/// After the window one last child works the rest for an interval, then the VWAP stops.
+pub struct VwapStrategy {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub interval: f64,
+    pub profile: VolumeProfile,
+    /// Market volume expected over the window; enables adaptation to realized volume
+    pub expected_volume: Option<f64>,
+    pub children: ChildOrders,
+    pub active_phases: Vec<TradingPhase>,
+    pub finished: bool,
+    /// The final child after the window has been sent
+    pub swept: bool,
+
+    market_volume: i64,
+    market_value: Decimal,
+    next_child_time: f64,
+}
+
+impl VwapStrategy {
+    pub fn new(parent: &Order, start_time: f64, end_time: f64, interval: f64, profile: VolumeProfile, pricing: ChildPricing) -> Self {
+        VwapStrategy {
+            start_time,
+            end_time,
+            interval,
+            profile,
+            expected_volume: None,
+            children: ChildOrders::new(parent, pricing),
+            active_phases: session::CONTINUOUS_PHASES.to_vec(),
+            finished: false,
+            swept: false,
+            market_volume: 0,
+            market_value: Decimal::ZERO,
+            next_child_time: start_time,
+        }
+    }
+
+    /// Build from `strategy_params` (see `VwapParams`): `end_time` (unix seconds) or `duration_seconds` (one is required),
+    /// `start_time` (default the order creation time), `volume_profile` (comma separated weights over
+    /// 09:00-15:30 KST, default `VolumeProfile::krx_equity`), `expected_volume`, `interval_seconds`
+    /// (default 30), `pricing` (`AGGRESSIVE` or `PASSIVE`) and `active_phases` (default `CONTINUOUS,NIGHT_SESSION`).
+    pub fn from_order(order: &Order) -> Result<Self> {
+        let params = VwapParams::from_params(&order.strategy, &order.strategy_params)?;
+        let start_time = params.start_time.unwrap_or(order.created_at);
+        let end_time = match (params.end_time, params.duration) {
+            (Some(end), _) => end,
+            (None, Some(duration)) => start_time + duration,
+            (None, None) => return Err(anyhow!("VWAP requires end_time or duration_seconds")),
+        };
+        if end_time <= start_time {
+            return Err(anyhow!("VWAP window must end after it starts"));
+        }
+        if order.order_type == OrderType::LIMIT && order.price.is_none() {
+            return Err(anyhow!("LIMIT VWAP requires a price"));
+        }
+
+        let mut strat = Self::new(order, start_time, end_time, params.interval, params.profile()?, params.pricing);
+        strat.expected_volume = params.expected_volume;
+        if let Some(phases) = params.active_phases {
+            strat.active_phases = phases;
+        }
+        Ok(strat)
+    }
+
+    pub fn parent_order_id(&self) -> &str {
+        &self.children.parent_order_id
+    }
+
+    pub fn filled_quantity(&self) -> i64 {
+        self.children.filled_quantity()
+    }
+
+    /// Share of the window's volume the profile expects by `now`. Windows the profile
+    /// has no volume in (outside the session) are scheduled evenly in time.
+    pub fn scheduled_fraction(&self, now: f64) -> f64 {
+        if now <= self.start_time {
+            return 0.0;
+        }
+        if now >= self.end_time {
+            return 1.0;
+        }
+        let (from, to) = (self.profile.cumulative(self.start_time), self.profile.cumulative(self.end_time));
+        let fraction = if to - from > 1e-9 {
+            (self.profile.cumulative(now) - from) / (to - from)
+        } else {
+            (now - self.start_time) / (self.end_time - self.start_time)
+        };
+        fraction.clamp(0.0, 1.0)
+    }
+
+    /// Share of the parent that should be done by `now`. With `expected_volume`, the volume
+    /// traded so far is weighed against what the profile still expects for the rest of the window.
+    pub fn target_fraction(&self, now: f64) -> f64 {
+        let scheduled = self.scheduled_fraction(now);
+        match self.expected_volume {
+            Some(expected) if scheduled < 1.0 && self.market_volume > 0 => {
+                let realized = self.market_volume as f64;
+                realized / (realized + expected * (1.0 - scheduled))
+            }
+            _ => scheduled,
+        }
+    }
+
+    pub fn target_quantity(&self, now: f64) -> i64 {
+        let quantity = self.children.quantity;
+        ((quantity as f64 * self.target_fraction(now)).ceil() as i64).min(quantity)
+    }
+
+    /// VWAP of the market trades seen since the start of the window.
+    pub fn market_vwap(&self) -> Option<Decimal> {
+        (self.market_volume > 0).then(|| self.market_value / Decimal::from_i64(self.market_volume).unwrap_or(Decimal::ONE))
+    }
+
+    /// Execution cost against the market VWAP in basis points; positive when the fills are worse.
+    pub fn tracking_bps(&self) -> Option<f64> {
+        let (average, vwap) = (self.children.average_fill_price()?, self.market_vwap()?);
+        if vwap.is_zero() {
+            return None;
+        }
+        let diff = match self.children.side {
+            OrderSide::BUY => average - vwap,
+            OrderSide::SELL => vwap - average,
+        };
+        (diff / vwap * Decimal::from(10000)).to_f64()
+    }
+
+    /// Timer step at `now` (unix seconds).
+    pub fn on_timer_at(&mut self, now: f64) -> StrategyAction {
+        if self.finished {
+            return StrategyAction::None;
+        }
+        if self.children.is_working() {
+            return self.children.expire(now);
+        }
+        // The window is over and the final child is done: stop rather than chase the rest
+        if now >= self.end_time && self.swept {
+            self.finished = true;
+            return StrategyAction::None;
+        }
+        if now < self.next_child_time {
+            return StrategyAction::None;
+        }
+
+        let need = self.target_quantity(now) - self.children.filled_quantity();
+        if need <= 0 || !self.children.can_price() {
+            return StrategyAction::None;
+        }
+        self.next_child_time = now + self.interval;
+        self.swept = now >= self.end_time;
+        self.children.place(need, now + self.interval)
+    }
+}
+
+impl Strategy for VwapStrategy {
+    fn on_order_book_update(&mut self, book: &OrderBook) -> Result<StrategyAction> {
+        self.children.on_book(book);
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, symbol: &str, price: Decimal, quantity: i64, timestamp: f64) -> Result<StrategyAction> {
+        if symbol == self.children.symbol && timestamp >= self.start_time && !self.finished {
+            self.market_volume += quantity;
+            self.market_value += price * Decimal::from_i64(quantity).unwrap_or_default();
+        }
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.on_timer_at(Local::now().timestamp_millis() as f64 / 1000.0))
+    }
+
+    fn on_order_status_update(&mut self, order: &Order) -> Result<StrategyAction> {
+        if order.order_id.as_deref() == Some(self.parent_order_id()) {
+            if order.state == OrderState::CANCELED && !self.finished {
+                self.finished = true;
+                return Ok(self.children.cancel_working());
+            }
+            return Ok(StrategyAction::None);
+        }
+
+        match self.children.on_child_update(order) {
+            ChildUpdate::Unrelated => return Ok(StrategyAction::None),
+            ChildUpdate::Rejected => {
+                log::warn!("VWAP {}: child {:?} rejected ({}), stopping", self.parent_order_id(), order.order_id, order.error_message.as_deref().unwrap_or(""));
+                self.finished = true;
+            }
+            ChildUpdate::Updated => {}
+        }
+        if self.children.remaining_quantity() == 0 {
+            self.finished = true;
+        }
+        Ok(self.children.resume_pull())
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.active_phases)
+    }
+
+    fn on_session_change(&mut self, _from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        if self.finished || self.active_phases.contains(&to) {
+            return Ok(StrategyAction::None);
+        }
+        Ok(self.children.pull_working())
+    }
+
+    fn is_completed(&self) -> bool {
+        self.finished
+    }
+
+    fn venue(&self) -> Option<&str> {
+        Some(&self.children.venue)
+    }
+
+    fn get_origin_order_id(&self) -> Option<String> {
+        Some(self.parent_order_id().to_string())
+    }
+
+    fn report(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "strategy": "VWAP",
+            "parent_order_id": self.parent_order_id(),
+            "quantity": self.children.quantity,
+            "filled_quantity": self.children.filled_quantity(),
+            "average_price": self.children.average_fill_price().map(|p| p.to_string()),
+            "market_vwap": self.market_vwap().map(|p| p.to_string()),
+            "market_volume": self.market_volume,
+            "tracking_bps": self.tracking_bps()
+        }))
+    }
+
+    fn restore_child(&mut self, child: &Order) {
+        self.children.restore_child(child, child.created_at + self.interval);
+        self.next_child_time = self.next_child_time.max(child.created_at + self.interval);
+        self.swept |= child.created_at >= self.end_time;
+        self.finished = self.children.remaining_quantity() == 0;
+    }
+}
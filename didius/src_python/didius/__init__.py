@@ -1,5 +1,10 @@
 # my_project/__init__.py
 from . import *
 # OR if using a specific module-name:
-from .core import utils, ExecutionStrategy, Order, OrderType, OrderSide, OMSEngine, OrderState
+from .core import utils, ExecutionStrategy, Order, OrderType, OrderSide, OMSEngine, OrderState, StrategyAction, TradingPhase, SessionSchedule, RiskLimits
+from .core import StopParams, TwapParams, VwapParams, IcebergParams, PegParams, PovParams, ShortfallParams, MarketMakerParams, SpreadParams, BracketParams
+from .core import (
+    AdapterException, AuthError, RateLimitError, OrderRejectedError, InsufficientBalanceError,
+    BrokerHttpError, AdapterTimeoutError, NetworkError, ConnectionLostError, UnknownOrderError, UnsupportedError,
+)
 from .client import Didius
\ No newline at end of file
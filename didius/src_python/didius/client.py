@@ -17,8 +17,9 @@ class Didius:
         Initialize the Didius client.
         
         Args:
-            venue: Backend venue ("mock", "hantoo", "hantoo_night").
-            config_path: Path to configuration file (required for Hantoo venues).
+            venue: Backend venue ("mock", "hantoo", "hantoo_night", "replay").
+            config_path: Path to configuration file (required for Hantoo venues),
+                or to a MARKET_DATA log file/directory for "replay".
             s3_bucket: Optional AWS S3 bucket for logging.
             s3_region: Optional AWS region.
             s3_prefix: Optional prefix for log files in S3.
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{auth_dir, limit_id};
+use didius::adapter::error::{AuthError, InsufficientBalanceError, OrderRejectedError, RateLimitError};
+use didius::adapter::hantoo::{HantooAdapter, HantooConfig};
+use didius::adapter::hantoo_stub::HantooStubServer;
+use didius::adapter::{Adapter, AdapterError};
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{OrderSide, OrderState};
+use pyo3::prelude::*;
+use rust_decimal::dec;
+use std::fs;
+use std::io::Read;
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[test]
+fn test_broker_responses_are_classified() {
+    let stub = HantooStubServer::start().unwrap();
+    let dir = auth_dir("classify");
+    let adapter = HantooAdapter::from_config(stub.config(&dir));
+
+    stub.set_order_reject(Some(("EGW00201", "초당 거래건수를 초과하였습니다.")));
+    let err = adapter.place_order(&limit_id("c1", "005930", OrderSide::BUY, 1, dec!(70000))).unwrap_err();
+    assert!(matches!(err, AdapterError::RateLimited(_)), "{:?}", err);
+    assert!(err.is_retryable() && !err.is_outcome_unknown());
+
+    stub.set_order_reject(Some(("APBK0952", "주문가능금액을 초과 했습니다")));
+    let err = adapter.place_order(&limit_id("c2", "005930", OrderSide::BUY, 1, dec!(70000))).unwrap_err();
+    assert!(matches!(err, AdapterError::InsufficientBalance(_)), "{:?}", err);
+    let status = err.broker_status().unwrap();
+    assert_eq!((status.rt_cd.as_str(), status.msg_cd.as_str(), status.http_status), ("1", "APBK0952", Some(200)));
+    assert!(!err.is_retryable());
+
+    stub.set_order_reject(Some(("APBK1234", "호가단위 오류입니다")));
+    let err = adapter.place_order(&limit_id("c3", "005930", OrderSide::BUY, 1, dec!(70001))).unwrap_err();
+    assert!(matches!(err, AdapterError::Rejected(_)), "{:?}", err);
+    stub.set_order_reject(None);
+
+    let err = adapter.cancel_order("never-placed").unwrap_err();
+    assert!(matches!(err, AdapterError::UnknownOrder(ref id) if id == "never-placed"));
+
+    // The cached token is revoked and a new one is refused
+    stub.expire_tokens();
+    let refused = HantooAdapter::from_config(HantooConfig { my_sec: "wrong-secret".to_string(), ..stub.config(&dir) });
+    let err = refused.get_account_snapshot("").unwrap_err();
+    assert!(matches!(err, AdapterError::Auth(_)), "{:?}", err);
+    assert_eq!(err.http_status(), Some(403));
+    assert_eq!(err.kind(), "auth");
+}
+
+#[test]
+fn test_unreachable_broker_is_a_network_error() {
+    let stub = HantooStubServer::start().unwrap();
+    let config = HantooConfig { prod: "http://127.0.0.1:1".to_string(), ..stub.config(&auth_dir("network")) };
+    let adapter = HantooAdapter::from_config(config);
+    let err = adapter.connect().unwrap_err();
+    assert!(matches!(err, AdapterError::Network(_)), "{:?}", err);
+    assert!(err.is_retryable());
+}
+
+#[test]
+fn test_connection_lost_mid_request_is_outcome_unknown() {
+    // The broker takes the request and hangs up without answering
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/uapi/domestic-stock/v1/trading/order-cash", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+    });
+    let err: AdapterError = reqwest::blocking::Client::new().post(&url).body("{}").send().unwrap_err().into();
+    server.join().unwrap();
+    assert!(matches!(err, AdapterError::ConnectionLost(_)), "{:?}", err);
+    assert!(err.is_outcome_unknown());
+    assert_eq!(err.kind(), "connection_lost");
+
+    // Nothing listening: the request never left
+    let err: AdapterError = reqwest::blocking::get("http://127.0.0.1:1/").unwrap_err().into();
+    assert!(matches!(err, AdapterError::Network(_)), "{:?}", err);
+    assert!(!err.is_outcome_unknown());
+}
+
+#[test]
+fn test_engine_rejects_order_and_keeps_error_kind() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth_dir("engine"))));
+    let log_path = "tests/adapter_error.jsonl";
+    let _ = fs::remove_file(log_path);
+    let config = LoggerConfig { destination: LogDestinationInfo::LocalFile { path: log_path.to_string() }, flush_interval_seconds: 60, batch_size: 1024 };
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    engine.start_internal(None).unwrap();
+
+    stub.set_order_reject(Some(("APBK0952", "주문가능금액을 초과 했습니다")));
+    let err = engine.send_order_internal(limit_id("o1", "005930", OrderSide::BUY, 10, dec!(70000))).unwrap_err();
+    assert!(matches!(err.downcast_ref::<AdapterError>(), Some(AdapterError::InsufficientBalance(_))));
+    let order = engine.get_orders().get("o1").cloned().unwrap();
+    assert_eq!(order.state, OrderState::REJECTED);
+    assert!(order.error_message.unwrap().contains("APBK0952"));
+
+    engine.stop_internal().unwrap();
+    let events: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "ADAPTER_ERROR")
+        .collect();
+    assert_eq!(events.len(), 1);
+    let body = &events[0]["log_body"];
+    assert_eq!(body["operation"], "place_order");
+    assert_eq!(body["kind"], "insufficient_balance");
+    assert_eq!(body["broker_status"]["msg_cd"], "APBK0952");
+    let _ = fs::remove_file(log_path);
+}
+
+#[test]
+fn test_python_exception_classes() {
+    pyo3::prepare_freethreaded_python();
+    let status = |code: &str| serde_json::json!({"rt_cd": "1", "msg_cd": code, "msg1": "msg"});
+    Python::with_gil(|py| {
+        let err: PyErr = AdapterError::from_response(200, &status("APBK0952")).into();
+        assert!(err.is_instance_of::<InsufficientBalanceError>(py));
+        assert!(err.is_instance_of::<OrderRejectedError>(py));
+        let value = err.value(py);
+        assert_eq!(value.getattr("msg_cd").unwrap().extract::<String>().unwrap(), "APBK0952");
+        assert_eq!(value.getattr("http_status").unwrap().extract::<Option<u16>>().unwrap(), Some(200));
+        assert!(!value.getattr("retryable").unwrap().extract::<bool>().unwrap());
+
+        let err: PyErr = AdapterError::from_response(500, &status("EGW00123")).into();
+        assert!(err.is_instance_of::<AuthError>(py));
+        let err: PyErr = AdapterError::from_response(200, &status("EGW00201")).into();
+        assert!(err.is_instance_of::<RateLimitError>(py));
+        assert!(err.value(py).getattr("retryable").unwrap().extract::<bool>().unwrap());
+
+        // Engine errors that do not come from an adapter stay RuntimeError
+        let err = didius::adapter::error::to_py_err(anyhow::anyhow!("Order not found"));
+        assert!(err.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+        assert!(!err.is_instance_of::<didius::adapter::error::AdapterException>(py));
+    });
+}
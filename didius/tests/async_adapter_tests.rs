@@ -0,0 +1,79 @@
+mod common;
+
+use common::{auth_dir, limit_id};
+use didius::adapter::hantoo::HantooAdapter;
+use didius::adapter::hantoo_ngt_futopt::HantooNightAdapter;
+use didius::adapter::hantoo_stub::{HantooStubServer, STUB_HTS_ID};
+use didius::adapter::{Adapter, AsyncAdapter, IncomingMessage};
+use didius::message::ConnectionStatus;
+use didius::oms::order::{Order, OrderSide};
+use futures_util::future::join_all;
+use rust_decimal::dec;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_concurrent_orders_on_async_adapter() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooAdapter::from_config(stub.config(&auth_dir("orders")));
+
+    let orders: Vec<Order> = (0..20).map(|i| limit_id(&format!("a{}", i), "005930", OrderSide::BUY, 1, dec!(70000))).collect();
+    let results = join_all(orders.iter().map(|o| AsyncAdapter::place_order(&adapter, o))).await;
+    assert!(results.iter().all(|r| matches!(r, Ok(true))), "{:?}", results);
+    assert_eq!(stub.orders().len(), 20);
+    assert!(AsyncAdapter::get_order_ref(&adapter, "a19").is_some());
+
+    // The blocking facade also works from inside a multi-thread runtime
+    let ok = Adapter::cancel_order(&adapter, "a0").unwrap();
+    assert!(ok);
+}
+
+#[test]
+fn test_night_symbols_share_one_connection() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooNightAdapter::from_config(stub.config(&auth_dir("night")));
+    let (tx, rx) = mpsc::channel();
+    Adapter::set_monitor(&adapter, tx);
+    Adapter::connect(&adapter).unwrap();
+
+    let symbols: Vec<String> = (0..50).map(|i| format!("A0{:04}", i)).collect();
+    Adapter::subscribe(&adapter, &symbols[..25]).unwrap();
+    Adapter::subscribe(&adapter, &symbols[25..]).unwrap();
+    for s in &symbols {
+        assert!(stub.wait_for_subscription("H0MFCNT0", s, Duration::from_secs(2)), "{}", s);
+    }
+    assert!(stub.wait_for_subscription("H0MFASP0", &symbols[49], Duration::from_secs(2)));
+    assert_eq!(stub.subscription_count("H0MFCNI0", STUB_HTS_ID), 1);
+    assert_eq!(stub.subscription_count("H0MFCNT0", &symbols[0]), 1);
+
+    assert!(matches!(rx.recv_timeout(Duration::from_secs(2)).unwrap(), IncomingMessage::ConnectionStatus(ConnectionStatus::Connected)));
+    stub.push_night_trade(&symbols[30], dec!(351.5), 2);
+    let msg = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert!(matches!(msg, IncomingMessage::MarketTrade { ref symbol, quantity: 2, .. } if symbol == &symbols[30]), "{:?}", msg);
+
+    Adapter::disconnect(&adapter).unwrap();
+}
+
+#[test]
+fn test_stock_subscription_after_connect() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooAdapter::from_config(stub.config(&auth_dir("stock")));
+    let (tx, rx) = mpsc::channel();
+    Adapter::set_monitor(&adapter, tx);
+    adapter.subscribe_market(&["005930".to_string()]).unwrap();
+    Adapter::connect(&adapter).unwrap();
+    assert!(stub.wait_for_subscription("H0SCCNT0", "005930", Duration::from_secs(2)));
+
+    // Symbols added to a running session are subscribed right away
+    Adapter::subscribe(&adapter, &["000660".to_string()]).unwrap();
+    assert!(stub.wait_for_subscription("H0SCCNT0", "000660", Duration::from_secs(2)));
+    assert!(stub.wait_for_subscription("H0UNASP0", "000660", Duration::from_secs(2)));
+    assert_eq!(stub.subscription_count("H0STCNI0", STUB_HTS_ID), 1);
+
+    assert!(matches!(rx.recv_timeout(Duration::from_secs(2)).unwrap(), IncomingMessage::ConnectionStatus(ConnectionStatus::Connected)));
+    stub.push_trade("000660", dec!(180000), 7);
+    let msg = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert!(matches!(msg, IncomingMessage::MarketTrade { ref symbol, quantity: 7, .. } if symbol == "000660"), "{:?}", msg);
+
+    Adapter::disconnect(&adapter).unwrap();
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::{book, setup, snapshot, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::bracket::BracketStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn order(strategy: ExecutionStrategy, side: OrderSide, qty: i64, price: Decimal, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Order::new("TEST".to_string(), side, OrderType::LIMIT, qty, Some(price.to_string()), Some(strategy), Some(params), None, "KRX".to_string(), None)
+}
+
+fn with_state(mut child: Order, filled: i64, state: OrderState) -> Order {
+    child.filled_quantity = filled;
+    child.state = state;
+    child
+}
+
+fn filled(child: Order, qty: i64) -> Order {
+    let state = if qty >= child.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+    with_state(child, qty, state)
+}
+
+#[test]
+fn test_oco_stop_reprices_the_limit_leg() {
+    let mut parent = order(ExecutionStrategy::OCO, OrderSide::SELL, 5, dec!(110), &[("stop_price", "95")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = BracketStrategy::from_order(&parent).unwrap();
+
+    let StrategyAction::PlaceOrder(leg) = strat.on_timer().unwrap() else { panic!("no leg") };
+    assert_eq!((leg.order_id.as_deref(), leg.side.clone(), leg.quantity, leg.price), (Some("P-1"), OrderSide::SELL, 5, Some(dec!(110))));
+
+    // Take-profit partially fills: the stop now covers the remaining 3
+    assert!(matches!(strat.on_order_status_update(&filled(leg.clone(), 2)).unwrap(), StrategyAction::None));
+    assert_eq!(strat.position(), 3);
+
+    let action = strat.on_order_book_update(&book(dec!(94), dec!(95))).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(ref id, None) if id == "P-1"), "{:?}", action);
+    assert!(matches!(strat.on_timer().unwrap(), StrategyAction::None));
+
+    strat.on_order_status_update(&filled(leg, 5)).unwrap();
+    assert!(strat.is_completed());
+    assert_eq!(strat.report().unwrap()["stop_triggered"], true);
+}
+
+#[test]
+fn test_bracket_resizes_exit_with_entry_fills() {
+    let mut parent = order(ExecutionStrategy::BRACKET, OrderSide::BUY, 10, dec!(100), &[("take_profit_price", "110"), ("stop_price", "95")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = BracketStrategy::from_order(&parent).unwrap();
+
+    let StrategyAction::PlaceOrder(entry) = strat.on_timer().unwrap() else { panic!("no entry") };
+    assert_eq!((entry.order_id.as_deref(), entry.quantity, entry.price), (Some("P-1"), 10, Some(dec!(100))));
+    // Nothing to protect yet: the stop is ignored
+    assert!(matches!(strat.on_order_book_update(&book(dec!(90), dec!(91))).unwrap(), StrategyAction::None));
+
+    let StrategyAction::PlaceOrder(exit) = strat.on_order_status_update(&filled(entry.clone(), 4)).unwrap() else { panic!("no exit") };
+    assert_eq!((exit.order_id.as_deref(), exit.side.clone(), exit.quantity, exit.price), (Some("P-x-1"), OrderSide::SELL, 4, Some(dec!(110))));
+
+    // The rest of the entry fills: replace the exit with one for 10 once it is acknowledged
+    assert!(matches!(strat.on_order_status_update(&filled(entry.clone(), 10)).unwrap(), StrategyAction::None));
+    let action = strat.on_order_status_update(&with_state(exit.clone(), 0, OrderState::NEW)).unwrap();
+    assert!(matches!(action, StrategyAction::CancelOrder(ref id) if id == "P-x-1"), "{:?}", action);
+    assert!(matches!(strat.on_timer().unwrap(), StrategyAction::None));
+    let StrategyAction::PlaceOrder(exit) = strat.on_order_status_update(&with_state(exit, 0, OrderState::CANCELED)).unwrap() else { panic!("no replacement") };
+    assert_eq!((exit.order_id.as_deref(), exit.quantity), (Some("P-x-2"), 10));
+
+    let action = strat.on_order_book_update(&book(dec!(94), dec!(95))).unwrap();
+    assert!(matches!(action, StrategyAction::None), "{:?}", action);
+    let action = strat.on_order_status_update(&with_state(exit.clone(), 0, OrderState::NEW)).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(ref id, None) if id == "P-x-2"), "{:?}", action);
+    strat.on_order_status_update(&filled(exit, 10)).unwrap();
+    assert!(strat.is_completed());
+    let report = strat.report().unwrap();
+    assert_eq!((report["entry_filled_quantity"].as_i64(), report["exit_filled_quantity"].as_i64()), (Some(10), Some(10)));
+}
+
+#[test]
+fn test_bracket_leg_canceled_elsewhere_pulls_the_rest() {
+    let mut parent = order(ExecutionStrategy::BRACKET, OrderSide::SELL, 4, dec!(100), &[("take_profit_price", "90"), ("stop_price", "105")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = BracketStrategy::from_order(&parent).unwrap();
+
+    let StrategyAction::PlaceOrder(entry) = strat.on_timer().unwrap() else { panic!("no entry") };
+    let StrategyAction::PlaceOrder(exit) = strat.on_order_status_update(&filled(entry, 1)).unwrap() else { panic!("no exit") };
+    assert_eq!((exit.side.clone(), exit.price), (OrderSide::BUY, Some(dec!(90))));
+
+    let action = strat.on_order_status_update(&with_state(exit, 0, OrderState::CANCELED)).unwrap();
+    assert!(matches!(action, StrategyAction::CancelOrder(ref id) if id == "P-1"), "{:?}", action);
+    assert!(!strat.is_completed());
+    assert!(strat.stopping);
+}
+
+#[test]
+fn test_bracket_entry_canceled_by_venue_keeps_the_exit() {
+    let mut parent = order(ExecutionStrategy::BRACKET, OrderSide::BUY, 10, dec!(100), &[("take_profit_price", "110"), ("stop_price", "95")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = BracketStrategy::from_order(&parent).unwrap();
+
+    let StrategyAction::PlaceOrder(entry) = strat.on_timer().unwrap() else { panic!("no entry") };
+    let StrategyAction::PlaceOrder(exit) = strat.on_order_status_update(&filled(entry.clone(), 4)).unwrap() else { panic!("no exit") };
+    assert_eq!((exit.order_id.as_deref(), exit.quantity), (Some("P-x-1"), 4));
+
+    // The venue cancels the rest of the entry: the exit keeps covering the 4 filled
+    let action = strat.on_order_status_update(&with_state(entry, 4, OrderState::CANCELED)).unwrap();
+    assert!(matches!(action, StrategyAction::None), "{:?}", action);
+    assert!(matches!(strat.on_order_status_update(&with_state(exit.clone(), 0, OrderState::NEW)).unwrap(), StrategyAction::None));
+    assert!(!strat.stopping && !strat.is_completed());
+    assert_eq!(strat.position(), 4);
+    // The stop still protects it, and no new entry goes out
+    let action = strat.on_order_book_update(&book(dec!(94), dec!(95))).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(ref id, None) if id == "P-x-1"), "{:?}", action);
+    strat.on_order_status_update(&filled(exit, 4)).unwrap();
+    assert!(strat.is_completed());
+
+    // Filled and canceled before the strategy saw the fill: the exit is placed on the cancel
+    let mut strat = BracketStrategy::from_order(&parent).unwrap();
+    let StrategyAction::PlaceOrder(entry) = strat.on_timer().unwrap() else { panic!("no entry") };
+    let StrategyAction::PlaceOrder(exit) = strat.on_order_status_update(&with_state(entry, 3, OrderState::CANCELED)).unwrap() else { panic!("no exit") };
+    assert_eq!((exit.order_id.as_deref(), exit.side, exit.quantity, exit.price), (Some("P-x-1"), OrderSide::SELL, 3, Some(dec!(110))));
+}
+
+#[test]
+fn test_invalid_params() {
+    let err = |o: Order| BracketStrategy::from_order(&o).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(order(ExecutionStrategy::OCO, OrderSide::SELL, 5, dec!(110), &[])).contains("stop_price"));
+    assert!(err(order(ExecutionStrategy::BRACKET, OrderSide::BUY, 5, dec!(100), &[("stop_price", "95")])).contains("take_profit_price"));
+    assert!(err(order(ExecutionStrategy::OCO, OrderSide::SELL, 5, dec!(110), &[("stop_price", "120")])).contains("losing side"));
+    assert!(err(order(ExecutionStrategy::BRACKET, OrderSide::BUY, 5, dec!(100), &[("take_profit_price", "90"), ("stop_price", "95")])).contains("losing side"));
+    let mut market = order(ExecutionStrategy::OCO, OrderSide::SELL, 5, dec!(110), &[("stop_price", "95")]);
+    market.order_type = OrderType::MARKET;
+    assert!(err(market).contains("LIMIT"));
+}
+
+#[test]
+fn test_engine_bracket_entry_take_profit_and_stop() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(101), 10)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(order(ExecutionStrategy::BRACKET, OrderSide::BUY, 5, dec!(100),
+        &[("take_profit_price", "105"), ("stop_price", "97")])).unwrap();
+    let child = |suffix: &str| format!("{}-{}", parent, suffix);
+    assert!(wait_until(|| adapter.get_open_quantity(&child("1")) == Some(5)));
+
+    // Entry fills 2: a take-profit for 2 goes out
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(100), 2)]));
+    assert!(wait_until(|| adapter.get_open_quantity(&child("x-1")) == Some(2)));
+
+    // Entry completes: the take-profit is replaced by one for 5
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(100), 10)]));
+    assert!(wait_until(|| adapter.get_open_quantity(&child("x-2")) == Some(5)));
+    assert_eq!(engine.get_orders()[&child("x-1")].state, OrderState::CANCELED);
+    let p = engine.get_orders()[&parent].clone();
+    assert_eq!((p.state, p.filled_quantity, p.average_fill_price), (OrderState::FILLED, 5, dec!(100)));
+
+    // Stop: the take-profit is re-priced to market and sells into the bid
+    adapter.push_order_book(snapshot(vec![(dec!(96), 10)], vec![(dec!(96.5), 10)]));
+    assert!(wait_until(|| engine.get_orders()[&child("x-2")].state == OrderState::FILLED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert!(!engine.get_account().positions.contains_key("TEST"));
+    // Exits are not rolled into the entry parent
+    assert_eq!(engine.get_orders()[&parent].filled_quantity, 5);
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_cancel_oco_pulls_the_leg() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(101), 10)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(order(ExecutionStrategy::OCO, OrderSide::SELL, 3, dec!(110), &[("stop_price", "90")])).unwrap();
+    let leg = format!("{}-1", parent);
+    assert!(wait_until(|| adapter.get_open_quantity(&leg) == Some(3)));
+
+    engine.cancel_order_internal(parent.clone()).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&leg].state == OrderState::CANCELED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert_eq!(engine.get_orders()[&parent].state, OrderState::CANCELED);
+    engine.stop_internal().unwrap();
+}
@@ -0,0 +1,90 @@
+//! Helpers shared by the integration tests (`mod common;`).
+#![allow(dead_code)]
+
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use rust_decimal::Decimal;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Poll `cond` every 10ms for up to 3 seconds.
+pub fn wait_until(mut cond: impl FnMut() -> bool) -> bool {
+    for _ in 0..300 {
+        if cond() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+/// Wait for `order_id` to reach `state` in `engine`.
+pub fn wait_for_state(engine: &OMSEngine, order_id: &str, state: OrderState) -> bool {
+    wait_until(|| engine.get_orders().get(order_id).is_some_and(|o| o.state == state))
+}
+
+/// KRX limit order without an ID, which the engine assigns.
+pub fn limit(symbol: &str, side: OrderSide, qty: i64, price: Decimal) -> Order {
+    Order::new(symbol.to_string(), side, OrderType::LIMIT, qty, Some(price.to_string()), None, None, None, "KRX".to_string(), None)
+}
+
+/// `limit` carrying its own ID, as adapters and strategies hand it over.
+pub fn limit_id(id: &str, symbol: &str, side: OrderSide, qty: i64, price: Decimal) -> Order {
+    let mut o = limit(symbol, side, qty, price);
+    o.order_id = Some(id.to_string());
+    o
+}
+
+/// `order` routed to `venue`.
+pub fn on_venue(mut order: Order, venue: &str) -> Order {
+    order.venue = venue.to_string();
+    order
+}
+
+/// Book snapshot of `TEST`.
+pub fn snapshot(bids: Vec<(Decimal, i64)>, asks: Vec<(Decimal, i64)>) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: "TEST".to_string(), bids, asks, update_id: 1, timestamp: 1.0 }
+}
+
+/// `TEST` book with 10 on each side of the touch.
+pub fn book(bid: Decimal, ask: Decimal) -> OrderBook {
+    let mut book = OrderBook::new("TEST".to_string());
+    let s = snapshot(vec![(bid, 10)], vec![(ask, 10)]);
+    book.rebuild(s.bids, s.asks, s.update_id, s.timestamp);
+    book
+}
+
+/// Fresh token cache directory for a Hantoo adapter test.
+pub fn auth_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("didius_auth_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir.to_string_lossy().to_string()
+}
+
+/// Engine on `adapter` logging to the console, with the adapter's messages flowing into it.
+pub fn setup(adapter: Arc<MockAdapter>) -> OMSEngine {
+    engine_with_log(adapter, LogDestinationInfo::Console)
+}
+
+/// Like `setup`, logging to a fresh `log_path`.
+pub fn setup_logged(adapter: Arc<MockAdapter>, log_path: &str) -> OMSEngine {
+    let _ = fs::remove_file(log_path);
+    engine_with_log(adapter, LogDestinationInfo::LocalFile { path: log_path.to_string() })
+}
+
+fn engine_with_log(adapter: Arc<MockAdapter>, destination: LogDestinationInfo) -> OMSEngine {
+    let config = LoggerConfig { destination, flush_interval_seconds: 60, batch_size: 1024 };
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    engine
+}
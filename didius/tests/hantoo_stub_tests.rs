@@ -0,0 +1,246 @@
+mod common;
+
+use common::{auth_dir, limit_id, wait_for_state};
+use didius::adapter::hantoo::HantooAdapter;
+use didius::adapter::hantoo_ngt_futopt::HantooNightAdapter;
+use didius::adapter::hantoo_stub::{HantooStubServer, StubPosition, STUB_HTS_ID};
+use didius::adapter::{Adapter, AdapterError, IncomingMessage};
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::message::ConnectionStatus;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use rust_decimal::dec;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn recv_until<F: Fn(&IncomingMessage) -> bool>(rx: &mpsc::Receiver<IncomingMessage>, pred: F) -> Option<IncomingMessage> {
+    while let Ok(m) = rx.recv_timeout(Duration::from_secs(2)) {
+        if pred(&m) {
+            return Some(m);
+        }
+    }
+    None
+}
+
+#[test]
+fn test_token_cache_refresh_and_reuse() {
+    let stub = HantooStubServer::start().unwrap();
+    let dir = auth_dir("token");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(format!("{}/hantoo_token.yaml", dir), "token: stale\nvalid-date: 2000-01-01 00:00:00\n").unwrap();
+
+    // Expired cache: a new token is issued and written back
+    let adapter = HantooAdapter::from_config(stub.config(&dir));
+    adapter.get_account_snapshot("").unwrap();
+    assert_eq!(stub.token_requests(), 1);
+    let cached = fs::read_to_string(format!("{}/hantoo_token.yaml", dir)).unwrap();
+    assert!(cached.contains("token: stub-token-1"));
+
+    // Valid cache: reused by a fresh adapter
+    let adapter = HantooAdapter::from_config(stub.config(&dir));
+    adapter.get_account_snapshot("").unwrap();
+    assert_eq!(stub.token_requests(), 1);
+    assert!(stub.requests().iter().filter(|r| r.path.starts_with("/uapi")).all(|r| r.authorization.as_deref() == Some("Bearer stub-token-1")));
+
+    // Token revoked server-side: the refused call drops the cache and goes through on a new token
+    stub.expire_tokens();
+    adapter.get_account_snapshot("").unwrap();
+    assert_eq!(stub.token_requests(), 2);
+    assert_eq!(stub.requests().last().unwrap().authorization.as_deref(), Some("Bearer stub-token-2"));
+    let cached = fs::read_to_string(format!("{}/hantoo_token.yaml", dir)).unwrap();
+    assert!(cached.contains("token: stub-token-2"));
+    adapter.get_account_snapshot("").unwrap();
+    assert_eq!(stub.token_requests(), 2);
+}
+
+#[test]
+fn test_order_cash_cancel_and_modify_use_order_numbers() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooAdapter::from_config(stub.config(&auth_dir("orders")));
+
+    assert!(adapter.place_order(&limit_id("c1", "005930", OrderSide::BUY, 10, dec!(70000))).unwrap());
+    assert!(adapter.place_order(&limit_id("c2", "005930", OrderSide::SELL, 3, dec!(71000))).unwrap());
+    let orders = stub.orders();
+    assert_eq!((orders[0].side.as_str(), orders[0].quantity, orders[0].price), ("BUY", 10, dec!(70000)));
+    assert_eq!(orders[1].side, "SELL");
+
+    assert!(adapter.modify_order("c1", Some(dec!(69900)), Some(10)).unwrap());
+    assert!(adapter.cancel_order("c1").unwrap());
+    assert!(adapter.cancel_order("c2").unwrap());
+
+    let revise: Vec<_> = stub.requests().into_iter().filter(|r| r.path.ends_with("order-rvsecncl")).collect();
+    assert_eq!(revise[0].body["ORGN_ODNO"], orders[0].order_no.as_str());
+    assert_eq!(revise[0].body["RVSE_CNCL_DVSN_CD"], "01");
+    // Cancel after modify targets the order number issued by the revision
+    let revised_no = stub.orders()[2].order_no.clone();
+    assert_eq!(revise[1].body["ORGN_ODNO"], revised_no.as_str());
+    assert_eq!(revise[2].body["ORGN_ODNO"], orders[1].order_no.as_str());
+
+    // API-level rejection is reported as a typed error
+    stub.set_order_reject(Some(("APBK0952", "주문가능금액을 초과 했습니다")));
+    let err = adapter.place_order(&limit_id("c3", "005930", OrderSide::BUY, 1, dec!(70000))).unwrap_err();
+    assert!(matches!(err, AdapterError::InsufficientBalance(_)), "{:?}", err);
+    assert!(adapter.cancel_order("c3").is_err());
+}
+
+#[test]
+fn test_encrypted_notices_drive_engine() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth_dir("engine"))));
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let logger = Arc::new(Mutex::new(Logger::new(config)));
+    let engine = OMSEngine::new(adapter.clone(), logger);
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+
+    adapter.subscribe(&["005930".to_string()]).unwrap();
+    adapter.connect().unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+    assert!(stub.wait_for_subscription("H0UNASP0", "005930", Duration::from_secs(2)));
+
+    let mut order = limit_id("", "005930", OrderSide::BUY, 10, dec!(70000));
+    order.order_id = None;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::NEW));
+
+    let order_no = stub.orders()[0].order_no.clone();
+    stub.fill(&order_no, 4, dec!(70000)).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::PARTIALLY_FILLED));
+    stub.fill(&order_no, 6, dec!(69900)).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::FILLED));
+    assert_eq!(engine.get_orders().get(&oid).unwrap().filled_quantity, 10);
+
+    stub.push_asking_price("005930", &[(dec!(69900), 120), (dec!(69800), 50)], &[(dec!(70000), 80)]);
+    for _ in 0..200 {
+        if engine.get_order_book("005930").and_then(|b| b.get_best_ask()).is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let book = engine.get_order_book("005930").unwrap();
+    assert_eq!(book.get_best_bid(), Some((dec!(69900), 120)));
+    assert_eq!(book.get_best_ask(), Some((dec!(70000), 80)));
+}
+
+#[test]
+fn test_twap_rolls_slices_on_cancel_notices() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth_dir("twap"))));
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+
+    adapter.subscribe(&["005930".to_string()]).unwrap();
+    adapter.connect().unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+    assert!(stub.wait_for_subscription("H0UNASP0", "005930", Duration::from_secs(2)));
+    stub.push_asking_price("005930", &[(dec!(69900), 120)], &[(dec!(70000), 80)]);
+    for _ in 0..200 {
+        if engine.get_order_book("005930").is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    engine.start_internal(None).unwrap();
+
+    let params: HashMap<String, String> = [("duration_seconds", "2"), ("slices", "2"), ("pricing", "PASSIVE")]
+        .iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let order = Order::new("005930".to_string(), OrderSide::BUY, OrderType::LIMIT, 10, Some("70000".to_string()), Some(ExecutionStrategy::TWAP), Some(params), None, "KRX".to_string(), None);
+    let parent = engine.send_order_internal(order).unwrap();
+    let (first, second) = (format!("{}-1", parent), format!("{}-2", parent));
+
+    // Slice 1 rests on the bid until its deadline; the venue's cancel notice (RCTF_CLS 2) ends it
+    assert!(wait_for_state(&engine, &first, OrderState::NEW));
+    assert!(wait_for_state(&engine, &first, OrderState::CANCELED));
+    let cancel = stub.requests().into_iter().find(|r| r.path.ends_with("order-rvsecncl")).unwrap();
+    assert_eq!(cancel.body["RVSE_CNCL_DVSN_CD"], "02");
+
+    // Slice 2 carries the whole parent and fills it
+    assert!(wait_for_state(&engine, &second, OrderState::NEW));
+    assert_eq!(engine.get_orders()[&second].quantity, 10);
+    let order_no = stub.orders()[1].order_no.clone();
+    stub.fill(&order_no, 10, dec!(69900)).unwrap();
+    assert!(wait_for_state(&engine, &parent, OrderState::FILLED));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_rest_book_and_balance_parsing() {
+    let stub = HantooStubServer::start().unwrap();
+    stub.set_order_book("005930", vec![(dec!(69900), 10), (dec!(69800), 20)], vec![(dec!(70000), 5)]);
+    stub.set_balance(dec!(1000000), vec![StubPosition {
+        symbol: "005930".to_string(),
+        quantity: 7,
+        average_price: dec!(68000),
+        current_price: dec!(70000),
+    }]);
+
+    let adapter = HantooAdapter::from_config(stub.config(&auth_dir("rest")));
+    let book = adapter.get_order_book_snapshot("005930").unwrap();
+    assert_eq!(book.get_best_bid(), Some((dec!(69900), 10)));
+    assert_eq!(book.get_best_ask(), Some((dec!(70000), 5)));
+    assert_eq!(book.bids.len(), 2);
+
+    let acct = adapter.get_account_snapshot("").unwrap();
+    assert_eq!(acct.balance, dec!(1000000));
+    assert_eq!(acct.positions.get("005930").unwrap().quantity, 7);
+
+    let night = HantooNightAdapter::from_config(stub.config(&auth_dir("rest_night")));
+    assert_eq!(night.get_account_snapshot("").unwrap().balance, dec!(1000000));
+}
+
+#[test]
+fn test_feed_reports_connection_status() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooAdapter::from_config(stub.config(&auth_dir("status")));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    adapter.connect().unwrap();
+    assert!(recv_until(&rx, |m| matches!(m, IncomingMessage::ConnectionStatus(ConnectionStatus::Connected))).is_some());
+
+    // The feed goes away under the adapter
+    drop(stub);
+    assert!(recv_until(&rx, |m| matches!(m, IncomingMessage::ConnectionStatus(ConnectionStatus::Disconnected))).is_some());
+}
+
+#[test]
+fn test_night_adapter_orders_and_feed() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = HantooNightAdapter::from_config(stub.config(&auth_dir("night")));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    adapter.connect().unwrap();
+    Adapter::subscribe(&adapter, &["A05602".to_string()]).unwrap();
+    assert!(recv_until(&rx, |m| matches!(m, IncomingMessage::ConnectionStatus(ConnectionStatus::Connected))).is_some());
+    assert!(stub.wait_for_subscription("H0MFCNT0", "A05602", Duration::from_secs(2)));
+    assert!(stub.wait_for_subscription("H0MFCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+
+    assert!(adapter.place_order(&limit_id("n1", "A05602", OrderSide::SELL, 2, dec!(350.5))).unwrap());
+    let placed = stub.orders()[0].clone();
+    assert_eq!((placed.side.as_str(), placed.quantity, placed.price, placed.night), ("SELL", 2, dec!(350.5), true));
+    assert!(recv_until(&rx, |m| matches!(m, IncomingMessage::OrderStatus { order_id, state: OrderState::NEW, .. } if order_id == "n1")).is_some());
+
+    stub.fill(&placed.order_no, 2, dec!(350.5)).unwrap();
+    assert!(recv_until(&rx, |m| matches!(m, IncomingMessage::Execution { order_id, fill_qty: 2, .. } if order_id == "n1")).is_some());
+
+    stub.push_night_trade("A05602", dec!(350.25), 3);
+    let trade = recv_until(&rx, |m| matches!(m, IncomingMessage::MarketTrade { .. })).unwrap();
+    assert!(matches!(trade, IncomingMessage::MarketTrade { price, quantity: 3, .. } if price == dec!(350.25)));
+
+    stub.push_night_asking_price("A05602", &[(dec!(350.2), 4)], &[(dec!(350.3), 6)]);
+    match recv_until(&rx, |m| matches!(m, IncomingMessage::OrderBookSnapshot(_))).unwrap() {
+        IncomingMessage::OrderBookSnapshot(s) => {
+            assert_eq!(s.bids, vec![(dec!(350.2), 4)]);
+            assert_eq!(s.asks, vec![(dec!(350.3), 6)]);
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{book, setup, snapshot, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::iceberg::IcebergStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn iceberg(qty: i64, price: Decimal, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Order::new("TEST".to_string(), OrderSide::BUY, OrderType::LIMIT, qty, Some(price.to_string()), Some(ExecutionStrategy::ICEBERG), Some(params), None, "KRX".to_string(), None)
+}
+
+fn filled(mut clip: Order, qty: i64) -> Order {
+    clip.filled_quantity = qty;
+    clip.state = if qty >= clip.quantity { OrderState::FILLED } else { OrderState::PARTIALLY_FILLED };
+    clip
+}
+
+#[test]
+fn test_clips_replenish_from_reserve() {
+    let mut parent = iceberg(7, dec!(100), &[("display_quantity", "3")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = IcebergStrategy::from_order(&parent).unwrap();
+
+    let StrategyAction::PlaceOrder(clip) = strat.on_timer().unwrap() else { panic!("no clip") };
+    assert_eq!((clip.order_id.as_deref(), clip.quantity, clip.price), (Some("P-1"), 3, Some(dec!(100))));
+    assert!(matches!(strat.on_timer().unwrap(), StrategyAction::None));
+
+    // A partial fill leaves the clip resting
+    assert!(matches!(strat.on_order_status_update(&filled(clip.clone(), 2)).unwrap(), StrategyAction::None));
+    let StrategyAction::PlaceOrder(next) = strat.on_order_status_update(&filled(clip, 3)).unwrap() else { panic!("no refill") };
+    assert_eq!((next.order_id.as_deref(), next.quantity), (Some("P-2"), 3));
+
+    let StrategyAction::PlaceOrder(last) = strat.on_order_status_update(&filled(next, 3)).unwrap() else { panic!("no refill") };
+    assert_eq!(last.quantity, 1);
+    assert!(matches!(strat.on_order_status_update(&filled(last, 1)).unwrap(), StrategyAction::None));
+    assert_eq!(strat.filled_quantity(), 7);
+    assert!(strat.is_completed());
+    assert_eq!(strat.report().unwrap()["clips"], 3);
+}
+
+#[test]
+fn test_drift_reprices_to_touch_within_limit() {
+    let mut parent = iceberg(10, dec!(103), &[("display_quantity", "5"), ("max_drift", "1")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = IcebergStrategy::from_order(&parent).unwrap();
+
+    strat.on_order_book_update(&book(dec!(99), dec!(105))).unwrap();
+    let StrategyAction::PlaceOrder(mut clip) = strat.on_timer().unwrap() else { panic!("no clip") };
+    assert_eq!(clip.price, Some(dec!(99)));
+
+    // Not at the venue yet: nothing to re-price
+    assert!(matches!(strat.on_order_book_update(&book(dec!(101), dec!(105))).unwrap(), StrategyAction::None));
+    clip.state = OrderState::NEW;
+    strat.on_order_status_update(&clip).unwrap();
+
+    // Within the drift: stay
+    assert!(matches!(strat.on_order_book_update(&book(dec!(100), dec!(105))).unwrap(), StrategyAction::None));
+    // Bid ran away: follow it, one re-price at a time
+    let action = strat.on_order_book_update(&book(dec!(101), dec!(105))).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(ref id, Some(p)) if id == "P-1" && p == dec!(101)), "{:?}", action);
+    assert!(matches!(strat.on_order_book_update(&book(dec!(102), dec!(105))).unwrap(), StrategyAction::None));
+
+    // Refused: the engine puts the old price back and the next book tries again
+    strat.on_order_status_update(&clip).unwrap();
+    let action = strat.on_order_book_update(&book(dec!(101), dec!(105))).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(101)), "{:?}", action);
+    clip.price = Some(dec!(101));
+    strat.on_order_status_update(&clip).unwrap();
+    assert_eq!(strat.report().unwrap()["reprices"], 1);
+    assert!(matches!(strat.on_order_book_update(&book(dec!(101), dec!(105))).unwrap(), StrategyAction::None));
+
+    // Never through the parent limit
+    let action = strat.on_order_book_update(&book(dec!(104), dec!(106))).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(103)), "{:?}", action);
+    clip.price = Some(dec!(103));
+    strat.on_order_status_update(&clip).unwrap();
+    assert!(matches!(strat.on_order_book_update(&book(dec!(104), dec!(106))).unwrap(), StrategyAction::None));
+}
+
+#[test]
+fn test_invalid_params() {
+    let err = |o: Order| IcebergStrategy::from_order(&o).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(iceberg(10, dec!(100), &[])).contains("display_quantity"));
+    assert!(err(iceberg(10, dec!(100), &[("display_quantity", "11")])).contains("between 1"));
+    assert!(err(iceberg(10, dec!(100), &[("display_quantity", "2"), ("max_drift", "-1")])).contains("negative"));
+    let mut market = iceberg(10, dec!(100), &[("display_quantity", "2")]);
+    market.order_type = OrderType::MARKET;
+    assert!(err(market).contains("LIMIT"));
+}
+
+#[test]
+fn test_engine_shows_one_clip_and_aggregates_parent() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(105), 10)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(iceberg(7, dec!(100), &[("display_quantity", "3")])).unwrap();
+    let clip = |n: i64| format!("{}-{}", parent, n);
+    assert!(wait_until(|| adapter.get_open_quantity(&clip(1)) == Some(3)));
+    assert_eq!(adapter.get_open_quantity(&parent), None);
+    assert_eq!(adapter.get_open_quantity(&clip(2)), None);
+
+    // 4 offered at 100: the first clip fills, the second takes 1 and rests with 2
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(100), 4)]));
+    assert!(wait_until(|| adapter.get_open_quantity(&clip(2)) == Some(2)));
+    assert!(wait_until(|| engine.get_orders()[&parent].filled_quantity == 4));
+    assert_eq!(engine.get_orders()[&parent].state, OrderState::PARTIALLY_FILLED);
+
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(100), 10)]));
+    assert!(wait_until(|| engine.get_orders()[&parent].state == OrderState::FILLED));
+    let p = engine.get_orders()[&parent].clone();
+    assert_eq!((p.filled_quantity, p.average_fill_price), (7, dec!(100)));
+    assert_eq!(engine.get_orders()[&clip(3)].quantity, 1);
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_reprices_clip_on_drift() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(105), 10)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(iceberg(10, dec!(103), &[("display_quantity", "5"), ("max_drift", "1")])).unwrap();
+    let clip = format!("{}-1", parent);
+    assert!(wait_until(|| adapter.get_open_quantity(&clip) == Some(5)));
+    assert_eq!(engine.get_orders()[&clip].price, Some(dec!(99)));
+
+    adapter.push_order_book(snapshot(vec![(dec!(102), 10)], vec![(dec!(105), 10)]));
+    assert!(wait_until(|| engine.get_orders()[&clip].price == Some(dec!(102))));
+    assert_eq!(adapter.get_open_quantity(&clip), Some(5));
+
+    engine.cancel_order_internal(parent.clone()).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&clip].state == OrderState::CANCELED));
+    engine.stop_internal().unwrap();
+}
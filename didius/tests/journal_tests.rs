@@ -0,0 +1,177 @@
+mod common;
+
+use common::limit;
+use didius::adapter::hantoo::HantooAdapter;
+use didius::adapter::hantoo_stub::{HantooStubServer, STUB_HTS_ID};
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::journal::OrderJournal;
+use didius::oms::order::{ExecutionStrategy, OrderSide, OrderState};
+use rust_decimal::dec;
+use std::fs;
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn journal_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("didius_journal_{}_{}.jsonl", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path.to_string_lossy().to_string()
+}
+
+fn engine_with(adapter: Arc<dyn Adapter>, journal: &str) -> OMSEngine {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    engine.set_journal(OrderJournal::open(journal).unwrap());
+    engine
+}
+
+fn wait_for<F: Fn() -> bool>(f: F) -> bool {
+    for _ in 0..200 {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn state_of(engine: &OMSEngine, oid: &str) -> Option<OrderState> {
+    engine.get_orders().get(oid).map(|o| o.state.clone())
+}
+
+#[test]
+fn test_crash_recovery_restores_orders_and_broker_refs() {
+    let stub = HantooStubServer::start().unwrap();
+    let auth = std::env::temp_dir().join(format!("didius_journal_auth_{}", std::process::id())).to_string_lossy().to_string();
+    let path = journal_path("hantoo");
+
+    // First process: two working orders, one partially filled
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth)));
+    let engine = engine_with(adapter.clone(), &path);
+    engine.start_internal(None).unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+
+    let partial = engine.send_order_internal(limit("005930", OrderSide::BUY, 10, dec!(70000))).unwrap();
+    let resting = engine.send_order_internal(limit("005930", OrderSide::SELL, 5, dec!(72000))).unwrap();
+    assert!(wait_for(|| state_of(&engine, &partial) == Some(OrderState::NEW) && state_of(&engine, &resting) == Some(OrderState::NEW)));
+    let exchange_orders = stub.orders();
+    stub.fill(&exchange_orders[0].order_no, 4, dec!(70000)).unwrap();
+    assert!(wait_for(|| state_of(&engine, &partial) == Some(OrderState::PARTIALLY_FILLED)));
+
+    // Crash: the engine and adapter are gone, only the journal survives
+    drop(engine);
+
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth)));
+    assert!(adapter.get_order_ref(&partial).is_none());
+    let engine = engine_with(adapter.clone(), &path);
+    engine.start_internal(None).unwrap();
+
+    let recovered = engine.get_orders().get(&partial).cloned().unwrap();
+    assert_eq!(recovered.state, OrderState::PARTIALLY_FILLED);
+    assert_eq!(recovered.filled_quantity, 4);
+    assert_eq!(state_of(&engine, &resting), Some(OrderState::NEW));
+    assert_eq!(adapter.get_order_ref(&partial).unwrap()["order_no"], exchange_orders[0].order_no.as_str());
+
+    // Notices for recovered orders map back to the client ids
+    assert!(wait_for(|| stub.subscription_count("H0STCNI0", STUB_HTS_ID) == 2));
+    stub.fill(&exchange_orders[0].order_no, 6, dec!(70000)).unwrap();
+    assert!(wait_for(|| state_of(&engine, &partial) == Some(OrderState::FILLED)));
+
+    engine.cancel_order_internal(resting.clone()).unwrap();
+    let cancel = stub.requests().into_iter().filter(|r| r.path.ends_with("order-rvsecncl")).last().unwrap();
+    assert_eq!(cancel.body["ORGN_ODNO"], exchange_orders[1].order_no.as_str());
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_recovery_reattaches_strategies() {
+    let path = journal_path("strategy");
+    let engine = engine_with(Arc::new(MockAdapter::new()), &path);
+    let mut order = limit("TEST", OrderSide::BUY, 3, dec!(100));
+    order.strategy = ExecutionStrategy::LIMIT;
+    let oid = engine.send_order_internal(order).unwrap();
+    let done = engine.send_order_internal(limit("TEST", OrderSide::SELL, 0, dec!(100))).unwrap();
+    assert_eq!(state_of(&engine, &done), Some(OrderState::REJECTED));
+    assert!(wait_for(|| state_of(&engine, &oid) == Some(OrderState::NEW)));
+    drop(engine);
+
+    let engine = engine_with(Arc::new(MockAdapter::new()), &path);
+    assert_eq!(engine.recover_from_journal().unwrap(), vec![oid.clone()]);
+    assert_eq!(engine.get_active_strategy_order_ids(), vec![oid.clone()]);
+    // Terminal orders are kept for reference but get no strategy
+    assert_eq!(state_of(&engine, &done), Some(OrderState::REJECTED));
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_journal_tolerates_torn_write_and_compacts() {
+    let path = journal_path("torn");
+    let engine = engine_with(Arc::new(MockAdapter::new()), &path);
+    let keep = engine.send_order_internal(limit("TEST", OrderSide::BUY, 1, dec!(100))).unwrap();
+    let gone = engine.send_order_internal(limit("TEST", OrderSide::BUY, 1, dec!(99))).unwrap();
+    engine.remove_order_internal(gone.clone()).unwrap();
+    drop(engine);
+
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"seq":99,"timestamp":1.0,"event":"Order","order":{"sym"#).unwrap();
+    drop(file);
+    let before = fs::read_to_string(&path).unwrap().lines().count();
+
+    let journal = OrderJournal::open(&path).unwrap();
+    let state = journal.recover().unwrap();
+    assert!(state.orders.contains_key(&keep));
+    assert!(!state.orders.contains_key(&gone));
+
+    let engine = engine_with(Arc::new(MockAdapter::new()), &path);
+    engine.recover_from_journal().unwrap();
+    let after = fs::read_to_string(&path).unwrap();
+    assert!(after.lines().count() < before);
+    assert!(after.contains(&keep) && !after.contains(&gone));
+
+    // Appends continue after compaction
+    engine.send_order_internal(limit("TEST", OrderSide::SELL, 1, dec!(101))).unwrap();
+    assert_eq!(OrderJournal::open(&path).unwrap().recover().unwrap().orders.len(), 2);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_compaction_drops_closed_orders() {
+    let path = journal_path("closed");
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = engine_with(adapter.clone(), &path);
+    let working = engine.send_order_internal(limit("TEST", OrderSide::BUY, 1, dec!(100))).unwrap();
+    let canceled = engine.send_order_internal(limit("TEST", OrderSide::BUY, 1, dec!(99))).unwrap();
+    assert!(wait_for(|| state_of(&engine, &canceled) == Some(OrderState::NEW)));
+    engine.cancel_order_internal(canceled.clone()).unwrap();
+    assert!(wait_for(|| state_of(&engine, &canceled) == Some(OrderState::CANCELED)));
+
+    // A closed child of a working parent stays: the parent's strategy takes over its fills
+    let mut parent = limit("TEST", OrderSide::BUY, 10, dec!(100));
+    parent.strategy = ExecutionStrategy::TWAP;
+    parent.strategy_params.insert("duration_seconds".to_string(), "60".to_string());
+    let parent = engine.send_order_internal(parent).unwrap();
+    let mut child = limit("TEST", OrderSide::BUY, 1, dec!(100));
+    child.parent_order_id = Some(parent.clone());
+    let child = engine.send_order_internal(child).unwrap();
+    assert!(wait_for(|| state_of(&engine, &child) == Some(OrderState::NEW)));
+    engine.cancel_order_internal(child.clone()).unwrap();
+    assert!(wait_for(|| state_of(&engine, &child) == Some(OrderState::CANCELED)));
+    drop(engine);
+
+    let engine = engine_with(Arc::new(MockAdapter::new()), &path);
+    engine.recover_from_journal().unwrap();
+    let state = OrderJournal::open(&path).unwrap().recover().unwrap();
+    assert!(state.orders.contains_key(&working) && state.orders.contains_key(&parent) && state.orders.contains_key(&child));
+    assert!(!state.orders.contains_key(&canceled));
+    assert!(!fs::read_to_string(&path).unwrap().contains(&canceled));
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use common::wait_until;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::message::ConnectionStatus;
+use didius::oms::account::{AccountState, Position};
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::market_maker::MarketMakerStrategy;
+use didius::strategy::registry::StrategyRegistry;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn market_maker(qty: i64, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let mut o = Order::new("TEST".to_string(), OrderSide::BUY, OrderType::MARKET, qty, None, Some(ExecutionStrategy::MM), Some(params), None, "KRX".to_string(), Some("default".to_string()));
+    o.order_id = Some("P".to_string());
+    o
+}
+
+fn strategy(params: &[(&str, &str)]) -> MarketMakerStrategy {
+    MarketMakerStrategy::from_order(&market_maker(10, params)).unwrap()
+}
+
+fn book(bid: (Decimal, i64), ask: (Decimal, i64)) -> OrderBook {
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(vec![bid], vec![ask], 1, 0.0);
+    book
+}
+
+fn account(position: i64) -> AccountState {
+    let mut account = AccountState::new();
+    account.positions.insert("TEST".to_string(), Position::new("TEST".to_string(), position, dec!(100), dec!(100)));
+    account
+}
+
+/// Quotes placed by `action`, as (order ID, side, price, quantity).
+fn placed(action: &StrategyAction) -> Vec<(String, OrderSide, Decimal, i64)> {
+    let actions = match action {
+        StrategyAction::Each(actions) => actions.clone(),
+        other => vec![other.clone()],
+    };
+    actions.into_iter().filter_map(|a| match a {
+        StrategyAction::PlaceOrder(o) => Some((o.order_id.unwrap(), o.side, o.price.unwrap(), o.quantity)),
+        _ => None,
+    }).collect()
+}
+
+/// Quotes canceled by `action`.
+fn canceled(action: &StrategyAction) -> Vec<String> {
+    let actions = match action {
+        StrategyAction::Each(actions) => actions.clone(),
+        other => vec![other.clone()],
+    };
+    actions.into_iter().filter_map(|a| match a {
+        StrategyAction::CancelOrder(id) => Some(id),
+        _ => None,
+    }).collect()
+}
+
+/// Play the venue's answer to a quote.
+fn update(strat: &mut MarketMakerStrategy, id: &str, side: OrderSide, state: OrderState) -> StrategyAction {
+    let mut order = Order::new("TEST".to_string(), side, OrderType::LIMIT, 10, Some("100".to_string()), None, None, None, "KRX".to_string(), None);
+    order.order_id = Some(id.to_string());
+    order.state = state;
+    strat.on_order_status_update(&order).unwrap()
+}
+
+/// Staleness runs off the local clock, which the callbacks without a time read.
+fn now() -> f64 {
+    chrono::Local::now().timestamp_millis() as f64 / 1000.0
+}
+
+#[test]
+fn test_quotes_skew_and_widen() {
+    let t = now();
+    let params = [("max_inventory", "30"), ("half_spread_ticks", "2"), ("skew_ticks", "3"), ("volatility_multiplier", "0")];
+    let mut strat = strategy(&params);
+    // Nothing goes out before the first timer tick
+    assert!(placed(&strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t)).is_empty());
+    assert_eq!(placed(&strat.on_timer_at(t)), vec![
+        ("P-1".to_string(), OrderSide::BUY, dec!(98), 10),
+        ("P-2".to_string(), OrderSide::SELL, dec!(102), 10),
+    ]);
+
+    // Long 25: both quotes shift down by 25/30 of the skew; the bid shrinks to what the cap leaves
+    strat.on_account_update(&account(25)).unwrap();
+    assert_eq!(strat.inventory, 25);
+    assert_eq!(strat.targets(t), (Some((dec!(95), 5)), Some((dec!(100), 10))));
+    // At the cap the bid is pulled and the ask stays clear of the bid
+    strat.on_account_update(&account(30)).unwrap();
+    assert_eq!(strat.targets(t), (None, Some((dec!(100), 10))));
+    strat.on_account_update(&account(-12)).unwrap();
+    assert_eq!(strat.targets(t), (Some((dec!(99), 10)), Some((dec!(104), 10))));
+
+    // Microprice leans toward the thin side
+    let mut micro = strategy(&[("max_inventory", "30"), ("fair_value", "MICROPRICE"), ("volatility_multiplier", "0")]);
+    micro.on_book_at(&book((dec!(99), 10), (dec!(103), 30)), t);
+    assert_eq!(micro.fair_value(), Some(dec!(100)));
+
+    // A volatile book quotes wider than a calm one
+    let mut calm = strategy(&[("max_inventory", "30")]);
+    let mut volatile = strategy(&[("max_inventory", "30")]);
+    for (i, mid) in [100, 100, 100].into_iter().enumerate() {
+        let mut b = book((Decimal::from(mid - 1), 10), (Decimal::from(mid + 1), 10));
+        b.timestamp = i as f64;
+        calm.on_book_at(&b, t);
+    }
+    for (i, mid) in [100, 104, 100].into_iter().enumerate() {
+        let mut b = book((Decimal::from(mid - 1), 10), (Decimal::from(mid + 1), 10));
+        b.timestamp = i as f64;
+        volatile.on_book_at(&b, t);
+    }
+    calm.on_timer_at(t);
+    volatile.on_timer_at(t);
+    let (calm_bid, calm_ask) = calm.targets(t);
+    let (volatile_bid, volatile_ask) = volatile.targets(t);
+    assert_eq!((calm_bid.unwrap().0, calm_ask.unwrap().0), (dec!(99), dec!(101)));
+    assert!(volatile_bid.unwrap().0 < dec!(99) && volatile_ask.unwrap().0 > dec!(101));
+}
+
+#[test]
+fn test_cancel_replace_waits_for_cancel() {
+    let t = now();
+    let mut strat = strategy(&[("max_inventory", "30"), ("volatility_multiplier", "0")]);
+    strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t);
+    strat.on_timer_at(t);
+
+    // A move before the acknowledgements waits for them
+    assert!(matches!(strat.on_book_at(&book((dec!(101), 10), (dec!(103), 10)), t), StrategyAction::None));
+    assert_eq!(canceled(&update(&mut strat, "P-1", OrderSide::BUY, OrderState::NEW)), vec!["P-1"]);
+    assert_eq!(canceled(&update(&mut strat, "P-2", OrderSide::SELL, OrderState::NEW)), vec!["P-2"]);
+    assert!(strat.bid.cancel_requested() && strat.ask.cancel_requested());
+
+    // Each replacement goes out once its cancel is confirmed
+    assert_eq!(placed(&update(&mut strat, "P-1", OrderSide::BUY, OrderState::CANCELED)), vec![("P-3".to_string(), OrderSide::BUY, dec!(101), 10)]);
+    assert_eq!(strat.ask.live_order_id(), Some("P-2"));
+    assert_eq!(placed(&update(&mut strat, "P-2", OrderSide::SELL, OrderState::CANCELED)), vec![("P-4".to_string(), OrderSide::SELL, dec!(103), 10)]);
+
+    // A rejected quote stops the strategy, which pulls the other side and completes
+    assert!(matches!(update(&mut strat, "P-3", OrderSide::BUY, OrderState::REJECTED), StrategyAction::None));
+    assert_eq!(canceled(&update(&mut strat, "P-4", OrderSide::SELL, OrderState::NEW)), vec!["P-4"]);
+    assert!(!strat.is_completed());
+    update(&mut strat, "P-4", OrderSide::SELL, OrderState::CANCELED);
+    assert!(strat.is_completed());
+
+    let registry = StrategyRegistry::default();
+    let err = |p: &[(&str, &str)]| registry.create(&market_maker(10, p), false).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(&[]).contains("MM requires max_inventory"));
+    assert!(err(&[("max_inventory", "10"), ("tick_size", "0")]).contains("tick_size"));
+    assert!(err(&[("max_inventory", "10"), ("fair_value", "LAST")]).contains("fair_value"));
+}
+
+#[test]
+fn test_pulls_on_stale_book_and_disconnect() {
+    let t = now();
+    let mut strat = strategy(&[("max_inventory", "30"), ("stale_seconds", "2")]);
+    strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t);
+    strat.on_timer_at(t);
+    update(&mut strat, "P-1", OrderSide::BUY, OrderState::NEW);
+    update(&mut strat, "P-2", OrderSide::SELL, OrderState::NEW);
+
+    assert!(matches!(strat.on_timer_at(t + 1.0), StrategyAction::None));
+    assert_eq!(canceled(&strat.on_timer_at(t + 3.0)), vec!["P-1", "P-2"]);
+    update(&mut strat, "P-1", OrderSide::BUY, OrderState::CANCELED);
+    update(&mut strat, "P-2", OrderSide::SELL, OrderState::CANCELED);
+    assert_eq!(placed(&strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t + 4.0)).len(), 2);
+    update(&mut strat, "P-3", OrderSide::BUY, OrderState::NEW);
+    update(&mut strat, "P-4", OrderSide::SELL, OrderState::NEW);
+
+    // Another venue going down changes nothing
+    assert!(matches!(strat.on_connection_status("other", &ConnectionStatus::Disconnected).unwrap(), StrategyAction::None));
+    assert_eq!(canceled(&strat.on_connection_status("default", &ConnectionStatus::Disconnected).unwrap()), vec!["P-3", "P-4"]);
+    update(&mut strat, "P-3", OrderSide::BUY, OrderState::CANCELED);
+    update(&mut strat, "P-4", OrderSide::SELL, OrderState::CANCELED);
+    // Back up, but quoting waits for a fresh book
+    assert!(matches!(strat.on_connection_status("default", &ConnectionStatus::Connected).unwrap(), StrategyAction::None));
+    assert_eq!(placed(&strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t + 5.0)).len(), 2);
+}
+
+#[test]
+fn test_refused_cancel_is_sent_again() {
+    let t = now();
+    let mut strat = strategy(&[("max_inventory", "30"), ("stale_seconds", "2")]);
+    strat.on_book_at(&book((dec!(99), 10), (dec!(101), 10)), t);
+    strat.on_timer_at(t);
+    update(&mut strat, "P-1", OrderSide::BUY, OrderState::NEW);
+    update(&mut strat, "P-2", OrderSide::SELL, OrderState::NEW);
+    assert_eq!(canceled(&strat.on_timer_at(t + 3.0)), vec!["P-1", "P-2"]);
+
+    // The venue refuses the bid's cancel and the engine puts it back to NEW: the pull goes out again
+    update(&mut strat, "P-2", OrderSide::SELL, OrderState::PENDING_CANCEL);
+    let action = update(&mut strat, "P-1", OrderSide::BUY, OrderState::NEW);
+    assert!(strat.ask.cancel_requested());
+    assert_eq!([canceled(&action), canceled(&strat.on_timer_at(t + 4.0))].concat(), vec!["P-1"]);
+    assert!(strat.bid.cancel_requested());
+}
+
+#[test]
+fn test_engine_quotes_and_caps_inventory() {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(98), 100)], asks: vec![(dec!(102), 100)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let mut order = market_maker(5, &[("max_inventory", "5"), ("volatility_multiplier", "0")]);
+    order.order_id = None;
+    let parent = engine.send_order_internal(order).unwrap();
+    let quote = |side: OrderSide| engine.get_child_orders(&parent).into_iter().rev().find(|o| o.side == side && o.is_active());
+    assert!(wait_until(|| quote(OrderSide::BUY).is_some_and(|o| o.state == OrderState::NEW) && quote(OrderSide::SELL).is_some_and(|o| o.state == OrderState::NEW)));
+    assert_eq!(quote(OrderSide::BUY).unwrap().price, Some(dec!(99)));
+    assert_eq!(quote(OrderSide::SELL).unwrap().price, Some(dec!(101)));
+
+    // Someone sells through our bid: long 5 is the cap, so only the ask is quoted, skewed down
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(97), 100)], asks: vec![(dec!(99), 100)], update_id: 2, timestamp: 2.0 });
+    assert!(wait_until(|| engine.get_account().positions.get("TEST").is_some_and(|p| p.quantity == 5)));
+    assert!(wait_until(|| quote(OrderSide::SELL).is_some_and(|o| o.price == Some(dec!(98)) && o.state == OrderState::NEW)));
+    thread::sleep(Duration::from_millis(200));
+    assert!(quote(OrderSide::BUY).is_none());
+    assert_eq!(engine.get_orders()[&parent].filled_quantity, 0, "quotes do not fill the parent");
+
+    // A dropped gateway pulls the quotes
+    adapter.push_connection_status(ConnectionStatus::Disconnected);
+    assert!(wait_until(|| quote(OrderSide::SELL).is_none()));
+
+    engine.cancel_order_internal(parent.clone()).unwrap();
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    engine.stop_internal().unwrap();
+}
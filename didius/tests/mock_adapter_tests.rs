@@ -0,0 +1,209 @@
+mod common;
+
+use common::{limit_id, setup, wait_for_state};
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::{Adapter, IncomingMessage};
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn market(id: &str, symbol: &str, side: OrderSide, qty: i64) -> Order {
+    let mut o = Order::new(symbol.to_string(), side, OrderType::MARKET, qty, None, None, None, None, "KRX".to_string(), None);
+    o.order_id = Some(id.to_string());
+    o
+}
+
+fn snapshot(symbol: &str, bids: Vec<(Decimal, i64)>, asks: Vec<(Decimal, i64)>) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: symbol.to_string(), bids, asks, update_id: 1, timestamp: 1.0 }
+}
+
+fn executions(rx: &mpsc::Receiver<IncomingMessage>) -> Vec<(String, i64, Decimal)> {
+    rx.try_iter()
+        .filter_map(|m| match m {
+            IncomingMessage::Execution { order_id, fill_qty, fill_price } => Some((order_id, fill_qty, fill_price)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_mock_limit_orders_cross() {
+    let adapter = MockAdapter::new();
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+
+    assert!(adapter.place_order(&limit_id("s1", "TEST", OrderSide::SELL, 10, dec!(100))).unwrap());
+    assert!(executions(&rx).is_empty());
+
+    // Aggressive buy trades at the resting price
+    assert!(adapter.place_order(&limit_id("b1", "TEST", OrderSide::BUY, 4, dec!(101))).unwrap());
+    let fills = executions(&rx);
+    assert_eq!(fills, vec![
+        ("s1".to_string(), 4, dec!(100)),
+        ("b1".to_string(), 4, dec!(100)),
+    ]);
+    assert_eq!(adapter.get_open_quantity("s1"), Some(6));
+    assert_eq!(adapter.get_open_quantity("b1"), None);
+}
+
+#[test]
+fn test_mock_price_time_priority() {
+    let adapter = MockAdapter::new();
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+
+    adapter.place_order(&limit_id("late_better", "TEST", OrderSide::SELL, 5, dec!(99))).unwrap();
+    adapter.place_order(&limit_id("first", "TEST", OrderSide::SELL, 5, dec!(100))).unwrap();
+    adapter.place_order(&limit_id("second", "TEST", OrderSide::SELL, 5, dec!(100))).unwrap();
+    let _ = executions(&rx);
+
+    adapter.place_order(&market("b1", "TEST", OrderSide::BUY, 8)).unwrap();
+    let fills: Vec<_> = executions(&rx).into_iter().filter(|(id, _, _)| id != "b1").collect();
+    assert_eq!(fills, vec![
+        ("late_better".to_string(), 5, dec!(99)),
+        ("first".to_string(), 3, dec!(100)),
+    ]);
+    assert_eq!(adapter.get_open_quantity("first"), Some(2));
+    assert_eq!(adapter.get_open_quantity("second"), Some(5));
+}
+
+#[test]
+fn test_mock_market_order_sweeps_depth() {
+    let adapter = MockAdapter::new();
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(99), 10)], vec![(dec!(100), 5), (dec!(101), 5)]));
+    adapter.place_order(&market("b1", "TEST", OrderSide::BUY, 8)).unwrap();
+    assert_eq!(executions(&rx), vec![
+        ("b1".to_string(), 5, dec!(100)),
+        ("b1".to_string(), 3, dec!(101)),
+    ]);
+
+    let book = adapter.get_order_book_snapshot("TEST").unwrap();
+    assert_eq!(book.get_best_ask(), Some((dec!(101), 2)));
+
+    // Market order larger than the book: remainder expires
+    adapter.place_order(&market("b2", "TEST", OrderSide::BUY, 5)).unwrap();
+    let msgs: Vec<_> = rx.try_iter().collect();
+    assert!(msgs.iter().any(|m| matches!(m, IncomingMessage::Execution { order_id, fill_qty: 2, .. } if order_id == "b2")));
+    assert!(msgs.iter().any(|m| matches!(m, IncomingMessage::OrderStatus { order_id, state: OrderState::CANCELED, filled_qty: 2, .. } if order_id == "b2")));
+}
+
+#[test]
+fn test_mock_depth_crosses_resting_order() {
+    let adapter = MockAdapter::new();
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+
+    adapter.place_order(&limit_id("b1", "TEST", OrderSide::BUY, 10, dec!(100))).unwrap();
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(98), 10)], vec![(dec!(99), 4), (dec!(100), 3), (dec!(101), 50)]));
+    assert_eq!(executions(&rx), vec![
+        ("b1".to_string(), 4, dec!(100)),
+        ("b1".to_string(), 3, dec!(100)),
+    ]);
+    assert_eq!(adapter.get_open_quantity("b1"), Some(3));
+}
+
+#[test]
+fn test_mock_cancel_and_modify() {
+    let adapter = MockAdapter::new();
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(99), 10)], vec![(dec!(101), 10)]));
+    adapter.place_order(&limit_id("s1", "TEST", OrderSide::SELL, 5, dec!(105))).unwrap();
+    adapter.place_order(&limit_id("s2", "TEST", OrderSide::SELL, 5, dec!(106))).unwrap();
+
+    assert!(adapter.cancel_order("s1").unwrap());
+    assert!(!adapter.cancel_order("s1").unwrap());
+    assert!(adapter.cancel_order("unknown").is_err());
+
+    // Re-price s2 through the bid: fills against depth
+    assert!(adapter.modify_order("s2", Some(dec!(99)), Some(5)).unwrap());
+    let msgs: Vec<_> = rx.try_iter().collect();
+    assert!(msgs.iter().any(|m| matches!(m, IncomingMessage::OrderStatus { order_id, state: OrderState::CANCELED, .. } if order_id == "s1")));
+    assert!(msgs.iter().any(|m| matches!(m, IncomingMessage::Execution { order_id, fill_qty: 5, fill_price, .. } if order_id == "s2" && *fill_price == dec!(99))));
+}
+
+#[test]
+fn test_engine_limit_fill_updates_account() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+
+    let mut order = limit_id("", "TEST", OrderSide::BUY, 10, dec!(100));
+    order.order_id = None;
+    order.strategy = ExecutionStrategy::LIMIT;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::NEW));
+
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(98), 10)], vec![(dec!(100), 4)]));
+    assert!(wait_for_state(&engine, &oid, OrderState::PARTIALLY_FILLED));
+
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(98), 10)], vec![(dec!(99), 20)]));
+    assert!(wait_for_state(&engine, &oid, OrderState::FILLED));
+
+    let order = engine.get_orders().get(&oid).cloned().unwrap();
+    assert_eq!(order.filled_quantity, 10);
+    assert_eq!(order.average_fill_price, dec!(100));
+
+    let acct = engine.get_account();
+    assert_eq!(acct.positions.get("TEST").unwrap().quantity, 10);
+    assert_eq!(acct.balance, dec!(-1000));
+    assert_eq!(adapter.get_account_snapshot("").unwrap().positions.get("TEST").unwrap().quantity, 10);
+}
+
+#[test]
+fn test_engine_stop_order_triggers_to_market() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(99), 10)], vec![(dec!(101), 10)]));
+
+    // Passive stop-sell resting above the market, trigger when ask <= 95
+    let mut params = HashMap::new();
+    params.insert("trigger_price".to_string(), "95".to_string());
+    let mut order = limit_id("", "TEST", OrderSide::SELL, 3, dec!(110));
+    order.order_id = None;
+    order.strategy = ExecutionStrategy::STOP;
+    order.strategy_params = params;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::NEW));
+
+    adapter.push_order_book(snapshot("TEST", vec![(dec!(94), 10)], vec![(dec!(95), 10)]));
+
+    // StopStrategy removes the order from the OMS once filled
+    for _ in 0..100 {
+        if !engine.get_orders().contains_key(&oid) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!engine.get_orders().contains_key(&oid));
+    assert_eq!(engine.get_account().positions.get("TEST").unwrap().quantity, -3);
+}
+
+#[test]
+fn test_engine_exchange_reject() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.set_reject_reason(Some("Insufficient balance".to_string()));
+
+    let mut order = limit_id("", "TEST", OrderSide::BUY, 1, dec!(100));
+    order.order_id = None;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::REJECTED));
+    assert_eq!(engine.get_orders().get(&oid).unwrap().error_message.as_deref(), Some("Insufficient balance"));
+
+    // Invalid request: rejected synchronously
+    adapter.set_reject_reason(None);
+    let mut order = limit_id("", "TEST", OrderSide::BUY, 0, dec!(100));
+    order.order_id = None;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert_eq!(engine.get_orders().get(&oid).unwrap().state, OrderState::REJECTED);
+}
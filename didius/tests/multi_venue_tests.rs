@@ -0,0 +1,202 @@
+mod common;
+
+use common::{limit, on_venue, wait_for_state, wait_until};
+use didius::adapter::hantoo::HantooAdapter;
+use didius::adapter::hantoo_ngt_futopt::HantooNightAdapter;
+use didius::adapter::hantoo_stub::HantooStubServer;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{OrderSide, OrderState};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::oms::risk::RiskLimits;
+use didius::strategy::base::{Strategy, StrategyAction};
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn logger() -> Arc<Mutex<Logger>> {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    Arc::new(Mutex::new(Logger::new(config)))
+}
+
+fn snapshot(symbol: &str, bid: Decimal, ask: Decimal) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: symbol.to_string(), bids: vec![(bid, 10)], asks: vec![(ask, 10)], update_id: 1, timestamp: 1.0 }
+}
+
+fn mock_engine() -> (OMSEngine, Arc<MockAdapter>, Arc<MockAdapter>) {
+    let day = Arc::new(MockAdapter::new());
+    let night = Arc::new(MockAdapter::new());
+    let mut adapters: HashMap<String, Arc<dyn Adapter>> = HashMap::new();
+    adapters.insert("day".to_string(), day.clone());
+    adapters.insert("night".to_string(), night.clone());
+    let engine = OMSEngine::with_venues(adapters, "day", logger());
+    engine.start_venue_gateways().unwrap();
+    (engine, day, night)
+}
+
+#[test]
+fn test_orders_routed_by_venue() {
+    let (engine, day, night) = mock_engine();
+
+    let day_id = engine.send_order_internal(limit("005930", OrderSide::BUY, 5, dec!(70000))).unwrap();
+    let night_id = engine.send_order_internal(on_venue(limit("A05602", OrderSide::SELL, 2, dec!(350)), "night")).unwrap();
+    assert!(wait_for_state(&engine, &day_id, OrderState::NEW));
+    assert!(wait_for_state(&engine, &night_id, OrderState::NEW));
+
+    // Missing venue resolves to the default one
+    assert_eq!(engine.get_orders().get(&day_id).unwrap().venue, "day");
+    assert_eq!(day.get_open_quantity(&day_id), Some(5));
+    assert_eq!(day.get_open_quantity(&night_id), None);
+    assert_eq!(night.get_open_quantity(&night_id), Some(2));
+
+    // Modify and cancel follow the order to its venue
+    engine.modify_order_internal(night_id.clone(), Some(dec!(351))).unwrap();
+    assert_eq!(night.get_open_quantity(&night_id), Some(2));
+    engine.cancel_order_internal(day_id.clone()).unwrap();
+    assert!(wait_for_state(&engine, &day_id, OrderState::CANCELED));
+    assert_eq!(night.get_open_quantity(&night_id), Some(2));
+
+    // Unknown venue is refused before anything is sent
+    let err = engine.send_order_internal(on_venue(limit("005930", OrderSide::BUY, 1, dec!(70000)), "nowhere")).unwrap_err();
+    assert!(err.to_string().contains("Unknown venue"));
+}
+
+#[test]
+fn test_merged_streams_tag_books_and_fills() {
+    let (engine, day, night) = mock_engine();
+
+    day.push_order_book(snapshot("005930", dec!(69900), dec!(70000)));
+    night.push_order_book(snapshot("A05602", dec!(349.5), dec!(350)));
+    for _ in 0..200 {
+        if engine.get_order_book("005930").is_some() && engine.get_order_book("A05602").is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(engine.get_order_book("005930").unwrap().venue, "day");
+    assert_eq!(engine.get_order_book("A05602").unwrap().venue, "night");
+
+    let oid = engine.send_order_internal(on_venue(limit("A05602", OrderSide::BUY, 3, dec!(350)), "night")).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::FILLED));
+    // The fill is booked to the account at the venue it traded on
+    let night_account = engine.get_venue_account("night");
+    assert_eq!((night_account.venue.as_str(), night_account.positions.get("A05602").unwrap().quantity), ("night", 3));
+    assert!(engine.get_account().positions.is_empty());
+}
+
+#[test]
+fn test_books_and_accounts_kept_per_venue() {
+    let (engine, day, night) = mock_engine();
+    day.push_order_book(snapshot("KOSPI", dec!(100), dec!(101)));
+    night.push_order_book(snapshot("KOSPI", dec!(200), dec!(201)));
+    for _ in 0..200 {
+        if engine.get_venue_order_book("day", "KOSPI").is_some() && engine.get_venue_order_book("night", "KOSPI").is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    // One symbol on two venues: two books, the default venue's by symbol alone
+    assert_eq!(engine.get_venue_order_book("day", "KOSPI").unwrap().get_best_bid().map(|b| b.0), Some(dec!(100)));
+    assert_eq!(engine.get_venue_order_book("night", "KOSPI").unwrap().get_best_bid().map(|b| b.0), Some(dec!(200)));
+    assert_eq!(engine.get_order_book("KOSPI").unwrap().venue, "day");
+
+    let mut limits = RiskLimits::default();
+    limits.max_position.insert("KOSPI".to_string(), 3);
+    engine.set_risk_limits(limits);
+    let night_buy = engine.send_order_internal(on_venue(limit("KOSPI", OrderSide::BUY, 3, dec!(201)), "night")).unwrap();
+    assert!(wait_for_state(&engine, &night_buy, OrderState::FILLED));
+    assert_eq!(engine.get_venue_account("night").positions.get("KOSPI").unwrap().quantity, 3);
+
+    // The night position counts against the night account only
+    let day_buy = engine.send_order_internal(limit("KOSPI", OrderSide::BUY, 3, dec!(100))).unwrap();
+    assert!(wait_for_state(&engine, &day_buy, OrderState::NEW));
+    let over = engine.send_order_internal(on_venue(limit("KOSPI", OrderSide::BUY, 1, dec!(200)), "night")).unwrap();
+    assert_eq!(engine.get_orders().get(&over).unwrap().state, OrderState::REJECTED);
+}
+
+/// Records the sizes of the prints it gets.
+struct Tape {
+    venue: String,
+    prints: Arc<Mutex<Vec<i64>>>,
+}
+
+impl Strategy for Tape {
+    fn on_order_book_update(&mut self, _book: &OrderBook) -> anyhow::Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, quantity: i64, _timestamp: f64) -> anyhow::Result<StrategyAction> {
+        self.prints.lock().unwrap().push(quantity);
+        Ok(StrategyAction::None)
+    }
+
+    fn venue(&self) -> Option<&str> {
+        Some(&self.venue)
+    }
+}
+
+#[test]
+fn test_trades_kept_per_venue() {
+    let (engine, day, night) = mock_engine();
+    day.push_order_book(snapshot("KOSPI", dec!(100), dec!(101)));
+    night.push_order_book(snapshot("KOSPI", dec!(100), dec!(101)));
+    assert!(wait_until(|| engine.get_venue_order_book("day", "KOSPI").is_some() && engine.get_venue_order_book("night", "KOSPI").is_some()));
+    let prints = Arc::new(Mutex::new(Vec::new()));
+    engine.add_strategy(Box::new(Tape { venue: "day".to_string(), prints: prints.clone() }));
+
+    let oid = engine.send_order_internal(limit("KOSPI", OrderSide::BUY, 3, dec!(100))).unwrap();
+    assert!(wait_until(|| engine.get_queue_position(&oid).is_some()));
+    assert_eq!(engine.get_queue_position(&oid).unwrap().queue_ahead, 7.0);
+
+    // The same symbol printing on the night venue moves neither the day queue nor the day strategy
+    night.push_trade("KOSPI", dec!(100), 4);
+    day.push_trade("KOSPI", dec!(100), 5);
+    assert!(wait_until(|| !prints.lock().unwrap().is_empty()));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(*prints.lock().unwrap(), vec![5]);
+    assert_eq!(engine.get_queue_position(&oid).unwrap().queue_ahead, 2.0);
+}
+
+#[test]
+fn test_hantoo_day_and_night_in_one_engine() {
+    let stub = HantooStubServer::start().unwrap();
+    let auth = std::env::temp_dir().join(format!("didius_multi_venue_{}", std::process::id()));
+    let auth = auth.to_string_lossy().to_string();
+
+    let day = Arc::new(HantooAdapter::from_config(stub.config(&auth)));
+    let night = Arc::new(HantooNightAdapter::from_config(stub.config(&auth)));
+    let mut adapters: HashMap<String, Arc<dyn Adapter>> = HashMap::new();
+    adapters.insert("hantoo".to_string(), day.clone());
+    adapters.insert("hantoo_night".to_string(), night.clone());
+    let engine = OMSEngine::with_venues(adapters, "hantoo", logger());
+    engine.start_venue_gateways().unwrap();
+
+    day.subscribe(&["005930".to_string()]).unwrap();
+    engine.start_internal(None).unwrap();
+    Adapter::subscribe(night.as_ref(), &["A05602".to_string()]).unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", "stubuser", Duration::from_secs(2)));
+    assert!(stub.wait_for_subscription("H0MFCNI0", "stubuser", Duration::from_secs(2)));
+
+    let stock = engine.send_order_internal(on_venue(limit("005930", OrderSide::BUY, 1, dec!(70000)), "hantoo")).unwrap();
+    let future = engine.send_order_internal(on_venue(limit("A05602", OrderSide::BUY, 1, dec!(350)), "hantoo_night")).unwrap();
+    assert!(wait_for_state(&engine, &stock, OrderState::NEW));
+    assert!(wait_for_state(&engine, &future, OrderState::NEW));
+
+    let paths: Vec<_> = stub.requests().into_iter().map(|r| r.path).filter(|p| p.contains("/trading/order")).collect();
+    assert_eq!(paths, vec![
+        "/uapi/domestic-stock/v1/trading/order-cash".to_string(),
+        "/uapi/domestic-futureoption/v1/trading/order".to_string(),
+    ]);
+
+    let orders = stub.orders();
+    stub.fill(&orders[1].order_no, 1, dec!(350)).unwrap();
+    assert!(wait_for_state(&engine, &future, OrderState::FILLED));
+    assert_eq!(engine.get_orders().get(&stock).unwrap().state, OrderState::NEW);
+    engine.stop_internal().unwrap();
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{limit, setup_logged, wait_for_state};
+use didius::adapter::mock::MockAdapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::message::Message;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use didius::state::State;
+use rust_decimal::dec;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+fn illegal_transitions(log_path: &str) -> Vec<serde_json::Value> {
+    fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "ILLEGAL_TRANSITION")
+        .map(|v| v["log_body"].clone())
+        .collect()
+}
+
+#[test]
+fn test_transition_table() {
+    use OrderState::*;
+    assert!(CREATED.can_transition_to(&PENDING_NEW));
+    assert!(PENDING_NEW.can_transition_to(&NEW));
+    assert!(NEW.can_transition_to(&PARTIALLY_FILLED));
+    assert!(PARTIALLY_FILLED.can_transition_to(&PARTIALLY_FILLED));
+    assert!(PARTIALLY_FILLED.can_transition_to(&FILLED));
+    assert!(PENDING_CANCEL.can_transition_to(&FILLED));
+    assert!(PENDING_REPLACE.can_transition_to(&NEW));
+    assert!(CANCELED.can_transition_to(&CANCELED));
+
+    assert!(!FILLED.can_transition_to(&NEW));
+    assert!(!CANCELED.can_transition_to(&PARTIALLY_FILLED));
+    assert!(!REJECTED.can_transition_to(&NEW));
+    assert!(!PARTIALLY_FILLED.can_transition_to(&NEW));
+    assert!(!CREATED.can_transition_to(&PENDING_CANCEL));
+    // A cancel can go out before the acknowledgement, which then does not undo it
+    assert!(PENDING_NEW.can_transition_to(&PENDING_CANCEL));
+    assert!(!PENDING_CANCEL.can_transition_to(&NEW));
+    assert!(FILLED.is_terminal() && !PENDING_CANCEL.is_terminal());
+
+    let mut order = limit("TEST", OrderSide::BUY, 1, dec!(100));
+    order.update_state(PENDING_NEW, None).unwrap();
+    order.update_state(FILLED, None).unwrap();
+    let err = order.update_state(NEW, Some("late ack".to_string())).unwrap_err();
+    assert_eq!((err.from, err.to), (FILLED, NEW));
+    assert_eq!(order.state, FILLED);
+    assert_eq!(order.error_message, None);
+}
+
+#[test]
+fn test_late_notices_do_not_reopen_orders() {
+    let log_path = "tests/order_state_late.jsonl";
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup_logged(adapter.clone(), log_path);
+    engine.start_internal(None).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 10)], asks: vec![(dec!(101), 10)], update_id: 1, timestamp: 1.0 });
+
+    let filled = engine.send_order_internal(limit("TEST", OrderSide::BUY, 2, dec!(101))).unwrap();
+    assert!(wait_for_state(&engine, &filled, OrderState::FILLED));
+    engine.on_order_status_update(&filled, OrderState::NEW, None);
+    assert_eq!(engine.get_orders().get(&filled).unwrap().state, OrderState::FILLED);
+    assert!(engine.cancel_order_internal(filled.clone()).is_err());
+    assert!(engine.modify_order_internal(filled.clone(), Some(dec!(100))).is_ok());
+
+    let canceled = engine.send_order_internal(limit("TEST", OrderSide::BUY, 5, dec!(98))).unwrap();
+    assert!(wait_for_state(&engine, &canceled, OrderState::NEW));
+    engine.cancel_order_internal(canceled.clone()).unwrap();
+    assert!(wait_for_state(&engine, &canceled, OrderState::CANCELED));
+    assert!(engine.modify_order_internal(canceled.clone(), Some(dec!(97))).is_err());
+
+    // A fill reported after the cancel is booked, but the order stays closed
+    engine.on_trade_update(&canceled, 1, dec!(98));
+    let order = engine.get_orders().get(&canceled).cloned().unwrap();
+    assert_eq!(order.state, OrderState::CANCELED);
+    assert_eq!(order.filled_quantity, 1);
+    assert_eq!(engine.get_account().positions.get("TEST").unwrap().quantity, 3);
+
+    engine.stop_internal().unwrap();
+    let events = illegal_transitions(log_path);
+    let summary: Vec<(String, String, String)> = events.iter()
+        .map(|e| (e["from"].as_str().unwrap().to_string(), e["to"].as_str().unwrap().to_string(), e["source"].as_str().unwrap().to_string()))
+        .collect();
+    assert_eq!(summary, vec![
+        ("FILLED".to_string(), "NEW".to_string(), "status".to_string()),
+        ("FILLED".to_string(), "PENDING_CANCEL".to_string(), "cancel".to_string()),
+        ("CANCELED".to_string(), "PARTIALLY_FILLED".to_string(), "execution".to_string()),
+    ]);
+    assert_eq!(events[0]["order_id"], filled.as_str());
+    let _ = fs::remove_file(log_path);
+}
+
+#[test]
+fn test_refused_cancel_and_modify_restore_the_order() {
+    // No gateway: the engine does not hear of the fill at the venue
+    let adapter = Arc::new(MockAdapter::new());
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 10)], asks: vec![(dec!(101), 10)], update_id: 1, timestamp: 1.0 });
+    let oid = engine.send_order_internal(limit("TEST", OrderSide::BUY, 2, dec!(101))).unwrap();
+    assert_eq!(adapter.get_open_quantity(&oid), None);
+
+    // Not acknowledged yet, the cancel goes out anyway; the venue has nothing left to cancel
+    assert!(engine.cancel_order_internal(oid.clone()).is_err());
+    assert_eq!(engine.get_orders()[&oid].state, OrderState::PENDING_NEW);
+
+    engine.on_order_status_update(&oid, OrderState::NEW, None);
+    assert!(engine.modify_order_internal(oid.clone(), Some(dec!(100))).is_err());
+    let order = engine.get_orders()[&oid].clone();
+    assert_eq!((order.state, order.price, order.order_type), (OrderState::NEW, Some(dec!(101)), OrderType::LIMIT));
+}
+
+#[test]
+fn test_modify_waits_for_the_venue() {
+    let log_path = "tests/order_state_modify.jsonl";
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup_logged(adapter.clone(), log_path);
+    let oid = engine.send_order_internal(limit("TEST", OrderSide::BUY, 2, dec!(98))).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::NEW));
+
+    // The replacement is pending until the venue acknowledges it; a cancel must wait
+    engine.modify_order_internal(oid.clone(), Some(dec!(97))).unwrap();
+    assert_eq!(engine.get_orders()[&oid].price, Some(dec!(97)));
+    assert!(wait_for_state(&engine, &oid, OrderState::NEW));
+    engine.cancel_order_internal(oid.clone()).unwrap();
+    assert!(wait_for_state(&engine, &oid, OrderState::CANCELED));
+    let _ = fs::remove_file(log_path);
+}
+
+#[test]
+fn test_state_mirror_uses_same_rules() {
+    let mut state = State::new();
+    let mut order = limit("TEST", OrderSide::SELL, 4, dec!(100));
+    order.order_id = Some("o1".to_string());
+    order.update_state(OrderState::PENDING_NEW, None).unwrap();
+    state.orders.insert("o1".to_string(), order);
+
+    let status = |s: OrderState, filled: i64| Message::OrderStatus {
+        order_id: "o1".to_string(), state: s, filled_qty: filled, filled_price: None, msg: None, updated_at: 0.0,
+    };
+    state.apply(&status(OrderState::NEW, 0));
+    state.apply(&Message::Execution { order_id: "o1".to_string(), fill_qty: 4, fill_price: dec!(100) });
+    assert_eq!(state.orders["o1"].state, OrderState::FILLED);
+
+    // A late acknowledgement is ignored entirely
+    state.apply(&status(OrderState::NEW, 0));
+    assert_eq!(state.orders["o1"].state, OrderState::FILLED);
+    assert_eq!(state.orders["o1"].filled_quantity, 4);
+}
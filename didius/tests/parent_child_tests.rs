@@ -0,0 +1,140 @@
+mod common;
+
+use common::{limit_id, wait_until};
+use anyhow::Result;
+use didius::adapter::mock::MockAdapter;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::oms::risk::RiskLimits;
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::bracket::BracketStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Emits one scripted action per timer tick, then completes.
+struct Scripted {
+    steps: VecDeque<StrategyAction>,
+}
+
+impl Strategy for Scripted {
+    fn on_order_book_update(&mut self, _book: &OrderBook) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        Ok(self.steps.pop_front().unwrap_or(StrategyAction::None))
+    }
+
+    fn is_completed(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+fn parent(strategy: ExecutionStrategy, qty: i64, price: Decimal, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Order::new("TEST".to_string(), OrderSide::BUY, OrderType::LIMIT, qty, Some(price.to_string()), Some(strategy), Some(params), None, "KRX".to_string(), None)
+}
+
+fn setup(adapter: Arc<MockAdapter>) -> OMSEngine {
+    let engine = common::setup(adapter.clone());
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 100)], asks: vec![(dec!(101), 100)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine
+}
+
+#[test]
+fn test_batch_leaves_out_nothing_to_do() {
+    assert!(matches!(StrategyAction::batch(vec![]), StrategyAction::None));
+    let one = StrategyAction::batch(vec![StrategyAction::None, StrategyAction::CancelOrder("A".to_string())]);
+    assert!(matches!(one, StrategyAction::CancelOrder(ref id) if id == "A"), "{:?}", one);
+    let two = StrategyAction::batch(vec![StrategyAction::CancelOrder("A".to_string()), StrategyAction::None, StrategyAction::CancelOrder("B".to_string())]);
+    assert!(matches!(two, StrategyAction::Batch(ref actions) if actions.len() == 2), "{:?}", two);
+}
+
+#[test]
+fn test_bracket_pulls_both_legs_in_one_batch() {
+    let mut order = parent(ExecutionStrategy::BRACKET, 10, dec!(100), &[("take_profit_price", "110"), ("stop_price", "95")]);
+    order.order_id = Some("P".to_string());
+    let mut strat = BracketStrategy::from_order(&order).unwrap();
+    let StrategyAction::PlaceOrder(mut entry) = strat.on_timer().unwrap() else { panic!("no entry") };
+    assert_eq!(entry.parent_order_id.as_deref(), Some("P"));
+    entry.filled_quantity = 4;
+    entry.state = OrderState::PARTIALLY_FILLED;
+    let StrategyAction::PlaceOrder(mut exit) = strat.on_order_status_update(&entry).unwrap() else { panic!("no exit") };
+    assert_eq!((exit.order_id.as_deref(), exit.parent_order_id.as_deref()), (Some("P-x-1"), Some("P")));
+    exit.state = OrderState::NEW;
+    strat.on_order_status_update(&exit).unwrap();
+
+    order.state = OrderState::CANCELED;
+    let action = strat.on_order_status_update(&order).unwrap();
+    let StrategyAction::Batch(actions) = action else { panic!("no batch: {:?}", action) };
+    let ids: Vec<&str> = actions.iter().map(|a| match a { StrategyAction::CancelOrder(id) => id.as_str(), _ => "" }).collect();
+    assert_eq!(ids, vec!["P-x-1", "P-1"]);
+}
+
+#[test]
+fn test_engine_batch_stops_at_first_failure() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.add_strategy(Box::new(Scripted { steps: VecDeque::from(vec![
+        StrategyAction::Batch(vec![StrategyAction::PlaceOrder(limit_id("A", "TEST", OrderSide::BUY, 1, dec!(98))), StrategyAction::PlaceOrder(limit_id("B", "TEST", OrderSide::BUY, 2, dec!(98)))]),
+        StrategyAction::Batch(vec![StrategyAction::CancelOrder("missing".to_string()), StrategyAction::PlaceOrder(limit_id("C", "TEST", OrderSide::BUY, 3, dec!(98)))]),
+        StrategyAction::Batch(vec![StrategyAction::CancelOrder("A".to_string()), StrategyAction::PlaceOrder(limit_id("D", "TEST", OrderSide::BUY, 4, dec!(98)))]),
+    ]) }));
+    engine.start_internal(None).unwrap();
+
+    assert!(wait_until(|| adapter.get_open_quantity("D") == Some(4)));
+    assert_eq!(adapter.get_open_quantity("B"), Some(2));
+    assert!(wait_until(|| engine.get_orders()["A"].state == OrderState::CANCELED));
+    assert!(!engine.get_orders().contains_key("C"));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_children_carry_their_parent() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.start_internal(None).unwrap();
+
+    let parent_id = engine.send_order_internal(parent(ExecutionStrategy::ICEBERG, 6, dec!(101), &[("display_quantity", "3")])).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&parent_id].state == OrderState::FILLED));
+    let p = engine.get_orders()[&parent_id].clone();
+    assert_eq!((p.filled_quantity, p.average_fill_price), (6, dec!(101)));
+
+    let children = engine.get_child_orders(&parent_id);
+    assert_eq!(children.len(), 2);
+    assert!(children.iter().all(|c| c.parent_order_id.as_deref() == Some(parent_id.as_str()) && c.state == OrderState::FILLED));
+    assert!(engine.get_child_orders("unknown").is_empty());
+
+    // An order sent directly is not a child, whatever its name
+    let direct = engine.send_order_internal(limit_id(&format!("{}-9", parent_id), "TEST", OrderSide::BUY, 1, dec!(101))).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&direct].state == OrderState::FILLED));
+    assert_eq!(engine.get_child_orders(&parent_id).len(), 2);
+    assert_eq!(engine.get_orders()[&parent_id].filled_quantity, 6);
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_closes_parent_when_strategy_stops() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+
+    // The parent is accepted; its first child is refused by risk once the timer runs
+    let parent_id = engine.send_order_internal(parent(ExecutionStrategy::ICEBERG, 6, dec!(98), &[("display_quantity", "3")])).unwrap();
+    engine.set_risk_limits(RiskLimits { max_order_qty: Some(2), ..Default::default() });
+    engine.start_internal(None).unwrap();
+
+    assert!(wait_until(|| engine.get_orders()[&parent_id].state == OrderState::CANCELED));
+    assert_eq!(engine.get_orders()[&parent_id].error_message.as_deref(), Some("Strategy stopped"));
+    assert_eq!(engine.get_child_orders(&parent_id)[0].state, OrderState::REJECTED);
+    assert!(engine.get_active_strategy_order_ids().is_empty());
+    engine.stop_internal().unwrap();
+}
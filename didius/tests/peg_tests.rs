@@ -0,0 +1,159 @@
+mod common;
+
+use common::{setup, snapshot, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBook;
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::peg::PegStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn pegged(side: OrderSide, qty: i64, price: Option<Decimal>, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Order::new("TEST".to_string(), side, OrderType::LIMIT, qty, price.map(|p| p.to_string()), Some(ExecutionStrategy::PEG), Some(params), None, "KRX".to_string(), None)
+}
+
+fn book(bids: Vec<(Decimal, i64)>, asks: Vec<(Decimal, i64)>) -> OrderBook {
+    let mut book = OrderBook::new("TEST".to_string());
+    let s = snapshot(bids, asks);
+    book.rebuild(s.bids, s.asks, s.update_id, s.timestamp);
+    book
+}
+
+/// Far enough ahead that book updates (wall clock) never pass the re-quote interval
+const T0: f64 = 4_000_000_000.0;
+
+/// A strategy for an acknowledged order last priced at `T0`
+fn strategy(side: OrderSide, price: Decimal, params: &[(&str, &str)]) -> PegStrategy {
+    let mut order = pegged(side, 5, Some(price), params);
+    order.order_id = Some("O".to_string());
+    order.state = OrderState::NEW;
+    order.updated_at = T0;
+    PegStrategy::from_order(&order).unwrap()
+}
+
+#[test]
+fn test_primary_peg_with_offset_and_requote_interval() {
+    let mut strat = strategy(OrderSide::BUY, dec!(100.5), &[("offset_ticks", "1"), ("tick_size", "0.5"), ("min_requote_interval", "1")]);
+
+    // Alone at 100.5: our own level is skipped, the peg stays one tick over 100
+    let b = book(vec![(dec!(100), 10), (dec!(100.5), 5)], vec![(dec!(102), 10)]);
+    assert_eq!(strat.target_price(&b), Some(dec!(100.5)));
+    // Joined by someone else: 100.5 is the touch
+    let b = book(vec![(dec!(100.5), 8)], vec![(dec!(102), 10)]);
+    assert_eq!(strat.target_price(&b), Some(dec!(101)));
+
+    // The bid moves up: wait for the interval, then follow
+    strat.on_order_book_update(&book(vec![(dec!(101), 10)], vec![(dec!(102), 10)])).unwrap();
+    assert!(matches!(strat.requote_at(T0 + 0.5), StrategyAction::None));
+    let action = strat.requote_at(T0 + 1.0);
+    assert!(matches!(action, StrategyAction::ModifyPrice(ref id, Some(p)) if id == "O" && p == dec!(101.5)), "{:?}", action);
+    // Nothing more until the venue answers
+    assert!(matches!(strat.requote_at(T0 + 5.0), StrategyAction::None));
+    assert_eq!(strat.report().unwrap()["requotes"], 0);
+    strat.on_order_status_update(&acked(OrderSide::BUY, dec!(101.5))).unwrap();
+    assert_eq!(strat.quote, Some(dec!(101.5)));
+    assert_eq!(strat.report().unwrap()["requotes"], 1);
+}
+
+/// The pegged order "O" as the OMS reports it, `NEW` at `price`
+fn acked(side: OrderSide, price: Decimal) -> Order {
+    let mut order = pegged(side, 5, Some(price), &[]);
+    order.order_id = Some("O".to_string());
+    order.state = OrderState::NEW;
+    order
+}
+
+#[test]
+fn test_refused_requote_keeps_the_resting_price() {
+    let mut strat = strategy(OrderSide::BUY, dec!(100), &[("min_requote_interval", "1")]);
+    strat.on_order_book_update(&book(vec![(dec!(101), 10)], vec![(dec!(103), 10)])).unwrap();
+    assert!(matches!(strat.requote_at(T0 + 1.0), StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(101)));
+
+    // The modify is in flight: a slow ack must not lead to a second one
+    let mut replacing = acked(OrderSide::BUY, dec!(101));
+    replacing.state = OrderState::PENDING_REPLACE;
+    assert!(matches!(strat.on_order_status_update(&replacing).unwrap(), StrategyAction::None));
+    assert!(matches!(strat.requote_at(T0 + 3.0), StrategyAction::None));
+
+    // The venue refused it: the order is back at 100 and the peg tries again
+    strat.on_order_status_update(&acked(OrderSide::BUY, dec!(100))).unwrap();
+    assert_eq!(strat.quote, Some(dec!(100)));
+    assert_eq!(strat.report().unwrap()["requotes"], 0);
+    assert!(matches!(strat.requote_at(T0 + 3.0), StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(101)));
+}
+
+#[test]
+fn test_mid_and_opposite_pegs_with_cap() {
+    // Mid 99.5 rounds up for a sell; the cap keeps it at 100.5
+    let strat = strategy(OrderSide::SELL, dec!(101), &[("peg", "mid"), ("tick_size", "1"), ("peg_limit", "100.5")]);
+    assert_eq!(strat.target_price(&book(vec![(dec!(98), 10)], vec![(dec!(101), 10)])), Some(dec!(100.5)));
+    assert_eq!(strat.target_price(&book(vec![(dec!(102), 10)], vec![(dec!(105), 10)])), Some(dec!(104)));
+
+    // One tick behind the offer, never above 101
+    let strat = strategy(OrderSide::BUY, dec!(99), &[("peg", "OPPOSITE"), ("offset_ticks", "-1"), ("tick_size", "1"), ("peg_limit", "101")]);
+    assert_eq!(strat.target_price(&book(vec![(dec!(98), 10)], vec![(dec!(101), 10)])), Some(dec!(100)));
+    assert_eq!(strat.target_price(&book(vec![(dec!(98), 10)], vec![(dec!(104), 10)])), Some(dec!(101)));
+}
+
+#[test]
+fn test_waits_for_acknowledgement() {
+    let mut order = pegged(OrderSide::BUY, 5, Some(dec!(99)), &[("min_requote_interval", "0")]);
+    order.order_id = Some("O".to_string());
+    let mut strat = PegStrategy::from_order(&order).unwrap();
+    assert!(matches!(strat.on_order_book_update(&book(vec![(dec!(100), 10)], vec![(dec!(101), 10)])).unwrap(), StrategyAction::None));
+
+    order.state = OrderState::NEW;
+    let action = strat.on_order_status_update(&order).unwrap();
+    assert!(matches!(action, StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(100)), "{:?}", action);
+
+    order.state = OrderState::CANCELED;
+    strat.on_order_status_update(&order).unwrap();
+    assert!(strat.is_completed());
+}
+
+#[test]
+fn test_invalid_params() {
+    let err = |p: &[(&str, &str)]| PegStrategy::from_order(&pegged(OrderSide::BUY, 1, None, p)).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(&[("peg", "LAST")]).contains("Unknown peg"));
+    assert!(err(&[("offset_ticks", "2")]).contains("tick_size"));
+    assert!(err(&[("tick_size", "0")]).contains("positive"));
+    assert!(err(&[("min_requote_interval", "-1")]).contains("negative"));
+}
+
+#[test]
+fn test_engine_pegged_order_follows_the_bid() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 10)], vec![(dec!(102), 10)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    // No price given: it goes out at the bid
+    let oid = engine.send_order_internal(pegged(OrderSide::BUY, 3, None, &[("min_requote_interval", "0")])).unwrap();
+    assert!(wait_until(|| adapter.get_open_quantity(&oid) == Some(3)));
+    assert_eq!(engine.get_orders()[&oid].price, Some(dec!(99)));
+
+    adapter.push_order_book(snapshot(vec![(dec!(100), 10)], vec![(dec!(102), 10)]));
+    assert!(wait_until(|| engine.get_orders()[&oid].price == Some(dec!(100))));
+    assert_eq!(adapter.get_open_quantity(&oid), Some(3));
+
+    engine.cancel_order_internal(oid.clone()).unwrap();
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_rejects_peg_without_book_or_price() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    let mut order = pegged(OrderSide::SELL, 1, None, &[]);
+    order.order_id = Some("no-book".to_string());
+    let err = engine.send_order_internal(order).unwrap_err();
+    assert!(err.to_string().contains("No book"));
+    assert_eq!(engine.get_orders()["no-book"].state, OrderState::REJECTED);
+    assert_eq!(adapter.get_open_quantity("no-book"), None);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{setup, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::pov::PovStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn pov(side: OrderSide, qty: i64, price: Option<&str>, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+    Order::new("TEST".to_string(), side, order_type, qty, price.map(|p| p.to_string()), Some(ExecutionStrategy::POV), Some(params), None, "KRX".to_string(), None)
+}
+
+fn snapshot(bids: Vec<(Decimal, i64)>, asks: Vec<(Decimal, i64)>) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: "TEST".to_string(), bids, asks, update_id: 1, timestamp: 1.0 }
+}
+
+/// A BUY 1000 capped at 101 over a 100 / 101 book, starting at 0
+fn strategy(params: &[(&str, &str)]) -> PovStrategy {
+    let mut params = params.to_vec();
+    params.push(("start_time", "0"));
+    let mut order = pov(OrderSide::BUY, 1000, Some("101"), &params);
+    order.order_id = Some("P".to_string());
+    let mut strat = PovStrategy::from_order(&order).unwrap();
+    let s = snapshot(vec![(dec!(100), 10)], vec![(dec!(101), 10)]);
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(s.bids, s.asks, s.update_id, s.timestamp);
+    strat.on_order_book_update(&book).unwrap();
+    strat
+}
+
+fn with_state(mut child: Order, filled: i64, state: OrderState) -> Order {
+    child.filled_quantity = filled;
+    child.state = state;
+    child
+}
+
+fn trade(strat: &mut PovStrategy, quantity: i64, timestamp: f64) {
+    strat.on_trade_update("TEST", dec!(100.5), quantity, timestamp).unwrap();
+}
+
+#[test]
+fn test_children_follow_market_volume() {
+    let mut strat = strategy(&[("participation", "0.1")]);
+    assert!(matches!(strat.on_timer_at(1.0), StrategyAction::None));
+
+    // 500 printed: 50 behind the target, taken at the ask
+    trade(&mut strat, 500, 1.0);
+    trade(&mut strat, 1, -1.0);
+    strat.on_trade_update("OTHER", dec!(1), 1000, 1.0).unwrap();
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(2.0) else { panic!("no child") };
+    assert_eq!((child.order_id.as_deref(), child.quantity, child.price), (Some("P-1"), 50, Some(dec!(101))));
+    assert!(matches!(strat.on_timer_at(3.0), StrategyAction::None));
+    strat.on_order_status_update(&with_state(child, 50, OrderState::FILLED)).unwrap();
+    assert!(matches!(strat.on_timer_at(3.0), StrategyAction::None));
+
+    // Falling behind again does not wait for the interval
+    trade(&mut strat, 300, 3.0);
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(3.0) else { panic!("no child") };
+    assert_eq!(child.quantity, 30);
+    let report = strat.report().unwrap();
+    assert_eq!((report["market_volume"].as_i64(), report["filled_quantity"].as_i64()), (Some(800), Some(50)));
+    assert_eq!(report["realized_participation"], 0.0625);
+}
+
+#[test]
+fn test_bands_rest_passively_and_catch_up_aggressively() {
+    let mut strat = strategy(&[("participation", "0.1"), ("min_participation", "0.05"), ("max_participation", "0.2")]);
+    trade(&mut strat, 1000, 1.0);
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(2.0) else { panic!("no child") };
+    assert_eq!((child.quantity, child.price), (100, Some(dec!(101))));
+    strat.on_order_status_update(&with_state(child, 100, OrderState::FILLED)).unwrap();
+
+    // Inside the bands: wait for the interval, then rest at the bid for up to 20% of the volume
+    trade(&mut strat, 1000, 5.0);
+    assert!(matches!(strat.on_timer_at(5.0), StrategyAction::None));
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(12.0) else { panic!("no child") };
+    assert_eq!((child.order_id.as_deref(), child.quantity, child.price), (Some("P-2"), 300, Some(dec!(100))));
+    strat.on_order_status_update(&with_state(child.clone(), 0, OrderState::NEW)).unwrap();
+
+    // The market runs away below 5%: the resting child is pulled and replaced by an aggressive one
+    trade(&mut strat, 1000, 13.0);
+    let action = strat.on_timer_at(13.0);
+    assert!(matches!(action, StrategyAction::CancelOrder(ref id) if id == "P-2"), "{:?}", action);
+    assert!(matches!(strat.on_timer_at(13.5), StrategyAction::None));
+    strat.on_order_status_update(&with_state(child, 20, OrderState::CANCELED)).unwrap();
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(14.0) else { panic!("no child") };
+    assert_eq!((child.quantity, child.price), (180, Some(dec!(101))));
+}
+
+#[test]
+fn test_end_time_completes_or_cancels() {
+    let mut strat = strategy(&[("participation", "0.1"), ("end_time", "100"), ("end_action", "complete")]);
+    trade(&mut strat, 500, 1.0);
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(2.0) else { panic!("no child") };
+    strat.on_order_status_update(&with_state(child, 50, OrderState::FILLED)).unwrap();
+    // Prints after the end do not count
+    trade(&mut strat, 500, 100.0);
+    assert_eq!(strat.market_volume(), 500);
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(100.0) else { panic!("no child") };
+    assert_eq!((child.quantity, child.price), (950, Some(dec!(101))));
+
+    let mut strat = strategy(&[("participation", "0.1"), ("end_time", "100")]);
+    trade(&mut strat, 500, 1.0);
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(2.0) else { panic!("no child") };
+    strat.on_order_status_update(&with_state(child.clone(), 0, OrderState::NEW)).unwrap();
+    let action = strat.on_timer_at(100.0);
+    assert!(matches!(action, StrategyAction::CancelOrder(ref id) if id == "P-1"), "{:?}", action);
+    strat.on_order_status_update(&with_state(child, 10, OrderState::CANCELED)).unwrap();
+    let action = strat.on_timer_at(101.0);
+    assert!(matches!(action, StrategyAction::CancelOrder(ref id) if id == "P"), "{:?}", action);
+    assert!(strat.is_completed());
+}
+
+#[test]
+fn test_invalid_params() {
+    let err = |p: &[(&str, &str)]| PovStrategy::from_order(&pov(OrderSide::BUY, 10, None, p)).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(&[]).contains("participation"));
+    assert!(err(&[("participation", "0")]).contains("(0, 1]"));
+    assert!(err(&[("participation", "1.5")]).contains("(0, 1]"));
+    assert!(err(&[("participation", "0.1"), ("min_participation", "0.2")]).contains("bands"));
+    assert!(err(&[("participation", "0.1"), ("max_participation", "0.05")]).contains("bands"));
+    assert!(err(&[("participation", "0.1"), ("end_action", "HOLD")]).contains("Unknown end_action"));
+    assert!(err(&[("participation", "0.1"), ("start_time", "10"), ("end_time", "5")]).contains("end after"));
+}
+
+#[test]
+fn test_engine_pov_follows_the_tape() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    adapter.push_order_book(snapshot(vec![(dec!(99), 100)], vec![(dec!(101), 100)]));
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(pov(OrderSide::BUY, 20, None, &[("participation", "0.5"), ("interval_seconds", "0.2")])).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(engine.get_orders()[&parent].filled_quantity, 0);
+
+    adapter.push_trade("TEST", dec!(100), 20);
+    assert!(wait_until(|| engine.get_orders()[&parent].filled_quantity == 10));
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(engine.get_orders()[&parent].filled_quantity, 10);
+
+    adapter.push_trade("TEST", dec!(100), 20);
+    assert!(wait_until(|| engine.get_orders()[&parent].state == OrderState::FILLED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert_eq!(engine.get_orders()[&parent].average_fill_price, dec!(101));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_engine_pov_cancels_at_end_time() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(pov(OrderSide::SELL, 5, Some("100"), &[("participation", "0.1"), ("duration_seconds", "0.3")])).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&parent].state == OrderState::CANCELED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert_eq!(adapter.get_open_quantity(&format!("{}-1", parent)), None);
+    engine.stop_internal().unwrap();
+}
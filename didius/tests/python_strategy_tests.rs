@@ -0,0 +1,289 @@
+mod common;
+
+use common::setup;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::oms::session::TradingPhase;
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::python::PyStrategy;
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use rust_decimal::dec;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const STRATEGIES: &CStr = c_str!(r#"
+from didius import StrategyAction, Order, OrderSide, OrderType
+
+class Scripted:
+    def __init__(self):
+        self.trades = []
+    def on_order_book_update(self, book):
+        order = Order(book["symbol"], OrderSide.BUY, OrderType.LIMIT, 1, book["bids"][0][0])
+        order.order_id = "A"
+        return order
+    def on_trade_update(self, symbol, price, quantity, timestamp):
+        self.trades.append((symbol, price, quantity))
+    def on_order_status_update(self, order):
+        return StrategyAction.remove(order.order_id)
+    def on_timer(self):
+        return [StrategyAction.cancel("A"), None, StrategyAction.modify("B", "100.5")]
+    def get_origin_order_id(self):
+        return "A"
+
+class Broken:
+    def on_order_book_update(self, book):
+        raise RuntimeError("boom")
+    def on_trade_update(self, symbol, price, quantity, timestamp):
+        raise RuntimeError("boom")
+    def on_order_status_update(self, order):
+        raise RuntimeError("boom")
+    def on_timer(self):
+        return "not an action"
+
+class NoTimer:
+    def on_order_book_update(self, book): pass
+    def on_trade_update(self, symbol, price, quantity, timestamp): pass
+    def on_order_status_update(self, order): pass
+
+class Buyer:
+    def __init__(self):
+        self.states = []
+    def on_order_book_update(self, book):
+        if self.states or not book["asks"]:
+            return None
+        self.states.append("SENT")
+        order = Order("TEST", OrderSide.BUY, OrderType.LIMIT, 2, book["asks"][0][0])
+        order.order_id = "py-1"
+        return StrategyAction.place(order)
+    def on_trade_update(self, symbol, price, quantity, timestamp):
+        pass
+    def on_order_status_update(self, order):
+        if order.order_id == "py-1":
+            self.states.append(str(order.state))
+    def on_timer(self):
+        return None
+    def is_completed(self):
+        return "OrderState.FILLED" in self.states
+    def report(self):
+        return {"states": self.states}
+
+class Ticker:
+    def on_order_book_update(self, book): pass
+    def on_trade_update(self, symbol, price, quantity, timestamp): pass
+    def on_order_status_update(self, order): pass
+    def on_timer(self):
+        # Gives up the GIL while the timer thread holds the strategies
+        import time
+        time.sleep(0.02)
+"#);
+
+/// Instantiate `class` from `STRATEGIES`, with the extension classes importable as `didius`.
+fn instance(py: Python<'_>, class: &str) -> PyObject {
+    let sys_modules = py.import("sys").unwrap().getattr("modules").unwrap();
+    if !sys_modules.contains("didius").unwrap() {
+        let m = PyModule::new(py, "didius").unwrap();
+        didius::oms::register(&m).unwrap();
+        didius::strategy::register(&m).unwrap();
+        sys_modules.set_item("didius", m).unwrap();
+    }
+    let module = PyModule::from_code(py, STRATEGIES, c_str!("strategies.py"), c_str!("strategies")).unwrap();
+    module.getattr(class).unwrap().call0().unwrap().unbind()
+}
+
+fn strategy(class: &str) -> PyStrategy {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| PyStrategy::new(py, instance(py, class))).unwrap()
+}
+
+fn snapshot() -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(98), 5), (dec!(99), 10)], asks: vec![(dec!(101), 10)], update_id: 1, timestamp: 1.0 }
+}
+
+fn book() -> OrderBook {
+    let s = snapshot();
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(s.bids, s.asks, s.update_id, s.timestamp);
+    book
+}
+
+#[test]
+fn test_python_actions_are_converted() {
+    let mut strat = strategy("Scripted");
+    // Best bid first
+    let action = strat.on_order_book_update(&book()).unwrap();
+    assert!(matches!(action, StrategyAction::PlaceOrder(ref o) if o.order_id.as_deref() == Some("A") && o.price == Some(dec!(99))), "{:?}", action);
+
+    let action = strat.on_timer().unwrap();
+    let StrategyAction::Batch(actions) = action else { panic!("no batch: {:?}", action) };
+    assert!(matches!(actions.as_slice(), [StrategyAction::CancelOrder(a), StrategyAction::ModifyPrice(b, Some(p))] if a == "A" && b == "B" && *p == dec!(100.5)));
+
+    let mut order = Order::new("TEST".to_string(), OrderSide::BUY, OrderType::LIMIT, 1, Some("99".to_string()), None, None, None, "KRX".to_string(), None);
+    order.order_id = Some("A".to_string());
+    assert!(matches!(strat.on_order_status_update(&order).unwrap(), StrategyAction::RemoveOrder(ref id) if id == "A"));
+    assert!(matches!(strat.on_trade_update("TEST", dec!(100), 3, 1.0).unwrap(), StrategyAction::None));
+    assert_eq!(strat.get_origin_order_id().as_deref(), Some("A"));
+    assert!(!strat.is_completed());
+    assert!(strat.report().is_none());
+}
+
+#[test]
+fn test_python_exceptions_are_isolated() {
+    let mut strat = strategy("Broken");
+    strat.max_errors = 3;
+    let err = strat.on_order_book_update(&book()).unwrap_err().to_string();
+    assert!(err.contains("Broken.on_order_book_update") && err.contains("boom"), "{}", err);
+    let err = strat.on_timer().unwrap_err().to_string();
+    assert!(err.contains("Expected StrategyAction"), "{}", err);
+    assert!(!strat.is_completed());
+
+    assert!(strat.on_trade_update("TEST", dec!(100), 1, 1.0).is_err());
+    assert!(strat.is_stopped() && strat.is_completed());
+    // Stopped: not called any more
+    assert!(matches!(strat.on_timer().unwrap(), StrategyAction::None));
+
+    pyo3::prepare_freethreaded_python();
+    let err = Python::with_gil(|py| PyStrategy::new(py, instance(py, "NoTimer")).err().unwrap().to_string());
+    assert!(err.contains("no on_timer method"), "{}", err);
+}
+
+#[test]
+fn test_engine_runs_python_strategy_next_to_a_broken_one() {
+    pyo3::prepare_freethreaded_python();
+    let log_path = "tests/python_strategy_report.jsonl";
+    let _ = fs::remove_file(log_path);
+    let config = LoggerConfig { destination: LogDestinationInfo::LocalFile { path: log_path.to_string() }, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+
+    Python::with_gil(|py| {
+        engine.add_python_strategy(py, instance(py, "Broken")).unwrap();
+        engine.add_python_strategy(py, instance(py, "Buyer")).unwrap();
+    });
+    engine.start_internal(None).unwrap();
+    adapter.push_order_book(snapshot());
+
+    let wait = |cond: &dyn Fn() -> bool| (0..300).any(|_| { thread::sleep(Duration::from_millis(10)); cond() });
+    assert!(wait(&|| engine.get_orders().get("py-1").is_some_and(|o| o.state == OrderState::FILLED)));
+    assert_eq!(engine.get_account().positions["TEST"].quantity, 2);
+    // The gateway thread survived the exceptions
+    adapter.push_order_book(OrderBookSnapshot { update_id: 2, ..snapshot() });
+    assert!(wait(&|| engine.get_order_book("TEST").is_some_and(|b| b.last_update_id == 2)));
+    // Completed strategies are dropped (and reported) on the next timer tick
+    thread::sleep(Duration::from_millis(300));
+    engine.stop_internal().unwrap();
+
+    let reports: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "STRATEGY_REPORT")
+        .collect();
+    assert_eq!(reports.len(), 1);
+    let body = &reports[0]["log_body"];
+    assert_eq!(body["strategy"], "Buyer");
+    assert_eq!(body["states"][0], "SENT");
+    assert_eq!(body["states"].as_array().unwrap().last().unwrap(), "OrderState.FILLED");
+    let _ = fs::remove_file(log_path);
+}
+
+#[test]
+fn test_python_strategy_added_while_the_timer_runs() {
+    pyo3::prepare_freethreaded_python();
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter);
+    Python::with_gil(|py| engine.add_python_strategy(py, instance(py, "Ticker")).unwrap());
+    engine.start_internal(None).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let registering = engine.clone();
+    thread::spawn(move || {
+        for _ in 0..20 {
+            Python::with_gil(|py| registering.add_python_strategy(py, instance(py, "Ticker")).unwrap());
+            thread::sleep(Duration::from_millis(10));
+        }
+        tx.send(()).unwrap();
+    });
+    assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok(), "add_python_strategy blocked on the timer thread");
+    engine.stop_internal().unwrap();
+}
+
+const ENGINE_SCRIPT: &CStr = c_str!(r#"
+from didius import OMSEngine, Order, OrderSide, OrderType, OrderState, RiskLimits, SessionSchedule, TradingPhase
+
+engine = OMSEngine.with_venues({"day": ("mock", None), "night": ("mock", None)}, "day")
+engine.start_venue_gateways()
+venues = engine.venues()
+order_id = engine.place_order(Order("A05602", OrderSide.BUY, OrderType.LIMIT, 2, "350", venue="night"))
+order_venue = engine.get_orders()[order_id].venue
+night_venue = engine.get_account(venue="night")["venue"]
+default_venue = engine.get_account()["venue"]
+book = engine.get_order_book("A05602", venue="night")
+
+engine.set_risk_limits(RiskLimits(max_order_qty=2, max_position={"A05602": 5}, price_collar="0.05"))
+limits = engine.get_risk_limits()
+too_big = engine.place_order(Order("A05602", OrderSide.BUY, OrderType.LIMIT, 3, "350", venue="night"))
+too_big_state = engine.get_orders()[too_big].state == OrderState.REJECTED
+
+import os, tempfile
+journal_path = os.path.join(tempfile.mkdtemp(), "journal.jsonl")
+engine.set_journal(journal_path)
+journaled = engine.place_order(Order("A05602", OrderSide.SELL, OrderType.LIMIT, 1, "351", venue="night"))
+journal_holds_order = journaled in open(journal_path).read()
+
+# 2026-10-19 is a Monday: 10:00 KST is continuous trading, unless it is a holiday
+monday_10am = 1792371600.0
+schedule = SessionSchedule.krx_equity()
+phase = schedule.phase_at(monday_10am)
+holiday_phase = schedule.with_holidays(["2026-10-19"]).phase_at(monday_10am)
+custom_phase = SessionSchedule([(TradingPhase.NIGHT_SESSION, "18:00", "05:00")]).phase_at(monday_10am)
+try:
+    SessionSchedule([(TradingPhase.CONTINUOUS, "9h", "15:20")])
+    bad_time_raises = False
+except ValueError:
+    bad_time_raises = True
+engine.set_session_schedule(schedule)
+engine.set_session_schedule(None)
+phase_without_schedule = engine.get_session_phase()
+"#);
+
+/// Run `ENGINE_SCRIPT` against the extension classes importable as `didius`.
+fn engine_script(py: Python<'_>) -> Bound<'_, PyModule> {
+    instance(py, "NoTimer");
+    PyModule::from_code(py, ENGINE_SCRIPT, c_str!("engine.py"), c_str!("engine")).unwrap()
+}
+
+#[test]
+fn test_python_oms_engine() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = engine_script(py);
+        let get = |name: &str| module.getattr(name).unwrap();
+        assert_eq!(get("venues").extract::<Vec<String>>().unwrap(), vec!["day", "night"]);
+        assert_eq!(get("order_venue").extract::<String>().unwrap(), "night");
+        assert_eq!(get("night_venue").extract::<String>().unwrap(), "night");
+        assert_eq!(get("default_venue").extract::<String>().unwrap(), "day");
+        assert!(get("book").is_none());
+        assert_eq!(get("limits").getattr("max_position").unwrap().extract::<HashMap<String, i64>>().unwrap()["A05602"], 5);
+        assert_eq!(get("limits").getattr("price_collar").unwrap().extract::<String>().unwrap(), "0.05");
+        assert!(get("too_big_state").extract::<bool>().unwrap());
+        assert!(get("journal_holds_order").extract::<bool>().unwrap());
+        assert_eq!(get("phase").extract::<TradingPhase>().unwrap(), TradingPhase::CONTINUOUS);
+        assert_eq!(get("holiday_phase").extract::<TradingPhase>().unwrap(), TradingPhase::CLOSED);
+        assert_eq!(get("custom_phase").extract::<TradingPhase>().unwrap(), TradingPhase::CLOSED);
+        assert!(get("bad_time_raises").extract::<bool>().unwrap());
+        assert!(get("phase_without_schedule").is_none());
+    });
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{limit_id, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{Order, OrderSide, OrderState};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::oms::queue::QueueTracker;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+fn resting(id: &str, side: OrderSide, price: Decimal, state: OrderState) -> Order {
+    let mut o = limit_id(id, "TEST", side, 10, price);
+    o.state = state;
+    o
+}
+
+fn book(bids: &[(Decimal, i64)], asks: &[(Decimal, i64)]) -> OrderBook {
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(bids.to_vec(), asks.to_vec(), 1, 0.0);
+    book
+}
+
+#[test]
+fn test_queue_follows_prints_and_level_drops() {
+    let mut tracker = QueueTracker::new();
+    let depth = book(&[(dec!(98), 50), (dec!(99), 100)], &[(dec!(101), 80)]);
+    // Not acknowledged yet: no place in the queue
+    tracker.on_order(&resting("B", OrderSide::BUY, dec!(99), OrderState::PENDING_NEW), Some(&depth), 0.0);
+    assert!(tracker.get("B").is_none());
+    // The level shown includes our own 10
+    tracker.on_order(&resting("B", OrderSide::BUY, dec!(99), OrderState::NEW), Some(&depth), 0.0);
+    assert_eq!(tracker.get("B").unwrap().queue_ahead, 90.0);
+
+    // 10 more join behind; a print at the price takes from the front
+    tracker.on_book(&book(&[(dec!(98), 50), (dec!(99), 110)], &[(dec!(101), 80)]));
+    assert_eq!(tracker.get("B").unwrap().queue_ahead, 90.0);
+    tracker.on_trade("", "TEST", dec!(99), 30);
+    tracker.on_trade("", "TEST", dec!(101), 500);
+    let b = tracker.get("B").unwrap();
+    assert_eq!((b.queue_ahead, b.level_quantity, b.traded_quantity), (60.0, 80, 30));
+    // The book catching up with the print is not counted twice
+    tracker.on_book(&book(&[(dec!(98), 50), (dec!(99), 80)], &[(dec!(101), 80)]));
+    assert_eq!(tracker.get("B").unwrap().queue_ahead, 60.0);
+    // 70 join behind, then 35 are canceled from the 140 others: the 60 ahead lose their share, 15
+    tracker.on_book(&book(&[(dec!(98), 50), (dec!(99), 150)], &[(dec!(101), 80)]));
+    tracker.on_book(&book(&[(dec!(98), 50), (dec!(99), 115)], &[(dec!(101), 80)]));
+    assert_eq!(tracker.get("B").unwrap().queue_ahead, 45.0);
+
+    // A sell resting beyond the shown depth keeps its estimate; a print through the price clears it
+    tracker.on_order(&resting("S", OrderSide::SELL, dec!(105), OrderState::NEW), Some(&depth), 0.0);
+    assert_eq!(tracker.get("S").unwrap().queue_ahead, 0.0);
+    tracker.on_order(&resting("S2", OrderSide::SELL, dec!(101), OrderState::NEW), Some(&depth), 0.0);
+    tracker.on_book(&book(&[(dec!(99), 40)], &[(dec!(100), 5)]));
+    assert_eq!(tracker.get("S2").unwrap().queue_ahead, 70.0);
+    tracker.on_trade("", "TEST", dec!(102), 1);
+    assert_eq!(tracker.get("S2").unwrap().queue_ahead, 0.0);
+
+    // Re-priced: back of the new level. Filled: at the front. Canceled: gone
+    let mut moved = resting("B", OrderSide::BUY, dec!(98), OrderState::NEW);
+    tracker.on_order(&moved, Some(&depth), 1.0);
+    assert_eq!((tracker.get("B").unwrap().queue_ahead, tracker.get("B").unwrap().joined_at), (40.0, 1.0));
+    tracker.on_fill("B", 4);
+    assert_eq!((tracker.get("B").unwrap().queue_ahead, tracker.get("B").unwrap().open_quantity), (0.0, 4));
+    moved.state = OrderState::CANCELED;
+    tracker.on_order(&moved, Some(&depth), 2.0);
+    assert!(tracker.get("B").is_none());
+    assert_eq!(tracker.positions().len(), 2);
+}
+
+#[test]
+fn test_fill_probability() {
+    let mut tracker = QueueTracker::new();
+    tracker.on_order(&resting("B", OrderSide::BUY, dec!(99), OrderState::NEW), Some(&book(&[(dec!(99), 31)], &[])), 0.0);
+    let position = tracker.get("B").unwrap().clone();
+    // Nothing has moved yet
+    assert_eq!(position.depletion_rate(10.0), 0.0);
+    assert_eq!(position.fill_probability(10.0, 60.0), 0.0);
+
+    // 20 of 21 gone in 10 seconds: 2 a second, 1 left ahead
+    tracker.on_trade("", "TEST", dec!(99), 20);
+    let position = tracker.get("B").unwrap();
+    assert_eq!(position.depletion_rate(10.0), 2.0);
+    // P(Poisson(2) > 1) = 1 - 3e^-2
+    assert!((position.fill_probability(10.0, 1.0) - (1.0 - 3.0 * (-2.0f64).exp())).abs() < 1e-9);
+    let (short, long) = (position.fill_probability(10.0, 0.5), position.fill_probability(10.0, 5.0));
+    assert!(short < long && long < 1.0, "{} {}", short, long);
+
+    tracker.on_trade("", "TEST", dec!(99), 1);
+    assert_eq!(tracker.get("B").unwrap().fill_probability(10.0, 0.0), 1.0);
+}
+
+#[test]
+fn test_engine_tracks_resting_orders() {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 100)], asks: vec![(dec!(101), 100)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+
+    let mut order = resting("Q", OrderSide::BUY, dec!(99), OrderState::CREATED);
+    order.order_id = None;
+    let oid = engine.send_order_internal(order).unwrap();
+    assert!(wait_until(|| engine.get_queue_position(&oid).is_some()));
+    assert_eq!(engine.get_queue_position(&oid).unwrap().queue_ahead, 90.0);
+
+    adapter.push_trade("TEST", dec!(99), 40);
+    assert!(wait_until(|| engine.get_queue_position(&oid).is_some_and(|p| p.queue_ahead == 50.0)));
+    // Everyone ahead sells out: the level left is only ours
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(98), 100)], asks: vec![(dec!(101), 100)], update_id: 2, timestamp: 2.0 });
+    assert!(wait_until(|| engine.get_queue_position(&oid).is_some_and(|p| p.queue_ahead == 0.0)));
+    assert_eq!(engine.get_queue_positions().len(), 1);
+
+    engine.cancel_order_internal(oid.clone()).unwrap();
+    assert!(wait_until(|| engine.get_queue_position(&oid).is_none()));
+    engine.stop_internal().unwrap();
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use common::{auth_dir, limit};
+use didius::adapter::hantoo::HantooAdapter;
+use didius::adapter::hantoo_ngt_futopt::HantooNightAdapter;
+use didius::adapter::hantoo_stub::{HantooStubServer, STUB_HTS_ID};
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::journal::OrderJournal;
+use didius::oms::order::{OrderSide, OrderState};
+use didius::oms::reconcile::ReconciliationBreak;
+use rust_decimal::dec;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn logger(path: Option<&str>) -> Arc<Mutex<Logger>> {
+    let destination = match path {
+        Some(path) => {
+            let _ = fs::remove_file(path);
+            LogDestinationInfo::LocalFile { path: path.to_string() }
+        }
+        None => LogDestinationInfo::Console,
+    };
+    let config = LoggerConfig { destination, flush_interval_seconds: 60, batch_size: 1024 };
+    Arc::new(Mutex::new(Logger::new(config)))
+}
+
+fn engine_on(venue: &str, adapter: Arc<dyn Adapter>, log_path: Option<&str>) -> OMSEngine {
+    let mut adapters: HashMap<String, Arc<dyn Adapter>> = HashMap::new();
+    adapters.insert(venue.to_string(), adapter);
+    let engine = OMSEngine::with_venues(adapters, venue, logger(log_path));
+    engine.start_venue_gateways().unwrap();
+    engine
+}
+
+fn wait_for<F: Fn() -> bool>(f: F) -> bool {
+    for _ in 0..200 {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn state_of(engine: &OMSEngine, oid: &str) -> Option<OrderState> {
+    engine.get_orders().get(oid).map(|o| o.state.clone())
+}
+
+#[test]
+fn test_reconcile_after_websocket_gap() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth_dir("gap"))));
+    let engine = engine_on("hantoo", adapter.clone(), None);
+    engine.start_internal(None).unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+
+    let a = engine.send_order_internal(limit("005930", OrderSide::BUY, 10, dec!(70100))).unwrap();
+    let b = engine.send_order_internal(limit("005930", OrderSide::SELL, 5, dec!(72000))).unwrap();
+    assert!(wait_for(|| state_of(&engine, &a) == Some(OrderState::NEW) && state_of(&engine, &b) == Some(OrderState::NEW)));
+    let placed = stub.orders();
+    stub.fill(&placed[0].order_no, 3, dec!(70000)).unwrap();
+    assert!(wait_for(|| state_of(&engine, &a) == Some(OrderState::PARTIALLY_FILLED)));
+
+    // Notices are lost while the broker keeps trading
+    stub.set_notices_muted(true);
+    stub.fill(&placed[0].order_no, 7, dec!(70100)).unwrap();
+    stub.cancel(&placed[1].order_no).unwrap();
+    let manual = stub.add_external_order("000660", "BUY", 2, dec!(120000), false);
+    let done_elsewhere = stub.add_external_order("035420", "SELL", 1, dec!(200000), false);
+    stub.fill(&done_elsewhere, 1, dec!(200000)).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(state_of(&engine, &a), Some(OrderState::PARTIALLY_FILLED));
+
+    let report = engine.reconcile_internal().unwrap();
+    assert_eq!(report.venues, vec!["hantoo".to_string()]);
+    assert_eq!(report.breaks.len(), 4, "{:?}", report.breaks);
+
+    // 3 @ 70000 seen, broker average 70070 over 10 => 7 @ 70100 missed
+    assert!(report.breaks.iter().any(|brk| matches!(brk,
+        ReconciliationBreak::MissedFill { order_id, quantity: 7, price, .. } if *order_id == a && *price == dec!(70100))));
+    let order_a = engine.get_orders().get(&a).cloned().unwrap();
+    assert_eq!(order_a.state, OrderState::FILLED);
+    assert_eq!(order_a.average_fill_price, dec!(70070));
+    assert_eq!(engine.get_account().positions.get("005930").unwrap().quantity, 10);
+
+    assert!(report.breaks.iter().any(|brk| matches!(brk,
+        ReconciliationBreak::StaleState { order_id, from: OrderState::NEW, to: OrderState::CANCELED, .. } if *order_id == b)));
+    assert_eq!(state_of(&engine, &b), Some(OrderState::CANCELED));
+
+    assert!(report.breaks.iter().any(|brk| matches!(brk,
+        ReconciliationBreak::UnknownExecution { execution, .. } if execution.exchange_order_id == done_elsewhere && execution.filled_quantity == 1)));
+
+    // The manual order is adopted and its notices are routed to the new id
+    assert_eq!(report.adopted.len(), 1);
+    let adopted = engine.get_orders().get(&report.adopted[0]).cloned().unwrap();
+    assert_eq!(adopted.symbol, "000660");
+    assert_eq!(adopted.venue, "hantoo");
+    assert_eq!(adopted.exchange_order_id.as_deref(), Some(manual.as_str()));
+    assert_eq!(adopted.state, OrderState::NEW);
+    stub.set_notices_muted(false);
+    stub.fill(&manual, 2, dec!(120000)).unwrap();
+    assert!(wait_for(|| state_of(&engine, &report.adopted[0]) == Some(OrderState::FILLED)));
+
+    // Nothing left to repair
+    assert!(engine.reconcile_internal().unwrap().breaks.iter().all(|b| matches!(b, ReconciliationBreak::UnknownExecution { .. })));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_reconcile_revised_order_keeps_its_fills() {
+    let stub = HantooStubServer::start().unwrap();
+    let adapter = Arc::new(HantooAdapter::from_config(stub.config(&auth_dir("revise"))));
+    let engine = engine_on("hantoo", adapter.clone(), None);
+    engine.start_internal(None).unwrap();
+    assert!(stub.wait_for_subscription("H0STCNI0", STUB_HTS_ID, Duration::from_secs(2)));
+
+    let oid = engine.send_order_internal(limit("005930", OrderSide::BUY, 10, dec!(70000))).unwrap();
+    assert!(wait_for(|| state_of(&engine, &oid) == Some(OrderState::NEW)));
+    stub.fill(&stub.orders()[0].order_no, 4, dec!(70000)).unwrap();
+    assert!(wait_for(|| state_of(&engine, &oid) == Some(OrderState::PARTIALLY_FILLED)));
+    engine.modify_order_internal(oid.clone(), Some(dec!(70100))).unwrap();
+    assert!(wait_for(|| stub.orders().len() == 2));
+    thread::sleep(Duration::from_millis(50));
+
+    // Fills of the original and the revision belong to the same client order
+    let open = adapter.get_open_orders().unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].order_id.as_deref(), Some(oid.as_str()));
+    assert_eq!(open[0].filled_quantity, 4);
+    assert_eq!(open[0].price, Some(dec!(70100)));
+    assert_eq!(open[0].exchange_order_id, stub.orders()[1].order_no);
+
+    let report = engine.reconcile_internal().unwrap();
+    assert!(report.breaks.iter().all(|b| !matches!(b, ReconciliationBreak::MissedFill { .. } | ReconciliationBreak::UnknownOrder { .. })), "{:?}", report.breaks);
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_restart_reconciles_night_orders_before_trading() {
+    let stub = HantooStubServer::start().unwrap();
+    let auth = auth_dir("night");
+    let journal = std::env::temp_dir().join(format!("didius_reconcile_night_{}.jsonl", std::process::id())).to_string_lossy().to_string();
+    let _ = fs::remove_file(&journal);
+    let log_path = "tests/reconcile_night.jsonl";
+
+    let night = Arc::new(HantooNightAdapter::from_config(stub.config(&auth)));
+    let engine = engine_on("hantoo_night", night, None);
+    engine.set_journal(OrderJournal::open(&journal).unwrap());
+    engine.start_internal(None).unwrap();
+    let oid = engine.send_order_internal(limit("A05602", OrderSide::BUY, 3, dec!(350))).unwrap();
+    drop(engine);
+
+    // Filled while the OMS was down
+    stub.set_notices_muted(true);
+    stub.fill(&stub.orders()[0].order_no, 2, dec!(349.5)).unwrap();
+
+    let night = Arc::new(HantooNightAdapter::from_config(stub.config(&auth)));
+    let engine = engine_on("hantoo_night", night.clone(), Some(log_path));
+    engine.set_journal(OrderJournal::open(&journal).unwrap());
+    engine.start_internal(None).unwrap();
+
+    let order = engine.get_orders().get(&oid).cloned().unwrap();
+    assert_eq!(order.state, OrderState::PARTIALLY_FILLED);
+    assert_eq!(order.filled_quantity, 2);
+    assert_eq!(order.average_fill_price, dec!(349.5));
+    assert!(night.get_order_ref(&oid).is_some());
+
+    engine.stop_internal().unwrap();
+    let events: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "RECONCILIATION")
+        .collect();
+    assert_eq!(events.len(), 1);
+    let breaks = events[0]["log_body"]["breaks"].as_array().unwrap();
+    assert_eq!(breaks.len(), 1);
+    assert_eq!(breaks[0]["kind"], "MissedFill");
+    assert_eq!(breaks[0]["order_id"], oid.as_str());
+    assert_eq!(breaks[0]["quantity"], 2);
+    let _ = fs::remove_file(log_path);
+    let _ = fs::remove_file(&journal);
+}
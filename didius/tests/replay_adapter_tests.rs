@@ -0,0 +1,146 @@
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::replay::{ReplayAdapter, ReplayPace};
+use didius::adapter::{Adapter, IncomingMessage};
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{Order, OrderSide, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use rust_decimal::dec;
+use std::fs;
+use std::io::Cursor;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn snapshot(bid: rust_decimal::Decimal, ask: rust_decimal::Decimal) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(bid, 10)], asks: vec![(ask, 10)], update_id: 1, timestamp: 1.0 }
+}
+
+/// Run a small session through the engine with a file logger. Returns the messages seen by the engine.
+fn record_session(path: &str) -> Vec<IncomingMessage> {
+    let _ = fs::remove_file(path);
+    let config = LoggerConfig {
+        destination: LogDestinationInfo::LocalFile { path: path.to_string() },
+        flush_interval_seconds: 60,
+        batch_size: 10000,
+    };
+    let logger = Arc::new(Mutex::new(Logger::new(config)));
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), logger.clone());
+    engine.start_internal(None).unwrap();
+
+    // Tap the adapter stream before forwarding it to the engine
+    let (tx, rx) = mpsc::channel();
+    let (engine_tx, engine_rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(engine_rx).unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    thread::spawn(move || {
+        for m in rx {
+            seen_clone.lock().unwrap().push(m.clone());
+            let _ = engine_tx.send(m);
+        }
+    });
+
+    adapter.push_order_book(snapshot(dec!(99), dec!(101)));
+    let order = Order::new("TEST".to_string(), OrderSide::BUY, OrderType::LIMIT, 5, Some("100".to_string()), None, None, None, "KRX".to_string(), None);
+    engine.send_order_internal(order).unwrap();
+    adapter.push_trade("TEST", dec!(100.5), 3);
+    adapter.push_order_book(snapshot(dec!(98), dec!(100)));
+
+    thread::sleep(Duration::from_millis(200));
+    engine.stop_internal().unwrap();
+    let recorded = seen.lock().unwrap().clone();
+    recorded
+}
+
+fn replay_all(adapter: &ReplayAdapter) -> Vec<IncomingMessage> {
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    adapter.connect().unwrap();
+    adapter.join();
+    rx.try_iter().collect()
+}
+
+fn as_json(msgs: &[IncomingMessage]) -> Vec<serde_json::Value> {
+    msgs.iter().map(|m| serde_json::to_value(m).unwrap()).collect()
+}
+
+#[test]
+fn test_replay_roundtrip_jsonl_and_zstd() {
+    let path = "tests/replay_roundtrip.jsonl";
+    let recorded = record_session(path);
+    assert!(recorded.iter().any(|m| matches!(m, IncomingMessage::Execution { .. })));
+
+    let replay = ReplayAdapter::new(path, ReplayPace::AsFastAsPossible).unwrap();
+    let replayed = replay_all(&replay);
+    assert_eq!(as_json(&replayed), as_json(&recorded));
+
+    // Same file, zstd-compressed as uploaded by the S3 logger
+    let zpath = "tests/replay_roundtrip.jsonl.zstd";
+    let raw = fs::read(path).unwrap();
+    fs::write(zpath, zstd::stream::encode_all(Cursor::new(raw), 0).unwrap()).unwrap();
+    let replay = ReplayAdapter::new(zpath, ReplayPace::AsFastAsPossible).unwrap();
+    assert_eq!(as_json(&replay_all(&replay)), as_json(&recorded));
+
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(zpath);
+}
+
+#[test]
+fn test_replay_paced_and_filtered() {
+    let path = "tests/replay_paced.jsonl";
+    let lines = [
+        r#"{"log_type":"MARKET_DATA","timestamp":100.0,"log_body":{"type":"Trade","symbol":"A","price":"10","qty":1}}"#,
+        r#"{"log_type":"ORDER_CANCEL_REQ","timestamp":100.1,"log_body":{"order_id":"x","success":true}}"#,
+        r#"{"log_type":"MARKET_DATA","timestamp":100.2,"log_body":{"type":"Trade","symbol":"B","price":"20","qty":2}}"#,
+        r#"{"log_type":"MARKET_DATA","timestamp":100.4,"log_body":{"type":"Trade","symbol":"A","price":"11","qty":3}}"#,
+    ];
+    fs::write(path, lines.join("\n")).unwrap();
+
+    let replay = ReplayAdapter::new(path, ReplayPace::Recorded { speed: 2.0 }).unwrap();
+    replay.subscribe(&["A".to_string()]).unwrap();
+    let started = Instant::now();
+    let msgs = replay_all(&replay);
+    assert!(started.elapsed() >= Duration::from_millis(190));
+    assert!(replay.is_finished());
+
+    let prices: Vec<_> = msgs.iter().filter_map(|m| match m {
+        IncomingMessage::MarketTrade { symbol, price, timestamp, .. } => Some((symbol.clone(), *price, *timestamp)),
+        _ => None,
+    }).collect();
+    assert_eq!(prices, vec![("A".to_string(), dec!(10), 100.0), ("A".to_string(), dec!(11), 100.4)]);
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_replay_orders_fill_against_tape() {
+    let path = "tests/replay_fill.jsonl";
+    let lines = [
+        r#"{"log_type":"MARKET_DATA","timestamp":1.0,"log_body":{"type":"OrderBookSnapshot","symbol":"A","update_id":1,"timestamp":1.0,"bids":[["99",10]],"asks":[["101",10]]}}"#,
+        r#"{"log_type":"MARKET_DATA","timestamp":2.0,"log_body":{"type":"OrderBookDelta","symbol":"A","update_id":2,"timestamp":2.0,"data":{"bp":[],"bv":[],"ap":["100"],"av":[4]}}}"#,
+    ];
+    fs::write(path, lines.join("\n")).unwrap();
+
+    let replay = ReplayAdapter::new(path, ReplayPace::AsFastAsPossible).unwrap();
+    let mut order = Order::new("A".to_string(), OrderSide::BUY, OrderType::LIMIT, 6, Some("100".to_string()), None, None, None, "KRX".to_string(), None);
+    order.order_id = Some("o1".to_string());
+    let (tx, rx) = mpsc::channel();
+    replay.set_monitor(tx);
+    replay.place_order(&order).unwrap();
+    replay.connect().unwrap();
+    replay.join();
+
+    let fills: Vec<_> = rx.try_iter().filter_map(|m| match m {
+        IncomingMessage::Execution { order_id, fill_qty, fill_price } => Some((order_id, fill_qty, fill_price)),
+        _ => None,
+    }).collect();
+    assert_eq!(fills, vec![("o1".to_string(), 4, dec!(100))]);
+    assert_eq!(replay.exchange().get_open_quantity("o1"), Some(2));
+
+    let _ = fs::remove_file(path);
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::{limit, setup, setup_logged};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::account::{AccountState, Position};
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use didius::oms::risk::RiskLimits;
+use rust_decimal::dec;
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn market(symbol: &str, side: OrderSide, qty: i64) -> Order {
+    Order::new(symbol.to_string(), side, OrderType::MARKET, qty, None, None, None, None, "KRX".to_string(), None)
+}
+
+fn wait_for_book(engine: &OMSEngine, symbol: &str) {
+    for _ in 0..100 {
+        if engine.get_order_book(symbol).is_some() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("no book for {}", symbol);
+}
+
+fn rejection(engine: &OMSEngine, oid: &str) -> Option<String> {
+    let order = engine.get_orders().get(oid).cloned().unwrap();
+    (order.state == OrderState::REJECTED).then(|| order.error_message.unwrap_or_default())
+}
+
+#[test]
+fn test_fat_finger_rejected_and_logged() {
+    let log_path = "tests/risk_reject.jsonl";
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup_logged(adapter.clone(), log_path);
+    engine.start_internal(None).unwrap();
+    engine.set_risk_limits(RiskLimits { max_order_qty: Some(10), max_notional: Some(dec!(1000000)), ..Default::default() });
+
+    let too_big = engine.send_order_internal(limit("101W09", OrderSide::BUY, 100, dec!(350))).unwrap();
+    assert!(rejection(&engine, &too_big).unwrap().contains("exceeds limit 10"));
+    assert_eq!(adapter.get_open_quantity(&too_big), None);
+
+    let too_rich = engine.send_order_internal(limit("101W09", OrderSide::BUY, 5, dec!(350000))).unwrap();
+    assert!(rejection(&engine, &too_rich).unwrap().contains("Notional 1750000"));
+
+    // Market order without a book cannot be valued
+    let blind = engine.send_order_internal(market("101W09", OrderSide::SELL, 1)).unwrap();
+    assert!(rejection(&engine, &blind).is_some());
+
+    let ok = engine.send_order_internal(limit("101W09", OrderSide::BUY, 5, dec!(350))).unwrap();
+    assert_eq!(rejection(&engine, &ok), None);
+    assert_eq!(adapter.get_open_quantity(&ok), Some(5));
+
+    engine.stop_internal().unwrap();
+    let events: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "RISK_REJECT")
+        .collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["log_body"]["order_id"], too_big.as_str());
+    assert_eq!(events[0]["log_body"]["check"], "max_order_qty");
+    assert_eq!(events[1]["log_body"]["check"], "max_notional");
+    assert_eq!(events[1]["log_body"]["price"], "350000");
+    let _ = fs::remove_file(log_path);
+}
+
+#[test]
+fn test_position_limit_counts_holdings_and_working_orders() {
+    let mut account = AccountState::new();
+    account.positions.insert("005930".to_string(), Position::new("005930".to_string(), 6, dec!(70000), dec!(70000)));
+    let adapter = Arc::new(MockAdapter::with_account_state(account));
+    let engine = setup(adapter);
+    engine.initialize_account_internal("".to_string()).unwrap();
+    let mut limits = RiskLimits { default_max_position: Some(10), ..Default::default() };
+    limits.max_position.insert("000660".to_string(), 1);
+    engine.set_risk_limits(limits);
+
+    let first = engine.send_order_internal(limit("005930", OrderSide::BUY, 3, dec!(70000))).unwrap();
+    assert_eq!(rejection(&engine, &first), None);
+    // 6 held + 3 working + 2 = 11
+    let second = engine.send_order_internal(limit("005930", OrderSide::BUY, 2, dec!(70000))).unwrap();
+    assert!(rejection(&engine, &second).unwrap().contains("Projected position 11"));
+    // Selling reduces exposure
+    let sell = engine.send_order_internal(limit("005930", OrderSide::SELL, 15, dec!(71000))).unwrap();
+    assert_eq!(rejection(&engine, &sell), None);
+    // The working sell may never fill: it makes no room for more buys, 6 + 3 + 2 = 11
+    let again = engine.send_order_internal(limit("005930", OrderSide::BUY, 2, dec!(70000))).unwrap();
+    assert!(rejection(&engine, &again).unwrap().contains("Projected position 11"));
+    // Nor do the working buys for more sells: 6 - 15 - 2 = -11
+    let short = engine.send_order_internal(limit("005930", OrderSide::SELL, 2, dec!(71000))).unwrap();
+    assert!(rejection(&engine, &short).unwrap().contains("Projected position -11"));
+
+    let other = engine.send_order_internal(limit("000660", OrderSide::SELL, 2, dec!(100000))).unwrap();
+    assert!(rejection(&engine, &other).unwrap().contains("exceeds limit 1"));
+}
+
+#[test]
+fn test_position_over_limit_can_be_reduced() {
+    let mut account = AccountState::new();
+    account.positions.insert("005930".to_string(), Position::new("005930".to_string(), 150, dec!(70000), dec!(70000)));
+    let adapter = Arc::new(MockAdapter::with_account_state(account));
+    let engine = setup(adapter);
+    engine.initialize_account_internal("".to_string()).unwrap();
+    engine.set_risk_limits(RiskLimits { default_max_position: Some(100), ..Default::default() });
+
+    // 150 - 10 = 140 is still over the limit, but closer to flat
+    let cut = engine.send_order_internal(limit("005930", OrderSide::SELL, 10, dec!(71000))).unwrap();
+    assert_eq!(rejection(&engine, &cut), None);
+    let more = engine.send_order_internal(limit("005930", OrderSide::BUY, 1, dec!(70000))).unwrap();
+    assert!(rejection(&engine, &more).unwrap().contains("Projected position 151"));
+    // Selling through flat to a short bigger than the long is not a reduction: 150 - 10 - 310 = -170
+    let flip = engine.send_order_internal(limit("005930", OrderSide::SELL, 310, dec!(71000))).unwrap();
+    assert!(rejection(&engine, &flip).unwrap().contains("Projected position -170"));
+}
+
+#[test]
+fn test_cash_and_open_order_limits() {
+    let mut account = AccountState::new();
+    account.balance = dec!(100000);
+    account.locked = dec!(20000);
+    let adapter = Arc::new(MockAdapter::with_account_state(account));
+    let engine = setup(adapter);
+    engine.initialize_account_internal("".to_string()).unwrap();
+    engine.set_risk_limits(RiskLimits { check_cash: true, max_open_orders: Some(2), ..Default::default() });
+
+    // 80000 available
+    let a = engine.send_order_internal(limit("TEST", OrderSide::BUY, 5, dec!(10000))).unwrap();
+    assert_eq!(rejection(&engine, &a), None);
+    let b = engine.send_order_internal(limit("TEST", OrderSide::BUY, 4, dec!(10000))).unwrap();
+    assert!(rejection(&engine, &b).unwrap().contains("available cash 30000"));
+    let c = engine.send_order_internal(limit("TEST", OrderSide::SELL, 1, dec!(20000))).unwrap();
+    assert_eq!(rejection(&engine, &c), None);
+    let d = engine.send_order_internal(limit("TEST", OrderSide::SELL, 1, dec!(20000))).unwrap();
+    assert!(rejection(&engine, &d).unwrap().contains("2 open orders"));
+}
+
+#[test]
+fn test_price_collar_against_mid() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.set_risk_limits(RiskLimits { price_collar: Some(dec!(0.05)), max_notional: Some(dec!(10000)), ..Default::default() });
+    adapter.push_order_book(OrderBookSnapshot {
+        symbol: "TEST".to_string(),
+        bids: vec![(dec!(99), 10)],
+        asks: vec![(dec!(101), 10)],
+        update_id: 1,
+        timestamp: 1.0,
+    });
+    wait_for_book(&engine, "TEST");
+
+    let far = engine.send_order_internal(limit("TEST", OrderSide::SELL, 1, dec!(90))).unwrap();
+    assert!(rejection(&engine, &far).unwrap().contains("away from mid 100"));
+    let near = engine.send_order_internal(limit("TEST", OrderSide::SELL, 1, dec!(96))).unwrap();
+    assert_eq!(rejection(&engine, &near), None);
+
+    // Market orders are valued at the touch: 101 * 100 > 10000
+    let sweep = engine.send_order_internal(market("TEST", OrderSide::BUY, 100)).unwrap();
+    assert!(rejection(&engine, &sweep).unwrap().contains("Notional 10100"));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{setup, wait_until};
+use anyhow::Result;
+use chrono::{NaiveDate, TimeZone, Utc};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::oms::session::{SessionSchedule, TradingPhase};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::params::StrategyParams;
+use didius::strategy::twap::TwapParams;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Unix seconds of a KST wall-clock time.
+fn kst(y: i32, m: u32, d: u32, h: u32, min: u32) -> f64 {
+    let utc = NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap() - chrono::Duration::hours(9);
+    Utc.from_utc_datetime(&utc).timestamp() as f64
+}
+
+#[test]
+fn test_krx_phases() {
+    let equity = SessionSchedule::krx_equity();
+    // Thursday 2026-10-15
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 7, 59)), TradingPhase::CLOSED);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 8, 10)), TradingPhase::PRE_OPEN);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 8, 45)), TradingPhase::OPENING_AUCTION);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 9, 0)), TradingPhase::CONTINUOUS);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 15, 19)), TradingPhase::CONTINUOUS);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 15, 20)), TradingPhase::CLOSING_AUCTION);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 16, 0)), TradingPhase::AFTER_HOURS);
+    assert_eq!(equity.phase_at(kst(2026, 10, 15, 18, 0)), TradingPhase::CLOSED);
+    // Saturday
+    assert_eq!(equity.phase_at(kst(2026, 10, 17, 10, 0)), TradingPhase::CLOSED);
+
+    let holiday = SessionSchedule::krx_equity().with_holidays([NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()]);
+    assert_eq!(holiday.phase_at(kst(2026, 10, 15, 10, 0)), TradingPhase::CLOSED);
+
+    // The night session belongs to the day it starts on, Friday's runs into Saturday morning
+    let derivatives = SessionSchedule::krx_derivatives();
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 15, 15, 40)), TradingPhase::CLOSING_AUCTION);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 15, 18, 0)), TradingPhase::NIGHT_SESSION);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 16, 4, 59)), TradingPhase::NIGHT_SESSION);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 16, 5, 0)), TradingPhase::CLOSED);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 17, 2, 0)), TradingPhase::NIGHT_SESSION);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 18, 2, 0)), TradingPhase::CLOSED);
+    assert_eq!(derivatives.phase_at(kst(2026, 10, 19, 2, 0)), TradingPhase::CLOSED);
+}
+
+/// Records the callbacks it gets.
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+    phases: Vec<TradingPhase>,
+}
+
+impl Strategy for Recorder {
+    fn on_order_book_update(&mut self, _book: &OrderBook) -> Result<StrategyAction> {
+        self.events.lock().unwrap().push("book".to_string());
+        Ok(StrategyAction::None)
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        self.events.lock().unwrap().push("trade".to_string());
+        Ok(StrategyAction::None)
+    }
+
+    fn on_timer(&mut self) -> Result<StrategyAction> {
+        self.events.lock().unwrap().push("timer".to_string());
+        Ok(StrategyAction::None)
+    }
+
+    fn active_phases(&self) -> Option<&[TradingPhase]> {
+        Some(&self.phases)
+    }
+
+    fn on_session_change(&mut self, from: TradingPhase, to: TradingPhase) -> Result<StrategyAction> {
+        self.events.lock().unwrap().push(format!("{}->{}", from, to));
+        Ok(StrategyAction::None)
+    }
+}
+
+#[test]
+fn test_engine_pauses_strategies_outside_active_phases() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    let events = Arc::new(Mutex::new(Vec::new()));
+    engine.add_strategy(Box::new(Recorder { events: events.clone(), phases: vec![TradingPhase::CONTINUOUS] }));
+    let take = || std::mem::take(&mut *events.lock().unwrap());
+
+    // No schedule: everything runs and no phase is reported
+    engine.check_strategies_at(kst(2026, 10, 15, 8, 10));
+    assert_eq!(engine.get_session_phase(), None);
+    assert_eq!(take(), vec!["timer"]);
+
+    engine.set_session_schedule(Some(SessionSchedule::krx_equity()));
+    engine.check_strategies_at(kst(2026, 10, 15, 8, 10));
+    assert_eq!(engine.get_session_phase(), Some(TradingPhase::PRE_OPEN));
+    engine.on_market_trade("", "TEST", dec!(100), 5, kst(2026, 10, 15, 8, 10));
+    assert_eq!(take(), vec!["CLOSED->PRE_OPEN"]);
+
+    engine.check_strategies_at(kst(2026, 10, 15, 9, 0));
+    engine.check_strategies_at(kst(2026, 10, 15, 9, 1));
+    engine.on_market_trade("", "TEST", dec!(100), 5, kst(2026, 10, 15, 9, 1));
+    assert_eq!(take(), vec!["PRE_OPEN->CONTINUOUS", "timer", "timer", "trade"]);
+
+    engine.check_strategies_at(kst(2026, 10, 15, 15, 20));
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 1)], asks: vec![(dec!(101), 1)], update_id: 1, timestamp: 1.0 });
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(take(), vec!["CONTINUOUS->CLOSING_AUCTION"]);
+}
+
+#[test]
+fn test_twap_pulls_child_before_closing_auction() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.set_session_schedule(Some(SessionSchedule::krx_equity()));
+
+    let params = TwapParams::from_params(&ExecutionStrategy::TWAP, &HashMap::from([("duration_seconds".to_string(), "3600".to_string()), ("slices".to_string(), "1".to_string()), ("pricing".to_string(), "PASSIVE".to_string())])).unwrap();
+    let order = Order::new("TEST".to_string(), OrderSide::BUY, OrderType::LIMIT, 10, Some("105".to_string()), Some(ExecutionStrategy::TWAP), Some(params.to_params()), None, "KRX".to_string(), None);
+    let parent = engine.send_order_internal(order).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 100)], asks: vec![(dec!(101), 100)], update_id: 1, timestamp: 1.0 });
+    thread::sleep(Duration::from_millis(100));
+
+    // Slicing runs off the wall clock; the phase is what the schedule says at the given time
+    let child = format!("{}-1", parent);
+    engine.check_strategies_at(kst(2026, 10, 15, 15, 10));
+    assert!(wait_until(|| engine.get_orders().get(&child).is_some_and(|o| o.state == OrderState::NEW)));
+
+    engine.check_strategies_at(kst(2026, 10, 15, 15, 20));
+    assert!(wait_until(|| engine.get_orders()[&child].state == OrderState::CANCELED));
+    engine.check_strategies_at(kst(2026, 10, 15, 15, 25));
+    thread::sleep(Duration::from_millis(100));
+    assert!(!engine.get_orders().contains_key(&format!("{}-2", parent)), "no slice into the auction");
+
+    // Next morning the schedule catches up
+    engine.check_strategies_at(kst(2026, 10, 16, 9, 0));
+    assert!(wait_until(|| engine.get_orders().get(&format!("{}-2", parent)).is_some_and(|o| o.quantity == 10)));
+}
+
+#[test]
+fn test_active_phases_param() {
+    let read = |value: &str| TwapParams::from_params(&ExecutionStrategy::TWAP, &HashMap::from([
+        ("duration_seconds".to_string(), "60".to_string()),
+        ("active_phases".to_string(), value.to_string()),
+    ]));
+    let params = read("continuous, closing_auction").unwrap();
+    assert_eq!(params.active_phases, Some(vec![TradingPhase::CONTINUOUS, TradingPhase::CLOSING_AUCTION]));
+    assert_eq!(params.to_params()["active_phases"], "CONTINUOUS,CLOSING_AUCTION");
+    assert!(read("LUNCH").unwrap_err().to_string().contains("Invalid active_phases"));
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::wait_until;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::registry::StrategyRegistry;
+use didius::strategy::shortfall::{BookEstimator, ShortfallStrategy};
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 2026-10-16 09:00:00 KST
+const OPEN: f64 = 1792108800.0;
+
+fn shortfall(side: OrderSide, qty: i64, price: Option<&str>, params: &[(&str, &str)]) -> Order {
+    let order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let mut o = Order::new("TEST".to_string(), side, order_type, qty, price.map(|p| p.to_string()), Some(ExecutionStrategy::IS), Some(params), None, "KRX".to_string(), None);
+    o.order_id = Some("P".to_string());
+    o
+}
+
+fn strategy(params: &[(&str, &str)]) -> ShortfallStrategy {
+    let start = OPEN.to_string();
+    let mut params = params.to_vec();
+    params.push(("start_time", start.as_str()));
+    ShortfallStrategy::from_order(&shortfall(OrderSide::BUY, 100, None, &params)).unwrap()
+}
+
+fn book(bid: Decimal, ask: Decimal, timestamp: f64) -> OrderBook {
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(vec![(bid, 10)], vec![(ask, 10)], 1, timestamp);
+    book
+}
+
+#[test]
+fn test_urgency_front_loads_trajectory() {
+    // Without estimates kappa * T is the urgency itself
+    let even = strategy(&[("duration_seconds", "1000"), ("urgency", "0")]);
+    assert_eq!(even.target_quantity(OPEN + 250.0), 25);
+    let urgent = strategy(&[("duration_seconds", "1000"), ("urgency", "3")]);
+    assert_eq!(urgent.kappa_t(), 3.0);
+    // 1 - sinh(2.25) / sinh(3) = 0.532
+    assert_eq!(urgent.target_quantity(OPEN + 250.0), 54);
+    assert_eq!(urgent.target_quantity(OPEN + 1000.0), 100);
+    assert_eq!(urgent.target_quantity(OPEN - 10.0), 0);
+
+    // The first child trades what is due by the end of its interval
+    let mut strat = strategy(&[("duration_seconds", "1000"), ("urgency", "3"), ("interval_seconds", "250")]);
+    strat.on_order_book_update(&book(dec!(99), dec!(101), OPEN)).unwrap();
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(OPEN) else { panic!("no child") };
+    assert_eq!((child.order_id.as_deref(), child.quantity, child.price), (Some("P-1"), 54, Some(dec!(101))));
+    let mut child = child.clone();
+    child.state = OrderState::NEW;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(matches!(strat.on_timer_at(OPEN + 251.0), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+}
+
+#[test]
+fn test_volatility_and_spread_estimates() {
+    let mut estimator = BookEstimator::new(3);
+    assert_eq!((estimator.volatility(), estimator.spread()), (None, None));
+    estimator.on_book(&book(dec!(99), dec!(101), 0.0));
+    estimator.on_book(&book(dec!(109), dec!(111), 1.0));
+    let vol = estimator.volatility().unwrap();
+    assert!((vol - 1.1f64.ln()).abs() < 1e-12, "{}", vol);
+    estimator.on_book(&book(dec!(109), dec!(111), 2.0));
+    assert!((estimator.volatility().unwrap() - (1.1f64.ln().powi(2) / 2.0).sqrt()).abs() < 1e-12);
+    // Only the last 3 books are kept
+    estimator.on_book(&book(dec!(109), dec!(111), 4.0));
+    assert_eq!(estimator.volatility(), Some(0.0));
+    assert!((estimator.spread().unwrap() - 2.0 / 110.0).abs() < 1e-12);
+
+    // A calm, wide book trades closer to evenly than a volatile, tight one
+    let params = [("duration_seconds", "100"), ("urgency", "1")];
+    let mut calm = strategy(&params);
+    let mut volatile = strategy(&params);
+    for (i, mid) in [100, 100, 101, 100].into_iter().enumerate() {
+        let mid = Decimal::from(mid);
+        calm.on_order_book_update(&book(mid - dec!(1), mid + dec!(1), OPEN + i as f64)).unwrap();
+        volatile.on_order_book_update(&book(mid * dec!(10) - dec!(1), mid * dec!(10) + dec!(1), OPEN + i as f64)).unwrap();
+    }
+    assert!(volatile.kappa_t() > calm.kappa_t());
+    assert!(volatile.target_quantity(OPEN + 20.0) > calm.target_quantity(OPEN + 20.0));
+    // Arrival falls back to the first book's mid
+    assert_eq!(calm.arrival_price, Some(dec!(100)));
+}
+
+#[test]
+fn test_shortfall_report() {
+    let mut strat = ShortfallStrategy::from_order(&shortfall(OrderSide::SELL, 10, Some("95"), &[("duration_seconds", "1"), ("arrival_price", "100"), ("start_time", &OPEN.to_string())])).unwrap();
+    strat.on_order_book_update(&book(dec!(97), dec!(99), OPEN)).unwrap();
+    assert_eq!(strat.arrival_price, Some(dec!(100)));
+
+    let StrategyAction::PlaceOrder(mut child) = strat.on_timer_at(OPEN + 10.0) else { panic!("no child") };
+    child.filled_quantity = 8;
+    child.average_fill_price = dec!(97);
+    child.state = OrderState::PARTIALLY_FILLED;
+    strat.on_order_status_update(&child).unwrap();
+
+    // Sold 8 three below arrival; 2 left while the mid is two below
+    assert_eq!(strat.shortfall_bps(), Some(300.0));
+    let report = strat.report().unwrap();
+    assert_eq!(report["arrival_price"], "100");
+    assert_eq!(report["shortfall_value"], "24");
+    assert_eq!(report["opportunity_cost"], "4");
+
+    let registry = StrategyRegistry::default();
+    let err = |p: &[(&str, &str)]| registry.create(&shortfall(OrderSide::BUY, 10, None, p), false).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(&[]).contains("IS requires duration_seconds"));
+    assert!(err(&[("duration_seconds", "60"), ("urgency", "-1")]).contains("urgency"));
+    assert!(err(&[("duration_seconds", "60"), ("estimation_window", "1")]).contains("estimation_window"));
+}
+
+#[test]
+fn test_engine_stamps_arrival_and_logs_shortfall() {
+    let log_path = "tests/shortfall_report.jsonl";
+    let _ = fs::remove_file(log_path);
+    let config = LoggerConfig { destination: LogDestinationInfo::LocalFile { path: log_path.to_string() }, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 100)], asks: vec![(dec!(101), 100)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let mut order = shortfall(OrderSide::BUY, 10, Some("102"), &[("duration_seconds", "1"), ("interval_seconds", "0.2")]);
+    order.order_id = None;
+    let parent = engine.send_order_internal(order).unwrap();
+    assert_eq!(engine.get_orders()[&parent].strategy_params["arrival_price"], "100");
+
+    assert!(wait_until(|| engine.get_orders()[&parent].state == OrderState::FILLED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    thread::sleep(Duration::from_millis(300));
+    engine.stop_internal().unwrap();
+
+    let reports: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "STRATEGY_REPORT")
+        .collect();
+    assert_eq!(reports.len(), 1);
+    let body = &reports[0]["log_body"];
+    assert_eq!(body["strategy"], "IS");
+    assert_eq!(body["arrival_price"], "100");
+    assert_eq!(body["average_price"], "101");
+    assert_eq!(body["shortfall_bps"], 100.0);
+    let _ = fs::remove_file(log_path);
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use common::wait_until;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::registry::StrategyRegistry;
+use didius::strategy::spread::SpreadStrategy;
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+fn spread_order(qty: i64, params: &[(&str, &str)]) -> Order {
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let mut o = Order::new("A".to_string(), OrderSide::BUY, OrderType::MARKET, qty, None, Some(ExecutionStrategy::SPREAD), Some(params), None, "KRX".to_string(), Some("default".to_string()));
+    o.order_id = Some("P".to_string());
+    o
+}
+
+fn strategy(params: &[(&str, &str)]) -> SpreadStrategy {
+    SpreadStrategy::from_order(&spread_order(10, params)).unwrap()
+}
+
+fn book(symbol: &str, bid: Decimal, ask: Decimal) -> OrderBook {
+    let mut book = OrderBook::new(symbol.to_string());
+    book.rebuild(vec![(bid, 100)], vec![(ask, 100)], 1, 0.0);
+    book
+}
+
+fn flatten(action: &StrategyAction) -> Vec<StrategyAction> {
+    match action {
+        StrategyAction::Each(actions) => actions.iter().flat_map(flatten).collect(),
+        StrategyAction::None => Vec::new(),
+        other => vec![other.clone()],
+    }
+}
+
+/// Orders placed by `action`, as (order ID, symbol, side, quantity).
+fn placed(action: &StrategyAction) -> Vec<(String, String, OrderSide, i64)> {
+    flatten(action).into_iter().filter_map(|a| match a {
+        StrategyAction::PlaceOrder(o) => Some((o.order_id.unwrap(), o.symbol, o.side, o.quantity)),
+        _ => None,
+    }).collect()
+}
+
+fn canceled(action: &StrategyAction) -> Vec<String> {
+    flatten(action).into_iter().filter_map(|a| match a {
+        StrategyAction::CancelOrder(id) => Some(id),
+        _ => None,
+    }).collect()
+}
+
+/// Play the venue's answer to a child.
+fn update(strat: &mut SpreadStrategy, id: &str, state: OrderState, filled: i64, price: Decimal) -> StrategyAction {
+    let mut order = Order::new("A".to_string(), OrderSide::BUY, OrderType::LIMIT, 10, Some("100".to_string()), None, None, None, "KRX".to_string(), None);
+    order.order_id = Some(id.to_string());
+    order.state = state;
+    order.filled_quantity = filled;
+    order.average_fill_price = price;
+    strat.on_order_status_update(&order).unwrap()
+}
+
+fn p(id: &str, symbol: &str, side: OrderSide, qty: i64) -> (String, String, OrderSide, i64) {
+    (id.to_string(), symbol.to_string(), side, qty)
+}
+
+fn now() -> f64 {
+    chrono::Local::now().timestamp_millis() as f64 / 1000.0
+}
+
+#[test]
+fn test_works_passive_leg_while_spread_holds() {
+    let t = now();
+    let mut strat = strategy(&[("hedge_symbol", "B"), ("entry_spread", "50")]);
+    assert_eq!(strat.symbols(), vec!["B".to_string()]);
+    strat.on_order_book_update(&book("A", dec!(99), dec!(101))).unwrap();
+    assert!(strat.spread().is_none(), "no hedge book yet");
+    // Books alone do not place before the first timer tick
+    assert!(placed(&strat.on_order_book_update(&book("B", dec!(49), dec!(51))).unwrap()).is_empty());
+    assert_eq!(strat.spread(), Some(dec!(50)));
+    assert_eq!(placed(&strat.on_timer_at(t)), vec![p("P-1", "A", OrderSide::BUY, 10)]);
+    update(&mut strat, "P-1", OrderState::NEW, 0, Decimal::ZERO);
+
+    // The hedge leg drops: buying A at 99 against selling B at 48 is too rich, so the child is pulled
+    assert_eq!(canceled(&strat.on_order_book_update(&book("B", dec!(48), dec!(50))).unwrap()), vec!["P-1"]);
+    assert!(placed(&update(&mut strat, "P-1", OrderState::CANCELED, 0, Decimal::ZERO)).is_empty());
+    // Back in range, the passive leg is joined again
+    assert_eq!(placed(&strat.on_order_book_update(&book("B", dec!(50), dec!(52))).unwrap()), vec![p("P-2", "A", OrderSide::BUY, 10)]);
+
+    // A ratio spread divides the legs
+    let mut ratio = strategy(&[("hedge_symbol", "B"), ("entry_spread", "2"), ("spread_type", "RATIO")]);
+    ratio.on_order_book_update(&book("A", dec!(100), dec!(101))).unwrap();
+    ratio.on_order_book_update(&book("B", dec!(40), dec!(41))).unwrap();
+    assert_eq!(ratio.spread(), Some(dec!(2.5)));
+    assert!(placed(&ratio.on_timer_at(t)).is_empty());
+
+    let registry = StrategyRegistry::default();
+    let err = |p: &[(&str, &str)]| registry.create(&spread_order(10, p), false).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(&[("entry_spread", "1")]).contains("hedge_symbol"));
+    assert!(err(&[("hedge_symbol", "A"), ("entry_spread", "1")]).contains("must differ"));
+    assert!(err(&[("hedge_symbol", "B"), ("entry_spread", "1"), ("hedge_ratio", "0")]).contains("hedge_ratio"));
+    assert!(err(&[("hedge_symbol", "B"), ("entry_spread", "1"), ("spread_type", "SUM")]).contains("spread_type"));
+}
+
+#[test]
+fn test_hedges_fills_and_unwinds_rejected_hedge() {
+    let t = now();
+    let mut strat = strategy(&[("hedge_symbol", "B"), ("entry_spread", "50"), ("hedge_ratio", "2")]);
+    strat.on_order_book_update(&book("A", dec!(99), dec!(101))).unwrap();
+    strat.on_order_book_update(&book("B", dec!(49), dec!(51))).unwrap();
+    strat.on_timer_at(t);
+
+    // Each passive fill is hedged at once, two for one, on the other side of B
+    assert_eq!(placed(&update(&mut strat, "P-1", OrderState::PARTIALLY_FILLED, 4, dec!(99))), vec![p("P-H-1", "B", OrderSide::SELL, 8)]);
+    update(&mut strat, "P-H-1", OrderState::FILLED, 8, dec!(49));
+    assert_eq!(strat.realized_spread(), Some(dec!(50)));
+    assert_eq!(strat.unhedged_quantity(), 0);
+
+    // The next hedge is refused: the passive child is pulled and the unhedged 3 sold back
+    assert_eq!(placed(&update(&mut strat, "P-1", OrderState::PARTIALLY_FILLED, 7, dec!(99))), vec![p("P-H-2", "B", OrderSide::SELL, 6)]);
+    assert_eq!(canceled(&update(&mut strat, "P-H-2", OrderState::REJECTED, 0, Decimal::ZERO)), vec!["P-1"]);
+    assert!(strat.unwinding);
+    assert_eq!(placed(&update(&mut strat, "P-1", OrderState::CANCELED, 7, dec!(99))), vec![p("P-U-1", "A", OrderSide::SELL, 3)]);
+    assert!(!strat.is_completed());
+    update(&mut strat, "P-U-1", OrderState::FILLED, 3, dec!(98));
+    assert!(strat.is_completed());
+
+    let report = strat.report().unwrap();
+    assert_eq!(report["strategy"], "SPREAD");
+    assert_eq!(report["hedge_failed"], true);
+    assert_eq!(report["unwound_quantity"], 3);
+    assert_eq!(report["unhedged_quantity"], 0);
+}
+
+#[test]
+fn test_unwinds_hedge_not_filled_in_time() {
+    let t = now();
+    let mut strat = strategy(&[("hedge_symbol", "B"), ("entry_spread", "50"), ("hedge_timeout_seconds", "1")]);
+    strat.on_order_book_update(&book("A", dec!(99), dec!(101))).unwrap();
+    strat.on_order_book_update(&book("B", dec!(49), dec!(51))).unwrap();
+    strat.on_timer_at(t);
+    update(&mut strat, "P-1", OrderState::FILLED, 10, dec!(99));
+    update(&mut strat, "P-H-1", OrderState::PARTIALLY_FILLED, 6, dec!(49));
+
+    // Past the timeout the hedge is pulled; what it left is unwound
+    assert!(canceled(&strat.on_timer_at(t + 0.5)).is_empty());
+    assert_eq!(canceled(&strat.on_timer_at(t + 5.0)), vec!["P-H-1"]);
+    assert_eq!(placed(&update(&mut strat, "P-H-1", OrderState::CANCELED, 6, dec!(49))), vec![p("P-U-1", "A", OrderSide::SELL, 4)]);
+
+    // A refused unwind leaves the position to the operator and ends the strategy
+    update(&mut strat, "P-U-1", OrderState::REJECTED, 0, Decimal::ZERO);
+    assert!(strat.is_completed());
+    assert_eq!(strat.unhedged_quantity(), 4);
+}
+
+#[test]
+fn test_engine_hedges_on_other_symbol() {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(OrderBookSnapshot { symbol: "A".to_string(), bids: vec![(dec!(99), 100)], asks: vec![(dec!(101), 100)], update_id: 1, timestamp: 1.0 });
+    adapter.push_order_book(OrderBookSnapshot { symbol: "B".to_string(), bids: vec![(dec!(49), 100)], asks: vec![(dec!(51), 100)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_order_book("A").is_some() && engine.get_order_book("B").is_some()));
+    engine.start_internal(None).unwrap();
+
+    // The hedge book is handed over at attach, so the first tick can price the spread
+    let mut order = spread_order(5, &[("hedge_symbol", "B"), ("entry_spread", "50")]);
+    order.order_id = None;
+    let parent = engine.send_order_internal(order).unwrap();
+    let child = |symbol: &str| engine.get_child_orders(&parent).into_iter().find(|o| o.symbol == symbol);
+    assert!(wait_until(|| child("A").is_some_and(|o| o.state == OrderState::NEW)));
+    assert_eq!(child("A").unwrap().price, Some(dec!(99)));
+
+    // Someone sells through the passive bid; the fill is hedged by selling B
+    adapter.push_order_book(OrderBookSnapshot { symbol: "A".to_string(), bids: vec![(dec!(98), 100)], asks: vec![(dec!(99), 100)], update_id: 2, timestamp: 2.0 });
+    assert!(wait_until(|| child("B").is_some_and(|o| o.state == OrderState::FILLED)));
+    let hedge = child("B").unwrap();
+    assert_eq!((hedge.side, hedge.quantity, hedge.average_fill_price), (OrderSide::SELL, 5, dec!(49)));
+
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    let parent = &engine.get_orders()[&parent];
+    assert_eq!((parent.state.clone(), parent.filled_quantity), (OrderState::FILLED, 5), "only the passive leg fills the parent");
+    assert_eq!(engine.get_account().positions.get("B").map(|p| p.quantity), Some(-5));
+    engine.stop_internal().unwrap();
+}
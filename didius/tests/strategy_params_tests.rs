@@ -0,0 +1,171 @@
+mod common;
+
+use common::{setup, wait_until};
+use anyhow::Result;
+use didius::adapter::mock::MockAdapter;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::child::ChildPricing;
+use didius::strategy::params::StrategyParams;
+use didius::strategy::pov::{PovEndAction, PovParams};
+use didius::strategy::registry::{BoxedStrategy, StrategyRegistry};
+use didius::strategy::stop::{StopParams, Trail};
+use didius::strategy::twap::TwapParams;
+use didius::strategy::vwap::VwapParams;
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyModule};
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn order(id: &str, strategy: ExecutionStrategy, pairs: &[(&str, &str)]) -> Order {
+    let mut order = Order::new("TEST".to_string(), OrderSide::SELL, OrderType::LIMIT, 4, Some("110".to_string()), Some(strategy), Some(params(pairs)), None, "KRX".to_string(), None);
+    order.order_id = Some(id.to_string());
+    order
+}
+
+#[test]
+fn test_params_parse_with_defaults_and_round_trip() {
+    let twap = TwapParams::from_params(&ExecutionStrategy::TWAP, &params(&[("duration_seconds", "60")])).unwrap();
+    assert_eq!(twap, TwapParams { duration: 60.0, slices: 10, start_time: None, pricing: ChildPricing::Aggressive, active_phases: None });
+    assert_eq!(TwapParams::from_params(&ExecutionStrategy::TWAP, &twap.to_params()).unwrap(), twap);
+
+    let stop = StopParams::from_params(&ExecutionStrategy::STOP, &params(&[("trail_ticks", "4"), ("tick_size", "0.05"), ("chained_price", "99")])).unwrap();
+    assert_eq!(stop.trail, Some(Trail::Ticks { ticks: 4, tick_size: dec!(0.05) }));
+    assert_eq!((stop.trigger_price, stop.chained_price, stop.trigger_timestamp), (None, Some(dec!(99)), 0.0));
+    assert_eq!(StopParams::from_params(&ExecutionStrategy::STOP, &stop.to_params()).unwrap(), stop);
+
+    let pov = PovParams::from_params(&ExecutionStrategy::POV, &params(&[("participation", "0.1"), ("end_action", "complete")])).unwrap();
+    assert_eq!((pov.min_participation, pov.max_participation, pov.end_action, pov.pricing), (0.1, 0.1, PovEndAction::Complete, ChildPricing::Passive));
+    assert_eq!(PovParams::from_params(&ExecutionStrategy::POV, &pov.to_params()).unwrap(), pov);
+
+    let vwap = VwapParams::from_params(&ExecutionStrategy::VWAP, &params(&[("duration_seconds", "600"), ("volume_profile", "1, 2,1")])).unwrap();
+    assert_eq!(vwap.volume_profile, Some(vec![1.0, 2.0, 1.0]));
+    assert_eq!(VwapParams::from_params(&ExecutionStrategy::VWAP, &vwap.to_params()).unwrap(), vwap);
+}
+
+#[test]
+fn test_schema_errors_name_the_key() {
+    let err = |strategy: ExecutionStrategy, p: &[(&str, &str)]| -> String {
+        let registry = StrategyRegistry::default();
+        registry.create(&order("X", strategy, p), false).err().map(|e| e.to_string()).unwrap_or_default()
+    };
+    let typo = err(ExecutionStrategy::STOP, &[("trigger_prise", "95")]);
+    assert!(typo.contains("Unknown STOP strategy_params: trigger_prise") && typo.contains("trigger_price"), "{}", typo);
+    assert!(err(ExecutionStrategy::STOP, &[]).contains("STOP requires trigger_price"));
+    assert!(err(ExecutionStrategy::STOP, &[("trigger_price", "abc")]).contains("Invalid trigger_price"));
+    assert!(err(ExecutionStrategy::STOP, &[("trigger_price", "95"), ("trigger_side", "UP")]).contains("Invalid trigger_side"));
+    assert!(err(ExecutionStrategy::LIMIT, &[("price", "1")]).contains("LIMIT takes no strategy_params"));
+    assert!(err(ExecutionStrategy::TWAP, &[("duration_seconds", "60"), ("slice", "5")]).contains("Unknown TWAP strategy_params: slice"));
+    assert!(err(ExecutionStrategy::OCO, &[("stop_price", "120"), ("take_profit_price", "100")]).contains("order price"));
+
+    // Orders without a managed strategy are left alone
+    assert!(StrategyRegistry::default().create(&order("X", ExecutionStrategy::NONE, &[("anything", "1")]), false).unwrap().is_none());
+}
+
+#[test]
+fn test_engine_rejects_misspelled_stop() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+
+    let err = engine.send_order_internal(order("typo", ExecutionStrategy::STOP, &[("trigger_prise", "95")])).unwrap_err();
+    assert!(err.to_string().contains("trigger_prise"), "{}", err);
+    let stored = engine.get_orders()["typo"].clone();
+    assert_eq!(stored.state, OrderState::REJECTED);
+    assert!(stored.error_message.unwrap().contains("trigger_prise"));
+    assert_eq!(adapter.get_open_quantity("typo"), None);
+
+    let oid = engine.send_order_internal(order("good", ExecutionStrategy::STOP, &[("trigger_price", "95")])).unwrap();
+    assert!(wait_until(|| engine.get_orders()[&oid].state == OrderState::NEW));
+    assert_eq!(engine.get_active_strategy_order_ids(), vec!["good".to_string()]);
+}
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Cancels its order on the first book.
+struct CancelOnBook {
+    order_id: String,
+    done: bool,
+}
+
+impl Strategy for CancelOnBook {
+    fn on_order_book_update(&mut self, _book: &OrderBook) -> Result<StrategyAction> {
+        if self.done {
+            return Ok(StrategyAction::None);
+        }
+        self.done = true;
+        Ok(StrategyAction::CancelOrder(self.order_id.clone()))
+    }
+
+    fn on_trade_update(&mut self, _symbol: &str, _price: Decimal, _quantity: i64, _timestamp: f64) -> Result<StrategyAction> {
+        Ok(StrategyAction::None)
+    }
+
+    fn is_completed(&self) -> bool {
+        self.done
+    }
+}
+
+fn cancel_on_book(order: &Order, _recovered: bool) -> Result<BoxedStrategy> {
+    CREATED.fetch_add(1, Ordering::SeqCst);
+    Ok(Box::new(CancelOnBook { order_id: order.order_id.clone().unwrap_or_default(), done: false }))
+}
+
+#[test]
+fn test_engine_uses_registered_factory() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    engine.register_strategy(ExecutionStrategy::LIMIT, cancel_on_book);
+    engine.start_internal(None).unwrap();
+
+    let oid = engine.send_order_internal(order("mine", ExecutionStrategy::LIMIT, &[("any", "thing")])).unwrap();
+    assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+    assert!(wait_until(|| engine.get_orders()[&oid].state == OrderState::NEW));
+    adapter.push_order_book(OrderBookSnapshot { symbol: "TEST".to_string(), bids: vec![(dec!(99), 1)], asks: vec![(dec!(101), 1)], update_id: 1, timestamp: 1.0 });
+    assert!(wait_until(|| engine.get_orders()[&oid].state == OrderState::CANCELED));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_python_params_classes() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let m = PyModule::new(py, "didius").unwrap();
+        didius::oms::register(&m).unwrap();
+        didius::strategy::register(&m).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("didius", m).unwrap();
+        py.run(c_str!(r#"
+p = didius.TwapParams(60, slices=4, pricing="passive")
+assert p.to_dict() == {"duration_seconds": "60", "slices": "4", "pricing": "PASSIVE"}, p.to_dict()
+assert p.strategy == didius.ExecutionStrategy.TWAP
+assert repr(p) == "TwapParams(duration_seconds='60', pricing='PASSIVE', slices='4')", repr(p)
+
+b = didius.BracketParams("95", take_profit_price="110")
+assert b.strategy == didius.ExecutionStrategy.BRACKET
+assert didius.BracketParams("95").strategy == didius.ExecutionStrategy.OCO
+
+order = didius.Order("TEST", didius.OrderSide.BUY, didius.OrderType.LIMIT, 10, "100",
+                     strategy=b.strategy, strategy_params=b.to_dict())
+assert order.strategy_params["take_profit_price"] == "110"
+
+for bad, text in [(lambda: didius.TwapParams(60, slices=0), "slices"),
+                  (lambda: didius.StopParams(), "trigger_price"),
+                  (lambda: didius.PovParams(0.1, max_participation=0.05), "bands"),
+                  (lambda: didius.PegParams(peg="LAST"), "Unknown peg")]:
+    try:
+        bad()
+        raise AssertionError("accepted: " + text)
+    except ValueError as e:
+        assert text in str(e), str(e)
+"#), Some(&globals), None).unwrap();
+    });
+}
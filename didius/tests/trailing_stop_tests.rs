@@ -0,0 +1,126 @@
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::{OrderBook, OrderBookSnapshot};
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::stop::{StopStrategy, Trail};
+use rust_decimal::dec;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn snapshot(symbol: &str, bid: Decimal, ask: Decimal) -> OrderBookSnapshot {
+    OrderBookSnapshot { symbol: symbol.to_string(), bids: vec![(bid, 10)], asks: vec![(ask, 10)], update_id: 1, timestamp: 1.0 }
+}
+
+fn book(bid: Decimal, ask: Decimal) -> OrderBook {
+    let s = snapshot("TEST", bid, ask);
+    let mut book = OrderBook::new("TEST".to_string());
+    book.rebuild(s.bids, s.asks, s.update_id, s.timestamp);
+    book
+}
+
+fn stop(side: OrderSide, trigger: Option<Decimal>, chained: Option<Decimal>, trail: Trail) -> StopStrategy {
+    let mut s = StopStrategy::new("S".to_string(), "TEST".to_string(), side.clone(), 1, side, trigger.unwrap_or_default(), 0.0, chained).with_trail(trail);
+    s.armed = trigger.is_some();
+    s
+}
+
+fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_sell_stop_trails_the_high() {
+    let mut s = stop(OrderSide::SELL, None, None, Trail::Amount(dec!(2)));
+    assert!(matches!(s.on_order_book_update(&book(dec!(100), dec!(101))).unwrap(), StrategyAction::None));
+    assert_eq!(s.trigger_price, dec!(99));
+    s.on_order_book_update(&book(dec!(104), dec!(105))).unwrap();
+    assert_eq!(s.trigger_price, dec!(103));
+    // Never backs off
+    s.on_order_book_update(&book(dec!(103), dec!(104))).unwrap();
+    assert_eq!(s.trigger_price, dec!(103));
+    // Other symbols are ignored
+    let mut other = book(dec!(1), dec!(2));
+    other.symbol = "OTHER".to_string();
+    assert!(matches!(s.on_order_book_update(&other).unwrap(), StrategyAction::None));
+
+    let fired = s.on_order_book_update(&book(dec!(102), dec!(103))).unwrap();
+    assert!(matches!(fired, StrategyAction::ModifyPrice(ref id, None) if id == "S"), "{:?}", fired);
+    assert!(s.triggered);
+}
+
+#[test]
+fn test_buy_stop_trails_the_low_by_percent() {
+    // Protecting a short: starts at 110, follows the bid down 5% above it
+    let mut s = stop(OrderSide::BUY, Some(dec!(110)), Some(dec!(103)), Trail::Percent(dec!(5)));
+    s.on_order_book_update(&book(dec!(100), dec!(100.5))).unwrap();
+    assert_eq!(s.trigger_price, dec!(105));
+    s.on_order_book_update(&book(dec!(96), dec!(96.5))).unwrap();
+    assert_eq!(s.trigger_price, dec!(100.8));
+    s.on_order_book_update(&book(dec!(98), dec!(98.5))).unwrap();
+    assert_eq!(s.trigger_price, dec!(100.8));
+
+    let fired = s.on_order_book_update(&book(dec!(101), dec!(101.5))).unwrap();
+    assert!(matches!(fired, StrategyAction::ModifyPrice(_, Some(p)) if p == dec!(103)), "{:?}", fired);
+}
+
+#[test]
+fn test_trail_params() {
+    assert_eq!(Trail::from_params(&params(&[])).unwrap(), None);
+    assert_eq!(Trail::from_params(&params(&[("trail_amount", "2.5")])).unwrap(), Some(Trail::Amount(dec!(2.5))));
+    let ticks = Trail::from_params(&params(&[("trail_ticks", "4"), ("tick_size", "0.05")])).unwrap().unwrap();
+    assert_eq!(ticks.distance(dec!(350)), dec!(0.20));
+
+    let err = |p: &[(&str, &str)]| Trail::from_params(&params(p)).unwrap_err().to_string();
+    assert!(err(&[("trail_ticks", "4")]).contains("tick_size"));
+    assert!(err(&[("trail_amount", "1"), ("trail_percent", "1")]).contains("only one"));
+    assert!(err(&[("trail_percent", "0")]).contains("positive"));
+    assert!(err(&[("trail_percent", "150")]).contains("below 100"));
+}
+
+#[test]
+fn test_engine_trailing_stop_protects_night_long() {
+    let config = LoggerConfig { destination: LogDestinationInfo::Console, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(snapshot("101W09", dec!(350.00), dec!(350.05)));
+
+    // Resting far above the market; 4 ticks of 0.05 behind the best ask
+    let mut order = Order::new("101W09".to_string(), OrderSide::SELL, OrderType::LIMIT, 1, Some("400".to_string()),
+        Some(ExecutionStrategy::STOP), Some(params(&[("trail_ticks", "4"), ("tick_size", "0.05")])), None, "KRX".to_string(), None);
+    order.order_id = Some("night-stop".to_string());
+    let oid = engine.send_order_internal(order).unwrap();
+
+    let wait = |cond: &dyn Fn() -> bool| (0..300).any(|_| { thread::sleep(Duration::from_millis(10)); cond() });
+    assert!(wait(&|| engine.get_orders().get(&oid).is_some_and(|o| o.state == OrderState::NEW)));
+
+    // Rally: trigger follows to 350.85; a dip to 350.90 does not fire
+    adapter.push_order_book(snapshot("101W09", dec!(351.00), dec!(351.05)));
+    adapter.push_order_book(snapshot("101W09", dec!(350.85), dec!(350.90)));
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(engine.get_orders()[&oid].price, Some(dec!(400)));
+
+    // Through the trigger: re-priced to market and filled at the bid
+    adapter.push_order_book(snapshot("101W09", dec!(350.80), dec!(350.85)));
+    assert!(wait(&|| !engine.get_orders().contains_key(&oid)));
+    let position = engine.get_account().positions.get("101W09").cloned().unwrap();
+    assert_eq!(position.quantity, -1);
+
+    // Bad trail parameters reject the order instead of sending it unprotected
+    let mut bad = Order::new("101W09".to_string(), OrderSide::SELL, OrderType::LIMIT, 1, Some("400".to_string()),
+        Some(ExecutionStrategy::STOP), Some(params(&[("trail_ticks", "4")])), None, "KRX".to_string(), None);
+    bad.order_id = Some("bad-stop".to_string());
+    assert!(engine.send_order_internal(bad).is_err());
+    assert_eq!(engine.get_orders()["bad-stop"].state, OrderState::REJECTED);
+    assert_eq!(adapter.get_open_quantity("bad-stop"), None);
+}
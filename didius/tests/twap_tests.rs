@@ -0,0 +1,188 @@
+mod common;
+
+use common::{setup, wait_until};
+use didius::adapter::mock::MockAdapter;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::twap::TwapStrategy;
+use rust_decimal::dec;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn twap(side: OrderSide, qty: i64, price: Option<&str>, params: &[(&str, &str)]) -> Order {
+    let order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Order::new("TEST".to_string(), side, order_type, qty, price.map(|p| p.to_string()), Some(ExecutionStrategy::TWAP), Some(params), None, "KRX".to_string(), None)
+}
+
+fn push_book(adapter: &MockAdapter, bid: i64, ask: i64) {
+    adapter.push_order_book(OrderBookSnapshot {
+        symbol: "TEST".to_string(),
+        bids: vec![(bid.into(), 100)],
+        asks: vec![(ask.into(), 100)],
+        update_id: 1,
+        timestamp: 1.0,
+    });
+}
+
+fn children(engine: &OMSEngine, parent: &str) -> Vec<Order> {
+    let prefix = format!("{}-", parent);
+    let mut out: Vec<Order> = engine.get_orders().into_values()
+        .filter(|o| o.order_id.as_deref().is_some_and(|id| id.starts_with(&prefix)))
+        .collect();
+    out.sort_by(|a, b| a.order_id.cmp(&b.order_id));
+    out
+}
+
+#[test]
+fn test_schedule_and_catch_up() {
+    let mut parent = twap(OrderSide::BUY, 10, None, &[("duration_seconds", "100"), ("slices", "4"), ("start_time", "1000")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = TwapStrategy::from_order(&parent).unwrap();
+
+    assert_eq!(strat.target_quantity(999.0), 0);
+    assert_eq!(strat.target_quantity(1000.0), 3);
+    assert_eq!(strat.target_quantity(1025.0), 5);
+    assert_eq!(strat.target_quantity(1050.0), 8);
+    assert_eq!(strat.target_quantity(1099.0), 10);
+    assert!(matches!(strat.on_timer_at(999.0), StrategyAction::None));
+
+    // No book: an aggressive child goes to market
+    let StrategyAction::PlaceOrder(first) = strat.on_timer_at(1001.0) else { panic!("no child") };
+    assert_eq!(first.order_id.as_deref(), Some("P-1"));
+    assert_eq!((first.quantity, first.order_type.clone()), (3, OrderType::MARKET));
+    assert!(matches!(strat.on_timer_at(1002.0), StrategyAction::None));
+
+    // Slice over with 1 of 3 done: cancel once, then send the rest with the next slice
+    let mut child = first.clone();
+    child.filled_quantity = 1;
+    child.state = OrderState::PARTIALLY_FILLED;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(matches!(strat.on_timer_at(1026.0), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+    assert!(matches!(strat.on_timer_at(1027.0), StrategyAction::None));
+    child.state = OrderState::CANCELED;
+    strat.on_order_status_update(&child).unwrap();
+
+    let StrategyAction::PlaceOrder(second) = strat.on_timer_at(1027.0) else { panic!("no catch-up child") };
+    assert_eq!((second.order_id.as_deref(), second.quantity), (Some("P-2"), 4));
+
+    let mut child = second.clone();
+    child.filled_quantity = 4;
+    child.state = OrderState::FILLED;
+    strat.on_order_status_update(&child).unwrap();
+    assert_eq!(strat.filled_quantity(), 5);
+    assert!(!strat.is_completed());
+
+    // After the window everything left goes out
+    let StrategyAction::PlaceOrder(last) = strat.on_timer_at(1200.0) else { panic!("no final child") };
+    let mut child = last.clone();
+    child.filled_quantity = last.quantity;
+    child.state = OrderState::FILLED;
+    strat.on_order_status_update(&child).unwrap();
+    assert_eq!(strat.filled_quantity(), 10);
+    assert!(strat.is_completed());
+}
+
+#[test]
+fn test_pull_waits_for_ack_and_window_ends_with_one_sweep() {
+    let mut parent = twap(OrderSide::BUY, 10, None, &[("duration_seconds", "100"), ("slices", "4"), ("start_time", "1000")]);
+    parent.order_id = Some("P".to_string());
+    let mut strat = TwapStrategy::from_order(&parent).unwrap();
+
+    // Slice over before the venue acknowledged the child: the cancel goes out with the ack
+    let StrategyAction::PlaceOrder(first) = strat.on_timer_at(1001.0) else { panic!("no child") };
+    assert!(matches!(strat.on_timer_at(1026.0), StrategyAction::None));
+    let mut child = first.clone();
+    child.state = OrderState::NEW;
+    assert!(matches!(strat.on_order_status_update(&child).unwrap(), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+    assert!(matches!(strat.on_timer_at(1027.0), StrategyAction::None));
+
+    // The venue refuses the cancel: the engine puts the child back and the next tick retries
+    strat.on_order_status_update(&child).unwrap();
+    assert!(matches!(strat.on_timer_at(1028.0), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+    child.state = OrderState::CANCELED;
+    strat.on_order_status_update(&child).unwrap();
+
+    // Past the window the final child gets a slice interval instead of an expired deadline
+    let StrategyAction::PlaceOrder(last) = strat.on_timer_at(1200.0) else { panic!("no final child") };
+    assert_eq!(last.quantity, 10);
+    let mut child = last.clone();
+    child.state = OrderState::NEW;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(matches!(strat.on_timer_at(1201.0), StrategyAction::None));
+    assert!(matches!(strat.on_timer_at(1226.0), StrategyAction::CancelOrder(ref id) if id == "P-2"));
+    child.state = OrderState::CANCELED;
+    strat.on_order_status_update(&child).unwrap();
+
+    // ...and once it is done the TWAP stops rather than sending another
+    assert!(matches!(strat.on_timer_at(1227.0), StrategyAction::None));
+    assert!(strat.is_completed());
+}
+
+#[test]
+fn test_invalid_params() {
+    let err = |o: Order| TwapStrategy::from_order(&o).err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(err(twap(OrderSide::BUY, 10, None, &[])).contains("duration_seconds"));
+    assert!(err(twap(OrderSide::BUY, 10, None, &[("duration_seconds", "0")])).contains("positive"));
+    assert!(err(twap(OrderSide::BUY, 3, None, &[("duration_seconds", "60"), ("slices", "5")])).contains("slices"));
+    assert!(err(twap(OrderSide::BUY, 10, None, &[("duration_seconds", "60"), ("pricing", "MIDPOINT")])).contains("MIDPOINT"));
+
+    // The engine refuses the parent and keeps it as REJECTED
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter);
+    let mut bad = twap(OrderSide::BUY, 10, None, &[]);
+    bad.order_id = Some("BAD".to_string());
+    assert!(engine.send_order_internal(bad).is_err());
+    assert_eq!(engine.get_orders()["BAD"].state, OrderState::REJECTED);
+    assert!(engine.get_active_strategy_order_ids().is_empty());
+}
+
+#[test]
+fn test_engine_slices_parent_into_children() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    push_book(&adapter, 99, 101);
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let parent = engine.send_order_internal(twap(OrderSide::BUY, 8, Some("102"), &[("duration_seconds", "1"), ("slices", "4")])).unwrap();
+    assert_eq!(engine.get_orders()[&parent].state, OrderState::NEW);
+    assert_eq!(adapter.get_open_quantity(&parent), None);
+
+    let done = wait_until(|| children(&engine, &parent).iter().map(|c| c.filled_quantity).sum::<i64>() == 8);
+    assert!(done, "{:?}", children(&engine, &parent));
+    let kids = children(&engine, &parent);
+    assert!(kids.len() >= 2, "{:?}", kids);
+    assert!(kids.iter().all(|c| c.price == Some(dec!(101)) && c.state == OrderState::FILLED));
+    // Child fills roll up into the parent
+    assert!(wait_until(|| engine.get_orders()[&parent].state == OrderState::FILLED));
+    assert_eq!(engine.get_orders()[&parent].average_fill_price, dec!(101));
+
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    engine.stop_internal().unwrap();
+}
+
+#[test]
+fn test_cancel_parent_pulls_working_child() {
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = setup(adapter.clone());
+    push_book(&adapter, 99, 101);
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    // Passive children rest on the bid
+    let order = twap(OrderSide::BUY, 10, None, &[("duration_seconds", "60"), ("slices", "2"), ("pricing", "PASSIVE")]);
+    let parent = engine.send_order_internal(order).unwrap();
+    let child = format!("{}-1", parent);
+    assert!(wait_until(|| adapter.get_open_quantity(&child) == Some(5)));
+    assert_eq!(engine.get_orders()[&child].price, Some(dec!(99)));
+
+    engine.cancel_order_internal(parent.clone()).unwrap();
+    assert_eq!(engine.get_orders()[&parent].state, OrderState::CANCELED);
+    assert!(wait_until(|| engine.get_orders()[&child].state == OrderState::CANCELED));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert_eq!(children(&engine, &parent).len(), 1);
+    engine.stop_internal().unwrap();
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::wait_until;
+use didius::adapter::mock::MockAdapter;
+use didius::adapter::Adapter;
+use didius::logger::config::{LogDestinationInfo, LoggerConfig};
+use didius::logger::Logger;
+use didius::oms::engine::OMSEngine;
+use didius::oms::order::{ExecutionStrategy, Order, OrderSide, OrderState, OrderType};
+use didius::oms::order_book::OrderBookSnapshot;
+use didius::strategy::base::{Strategy, StrategyAction};
+use didius::strategy::vwap::{VolumeProfile, VwapStrategy};
+use rust_decimal::dec;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+/// 2026-10-16 09:00:00 KST
+const OPEN: f64 = 1792108800.0;
+
+fn vwap(qty: i64, price: Option<&str>, params: &[(&str, &str)]) -> Order {
+    let order_type = if price.is_some() { OrderType::LIMIT } else { OrderType::MARKET };
+    let params: HashMap<String, String> = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let mut o = Order::new("TEST".to_string(), OrderSide::BUY, order_type, qty, price.map(|p| p.to_string()), Some(ExecutionStrategy::VWAP), Some(params), None, "KRX".to_string(), None);
+    o.order_id = Some("P".to_string());
+    o
+}
+
+fn window(start: f64, end: f64) -> Vec<(&'static str, String)> {
+    vec![("start_time", start.to_string()), ("end_time", end.to_string())]
+}
+
+fn strategy(qty: i64, params: &[(&'static str, String)]) -> VwapStrategy {
+    let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    VwapStrategy::from_order(&vwap(qty, None, &params)).unwrap()
+}
+
+#[test]
+fn test_profile_shapes_schedule() {
+    let profile = VolumeProfile::krx_equity();
+    assert_eq!(profile.cumulative(OPEN - 60.0), 0.0);
+    assert!((profile.cumulative(OPEN + 900.0) - 0.06).abs() < 1e-9);
+    assert!((profile.cumulative(OPEN + 1800.0) - 0.12).abs() < 1e-9);
+    assert_eq!(profile.cumulative(OPEN + 6.5 * 3600.0), 1.0);
+
+    // 09:00-10:00: the first half hour carries 12 of 20.5
+    let strat = strategy(100, &window(OPEN, OPEN + 3600.0));
+    assert_eq!(strat.target_quantity(OPEN), 0);
+    assert_eq!(strat.target_quantity(OPEN + 1800.0), 59);
+    assert_eq!(strat.target_quantity(OPEN + 3600.0), 100);
+
+    // A custom curve, and a night window without profile volume is spread evenly
+    let mut params = window(OPEN, OPEN + 6.5 * 3600.0);
+    params.push(("volume_profile", "1,0,0,0,0,0,0,0,0,0,0,0,1".to_string()));
+    assert_eq!(strategy(100, &params).target_quantity(OPEN + 3.0 * 3600.0), 50);
+    let night = strategy(100, &window(OPEN + 11.0 * 3600.0, OPEN + 12.0 * 3600.0));
+    assert_eq!(night.target_quantity(OPEN + 11.25 * 3600.0), 25);
+}
+
+#[test]
+fn test_adapts_to_realized_volume() {
+    let mut params = window(OPEN, OPEN + 3600.0);
+    params.push(("expected_volume", "1000".to_string()));
+    params.push(("interval_seconds", "60".to_string()));
+    let mut strat = strategy(100, &params);
+
+    // No prints yet: the profile alone
+    assert_eq!(strat.target_quantity(OPEN + 1800.0), 59);
+
+    // Twice the expected pace by 09:30: 1000 / (1000 + 1000 * 8.5 / 20.5)
+    strat.on_trade_update("TEST", dec!(100), 500, OPEN - 10.0).unwrap();
+    strat.on_trade_update("OTHER", dec!(100), 500, OPEN + 10.0).unwrap();
+    strat.on_trade_update("TEST", dec!(100), 1000, OPEN + 10.0).unwrap();
+    assert_eq!(strat.target_quantity(OPEN + 1800.0), 71);
+
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(OPEN + 1800.0) else { panic!("no child") };
+    assert_eq!((child.order_id.as_deref(), child.quantity), (Some("P-1"), 71));
+    let mut child = child.clone();
+    child.state = OrderState::NEW;
+    strat.on_order_status_update(&child).unwrap();
+    // Pulled after its interval
+    assert!(matches!(strat.on_timer_at(OPEN + 1850.0), StrategyAction::None));
+    assert!(matches!(strat.on_timer_at(OPEN + 1861.0), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+}
+
+#[test]
+fn test_tracking_against_market_vwap() {
+    let mut params = window(OPEN, OPEN + 600.0);
+    params.push(("interval_seconds", "1000".to_string()));
+    let mut strat = strategy(10, &params);
+    strat.on_trade_update("TEST", dec!(100), 30, OPEN + 1.0).unwrap();
+    strat.on_trade_update("TEST", dec!(104), 10, OPEN + 2.0).unwrap();
+    assert_eq!(strat.market_vwap(), Some(dec!(101)));
+
+    let StrategyAction::PlaceOrder(mut child) = strat.on_timer_at(OPEN + 601.0) else { panic!("no child") };
+    child.filled_quantity = 10;
+    child.average_fill_price = dec!(101.101);
+    child.state = OrderState::FILLED;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(strat.is_completed());
+
+    // Paid 0.101 over 101
+    assert!((strat.tracking_bps().unwrap() - 10.0).abs() < 1e-6);
+    let report = strat.report().unwrap();
+    assert_eq!(report["filled_quantity"], 10);
+    assert_eq!(report["market_vwap"], "101");
+    assert_eq!(report["market_volume"], 40);
+}
+
+#[test]
+fn test_window_ends_with_one_final_child() {
+    let mut params = window(OPEN, OPEN + 600.0);
+    params.push(("interval_seconds", "60".to_string()));
+    let mut strat = strategy(10, &params);
+
+    // The window is over: the rest goes out once
+    let StrategyAction::PlaceOrder(child) = strat.on_timer_at(OPEN + 601.0) else { panic!("no child") };
+    assert_eq!((child.order_id.as_deref(), child.quantity), (Some("P-1"), 10));
+    assert!(strat.swept);
+    let mut child = child.clone();
+    child.state = OrderState::NEW;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(matches!(strat.on_timer_at(OPEN + 650.0), StrategyAction::None));
+    assert!(matches!(strat.on_timer_at(OPEN + 662.0), StrategyAction::CancelOrder(ref id) if id == "P-1"));
+
+    // Pulled with 4 filled: no second child, the strategy stops
+    child.filled_quantity = 4;
+    child.state = OrderState::CANCELED;
+    strat.on_order_status_update(&child).unwrap();
+    assert!(!strat.is_completed());
+    assert!(matches!(strat.on_timer_at(OPEN + 725.0), StrategyAction::None));
+    assert!(strat.is_completed());
+    assert_eq!(strat.filled_quantity(), 4);
+}
+
+#[test]
+fn test_engine_runs_vwap_and_logs_report() {
+    let log_path = "tests/vwap_report.jsonl";
+    let _ = fs::remove_file(log_path);
+    let config = LoggerConfig { destination: LogDestinationInfo::LocalFile { path: log_path.to_string() }, flush_interval_seconds: 60, batch_size: 1024 };
+    let adapter = Arc::new(MockAdapter::new());
+    let engine = OMSEngine::new(adapter.clone(), Arc::new(Mutex::new(Logger::new(config))));
+    let (tx, rx) = mpsc::channel();
+    adapter.set_monitor(tx);
+    engine.start_gateway_listener(rx).unwrap();
+    adapter.push_order_book(OrderBookSnapshot {
+        symbol: "TEST".to_string(),
+        bids: vec![(dec!(99), 100)],
+        asks: vec![(dec!(101), 100)],
+        update_id: 1,
+        timestamp: 1.0,
+    });
+    assert!(wait_until(|| engine.get_order_book("TEST").is_some()));
+    engine.start_internal(None).unwrap();
+
+    let mut order = vwap(10, Some("102"), &[("duration_seconds", "1"), ("interval_seconds", "0.2")]);
+    order.order_id = None;
+    let parent = engine.send_order_internal(order).unwrap();
+    adapter.push_trade("TEST", dec!(100), 50);
+
+    let prefix = format!("{}-", parent);
+    let filled = || engine.get_orders().values()
+        .filter(|o| o.order_id.as_deref().is_some_and(|id| id.starts_with(&prefix)))
+        .map(|o| o.filled_quantity)
+        .sum::<i64>();
+    assert!(wait_until(|| filled() == 10));
+    assert!(wait_until(|| engine.get_active_strategy_order_ids().is_empty()));
+    assert_eq!(adapter.get_open_quantity(&parent), None);
+    engine.stop_internal().unwrap();
+
+    let reports: Vec<serde_json::Value> = fs::read_to_string(log_path).unwrap().lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["log_type"] == "STRATEGY_REPORT")
+        .collect();
+    assert_eq!(reports.len(), 1);
+    let body = &reports[0]["log_body"];
+    assert_eq!(body["parent_order_id"], parent.as_str());
+    assert_eq!(body["average_price"], "101");
+    assert_eq!(body["market_vwap"], "100");
+    assert_eq!(body["tracking_bps"], 100.0);
+    let _ = fs::remove_file(log_path);
+}